@@ -0,0 +1,56 @@
+/*
+ * C API of the alpkit library for reading metadata from the APKv2 package
+ * format and APKBUILD.
+ *
+ * The metadata is returned as a JSON document (the same as the output of
+ * apk-inspect) in a NUL-terminated UTF-8 string allocated by the library,
+ * which must be freed by alpkit_string_free(). If a function fails, it returns
+ * NULL and the error message can be obtained by alpkit_last_error().
+ *
+ * Link with -lalpkit_capi.
+ */
+#ifndef ALPKIT_H
+#define ALPKIT_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/*
+ * A flag for alpkit_package_load() to not read the package data segment, i.e.
+ * the "files" field will be empty. This is much faster for bigger packages.
+ */
+#define ALPKIT_PACKAGE_WITHOUT_FILES 1u
+
+/*
+ * Reads the APKv2 package at the given path and returns it as a JSON document.
+ * The flags is a bitwise OR of the ALPKIT_PACKAGE_* flags, or 0.
+ */
+char *alpkit_package_load(const char *path, unsigned int flags);
+
+/*
+ * Evaluates the APKBUILD at the given path using /bin/sh with an empty
+ * environment and returns it as a JSON document. If the evaluation exceeds
+ * timeout_ms milliseconds, the shell is killed and the call fails; 0 means no
+ * limit.
+ */
+char *alpkit_apkbuild_read(const char *path, unsigned int timeout_ms);
+
+/*
+ * Returns the error message of the last failed call in the current thread, or
+ * NULL if the last call succeeded. The string is owned by the library and
+ * valid until the next call of alpkit_package_load() or
+ * alpkit_apkbuild_read() in the same thread.
+ */
+const char *alpkit_last_error(void);
+
+/*
+ * Frees a string returned by this library. It does nothing if s is NULL.
+ */
+void alpkit_string_free(char *s);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* ALPKIT_H */
@@ -0,0 +1,132 @@
+//! C API of the alpkit library, see `include/alpkit.h`.
+//!
+//! The functions return the read metadata serialized into a JSON document
+//! (the same as the output of apk-inspect), because it's easy to consume from
+//! C and Lua and doesn't tie the ABI to the Rust types. The returned strings
+//! must be freed by [`alpkit_string_free`]. If a function fails, it returns
+//! `NULL` and the error message can be obtained by [`alpkit_last_error`].
+#![allow(clippy::missing_safety_doc)] // documented in the header file
+
+use std::cell::RefCell;
+use std::ffi::{CStr, CString, OsStr};
+use std::fs::File;
+use std::io::BufReader;
+use std::os::raw::{c_char, c_uint};
+use std::os::unix::ffi::OsStrExt;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::ptr;
+use std::time::Duration;
+
+use alpkit::apkbuild::ApkbuildReader;
+use alpkit::package::Package;
+
+/// A flag for [`alpkit_package_load`] to not read the package data segment,
+/// i.e. the "files" field will be empty.
+pub const ALPKIT_PACKAGE_WITHOUT_FILES: c_uint = 1;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Reads the APKv2 package at the `path` and returns it as a JSON document.
+#[no_mangle]
+pub unsafe extern "C" fn alpkit_package_load(path: *const c_char, flags: c_uint) -> *mut c_char {
+    ffi_call(|| {
+        let file = File::open(c_path(path)?).map(BufReader::new)?;
+        let pkg = if flags & ALPKIT_PACKAGE_WITHOUT_FILES != 0 {
+            Package::load_without_files(file)?
+        } else {
+            Package::load(file)?
+        };
+        Ok(serde_json::to_string(&pkg)?)
+    })
+}
+
+/// Evaluates the APKBUILD at the `path` and returns it as a JSON document.
+#[no_mangle]
+pub unsafe extern "C" fn alpkit_apkbuild_read(
+    path: *const c_char,
+    timeout_ms: c_uint,
+) -> *mut c_char {
+    ffi_call(|| {
+        let path = c_path(path)?;
+        let apkbuild = ApkbuildReader::new()
+            .time_limit(Duration::from_millis(timeout_ms.into()))
+            .read_apkbuild(path)?;
+        Ok(serde_json::to_string(&apkbuild)?)
+    })
+}
+
+/// Returns the error message of the last failed call in the current thread,
+/// or `NULL` if there's none. It's valid until the next call of a function
+/// that may fail.
+#[no_mangle]
+pub extern "C" fn alpkit_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(ptr::null(), |msg| msg.as_ptr())
+    })
+}
+
+/// Frees a string returned by this library. It does nothing if `s` is `NULL`.
+#[no_mangle]
+pub unsafe extern "C" fn alpkit_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+type BoxError = Box<dyn std::error::Error>;
+
+/// Calls the function `f`, records its error (if any) as the last error, and
+/// converts its result into a C string. Panics are caught, because unwinding
+/// into C is undefined behaviour.
+fn ffi_call<F: FnOnce() -> Result<String, BoxError>>(f: F) -> *mut c_char {
+    let result = panic::catch_unwind(AssertUnwindSafe(f))
+        .unwrap_or_else(|_| Err("unexpected panic in alpkit".into()))
+        .and_then(|s| Ok(CString::new(s)?));
+
+    LAST_ERROR.with(|last| match result {
+        Ok(s) => {
+            last.replace(None);
+            s.into_raw()
+        }
+        Err(e) => {
+            last.replace(Some(error_message(&*e)));
+            ptr::null_mut()
+        }
+    })
+}
+
+/// Formats the error with all its sources, e.g. "failed to read file: No such
+/// file or directory".
+fn error_message(error: &dyn std::error::Error) -> CString {
+    let mut msg = error.to_string();
+    let mut source = error.source();
+    while let Some(e) = source {
+        msg.push_str(": ");
+        msg.push_str(&e.to_string());
+        source = e.source();
+    }
+    // A C string cannot contain NUL bytes.
+    CString::new(msg.replace('\0', "\\0")).unwrap()
+}
+
+unsafe fn c_path<'a>(path: *const c_char) -> Result<&'a Path, BoxError> {
+    if path.is_null() {
+        return Err("path must not be NULL".into());
+    }
+    Ok(Path::new(OsStr::from_bytes(
+        CStr::from_ptr(path).to_bytes(),
+    )))
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+#[path = "lib.test.rs"]
+mod test;
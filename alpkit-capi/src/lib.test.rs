@@ -0,0 +1,55 @@
+use serde_json::Value;
+
+use super::*;
+
+/// Converts the result of a C API function into a JSON value, or the last
+/// error message.
+unsafe fn take_json(s: *mut c_char) -> Result<Value, String> {
+    if s.is_null() {
+        let msg = CStr::from_ptr(alpkit_last_error());
+        return Err(msg.to_string_lossy().into_owned());
+    }
+    let value = serde_json::from_slice(CStr::from_ptr(s).to_bytes()).unwrap();
+    alpkit_string_free(s);
+
+    Ok(value)
+}
+
+#[test]
+fn package_load() {
+    let path = CString::new("../fixtures/apk/rssh-2.3.4-r3.apk").unwrap();
+
+    let pkg = unsafe { take_json(alpkit_package_load(path.as_ptr(), 0)) }.unwrap();
+    assert_eq!(pkg["pkgname"], "rssh");
+    assert!(!pkg["files"].as_array().unwrap().is_empty());
+    assert!(alpkit_last_error().is_null());
+
+    let flags = ALPKIT_PACKAGE_WITHOUT_FILES;
+    let pkg = unsafe { take_json(alpkit_package_load(path.as_ptr(), flags)) }.unwrap();
+    assert_eq!(pkg["pkgname"], "rssh");
+    assert_eq!(pkg["files"], Value::Array(vec![]));
+}
+
+#[test]
+fn package_load_missing() {
+    let path = CString::new("../fixtures/apk/missing.apk").unwrap();
+
+    let err = unsafe { take_json(alpkit_package_load(path.as_ptr(), 0)) }.unwrap_err();
+    assert!(err.contains("No such file"), "{err}");
+
+    let err = unsafe { take_json(alpkit_package_load(ptr::null(), 0)) }.unwrap_err();
+    assert_eq!(err, "path must not be NULL");
+}
+
+#[test]
+fn apkbuild_read() {
+    let path = CString::new("../fixtures/aports/sample/APKBUILD").unwrap();
+
+    let apkbuild = unsafe { take_json(alpkit_apkbuild_read(path.as_ptr(), 0)) }.unwrap();
+    assert_eq!(apkbuild["pkgname"], "sample");
+}
+
+#[test]
+fn string_free_null() {
+    unsafe { alpkit_string_free(ptr::null_mut()) };
+}
@@ -0,0 +1,83 @@
+//! JavaScript bindings of the alpkit library built with wasm-bindgen, e.g.
+//! for a web-based package viewer.
+//!
+//! The metadata is returned as plain JS objects of the same shape as the JSON
+//! output of apk-inspect.
+//!
+//! Example:
+//! ```js
+//! import { Package, parseDependency } from 'alpkit-wasm'
+//!
+//! const bytes = new Uint8Array(await (await fetch(url)).arrayBuffer())
+//! const pkg = Package.load(bytes)
+//! console.log(pkg.toJSON().pkgname)
+//!
+//! parseDependency('!so:libfoo.so.1>=1.2')  // { name: 'so:libfoo.so.1', ... }
+//! ```
+
+use std::str::FromStr;
+
+use alpkit::dependency::Dependency;
+use alpkit::package::Package as AlpkitPackage;
+use serde_json::json;
+use wasm_bindgen::prelude::*;
+
+/// An APKv2 package.
+#[wasm_bindgen]
+pub struct Package(AlpkitPackage);
+
+#[wasm_bindgen]
+impl Package {
+    /// Loads a package from the bytes of an APKv2 file.
+    pub fn load(bytes: &[u8]) -> Result<Package, JsError> {
+        Ok(Package(AlpkitPackage::load(bytes)?))
+    }
+
+    /// Loads a package from the bytes of an APKv2 file, as `load`, but
+    /// doesn't read the package data segment - the `files` field will be
+    /// empty.
+    #[wasm_bindgen(js_name = loadWithoutFiles)]
+    pub fn load_without_files(bytes: &[u8]) -> Result<Package, JsError> {
+        Ok(Package(AlpkitPackage::load_without_files(bytes)?))
+    }
+
+    /// Returns the package metadata as a plain object; this is also used by
+    /// `JSON.stringify`.
+    #[wasm_bindgen(js_name = toJSON)]
+    pub fn to_json(&self) -> Result<JsValue, JsError> {
+        to_js(&serde_json::to_string(&self.0)?)
+    }
+}
+
+/// Parses a dependency string as used in PKGINFO and APKBUILD (e.g.
+/// `!foo>=1.2@edge`) into an object with the fields `name`, `constraint`
+/// (`{ op, version }` or `null`), `conflict` and `repo_pin`.
+#[wasm_bindgen(js_name = parseDependency)]
+pub fn parse_dependency(s: &str) -> Result<JsValue, JsError> {
+    let dependency = Dependency::from_str(s)?;
+    to_js(&dependency_to_json(&dependency).to_string())
+}
+
+fn dependency_to_json(dependency: &Dependency) -> serde_json::Value {
+    json!({
+        "name": dependency.name,
+        "constraint": dependency.constraint.as_ref().map(|c| json!({
+            "op": c.op.to_string(),
+            "version": c.version,
+        })),
+        "conflict": dependency.conflict,
+        "repo_pin": dependency.repo_pin,
+    })
+}
+
+/// Converts the JSON document into a JS value. This is simpler (and usually
+/// faster) than constructing the JS objects field by field.
+fn to_js(json: &str) -> Result<JsValue, JsError> {
+    js_sys::JSON::parse(json).map_err(|_| JsError::new("failed to parse JSON"))
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+#[path = "lib.test.rs"]
+mod test;
@@ -0,0 +1,33 @@
+use serde_json::json;
+
+use super::*;
+
+#[test]
+fn dependency_to_json_with_constraint() {
+    let dependency = Dependency::from_str("!foo>=1.2@edge").unwrap();
+
+    assert_eq!(
+        dependency_to_json(&dependency),
+        json!({
+            "name": "foo",
+            "constraint": { "op": ">=", "version": "1.2" },
+            "conflict": true,
+            "repo_pin": "edge",
+        })
+    );
+}
+
+#[test]
+fn dependency_to_json_without_constraint() {
+    let dependency = Dependency::from_str("so:libc.musl-x86_64.so.1").unwrap();
+
+    assert_eq!(
+        dependency_to_json(&dependency),
+        json!({
+            "name": "so:libc.musl-x86_64.so.1",
+            "constraint": null,
+            "conflict": false,
+            "repo_pin": null,
+        })
+    );
+}
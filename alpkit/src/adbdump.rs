@@ -0,0 +1,239 @@
+//! Conversion of packages into the structure and field names of the package
+//! in apk-tools 3.x (the ADB schema), as printed by `apk adbdump`. It's
+//! useful for side-by-side comparison with apk itself and for tools written
+//! for that format.
+//!
+//! Example:
+//! ```no_run
+//! # use std::fs::File;
+//! # use std::io::BufReader;
+//! use alpkit::adbdump::AdbPackage;
+//! use alpkit::package::Package;
+//!
+//! let file = File::open("example-1.0-r0.apk").map(BufReader::new).unwrap();
+//! let pkg = Package::load(file).unwrap();
+//!
+//! let adb = AdbPackage::from_package(&pkg);
+//! ```
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::dependency::Dependency;
+use crate::package::{FileInfo, FileType, Package, PkgInfo};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// A package in the shape of the apk-tools 3.x package (`schema package`).
+#[derive(Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct AdbPackage {
+    pub info: AdbPkgInfo,
+
+    /// The directories with the files in them, in the order of the package.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub paths: Vec<AdbPath>,
+
+    /// The scripts by their name (e.g. `post-install`).
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub scripts: BTreeMap<String, String>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub triggers: Vec<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replaces_priority: Option<u16>,
+}
+
+impl AdbPackage {
+    /// Converts the package. The `paths` are empty if the package has been
+    /// loaded without files.
+    pub fn from_package(pkg: &Package) -> Self {
+        let pkginfo = pkg.pkginfo();
+
+        AdbPackage {
+            info: AdbPkgInfo::from_pkginfo(pkginfo),
+            paths: group_by_dir(pkg.files_metadata()),
+            scripts: pkg
+                .scripts()
+                .filter_map(|s| {
+                    pkg.script_content(s)
+                        .map(|c| (s.name().to_owned(), c.to_owned()))
+                })
+                .collect(),
+            triggers: pkginfo.triggers.clone(),
+            replaces_priority: pkginfo.replaces_priority,
+        }
+    }
+}
+
+/// The package metadata in the shape of the apk-tools 3.x `schema pkginfo`.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct AdbPkgInfo {
+    pub name: String,
+    pub version: String,
+    pub description: String,
+    pub arch: String,
+    pub license: String,
+    pub origin: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maintainer: Option<String>,
+
+    pub url: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repo_commit: Option<String>,
+
+    pub build_time: i64,
+    pub installed_size: usize,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider_priority: Option<u16>,
+
+    /// The dependencies including the conflicts (prefixed with `!`).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub depends: Vec<String>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub provides: Vec<String>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub replaces: Vec<String>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub install_if: Vec<String>,
+}
+
+impl AdbPkgInfo {
+    pub fn from_pkginfo(pkginfo: &PkgInfo) -> Self {
+        AdbPkgInfo {
+            name: pkginfo.pkgname.clone(),
+            version: pkginfo.pkgver.clone(),
+            description: pkginfo.pkgdesc.clone(),
+            arch: pkginfo.arch.clone(),
+            license: pkginfo.license.clone(),
+            origin: pkginfo.origin.clone(),
+            maintainer: pkginfo.maintainer.clone(),
+            url: pkginfo.url.clone(),
+            repo_commit: pkginfo.commit.clone(),
+            build_time: pkginfo.builddate,
+            installed_size: pkginfo.size,
+            provider_priority: pkginfo.provider_priority,
+            // apk 3.x lists the conflicts among the dependencies; PkgInfo keeps
+            // them separately without the conflict flag.
+            depends: pkginfo
+                .depends
+                .iter()
+                .map(ToString::to_string)
+                .chain(pkginfo.conflicts.iter().map(|d| format!("!{d}")))
+                .collect(),
+            provides: to_strings(&pkginfo.provides),
+            replaces: to_strings(&pkginfo.replaces),
+            install_if: to_strings(&pkginfo.install_if),
+        }
+    }
+}
+
+/// A directory with the files in it (`schema dir`).
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct AdbPath {
+    /// The path relative to the root, without a trailing slash.
+    pub name: String,
+    pub acl: AdbAcl,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<AdbFile>,
+}
+
+/// A non-directory file (`schema file`).
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct AdbFile {
+    /// The file name without the directory.
+    pub name: String,
+    pub acl: AdbAcl,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+    /// The target of a symlink or hardlink.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct AdbAcl {
+    /// The permission bits as an octal number with a leading zero.
+    pub mode: String,
+    pub user: String,
+    pub group: String,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+fn to_strings<'a, I: IntoIterator<Item = &'a Dependency>>(deps: I) -> Vec<String> {
+    deps.into_iter().map(ToString::to_string).collect()
+}
+
+/// Groups the files by their parent directory; the directories without an
+/// entry in the package get the default ACL.
+fn group_by_dir<'a, I: Iterator<Item = &'a FileInfo>>(files: I) -> Vec<AdbPath> {
+    let mut paths: Vec<AdbPath> = Vec::new();
+
+    for file in files {
+        let path = file.path.to_string_lossy();
+        let path = path.trim_start_matches('/').trim_end_matches('/');
+
+        if file.file_type == FileType::Directory {
+            paths.push(AdbPath {
+                name: path.to_owned(),
+                acl: acl(file),
+                files: Vec::new(),
+            });
+            continue;
+        }
+        let (dir, name) = path.rsplit_once('/').unwrap_or(("", path));
+
+        // The files usually follow their directory, so search from the end.
+        let idx = match paths.iter().rposition(|p| p.name == dir) {
+            Some(idx) => idx,
+            None => {
+                paths.push(AdbPath {
+                    name: dir.to_owned(),
+                    acl: AdbAcl {
+                        mode: "0755".to_owned(),
+                        user: "root".to_owned(),
+                        group: "root".to_owned(),
+                    },
+                    files: Vec::new(),
+                });
+                paths.len() - 1
+            }
+        };
+        paths[idx].files.push(AdbFile {
+            name: name.to_owned(),
+            acl: acl(file),
+            size: file.size,
+            hash: file.digest.clone(),
+            target: file
+                .link_target
+                .as_ref()
+                .map(|p| p.to_string_lossy().into_owned()),
+        });
+    }
+    paths
+}
+
+fn acl(file: &FileInfo) -> AdbAcl {
+    AdbAcl {
+        mode: format!("0{:o}", file.mode),
+        user: file.uname.clone(),
+        group: file.gname.clone(),
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+#[path = "adbdump.test.rs"]
+mod test;
@@ -0,0 +1,54 @@
+use indoc::indoc;
+
+use super::*;
+use crate::internal::test_utils::{assert, load_fixture, S};
+
+#[test]
+fn adb_package_from_package() {
+    let adb = AdbPackage::from_package(&load_fixture("rssh-2.3.4-r3.apk"));
+
+    assert!(adb.info.name == "rssh");
+    assert!(adb.info.version == "2.3.4-r3");
+    assert!(adb.info.repo_commit == Some(S!("c57128b0e49d551220aff88af0f1487d80cdccf8")));
+    assert!(adb.info.build_time == 1666619671);
+    assert!(adb.info.depends == ["openssh", "/bin/sh", "so:libc.musl-x86_64.so.1"]);
+    assert!(adb.info.provides == ["cmd:rssh=2.3.4-r3"]);
+
+    assert!(adb.scripts.keys().collect::<Vec<_>>() == ["post-deinstall", "post-install"]);
+
+    let paths = adb.paths.iter().map(|p| &p.name).collect::<Vec<_>>();
+    assert!(paths == ["etc", "usr", "usr/bin", "usr/lib", "usr/lib/rssh"]);
+
+    let helper = &adb.paths[4].files[0];
+    assert!(helper.name == "rssh_chroot_helper");
+    assert!(helper.acl.mode == "04755");
+    assert!(helper.size == Some(26560));
+}
+
+#[test]
+fn adb_pkginfo_with_conflicts() {
+    let pkginfo = PkgInfo::parse(indoc! {"
+        pkgname = sample
+        pkgver = 1.0-r0
+        pkgdesc = A sample
+        url = https://example.org
+        builddate = 1666619671
+        packager = Jakub Jirutka <jakub@jirutka.cz>
+        size = 1024
+        arch = noarch
+        origin = sample
+        license = MIT
+        depend = foo>=1.0
+        depend = !bar
+        install_if = sample-base baz
+        datahash = db62becd32465838640f39bd35854bd03e9b5e56b1ea8574e9188c3910121477
+    "})
+    .unwrap();
+
+    let adb = AdbPkgInfo::from_pkginfo(&pkginfo);
+
+    assert!(adb.depends == ["foo>=1.0", "!bar"]);
+    assert!(adb.install_if == ["sample-base", "baz"]);
+    assert!(adb.maintainer.is_none());
+    assert!(adb.repo_commit.is_none());
+}
@@ -0,0 +1,798 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::ops::Range;
+use std::path::Path;
+
+use super::{
+    find_assignment, is_function_start, parse_comment_attribute, parse_contributors, Apkbuild,
+    Error, SourceStatus, VARIABLES_ORDER,
+};
+use crate::dependency::Dependency;
+use crate::internal::macros::bail;
+use crate::internal::mailbox::is_valid_mailbox;
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Names of the split functions provided by abuild (`default_<name>`).
+const DEFAULT_SPLIT_FUNCTIONS: &[&str] = &[
+    "bashcomp", "dbg", "dev", "doc", "fishcomp", "lang", "libs", "openrc", "pyc", "static",
+    "zshcomp",
+];
+
+/// A function that returns the key of a list entry, e.g. the name of
+/// a dependency.
+type KeyFn = fn(&str) -> String;
+
+/// An editor for making small changes to an existing APKBUILD file without
+/// evaluating it. Only the values of the modified variables are rewritten, all
+/// other content (formatting, comments, functions) is preserved byte-for-byte.
+///
+/// Only top-level assignments (i.e. at the start of a line) are recognized and
+/// only the first assignment of each variable is modified.
+///
+/// Example:
+/// ```no_run
+/// use alpkit::apkbuild::ApkbuildEditor;
+///
+/// let mut editor = ApkbuildEditor::read_file("aports/main/sample/APKBUILD").unwrap();
+/// editor.set_pkgver("1.3.0").unwrap();
+/// editor.write_file("aports/main/sample/APKBUILD").unwrap();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApkbuildEditor {
+    text: String,
+}
+
+impl ApkbuildEditor {
+    /// Creates an editor for the given APKBUILD contents.
+    pub fn new<S: Into<String>>(text: S) -> Self {
+        Self { text: text.into() }
+    }
+
+    /// Reads the APKBUILD file at the given path.
+    pub fn read_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        fs::read_to_string(path)
+            .map(Self::new)
+            .map_err(|e| Error::ReadFile(e, path.to_owned()))
+    }
+
+    /// Writes the (modified) APKBUILD into a file at the given path.
+    pub fn write_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let path = path.as_ref();
+        fs::write(path, &self.text).map_err(|e| Error::WriteFile(e, path.to_owned()))
+    }
+
+    /// Returns the (modified) APKBUILD contents.
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    pub fn into_string(self) -> String {
+        self.text
+    }
+
+    /// Returns the raw (unexpanded) value of the given variable, or `None` if
+    /// it's not assigned.
+    pub fn variable(&self, name: &str) -> Option<&str> {
+        find_assignment(&self.text, name).map(|a| &self.text[a.value])
+    }
+
+    /// Replaces the value of the given variable, keeping its quotes (if any).
+    /// The value is written literally, i.e. it's not escaped.
+    pub fn set_variable(&mut self, name: &str, value: &str) -> Result<&mut Self, Error> {
+        let assignment = match find_assignment(&self.text, name) {
+            Some(a) => a,
+            None => bail!(Error::MissingVariable(name.to_owned())),
+        };
+        if assignment.quote.is_none() && value.contains(|c: char| c.is_ascii_whitespace()) {
+            self.text
+                .replace_range(assignment.value, &format!("\"{value}\""));
+        } else {
+            self.text.replace_range(assignment.value, value);
+        }
+        Ok(self)
+    }
+
+    /// Sets `pkgver` to the given version. If the version differs from the
+    /// current one, `pkgrel` is reset to 0.
+    pub fn set_pkgver(&mut self, pkgver: &str) -> Result<&mut Self, Error> {
+        if self.variable("pkgver") != Some(pkgver) {
+            self.set_variable("pkgver", pkgver)?;
+            self.set_pkgrel(0)?;
+        }
+        Ok(self)
+    }
+
+    /// Sets `pkgrel` to the given number.
+    pub fn set_pkgrel(&mut self, pkgrel: u32) -> Result<&mut Self, Error> {
+        self.set_variable("pkgrel", &pkgrel.to_string())
+    }
+
+    /// Increments `pkgrel` and returns the new value.
+    pub fn bump_pkgrel(&mut self) -> Result<u32, Error> {
+        let pkgrel = match self.variable("pkgrel") {
+            Some(value) => value
+                .parse::<u32>()
+                .map_err(|_| Error::InvalidVariable("pkgrel".to_owned(), value.to_owned()))?,
+            None => bail!(Error::MissingVariable("pkgrel".to_owned())),
+        };
+        self.set_pkgrel(pkgrel + 1)?;
+        Ok(pkgrel + 1)
+    }
+
+    /// Adds the dependency `dep` (e.g. `foo>=1.0`) to the list in the given
+    /// variable (e.g. `makedepends`), unless a dependency with the same name is
+    /// already there.
+    ///
+    /// If the existing entries are sorted, the new one is inserted at the
+    /// sorted position, otherwise it's appended. If the list is wrapped (one
+    /// entry per line), the new entry is put on a separate line with the same
+    /// indentation. If the variable is not assigned, a new assignment is
+    /// inserted after the preceding variable in the conventional order.
+    ///
+    /// Returns `false` if the dependency was already present.
+    pub fn add_dependency(&mut self, var: &str, dep: &str) -> bool {
+        self.add_entry(var, dep, dependency_name)
+    }
+
+    /// Removes the dependency with the given name (regardless of its version
+    /// constraint) from the list in the given variable. If the entry is alone
+    /// on its line, the whole line is removed.
+    ///
+    /// Returns `false` if the dependency was not found.
+    pub fn remove_dependency(&mut self, var: &str, name: &str) -> bool {
+        self.remove_entry(var, name, dependency_name)
+    }
+
+    /// Replaces the dependency with the given name (regardless of its version
+    /// constraint) in the list in the given variable with `dep`, keeping its
+    /// position.
+    ///
+    /// Returns `false` if the dependency was not found.
+    pub fn replace_dependency(&mut self, var: &str, name: &str, dep: &str) -> bool {
+        match self.find_entry(var, name, dependency_name) {
+            Some(range) => {
+                self.text.replace_range(range, dep);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Replaces the `source` entry with the given URI (e.g.
+    /// `https://example.org/foo-1.0.tar.gz`) with the `new_entry` (e.g.
+    /// `https://mirror.example.org/foo-$pkgver.tar.gz`), keeping its position.
+    /// The entries are compared both literally and with `$pkgname` and
+    /// `$pkgver` expanded, so `uri` may be either the raw or the evaluated one
+    /// (see [`Source::uri`](super::Source::uri)).
+    ///
+    /// If the file name changes, it's updated in the checksums blocks too (the
+    /// checksum itself is kept, it should be recomputed if the file differs).
+    ///
+    /// Returns `false` if there's no such source entry.
+    pub fn replace_source(&mut self, uri: &str, new_entry: &str) -> bool {
+        let a = match find_assignment(&self.text, "source") {
+            Some(a) => a,
+            None => return false,
+        };
+        let found = tokens(&self.text, a.value).into_iter().find(|t| {
+            let entry_uri = source_uri(&self.text[t.clone()]);
+            entry_uri == uri || self.expand(entry_uri) == uri
+        });
+        let range = match found {
+            Some(range) => range,
+            None => return false,
+        };
+        let old_filename = source_filename(&self.expand(&self.text[range.clone()]));
+        let new_filename = source_filename(&self.expand(new_entry));
+        self.text.replace_range(range, new_entry);
+
+        if old_filename != new_filename {
+            for var in ["sha512sums", "sha256sums", "md5sums"] {
+                let a = match find_assignment(&self.text, var) {
+                    Some(a) => a,
+                    None => continue,
+                };
+                let found = tokens(&self.text, a.value)
+                    .into_iter()
+                    .skip(1)
+                    .step_by(2)
+                    .find(|t| self.text[t.clone()] == old_filename);
+                if let Some(range) = found {
+                    self.text.replace_range(range, &new_filename);
+                }
+            }
+        }
+        true
+    }
+
+    /// Expands the `$pkgname` and `$pkgver` variables (also in the `${name}`
+    /// form) in the given string using their raw values in the APKBUILD.
+    fn expand(&self, s: &str) -> String {
+        let mut out = s.to_owned();
+        for name in ["pkgname", "pkgver"] {
+            let value = match self.variable(name) {
+                Some(value) => value.to_owned(),
+                None => continue,
+            };
+            out = out.replace(&format!("${{{name}}}"), &value);
+
+            let var = format!("${name}");
+            let mut pos = 0;
+            while let Some(i) = out[pos..].find(&var).map(|i| pos + i) {
+                let end = i + var.len();
+                if out[end..].starts_with(|c: char| c.is_ascii_alphanumeric() || c == '_') {
+                    pos = end;
+                } else {
+                    out.replace_range(i..end, &value);
+                    pos = i + value.len();
+                }
+            }
+        }
+        out
+    }
+
+    /// Adds the subpackage `spec` (`<name>[:<split_func>[:<arch>]]`, e.g.
+    /// `$pkgname-doc`) to `subpackages`, unless a subpackage with the same name
+    /// is already there. The entry is placed the same way as in
+    /// [`ApkbuildEditor::add_dependency`]; names are compared literally, i.e.
+    /// variables are not expanded.
+    ///
+    /// If `stub` is `true` and the subpackage's split function is neither one
+    /// of the abuild's default split functions nor already defined, a stub of
+    /// the function is inserted before the checksums (or at the end of the
+    /// file).
+    ///
+    /// Returns `false` if the subpackage was already present.
+    pub fn add_subpackage(&mut self, spec: &str, stub: bool) -> bool {
+        if !self.add_entry("subpackages", spec, subpackage_name) {
+            return false;
+        }
+        let func = split_function_name(spec);
+
+        if stub && !DEFAULT_SPLIT_FUNCTIONS.contains(&func.as_str()) && !self.has_function(&func) {
+            let mut text = format!(
+                "{func}() {{\n\tpkgdesc=\"$pkgdesc ({func})\"\n\n\t# TODO: move files with amove\n}}\n"
+            );
+            let pos = ["sha512sums", "sha256sums", "md5sums"]
+                .iter()
+                .filter_map(|name| find_assignment(&self.text, name))
+                .map(|a| a.start)
+                .min();
+            match pos {
+                Some(pos) => {
+                    text.push('\n');
+                    self.text.insert_str(pos, &text);
+                }
+                None => {
+                    if !self.text.is_empty() && !self.text.ends_with('\n') {
+                        self.text.push('\n');
+                    }
+                    self.text.push('\n');
+                    self.text.push_str(&text);
+                }
+            }
+        }
+        true
+    }
+
+    /// Removes the subpackage with the given name (without the split function
+    /// and arch) from `subpackages`. The split function, if any, is kept.
+    ///
+    /// Returns `false` if the subpackage was not found.
+    pub fn remove_subpackage(&mut self, name: &str) -> bool {
+        self.remove_entry("subpackages", name, subpackage_name)
+    }
+
+    /// Returns `true` if a function with the given name is defined.
+    fn has_function(&self, name: &str) -> bool {
+        self.text.lines().any(|line| {
+            is_function_start(line)
+                && line.split_once("()").map(|(s, _)| s.trim_end()) == Some(name)
+        })
+    }
+
+    /// Adds the `entry` to the list in the given variable, unless an entry with
+    /// the same key is already there. See [`ApkbuildEditor::add_dependency`].
+    fn add_entry(&mut self, var: &str, entry: &str, key: KeyFn) -> bool {
+        let entry_key = key(entry);
+
+        let a = match find_assignment(&self.text, var) {
+            Some(a) => a,
+            None => {
+                let pos = self.new_variable_position(var);
+                self.text.insert_str(pos, &format!("{var}=\"{entry}\"\n"));
+                return true;
+            }
+        };
+        let tokens = tokens(&self.text, a.value.clone());
+        if tokens
+            .iter()
+            .any(|t| key(&self.text[t.clone()]) == entry_key)
+        {
+            return false;
+        }
+        let multiline = self.text[a.value.clone()].contains('\n');
+
+        if tokens.is_empty() {
+            if a.quote.is_some() {
+                self.text.insert_str(a.value.start, entry);
+            } else {
+                self.text
+                    .replace_range(a.value.clone(), &format!("\"{entry}\""));
+            }
+            return true;
+        }
+
+        let is_sorted = tokens
+            .windows(2)
+            .all(|w| self.text[w[0].clone()] <= self.text[w[1].clone()]);
+        let before = if is_sorted {
+            tokens.iter().find(|t| &self.text[(*t).clone()] > entry)
+        } else {
+            None
+        };
+
+        match before {
+            Some(next) if multiline => {
+                let line_start = line_start(&self.text, next.start);
+                let indent = self.text[line_start..next.start].to_owned();
+                self.text
+                    .insert_str(line_start, &format!("{indent}{entry}\n"));
+            }
+            Some(next) => self.text.insert_str(next.start, &format!("{entry} ")),
+            None => {
+                let last = tokens.last().unwrap(); // tokens is not empty
+                if multiline {
+                    let line_start = line_start(&self.text, last.start);
+                    let indent = self.text[line_start..last.start].to_owned();
+                    let line_end = self.text[last.end..a.value.end]
+                        .find('\n')
+                        .map_or(a.value.end, |i| last.end + i);
+                    self.text
+                        .insert_str(line_end, &format!("\n{indent}{entry}"));
+                } else if a.quote.is_some() {
+                    self.text.insert_str(last.end, &format!(" {entry}"));
+                } else {
+                    let value = format!("\"{} {entry}\"", &self.text[a.value.clone()]);
+                    self.text.replace_range(a.value, &value);
+                }
+            }
+        }
+        true
+    }
+
+    /// Removes the entry with the given key from the list in the given
+    /// variable. See [`ApkbuildEditor::remove_dependency`].
+    fn remove_entry(&mut self, var: &str, name: &str, key: KeyFn) -> bool {
+        let range = match self.find_entry(var, name, key) {
+            Some(range) => range,
+            None => return false,
+        };
+        let line_start = line_start(&self.text, range.start);
+        let line_end = self.text[range.end..]
+            .find('\n')
+            .map_or(self.text.len(), |i| range.end + i);
+
+        let remove = if self.text[line_start..range.start].trim().is_empty()
+            && self.text[range.end..line_end].trim().is_empty()
+            && line_end < self.text.len()
+        {
+            line_start..line_end + 1
+        } else {
+            let trailing = self.text[range.end..]
+                .find(|c| c != ' ' && c != '\t')
+                .map_or(self.text.len() - range.end, |i| i);
+            if trailing > 0 && !self.text[range.end + trailing..].starts_with(['"', '\'', '\n']) {
+                range.start..range.end + trailing
+            } else {
+                let leading = self.text[..range.start].trim_end_matches([' ', '\t']).len();
+                leading..range.end
+            }
+        };
+        self.text.replace_range(remove, "");
+        true
+    }
+
+    /// Returns the byte range of the entry with the given key in the list in
+    /// the given variable.
+    fn find_entry(&self, var: &str, name: &str, key: KeyFn) -> Option<Range<usize>> {
+        let a = find_assignment(&self.text, var)?;
+        tokens(&self.text, a.value)
+            .into_iter()
+            .find(|t| key(&self.text[t.clone()]) == name)
+    }
+
+    /// Returns the byte offset at which a new assignment of the given variable
+    /// should be inserted.
+    fn new_variable_position(&self, var: &str) -> usize {
+        let idx = VARIABLES_ORDER
+            .iter()
+            .position(|v| *v == var)
+            .unwrap_or(VARIABLES_ORDER.len());
+
+        VARIABLES_ORDER[..idx]
+            .iter()
+            .rev()
+            .find_map(|v| find_assignment(&self.text, v))
+            .map_or(0, |a| {
+                self.text[a.end..]
+                    .find('\n')
+                    .map_or(self.text.len(), |i| a.end + i + 1)
+            })
+    }
+
+    /// Adds the security fixes (e.g. CVE IDs) for the given `version` (i.e.
+    /// `<pkgver>-r<pkgrel>`) into the `# secfixes:` comment block.
+    ///
+    /// If there's no entry for the `version` yet, it's inserted at the top of
+    /// the block (the newest first), otherwise the fixes not yet listed are
+    /// appended to the existing entry. The indentation of the existing entries
+    /// is preserved. If there's no secfixes block at all, it's inserted before
+    /// the first function (or at the end of the file).
+    pub fn add_secfixes<S: AsRef<str>>(&mut self, version: &str, fixes: &[S]) -> &mut Self {
+        let block = match find_secfixes_block(&self.text) {
+            Some(block) => block,
+            None => {
+                let mut block = format!("# secfixes:\n#   {version}:\n");
+                for fix in fixes {
+                    writeln!(block, "#     - {}", fix.as_ref()).unwrap();
+                }
+                let pos = function_offset(&self.text).unwrap_or(self.text.len());
+                let before = &self.text[..pos];
+                if !before.is_empty() && !before.ends_with('\n') {
+                    block.insert(0, '\n');
+                }
+                if !before.is_empty() && !before.ends_with("\n\n") {
+                    block.insert(0, '\n');
+                }
+                if pos < self.text.len() {
+                    block.push('\n');
+                }
+                self.text.insert_str(pos, &block);
+                return self;
+            }
+        };
+
+        let mut version_prefix = None;
+        let mut fix_prefix = None;
+        let mut in_entry = false;
+        let mut entry_end = None;
+        let mut existing: Vec<&str> = Vec::new();
+        let mut offset = block.start;
+
+        for line in self.text[block.clone()].split_inclusive('\n') {
+            let content = line.trim_end();
+            let stripped = content.trim_start_matches(['#', ' ', '\t']);
+
+            if let Some(ids) = stripped.strip_prefix('-') {
+                fix_prefix.get_or_insert(&content[..content.len() - ids.trim_start().len()]);
+                if in_entry {
+                    existing.extend(ids.split_ascii_whitespace());
+                    entry_end = Some(offset + line.len());
+                }
+            } else if let Some(key) = stripped.strip_suffix(':') {
+                version_prefix.get_or_insert(&content[..content.len() - stripped.len()]);
+                in_entry = key.trim_end() == version;
+                if in_entry {
+                    entry_end = Some(offset + line.len());
+                }
+            }
+            offset += line.len();
+        }
+
+        let version_prefix = version_prefix.unwrap_or("#   ").to_owned();
+        let fix_prefix = fix_prefix.unwrap_or("#     - ").to_owned();
+        let mut lines = String::new();
+        let pos = match entry_end {
+            Some(end) => end,
+            None => {
+                writeln!(lines, "{version_prefix}{version}:").unwrap();
+                block.start
+            }
+        };
+        for fix in fixes {
+            let fix = fix.as_ref();
+            if !existing.contains(&fix) {
+                writeln!(lines, "{fix_prefix}{fix}").unwrap();
+            }
+        }
+        self.text.insert_str(pos, &lines);
+        self
+    }
+
+    /// Sets the `# Maintainer:` comment to the given mailbox (e.g.
+    /// `Kevin Flynn <kevin.flynn@encom.com>`). If there's no such comment yet,
+    /// it's inserted after the `# Contributor:` comments or at the top of the
+    /// file.
+    pub fn set_maintainer(&mut self, maintainer: &str) -> Result<&mut Self, Error> {
+        if !is_valid_mailbox(maintainer) {
+            bail!(Error::InvalidMailbox(maintainer.to_owned()));
+        }
+        let line = format!("# Maintainer: {maintainer}");
+
+        match find_comment_line(&self.text, "Maintainer:") {
+            Some(range) => self.text.replace_range(range, &line),
+            None => {
+                let pos = self.header_insert_position();
+                self.text.insert_str(pos, &(line + "\n"));
+            }
+        }
+        Ok(self)
+    }
+
+    /// Adds the `# Contributor:` comment with the given mailbox after the
+    /// existing ones, or before the `# Maintainer:` comment, or at the top of
+    /// the file.
+    ///
+    /// Returns `false` if the contributor is already listed.
+    pub fn add_contributor(&mut self, contributor: &str) -> Result<bool, Error> {
+        if !is_valid_mailbox(contributor) {
+            bail!(Error::InvalidMailbox(contributor.to_owned()));
+        }
+        if parse_contributors(&self.text, None).any(|s| s == contributor) {
+            return Ok(false);
+        }
+        let pos = if parse_contributors(&self.text, None).next().is_some() {
+            self.header_insert_position()
+        } else {
+            find_comment_line(&self.text, "Maintainer:").map_or(0, |range| range.start)
+        };
+        self.text
+            .insert_str(pos, &format!("# Contributor: {contributor}\n"));
+        Ok(true)
+    }
+
+    /// Returns the byte offset just after the last `# Contributor:` comment in
+    /// the header, or 0 if there's none.
+    fn header_insert_position(&self) -> usize {
+        let mut offset = 0;
+        let mut pos = 0;
+        for line in self.text.split_inclusive('\n') {
+            let trimmed = line.trim();
+            if !(trimmed.is_empty() || trimmed.starts_with('#')) {
+                break;
+            }
+            offset += line.len();
+            if parse_comment_attribute("Contributor:", line).is_some() {
+                pos = offset;
+            }
+        }
+        pos
+    }
+
+    /// Replaces the `sha512sums` block with the given pairs of file name and
+    /// checksum, formatted the same way as `abuild checksum` does. The legacy
+    /// `sha256sums` and `md5sums` blocks are replaced or removed. If there's no
+    /// checksums block at all, it's appended to the end of the file.
+    pub fn set_checksums<I, N, C>(&mut self, checksums: I) -> &mut Self
+    where
+        I: IntoIterator<Item = (N, C)>,
+        N: AsRef<str>,
+        C: AsRef<str>,
+    {
+        let mut block = String::from("sha512sums=\"\n");
+        for (name, checksum) in checksums {
+            writeln!(block, "{}  {}", checksum.as_ref(), name.as_ref()).unwrap();
+        }
+        block.push('"');
+
+        let existing = ["sha512sums", "sha256sums", "md5sums"]
+            .into_iter()
+            .find(|name| find_assignment(&self.text, name).is_some());
+
+        if let Some(existing) = existing {
+            for name in ["sha256sums", "md5sums"] {
+                if name != existing {
+                    self.remove_variable(name);
+                }
+            }
+            if let Some(a) = find_assignment(&self.text, existing) {
+                self.text.replace_range(a.start..a.end, &block);
+            }
+        } else {
+            if !self.text.is_empty() && !self.text.ends_with('\n') {
+                self.text.push('\n');
+            }
+            writeln!(self.text, "\n{block}").unwrap();
+        }
+        self
+    }
+
+    /// Recomputes checksums of all the sources of the given APKBUILD (see
+    /// [`Apkbuild::compute_checksums`]) and replaces the `sha512sums` block
+    /// (see [`ApkbuildEditor::set_checksums`]).
+    pub fn update_checksums<P, Q>(
+        &mut self,
+        apkbuild: &Apkbuild,
+        startdir: P,
+        srcdest: Q,
+    ) -> Result<&mut Self, Error>
+    where
+        P: AsRef<Path>,
+        Q: AsRef<Path>,
+    {
+        let checksums = apkbuild.compute_checksums(startdir, srcdest)?;
+        Ok(self.set_checksums(checksums))
+    }
+
+    /// Converts the legacy `md5sums` and `sha256sums` blocks to `sha512sums`.
+    /// The source files are first verified against the existing checksums
+    /// (see [`Apkbuild::verify_sources`]) and then the SHA-512 checksums are
+    /// computed from them (see [`ApkbuildEditor::update_checksums`]).
+    ///
+    /// Returns `false` if there's nothing to migrate.
+    pub fn migrate_checksums<P, Q>(
+        &mut self,
+        apkbuild: &Apkbuild,
+        startdir: P,
+        srcdest: Q,
+    ) -> Result<bool, Error>
+    where
+        P: AsRef<Path>,
+        Q: AsRef<Path>,
+    {
+        let has_legacy = ["sha256sums", "md5sums"]
+            .iter()
+            .any(|name| find_assignment(&self.text, name).is_some());
+        if !has_legacy {
+            return Ok(false);
+        }
+
+        for (source, status) in apkbuild.verify_sources(&startdir, &srcdest)? {
+            // Missing files are reported by compute_checksums.
+            if let SourceStatus::Mismatch(actual) = status {
+                if !source.checksum.is_empty() {
+                    bail!(Error::ChecksumMismatch(
+                        source.name.clone(),
+                        source.checksum.clone(),
+                        actual
+                    ));
+                }
+            }
+        }
+        self.update_checksums(apkbuild, startdir, srcdest)?;
+        Ok(true)
+    }
+
+    /// Removes the first assignment of the given variable including the
+    /// trailing newline (if any).
+    fn remove_variable(&mut self, name: &str) {
+        if let Some(a) = find_assignment(&self.text, name) {
+            let end = if self.text[a.end..].starts_with('\n') {
+                a.end + 1
+            } else {
+                a.end
+            };
+            self.text.replace_range(a.start..end, "");
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Returns byte ranges of the whitespace-separated words in the given range.
+fn tokens(text: &str, range: Range<usize>) -> Vec<Range<usize>> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+
+    for (i, c) in text[range.clone()].char_indices() {
+        match (c.is_ascii_whitespace(), start) {
+            (true, Some(s)) => {
+                tokens.push(range.start + s..range.start + i);
+                start = None;
+            }
+            (false, None) => start = Some(i),
+            _ => (),
+        }
+    }
+    if let Some(s) = start {
+        tokens.push(range.start + s..range.end);
+    }
+    tokens
+}
+
+/// Returns the byte range of the entries in the secfixes comment block, i.e.
+/// the comment lines following the `# secfixes:` line.
+fn find_secfixes_block(text: &str) -> Option<Range<usize>> {
+    let mut offset = 0;
+    let mut lines = text.split_inclusive('\n');
+
+    for line in lines.by_ref() {
+        offset += line.len();
+        let is_start = line
+            .strip_prefix('#')
+            .map_or(false, |s| matches!(s.trim(), "secfixes:" | "secfixes"));
+        if is_start {
+            let len = lines
+                .take_while(|s| s.starts_with('#') && !s[1..].trim().is_empty())
+                .map(str::len)
+                .sum::<usize>();
+            return Some(offset..offset + len);
+        }
+    }
+    None
+}
+
+/// Returns the byte range (without the newline) of the first comment line
+/// with the given attribute name, e.g. `Maintainer:`.
+fn find_comment_line(text: &str, name: &str) -> Option<Range<usize>> {
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let is_match = line
+            .trim()
+            .strip_prefix('#')
+            .map_or(false, |s| s.trim_start().starts_with(name));
+        if is_match {
+            return Some(offset..offset + line.trim_end_matches(['\r', '\n']).len());
+        }
+        offset += line.len();
+    }
+    None
+}
+
+/// Returns the byte offset of the first function definition.
+fn function_offset(text: &str) -> Option<usize> {
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        if is_function_start(line) {
+            return Some(offset);
+        }
+        offset += line.len();
+    }
+    None
+}
+
+/// Returns the byte offset of the start of the line containing `pos`.
+fn line_start(text: &str, pos: usize) -> usize {
+    text[..pos].rfind('\n').map_or(0, |i| i + 1)
+}
+
+/// Returns the URI part of the `source` entry (`[<filename>::]<uri>`).
+fn source_uri(entry: &str) -> &str {
+    entry.split_once("::").map_or(entry, |(_, uri)| uri)
+}
+
+/// Returns the file name of the `source` entry (`[<filename>::]<uri>`), i.e.
+/// the explicit name or the last path segment of the URI.
+fn source_filename(entry: &str) -> String {
+    match entry.split_once("::") {
+        Some((name, _)) => name.to_owned(),
+        None => entry.rsplit('/').next().unwrap_or(entry).to_owned(),
+    }
+}
+
+/// Returns the subpackage name from the `subpackages` entry.
+fn subpackage_name(spec: &str) -> String {
+    spec.split(':').next().unwrap_or(spec).to_owned()
+}
+
+/// Returns the name of the split function of the subpackage; either the one
+/// specified in the `subpackages` entry, or derived from the name as abuild
+/// does.
+fn split_function_name(spec: &str) -> String {
+    let mut parts = spec.split(':');
+    let name = parts.next().unwrap_or(spec);
+
+    match parts.next().filter(|s| !s.is_empty()) {
+        Some(func) => func.to_owned(),
+        None if name.ends_with("-bash-completion") => "bashcomp".to_owned(),
+        None if name.ends_with("-zsh-completion") => "zshcomp".to_owned(),
+        None if name.ends_with("-fish-completion") => "fishcomp".to_owned(),
+        None => name.rsplit('-').next().unwrap_or(name).to_owned(),
+    }
+}
+
+/// Returns the name of the dependency (without version constraint), or the
+/// whole string if it's not a valid dependency.
+fn dependency_name(s: &str) -> String {
+    s.parse::<Dependency>()
+        .map_or_else(|_| s.to_owned(), |d| d.name)
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+#[path = "editor.test.rs"]
+mod test;
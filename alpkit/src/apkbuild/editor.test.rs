@@ -0,0 +1,654 @@
+use std::fs;
+
+use indoc::indoc;
+
+use super::*;
+use crate::apkbuild::{ChecksumAlg, Source};
+use crate::internal::test_utils::{assert, assert_let};
+
+const APKBUILD: &str = indoc! {r#"
+    # Maintainer: Kevin Flynn <kevin.flynn@encom.com>
+    pkgname=sample
+    pkgver=1.2.3 # keep in sync with foo
+    pkgrel=2
+    pkgdesc="A sample  package"
+    _pkgver='1.2.3'
+    source="https://example.org/sample-$pkgver.tar.gz"
+
+    build() {
+    	pkgver=0.0 make
+    }
+    "#};
+
+#[test]
+fn set_pkgver_resets_pkgrel() {
+    let mut editor = ApkbuildEditor::new(APKBUILD);
+    editor.set_pkgver("1.3.0").unwrap();
+
+    assert!(
+        editor.as_str()
+            == APKBUILD
+                .replace("pkgver=1.2.3 #", "pkgver=1.3.0 #")
+                .replace("pkgrel=2", "pkgrel=0")
+    );
+}
+
+#[test]
+fn set_pkgver_same_version() {
+    let mut editor = ApkbuildEditor::new(APKBUILD);
+    editor.set_pkgver("1.2.3").unwrap();
+
+    assert!(editor.as_str() == APKBUILD);
+}
+
+#[test]
+fn set_variable_keeps_quotes() {
+    let mut editor = ApkbuildEditor::new(APKBUILD);
+    editor.set_variable("_pkgver", "2.0").unwrap();
+    editor.set_variable("pkgdesc", "Another package").unwrap();
+
+    assert!(editor.variable("_pkgver") == Some("2.0"));
+    assert!(
+        editor.as_str()
+            == APKBUILD
+                .replace("_pkgver='1.2.3'", "_pkgver='2.0'")
+                .replace("\"A sample  package\"", "\"Another package\"")
+    );
+}
+
+#[test]
+fn set_variable_quotes_whitespace() {
+    let mut editor = ApkbuildEditor::new(APKBUILD);
+    editor.set_variable("pkgname", "a b").unwrap();
+
+    assert!(editor.variable("pkgname") == Some("a b"));
+    assert!(editor.as_str().contains("\npkgname=\"a b\"\n"));
+}
+
+#[test]
+fn set_variable_missing() {
+    let mut editor = ApkbuildEditor::new(APKBUILD);
+
+    assert_let!(Err(Error::MissingVariable(name)) = editor.set_variable("url", "x"));
+    assert!(name == "url");
+    assert!(editor.as_str() == APKBUILD);
+}
+
+#[test]
+fn bump_pkgrel() {
+    let mut editor = ApkbuildEditor::new(APKBUILD);
+
+    assert!(editor.bump_pkgrel().unwrap() == 3);
+    assert!(editor.as_str() == APKBUILD.replace("pkgrel=2", "pkgrel=3"));
+}
+
+#[test]
+fn bump_pkgrel_invalid() {
+    let mut editor = ApkbuildEditor::new(APKBUILD.replace("pkgrel=2", "pkgrel=$_rel"));
+
+    assert_let!(Err(Error::InvalidVariable(name, value)) = editor.bump_pkgrel());
+    assert!(name == "pkgrel");
+    assert!(value == "$_rel");
+}
+
+#[test]
+fn set_checksums_replace() {
+    let apkbuild = indoc! {r#"
+        pkgname=sample
+        source="a.tar.gz b.patch"
+
+        sha512sums="
+        1111  a.tar.gz
+        "
+        # trailing comment
+    "#};
+    let mut editor = ApkbuildEditor::new(apkbuild);
+    editor.set_checksums([("a.tar.gz", "aaaa"), ("b.patch", "bbbb")]);
+
+    assert!(
+        editor.as_str()
+            == indoc! {r#"
+        pkgname=sample
+        source="a.tar.gz b.patch"
+
+        sha512sums="
+        aaaa  a.tar.gz
+        bbbb  b.patch
+        "
+        # trailing comment
+    "#}
+    );
+}
+
+#[test]
+fn set_checksums_legacy() {
+    let apkbuild = indoc! {r#"
+        pkgname=sample
+        source="a.tar.gz"
+
+        md5sums="1111  a.tar.gz"
+        sha256sums="2222  a.tar.gz"
+    "#};
+    let mut editor = ApkbuildEditor::new(apkbuild);
+    editor.set_checksums([("a.tar.gz", "aaaa")]);
+
+    assert!(
+        editor.as_str()
+            == indoc! {r#"
+        pkgname=sample
+        source="a.tar.gz"
+
+        sha512sums="
+        aaaa  a.tar.gz
+        "
+    "#}
+    );
+}
+
+#[test]
+fn set_checksums_append() {
+    let mut editor = ApkbuildEditor::new("pkgname=sample\nsource=\"a.tar.gz\"");
+    editor.set_checksums([("a.tar.gz", "aaaa")]);
+
+    assert!(
+        editor.as_str()
+            == "pkgname=sample\nsource=\"a.tar.gz\"\n\nsha512sums=\"\naaaa  a.tar.gz\n\"\n"
+    );
+}
+
+#[test]
+fn update_checksums() {
+    let startdir = tempfile::tempdir().unwrap();
+    fs::write(startdir.path().join("foo.patch"), "foo\n").unwrap();
+
+    let apkbuild = Apkbuild {
+        source: vec![Source::new("foo.patch", "foo.patch", "")],
+        ..Default::default()
+    };
+    let mut editor = ApkbuildEditor::new("source=\"foo.patch\"\n");
+    editor
+        .update_checksums(&apkbuild, startdir.path(), startdir.path())
+        .unwrap();
+
+    assert!(
+        editor.as_str()
+            == indoc! {r#"
+        source="foo.patch"
+
+        sha512sums="
+        0cf9180a764aba863a67b6d72f0918bc131c6772642cb2dce5a34f0a702f9470ddc2bf125c12198b1995c233c34b4afd346c54a2334c350a948a51b6e8b4e6b6  foo.patch
+        "
+    "#}
+    );
+}
+
+#[test]
+fn update_checksums_missing_file() {
+    let startdir = tempfile::tempdir().unwrap();
+    let apkbuild = Apkbuild {
+        source: vec![Source::new("foo.patch", "foo.patch", "")],
+        ..Default::default()
+    };
+    let mut editor = ApkbuildEditor::new("source=\"foo.patch\"\n");
+
+    assert_let!(
+        Err(Error::ReadFile(..)) =
+            editor.update_checksums(&apkbuild, startdir.path(), startdir.path())
+    );
+    assert!(editor.as_str() == "source=\"foo.patch\"\n");
+}
+
+const DEPS_APKBUILD: &str = indoc! {r#"
+    pkgname=sample
+    license="MIT"
+    depends="bash coreutils"
+    makedepends="
+    	cargo
+    	openssl-dev>3
+    	zlib-dev
+    	"
+    checkdepends="zzz aaa"
+    subpackages="$pkgname-doc"
+    "#};
+
+#[rustfmt::skip]
+#[test]
+fn add_dependency() {
+    for (var, dep, expected) in [
+        ("depends", "curl", DEPS_APKBUILD.replace("bash coreutils", "bash coreutils curl")),
+        ("depends", "awk", DEPS_APKBUILD.replace("bash coreutils", "awk bash coreutils")),
+        ("depends", "busybox", DEPS_APKBUILD.replace("bash coreutils", "bash busybox coreutils")),
+        ("makedepends", "libgit2-dev", DEPS_APKBUILD.replace("\topenssl", "\tlibgit2-dev\n\topenssl")),
+        ("makedepends", "zstd-dev", DEPS_APKBUILD.replace("\tzlib-dev\n", "\tzlib-dev\n\tzstd-dev\n")),
+        ("checkdepends", "bats", DEPS_APKBUILD.replace("zzz aaa", "zzz aaa bats")),
+        ("makedepends_host", "foo", DEPS_APKBUILD.replace("\tzlib-dev\n\t\"\n", "\tzlib-dev\n\t\"\nmakedepends_host=\"foo\"\n")),
+    ] {
+        let mut editor = ApkbuildEditor::new(DEPS_APKBUILD);
+        assert!(editor.add_dependency(var, dep));
+        assert!(editor.as_str() == expected, "{var} += {dep}");
+    }
+}
+
+#[test]
+fn add_dependency_existing() {
+    let mut editor = ApkbuildEditor::new(DEPS_APKBUILD);
+
+    assert!(!editor.add_dependency("makedepends", "openssl-dev>=3.1"));
+    assert!(editor.as_str() == DEPS_APKBUILD);
+}
+
+#[test]
+fn add_dependency_unquoted() {
+    let mut editor = ApkbuildEditor::new("pkgname=sample\ndepends=bash\n");
+    editor.add_dependency("depends", "curl");
+
+    assert!(editor.as_str() == "pkgname=sample\ndepends=\"bash curl\"\n");
+}
+
+#[rustfmt::skip]
+#[test]
+fn remove_dependency() {
+    for (var, name, expected) in [
+        ("depends", "bash", DEPS_APKBUILD.replace("bash coreutils", "coreutils")),
+        ("depends", "coreutils", DEPS_APKBUILD.replace("bash coreutils", "bash")),
+        ("makedepends", "openssl-dev", DEPS_APKBUILD.replace("\topenssl-dev>3\n", "")),
+        ("checkdepends", "aaa", DEPS_APKBUILD.replace("zzz aaa", "zzz")),
+    ] {
+        let mut editor = ApkbuildEditor::new(DEPS_APKBUILD);
+        assert!(editor.remove_dependency(var, name));
+        assert!(editor.as_str() == expected, "{var} -= {name}");
+    }
+}
+
+#[test]
+fn remove_dependency_missing() {
+    let mut editor = ApkbuildEditor::new(DEPS_APKBUILD);
+
+    assert!(!editor.remove_dependency("depends", "curl"));
+    assert!(!editor.remove_dependency("install_if", "curl"));
+    assert!(editor.as_str() == DEPS_APKBUILD);
+}
+
+#[test]
+fn replace_dependency() {
+    let mut editor = ApkbuildEditor::new(DEPS_APKBUILD);
+
+    assert!(editor.replace_dependency("makedepends", "openssl-dev", "openssl3-dev"));
+    assert!(editor.as_str() == DEPS_APKBUILD.replace("openssl-dev>3", "openssl3-dev"));
+}
+
+const SECFIXES_APKBUILD: &str = indoc! {r#"
+    pkgname=sample
+    pkgver=1.2.3
+
+    # secfixes:
+    #   1.2.0-r0:
+    #     - CVE-2022-0001
+    #   1.0.0-r0:
+    #     - CVE-2021-0001
+
+    build() {
+    	make
+    }
+    "#};
+
+#[test]
+fn add_secfixes_new_version() {
+    let mut editor = ApkbuildEditor::new(SECFIXES_APKBUILD);
+    editor.add_secfixes("1.2.3-r0", &["CVE-2023-0001", "CVE-2023-0002"]);
+
+    assert!(
+        editor.as_str()
+            == SECFIXES_APKBUILD.replace(
+                "# secfixes:\n",
+                "# secfixes:\n#   1.2.3-r0:\n#     - CVE-2023-0001\n#     - CVE-2023-0002\n",
+            )
+    );
+}
+
+#[test]
+fn add_secfixes_existing_version() {
+    let mut editor = ApkbuildEditor::new(SECFIXES_APKBUILD);
+    editor.add_secfixes("1.2.0-r0", &["CVE-2022-0001", "CVE-2022-0002"]);
+
+    assert!(
+        editor.as_str()
+            == SECFIXES_APKBUILD
+                .replace("CVE-2022-0001\n", "CVE-2022-0001\n#     - CVE-2022-0002\n",)
+    );
+}
+
+#[test]
+fn add_secfixes_keeps_indentation() {
+    let apkbuild = "pkgname=sample\n# secfixes:\n#  1.0-r0:\n#  - CVE-2021-0001\n";
+    let mut editor = ApkbuildEditor::new(apkbuild);
+    editor.add_secfixes("1.1-r0", &["CVE-2022-0001"]);
+
+    assert!(editor.as_str() == "pkgname=sample\n# secfixes:\n#  1.1-r0:\n#  - CVE-2022-0001\n#  1.0-r0:\n#  - CVE-2021-0001\n");
+}
+
+#[test]
+fn add_secfixes_new_block() {
+    let apkbuild = indoc! {r#"
+        pkgname=sample
+        pkgver=1.2.3
+
+        build() {
+        	make
+        }
+    "#};
+    let mut editor = ApkbuildEditor::new(apkbuild);
+    editor.add_secfixes("1.2.3-r0", &["CVE-2023-0001"]);
+
+    assert!(
+        editor.as_str()
+            == indoc! {r#"
+        pkgname=sample
+        pkgver=1.2.3
+
+        # secfixes:
+        #   1.2.3-r0:
+        #     - CVE-2023-0001
+
+        build() {
+        	make
+        }
+    "#}
+    );
+}
+
+#[test]
+fn add_secfixes_new_block_at_end() {
+    let mut editor = ApkbuildEditor::new("pkgname=sample");
+    editor.add_secfixes("0", &["CVE-2023-0001"]);
+
+    assert!(editor.as_str() == "pkgname=sample\n\n# secfixes:\n#   0:\n#     - CVE-2023-0001\n");
+}
+
+const HEADER_APKBUILD: &str = indoc! {r#"
+    # Contributor: Sam Flynn <sam.flynn@encom.com>
+    # Maintainer: Kevin Flynn <kevin.flynn@encom.com>
+    pkgname=sample
+    "#};
+
+#[test]
+fn set_maintainer_replace() {
+    let mut editor = ApkbuildEditor::new(HEADER_APKBUILD);
+    editor
+        .set_maintainer("Alan Bradley <alan@encom.com>")
+        .unwrap();
+
+    assert!(
+        editor.as_str()
+            == HEADER_APKBUILD.replace(
+                "Kevin Flynn <kevin.flynn@encom.com>",
+                "Alan Bradley <alan@encom.com>"
+            )
+    );
+}
+
+#[test]
+fn set_maintainer_insert() {
+    let mut editor =
+        ApkbuildEditor::new("# Contributor: Sam Flynn <sam.flynn@encom.com>\npkgname=sample\n");
+    editor
+        .set_maintainer("Alan Bradley <alan@encom.com>")
+        .unwrap();
+
+    assert!(
+        editor.as_str()
+            == HEADER_APKBUILD.replace(
+                "Kevin Flynn <kevin.flynn@encom.com>",
+                "Alan Bradley <alan@encom.com>"
+            )
+    );
+
+    let mut editor = ApkbuildEditor::new("pkgname=sample\n");
+    editor
+        .set_maintainer("Alan Bradley <alan@encom.com>")
+        .unwrap();
+
+    assert!(editor.as_str() == "# Maintainer: Alan Bradley <alan@encom.com>\npkgname=sample\n");
+}
+
+#[test]
+fn set_maintainer_invalid() {
+    let mut editor = ApkbuildEditor::new(HEADER_APKBUILD);
+
+    assert_let!(Err(Error::InvalidMailbox(_)) = editor.set_maintainer("alan@encom.com"));
+    assert!(editor.as_str() == HEADER_APKBUILD);
+}
+
+#[test]
+fn add_contributor() {
+    let mut editor = ApkbuildEditor::new(HEADER_APKBUILD);
+
+    assert!(editor
+        .add_contributor("Alan Bradley <alan@encom.com>")
+        .unwrap());
+    assert!(!editor
+        .add_contributor("Sam Flynn <sam.flynn@encom.com>")
+        .unwrap());
+    assert!(
+        editor.as_str()
+            == HEADER_APKBUILD.replace(
+                "# Maintainer:",
+                "# Contributor: Alan Bradley <alan@encom.com>\n# Maintainer:",
+            )
+    );
+}
+
+#[test]
+fn add_contributor_first() {
+    let mut editor =
+        ApkbuildEditor::new("# Maintainer: Kevin Flynn <kevin.flynn@encom.com>\npkgname=sample\n");
+    editor
+        .add_contributor("Sam Flynn <sam.flynn@encom.com>")
+        .unwrap();
+
+    assert!(editor.as_str() == HEADER_APKBUILD);
+}
+
+const SUBPACKAGES_APKBUILD: &str = indoc! {r#"
+    pkgname=sample
+    subpackages="$pkgname-dev $pkgname-doc"
+    source="sample.tar.gz"
+
+    package() {
+    	make install
+    }
+
+    sha512sums="
+    aaaa  sample.tar.gz
+    "
+    "#};
+
+#[test]
+fn add_subpackage() {
+    let mut editor = ApkbuildEditor::new(SUBPACKAGES_APKBUILD);
+
+    assert!(editor.add_subpackage("$pkgname-openrc", true));
+    assert!(!editor.add_subpackage("$pkgname-dev:mydev", true));
+    assert!(editor.as_str() == SUBPACKAGES_APKBUILD.replace("-doc\"", "-doc $pkgname-openrc\""));
+}
+
+#[test]
+fn add_subpackage_with_stub() {
+    let mut editor = ApkbuildEditor::new(SUBPACKAGES_APKBUILD);
+
+    assert!(editor.add_subpackage("$pkgname-utils", true));
+    assert!(editor.add_subpackage("py3-$pkgname:_py3:noarch", true));
+    assert!(editor.add_subpackage("$pkgname-bash-completion", true));
+    assert!(editor.add_subpackage("$pkgname-tools", false));
+    assert!(
+        editor.as_str()
+            == indoc! {r#"
+        pkgname=sample
+        subpackages="$pkgname-bash-completion $pkgname-dev $pkgname-doc $pkgname-tools $pkgname-utils py3-$pkgname:_py3:noarch"
+        source="sample.tar.gz"
+
+        package() {
+        	make install
+        }
+
+        utils() {
+        	pkgdesc="$pkgdesc (utils)"
+
+        	# TODO: move files with amove
+        }
+
+        _py3() {
+        	pkgdesc="$pkgdesc (_py3)"
+
+        	# TODO: move files with amove
+        }
+
+        sha512sums="
+        aaaa  sample.tar.gz
+        "
+    "#}
+    );
+}
+
+#[test]
+fn add_subpackage_stub_at_end() {
+    let mut editor =
+        ApkbuildEditor::new("pkgname=sample\nsubpackages=\"\"\n\npackage() {\n\tmake install\n}\n");
+    editor.add_subpackage("$pkgname-package", true);
+
+    assert!(editor.as_str() == "pkgname=sample\nsubpackages=\"$pkgname-package\"\n\npackage() {\n\tmake install\n}\n");
+
+    editor.add_subpackage("$pkgname-extra", true);
+    assert!(editor.as_str().ends_with(
+        "}\n\nextra() {\n\tpkgdesc=\"$pkgdesc (extra)\"\n\n\t# TODO: move files with amove\n}\n"
+    ));
+}
+
+#[test]
+fn remove_subpackage() {
+    let mut editor = ApkbuildEditor::new(
+        SUBPACKAGES_APKBUILD.replace("-doc\"", "-doc $pkgname-utils:_utils:noarch\""),
+    );
+
+    assert!(editor.remove_subpackage("$pkgname-utils"));
+    assert!(!editor.remove_subpackage("$pkgname-utils"));
+    assert!(editor.as_str() == SUBPACKAGES_APKBUILD);
+}
+
+const SOURCE_APKBUILD: &str = indoc! {r#"
+    pkgname=sample
+    pkgver=1.2.3
+    source="https://downloads.example.org/$pkgname-$pkgver.tar.gz
+    	fix.patch
+    	"
+
+    sha512sums="
+    aaaa  sample-1.2.3.tar.gz
+    bbbb  fix.patch
+    "
+    "#};
+
+#[test]
+fn replace_source_same_filename() {
+    let mut editor = ApkbuildEditor::new(SOURCE_APKBUILD);
+
+    assert!(editor.replace_source(
+        "https://downloads.example.org/sample-1.2.3.tar.gz",
+        "https://mirror.example.org/$pkgname-$pkgver.tar.gz",
+    ));
+    assert!(
+        editor.as_str() == SOURCE_APKBUILD.replace("downloads.example.org", "mirror.example.org")
+    );
+}
+
+#[test]
+fn replace_source_new_filename() {
+    let mut editor = ApkbuildEditor::new(SOURCE_APKBUILD);
+
+    assert!(editor.replace_source(
+        "https://downloads.example.org/$pkgname-$pkgver.tar.gz",
+        "https://github.com/example/sample/archive/v${pkgver}.tar.gz",
+    ));
+    assert!(
+        editor.as_str()
+            == SOURCE_APKBUILD
+                .replace(
+                    "downloads.example.org/$pkgname-$pkgver",
+                    "github.com/example/sample/archive/v${pkgver}"
+                )
+                .replace("aaaa  sample-1.2.3.tar.gz", "aaaa  v1.2.3.tar.gz")
+    );
+}
+
+#[test]
+fn replace_source_missing() {
+    let mut editor = ApkbuildEditor::new(SOURCE_APKBUILD);
+
+    assert!(!editor.replace_source("https://example.org/sample-1.2.3.tar.gz", "foo"));
+    assert!(!editor.replace_source("fix", "foo"));
+    assert!(editor.as_str() == SOURCE_APKBUILD);
+}
+
+#[test]
+fn replace_source_local() {
+    let mut editor = ApkbuildEditor::new(SOURCE_APKBUILD);
+
+    assert!(editor.replace_source("fix.patch", "0001-fix.patch"));
+    assert!(editor.as_str() == SOURCE_APKBUILD.replace("fix.patch", "0001-fix.patch"));
+}
+
+#[test]
+fn migrate_checksums() {
+    let startdir = tempfile::tempdir().unwrap();
+    fs::write(startdir.path().join("foo.patch"), "foo\n").unwrap();
+
+    let apkbuild = Apkbuild {
+        source: vec![
+            Source::new("foo.patch", "foo.patch", "d3b07384d113edec49eaa6238ad5ff00")
+                .checksum_alg(ChecksumAlg::Md5),
+        ],
+        ..Default::default()
+    };
+    let mut editor = ApkbuildEditor::new(
+        "source=\"foo.patch\"\n\nmd5sums=\"d3b07384d113edec49eaa6238ad5ff00  foo.patch\"\n",
+    );
+
+    assert!(editor
+        .migrate_checksums(&apkbuild, startdir.path(), startdir.path())
+        .unwrap());
+    assert!(
+        editor.as_str()
+            == indoc! {r#"
+        source="foo.patch"
+
+        sha512sums="
+        0cf9180a764aba863a67b6d72f0918bc131c6772642cb2dce5a34f0a702f9470ddc2bf125c12198b1995c233c34b4afd346c54a2334c350a948a51b6e8b4e6b6  foo.patch
+        "
+    "#}
+    );
+    assert!(!editor
+        .migrate_checksums(&apkbuild, startdir.path(), startdir.path())
+        .unwrap());
+}
+
+#[test]
+fn migrate_checksums_mismatch() {
+    let startdir = tempfile::tempdir().unwrap();
+    fs::write(startdir.path().join("foo.patch"), "bar\n").unwrap();
+
+    let apkbuild = Apkbuild {
+        source: vec![
+            Source::new("foo.patch", "foo.patch", "d3b07384d113edec49eaa6238ad5ff00")
+                .checksum_alg(ChecksumAlg::Md5),
+        ],
+        ..Default::default()
+    };
+    let text = "source=\"foo.patch\"\n\nmd5sums=\"d3b07384d113edec49eaa6238ad5ff00  foo.patch\"\n";
+    let mut editor = ApkbuildEditor::new(text);
+
+    assert_let!(
+        Err(Error::ChecksumMismatch(..)) =
+            editor.migrate_checksums(&apkbuild, startdir.path(), startdir.path())
+    );
+    assert!(editor.as_str() == text);
+}
@@ -0,0 +1,134 @@
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use super::{Apkbuild, Error, Hasher, Source, SourceStatus};
+use crate::internal::macros::bail;
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// A downloader of the remote source files, the equivalent of `abuild fetch`.
+///
+/// Example:
+/// ```no_run
+/// use alpkit::apkbuild::{ApkbuildReader, SourceFetcher};
+///
+/// let apkbuild = ApkbuildReader::new().read_apkbuild("aports/main/foo/APKBUILD").unwrap();
+/// let paths = SourceFetcher::new("/var/cache/distfiles").fetch_all(&apkbuild).unwrap();
+/// ```
+pub struct SourceFetcher {
+    agent: ureq::Agent,
+    srcdest: PathBuf,
+}
+
+impl SourceFetcher {
+    /// Creates a new `SourceFetcher` that downloads files into the given
+    /// directory (`SRCDEST` in abuild).
+    pub fn new<P: AsRef<Path>>(srcdest: P) -> Self {
+        Self {
+            agent: ureq::AgentBuilder::new()
+                .timeout_connect(Duration::from_secs(30))
+                .user_agent(concat!("alpkit/", env!("CARGO_PKG_VERSION")))
+                .build(),
+            srcdest: srcdest.as_ref().to_owned(),
+        }
+    }
+
+    /// Sets the HTTP client used for downloading.
+    pub fn agent(&mut self, agent: ureq::Agent) -> &mut Self {
+        self.agent = agent;
+        self
+    }
+
+    /// Downloads all remote sources of the given APKBUILD and returns paths
+    /// of the downloaded files (in the same order as the remote sources).
+    /// See [`SourceFetcher::fetch`].
+    pub fn fetch_all(&self, apkbuild: &Apkbuild) -> Result<Vec<PathBuf>, Error> {
+        apkbuild
+            .source
+            .iter()
+            .filter(|source| source.is_remote())
+            .map(|source| self.fetch(source))
+            .collect()
+    }
+
+    /// Downloads the given remote source into the `srcdest` directory, unless
+    /// the file already exists there with the expected checksum, and returns
+    /// a path of the file.
+    ///
+    /// The checksum is computed while downloading; the file is written to
+    /// a temporary `<name>.part` file which is renamed only if the checksum
+    /// matches, otherwise it's deleted and [`Error::ChecksumMismatch`] is
+    /// returned.
+    pub fn fetch(&self, source: &Source) -> Result<PathBuf, Error> {
+        let dest = self.srcdest.join(&source.name);
+
+        if source.verify_file(&dest)? == SourceStatus::Ok {
+            return Ok(dest);
+        }
+
+        let url = source_url(&source.uri);
+        let resp = self
+            .agent
+            .get(url)
+            .call()
+            .map_err(|e| Error::Download(Box::new(e), url.to_owned()))?;
+
+        let part = self.srcdest.join(format!("{}.part", source.name));
+        let mut file = File::create(&part).map_err(|e| Error::WriteFile(e, part.clone()))?;
+        let mut writer = HashingWriter {
+            inner: &mut file,
+            hasher: Hasher::new(source.checksum_alg),
+        };
+
+        if let Err(e) = io::copy(&mut resp.into_reader(), &mut writer) {
+            let _ = fs::remove_file(&part);
+            bail!(Error::Io(e, "downloading file"));
+        }
+        let actual = writer.hasher.finish_hex();
+
+        if actual != source.checksum {
+            let _ = fs::remove_file(&part);
+            bail!(Error::ChecksumMismatch(
+                source.name.clone(),
+                source.checksum.clone(),
+                actual
+            ));
+        }
+        fs::rename(&part, &dest).map_err(|e| Error::WriteFile(e, dest.clone()))?;
+
+        Ok(dest)
+    }
+}
+
+/// Strips the abuild's `saveas-` pseudo-scheme from the URI, if present.
+fn source_url(uri: &str) -> &str {
+    uri.strip_prefix("saveas-")
+        .and_then(|s| s.rsplit_once('/'))
+        .map_or(uri, |(url, _)| url)
+}
+
+/// A writer that computes a digest of the data while writing it.
+struct HashingWriter<W> {
+    inner: W,
+    hasher: Hasher,
+}
+
+impl<W: Write> Write for HashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = self.inner.write(buf)?;
+        self.hasher.write_all(&buf[..len])?;
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+#[path = "fetch.test.rs"]
+mod test;
@@ -0,0 +1,79 @@
+use std::io::{BufRead, BufReader};
+use std::net::TcpListener;
+use std::thread;
+
+use super::*;
+use crate::apkbuild::ChecksumAlg;
+use crate::internal::test_utils::{assert, assert_let};
+
+/// Spawns an HTTP server that responds to `count` requests with the given body
+/// and returns its base URL.
+fn serve(body: &'static [u8], count: usize) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    thread::spawn(move || {
+        for stream in listener.incoming().take(count) {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(&stream);
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            )
+            .unwrap();
+            stream.write_all(body).unwrap();
+        }
+    });
+    format!("http://{addr}")
+}
+
+#[test]
+fn source_fetcher_fetch() {
+    let srcdest = tempfile::tempdir().unwrap();
+    let url = serve(b"hello", 1);
+
+    let source = Source::new(
+        "hello.txt",
+        format!("{url}/hello.txt"),
+        "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824",
+    )
+    .checksum_alg(ChecksumAlg::Sha256);
+    let fetcher = SourceFetcher::new(srcdest.path());
+
+    assert_let!(Ok(path) = fetcher.fetch(&source));
+    assert!(path == srcdest.path().join("hello.txt"));
+    assert!(fs::read(&path).unwrap() == b"hello");
+
+    // The file is already downloaded, so it shouldn't be fetched again (the
+    // server accepts only one request).
+    assert_let!(Ok(_) = fetcher.fetch(&source));
+}
+
+#[test]
+fn source_fetcher_fetch_mismatch() {
+    let srcdest = tempfile::tempdir().unwrap();
+    let url = serve(b"hello", 1);
+
+    let source = Source::new("hello.txt", format!("{url}/hello.txt"), "f00");
+
+    assert_let!(
+        Err(Error::ChecksumMismatch(name, ..)) = SourceFetcher::new(srcdest.path()).fetch(&source)
+    );
+    assert!(name == "hello.txt");
+    assert!(!srcdest.path().join("hello.txt").exists());
+    assert!(!srcdest.path().join("hello.txt.part").exists());
+}
+
+#[test]
+fn test_source_url() {
+    assert!(source_url("https://example.org/foo.tar.gz") == "https://example.org/foo.tar.gz");
+    assert!(
+        source_url("saveas-https://example.org/foo?v=1/foo.tar.gz")
+            == "https://example.org/foo?v=1"
+    );
+}
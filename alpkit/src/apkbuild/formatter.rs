@@ -0,0 +1,414 @@
+use std::fmt::{self, Write as _};
+use std::mem;
+
+use super::{
+    assignment_at, is_function_start, is_variable_name, parse_secfixes_lenient, Assignment,
+    VARIABLES_ORDER,
+};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// A formatter that normalizes an APKBUILD into the canonical form:
+///
+/// - the known variables in the header are sorted in the conventional order
+///   (see [`ApkbuildFormatter::reorder`]); other variables and comments are
+///   moved together with the preceding known variable,
+/// - `pkgname`, `pkgver`, `pkgrel` and the priorities are unquoted, other known
+///   variables are double-quoted (unless it would change the meaning),
+/// - `source` with multiple entries is written one entry per line and lines of
+///   other multi-line values are indented by a tab, with the closing quote on
+///   a separate line,
+/// - `sha512sums` (and the legacy checksums) are written as `abuild checksum`
+///   does,
+/// - the secfixes comment block is indented as described in the
+///   [Alpine wiki](https://wiki.alpinelinux.org/wiki/APKBUILD_Reference#secfixes).
+///
+/// Functions and other commands are left intact.
+///
+/// Example:
+/// ```no_run
+/// use alpkit::apkbuild::ApkbuildFormatter;
+///
+/// let text = std::fs::read_to_string("aports/main/sample/APKBUILD").unwrap();
+/// for diff in ApkbuildFormatter::new().check(&text) {
+///     print!("{diff}");
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ApkbuildFormatter {
+    reorder: bool,
+}
+
+impl ApkbuildFormatter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets if the known variables in the header should be sorted in the
+    /// conventional order. The default is `true`.
+    pub fn reorder(&mut self, cond: bool) -> &mut Self {
+        self.reorder = cond;
+        self
+    }
+
+    /// Formats the given APKBUILD contents.
+    pub fn format(&self, apkbuild: &str) -> String {
+        let mut chunks = parse_chunks(apkbuild);
+
+        if self.reorder {
+            let header_len = chunks
+                .iter()
+                .position(|c| c.kind == ChunkKind::Other)
+                .unwrap_or(chunks.len());
+            reorder_header(&mut chunks[..header_len]);
+        }
+        chunks.iter().map(|c| c.text.as_str()).collect()
+    }
+
+    /// Formats the given APKBUILD contents and returns the differences between
+    /// the original and the formatted text. If the returned vector is empty,
+    /// the APKBUILD is already formatted.
+    pub fn check(&self, apkbuild: &str) -> Vec<LineDiff> {
+        diff_lines(apkbuild, &self.format(apkbuild))
+    }
+}
+
+impl Default for ApkbuildFormatter {
+    fn default() -> Self {
+        Self { reorder: true }
+    }
+}
+
+/// A difference between the original and the formatted APKBUILD.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineDiff {
+    /// The line number (1-based) in the original text where the difference
+    /// starts.
+    pub line: usize,
+
+    /// The original lines.
+    pub removed: Vec<String>,
+
+    /// The formatted lines.
+    pub added: Vec<String>,
+}
+
+impl fmt::Display for LineDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "@@ line {} @@", self.line)?;
+        for line in &self.removed {
+            writeln!(f, "-{line}")?;
+        }
+        for line in &self.added {
+            writeln!(f, "+{line}")?;
+        }
+        Ok(())
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// A formatted top-level part of the APKBUILD.
+#[derive(Debug)]
+struct Chunk {
+    kind: ChunkKind,
+    text: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChunkKind {
+    /// An assignment of a variable with the given sort key.
+    Known(usize),
+    /// An assignment of a variable not in [`VARIABLES_ORDER`].
+    Unknown,
+    /// Comment or blank line(s).
+    Trivia,
+    /// Function or any other command.
+    Other,
+}
+
+/// Splits the APKBUILD into top-level chunks and formats them.
+fn parse_chunks(text: &str) -> Vec<Chunk> {
+    let mut chunks = Vec::new();
+    let mut pos = 0;
+    let mut in_function = false;
+
+    while pos < text.len() {
+        let line_end = text[pos..].find('\n').map_or(text.len(), |i| pos + i + 1);
+        let line = &text[pos..line_end];
+
+        if in_function {
+            in_function = line.trim_end() != "}";
+        } else if let Some((name, a)) = line
+            .split_once('=')
+            .map(|(name, _)| name)
+            .filter(|name| is_variable_name(name))
+            .and_then(|name| assignment_at(text, pos, name).map(|a| (name, a)))
+        {
+            let end = text[a.end..]
+                .find('\n')
+                .map_or(text.len(), |i| a.end + i + 1);
+            let kind = match sort_key(name) {
+                Some(key) => ChunkKind::Known(key),
+                None => ChunkKind::Unknown,
+            };
+            chunks.push(Chunk {
+                kind,
+                text: format_assignment(text, name, &a) + &text[a.end..end],
+            });
+            pos = end;
+            continue;
+        } else if is_secfixes_start(line) {
+            let end = text[line_end..]
+                .split_inclusive('\n')
+                .take_while(|s| s.starts_with('#') && !s[1..].trim().is_empty())
+                .fold(line_end, |end, s| end + s.len());
+            chunks.push(Chunk {
+                kind: ChunkKind::Trivia,
+                text: format_secfixes(&text[pos..end]),
+            });
+            pos = end;
+            continue;
+        } else if is_function_start(line) {
+            in_function = !line.trim_end().ends_with('}');
+        }
+
+        let kind = if in_function || !(line.trim().is_empty() || line.starts_with('#')) {
+            ChunkKind::Other
+        } else {
+            ChunkKind::Trivia
+        };
+        // Function bodies are never reordered, so they don't need to be split.
+        match chunks.last_mut() {
+            Some(last) if kind == ChunkKind::Other && last.kind == ChunkKind::Other => {
+                last.text.push_str(line)
+            }
+            _ => chunks.push(Chunk {
+                kind,
+                text: line.to_owned(),
+            }),
+        }
+        pos = line_end;
+    }
+    chunks
+}
+
+/// Sorts the known variables in the header in the conventional order. Unknown
+/// variables are moved together with the preceding known variable, comments
+/// together with the following variable.
+fn reorder_header(chunks: &mut [Chunk]) {
+    let first_var = match chunks.iter().position(|c| c.kind != ChunkKind::Trivia) {
+        Some(idx) => idx,
+        None => return,
+    };
+    let last_var = chunks
+        .iter()
+        .rposition(|c| c.kind != ChunkKind::Trivia)
+        .unwrap_or(first_var); // this cannot fail
+
+    // Assign a sort key to each chunk between the preamble and trailing trivia.
+    let mut keys = Vec::with_capacity(last_var - first_var + 1);
+    let mut current = 0;
+    let mut pending = 0;
+    for chunk in &chunks[first_var..=last_var] {
+        match chunk.kind {
+            ChunkKind::Known(key) => {
+                keys.extend((0..=pending).map(|_| key));
+                current = key;
+                pending = 0;
+            }
+            ChunkKind::Trivia => pending += 1,
+            _ => {
+                keys.extend((0..=pending).map(|_| current));
+                pending = 0;
+            }
+        }
+    }
+
+    let mut indexed: Vec<_> = keys.into_iter().enumerate().collect();
+    indexed.sort_by_key(|&(_, key)| key); // stable
+    let mut taken: Vec<_> = chunks[first_var..=last_var]
+        .iter_mut()
+        .map(|c| Some(mem::take(&mut c.text)))
+        .collect();
+    for (chunk, (idx, _)) in chunks[first_var..=last_var].iter_mut().zip(indexed) {
+        chunk.text = taken[idx].take().unwrap_or_default();
+    }
+}
+
+/// Returns the sort key of the given variable, or `None` if it's not known.
+fn sort_key(name: &str) -> Option<usize> {
+    VARIABLES_ORDER
+        .iter()
+        .position(|v| *v == name)
+        .map(|idx| idx * 2 + 1)
+        .or_else(|| {
+            // depends_<subpkg> go right after checkdepends.
+            name.strip_prefix("depends_")
+                .and_then(|_| VARIABLES_ORDER.iter().position(|v| *v == "checkdepends"))
+                .map(|idx| idx * 2 + 2)
+        })
+}
+
+/// Formats the assignment, or returns it as is if it's not a known variable or
+/// it cannot be reformatted without changing its meaning.
+fn format_assignment(text: &str, name: &str, a: &Assignment) -> String {
+    let original = &text[a.start..a.end];
+    let value = &text[a.value.clone()];
+
+    match name {
+        "pkgname" | "pkgver" | "pkgrel" | "provider_priority" | "replaces_priority" => {
+            let is_plain = value
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '+' | '-'));
+            if a.quote.is_some() && !value.is_empty() && is_plain {
+                format!("{name}={value}")
+            } else {
+                original.to_owned()
+            }
+        }
+        "sha512sums" | "sha256sums" | "md5sums" => {
+            let words: Vec<_> = value.split_ascii_whitespace().collect();
+            if words.len() % 2 != 0 || value.contains(['$', '`', '\\']) {
+                return original.to_owned();
+            }
+            let mut out = format!("{name}=\"\n");
+            for pair in words.chunks(2) {
+                writeln!(out, "{}  {}", pair[0], pair[1]).unwrap();
+            }
+            out.push('"');
+            out
+        }
+        _ if sort_key(name).is_some() => {
+            let can_double_quote = match a.quote {
+                Some('\'') => !value.contains(['"', '$', '`', '\\']),
+                Some(_) => true,
+                None => !value.contains(['"', '\'', '`', '\\', '(']),
+            };
+            if !can_double_quote {
+                return original.to_owned();
+            }
+            let words: Vec<_> = value.split_ascii_whitespace().collect();
+
+            let multiline = if name == "source" {
+                words.len() > 1
+            } else {
+                value.contains('\n')
+            };
+            if multiline {
+                let mut out = format!("{name}=\"\n");
+                let lines = if name == "source" {
+                    words.iter().map(|s| s.to_string()).collect()
+                } else {
+                    value
+                        .lines()
+                        .map(|line| line.split_ascii_whitespace().collect::<Vec<_>>().join(" "))
+                        .filter(|line| !line.is_empty())
+                        .collect::<Vec<_>>()
+                };
+                for line in lines {
+                    writeln!(out, "\t{line}").unwrap();
+                }
+                out.push_str("\t\"");
+                out
+            } else if name == "source" {
+                format!("{name}=\"{}\"", words.join(" "))
+            } else {
+                format!("{name}=\"{value}\"")
+            }
+        }
+        _ => original.to_owned(),
+    }
+}
+
+/// Formats the secfixes comment block, or returns it as is if it's malformed.
+fn format_secfixes(block: &str) -> String {
+    if block.lines().skip(1).any(|line| line[1..].contains(" #")) {
+        return block.to_owned();
+    }
+    let (secfixes, _) = parse_secfixes_lenient(block);
+
+    let mut out = String::from("# secfixes:\n");
+    for secfix in secfixes {
+        writeln!(out, "#   {}:", secfix.version).unwrap();
+        for fix in secfix.fixes {
+            writeln!(out, "#     - {fix}").unwrap();
+        }
+    }
+    // Only whitespace may be changed. If the lenient parser has recovered
+    // from a mistake that affects the content (e.g. it has split multiple IDs
+    // on one line or skipped an unparseable line), the block is left as is.
+    if secfixes_words(block).ne(secfixes_words(&out)) {
+        return block.to_owned();
+    }
+    out
+}
+
+/// Returns the words of the secfixes comment block, except the first line.
+fn secfixes_words(block: &str) -> impl Iterator<Item = &str> {
+    block.lines().skip(1).flat_map(|line| {
+        line.strip_prefix('#')
+            .unwrap_or(line)
+            .split_ascii_whitespace()
+    })
+}
+
+fn is_secfixes_start(line: &str) -> bool {
+    line.strip_prefix('#')
+        .map(|s| matches!(s.trim(), "secfixes:" | "secfixes"))
+        .unwrap_or(false)
+}
+
+/// Computes a line-based diff between `old` and `new` using LCS.
+fn diff_lines(old: &str, new: &str) -> Vec<LineDiff> {
+    let old: Vec<_> = old.lines().collect();
+    let new: Vec<_> = new.lines().collect();
+    let (n, m) = (old.len(), new.len());
+
+    // lcs[i][j] is the length of LCS of old[i..] and new[j..].
+    let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut diffs: Vec<LineDiff> = Vec::new();
+    let mut in_hunk = false;
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && old[i] == new[j] {
+            in_hunk = false;
+            i += 1;
+            j += 1;
+            continue;
+        }
+        if !in_hunk {
+            diffs.push(LineDiff {
+                line: i + 1,
+                removed: Vec::new(),
+                added: Vec::new(),
+            });
+            in_hunk = true;
+        }
+        let hunk = diffs.last_mut().unwrap(); // this cannot fail
+        if j < m && (i == n || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            hunk.added.push(new[j].to_owned());
+            j += 1;
+        } else {
+            hunk.removed.push(old[i].to_owned());
+            i += 1;
+        }
+    }
+    diffs
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+#[path = "formatter.test.rs"]
+mod test;
@@ -0,0 +1,139 @@
+use std::fs;
+
+use indoc::indoc;
+
+use super::*;
+use crate::internal::test_utils::{assert, S};
+
+#[test]
+fn format() {
+    let input = indoc! {r#"
+        # Contributor: Kevin Flynn <kevin.flynn@encom.com>
+        # Maintainer: Kevin Flynn <kevin.flynn@encom.com>
+        pkgname="sample"
+        pkgver='1.2.0'
+        pkgrel=0
+        pkgdesc='A sample aport'
+        url=https://example.org/sample
+        license="MIT"
+        arch="all"
+        _commit=abcdef # pinned
+        makedepends="
+            cargo   openssl-dev
+          zlib-dev"
+        # the runtime deps
+        depends=''
+        source="https://example.org/sample/$pkgname-$pkgver.tar.gz sample.initd"
+        options='!check $foo'
+
+        #secfixes:
+        #  1.2.0-r0:
+        #  - CVE-2022-12345
+        #   - CVE-2022-12346
+
+        build() {
+        	pkgdesc=foo make
+        }
+
+        sha512sums="aaaa  sample-1.2.0.tar.gz
+          bbbb  sample.initd"
+    "#};
+
+    assert!(
+        ApkbuildFormatter::new().format(input)
+            == indoc! {r#"
+        # Contributor: Kevin Flynn <kevin.flynn@encom.com>
+        # Maintainer: Kevin Flynn <kevin.flynn@encom.com>
+        pkgname=sample
+        pkgver=1.2.0
+        pkgrel=0
+        pkgdesc="A sample aport"
+        url="https://example.org/sample"
+        arch="all"
+        _commit=abcdef # pinned
+        license="MIT"
+        # the runtime deps
+        depends=""
+        makedepends="
+        	cargo openssl-dev
+        	zlib-dev
+        	"
+        source="
+        	https://example.org/sample/$pkgname-$pkgver.tar.gz
+        	sample.initd
+        	"
+        options='!check $foo'
+
+        # secfixes:
+        #   1.2.0-r0:
+        #     - CVE-2022-12345
+        #     - CVE-2022-12346
+
+        build() {
+        	pkgdesc=foo make
+        }
+
+        sha512sums="
+        aaaa  sample-1.2.0.tar.gz
+        bbbb  sample.initd
+        "
+    "#}
+    );
+}
+
+#[test]
+fn format_secfixes_keeps_content() {
+    for block in [
+        "# secfixes:\n#   1.0-r0:\n#   - CVE-2022-1 CVE-2022-2\n",
+        "# secfixes:\n#   1.0-r0\n#     - CVE-2022-1\n",
+        "# secfixes:\n#   1.0-r0:\n#     -CVE-2022-1\n",
+        "# secfixes:\n#   what is this\n#   1.0-r0:\n#     - CVE-2022-1\n",
+    ] {
+        let input = format!("pkgname=sample\n\n{block}");
+        assert!(ApkbuildFormatter::new().format(&input) == input, "{block}");
+    }
+}
+
+#[test]
+fn format_without_reorder() {
+    let input = "pkgver=1.0\npkgname='foo'\n";
+
+    assert!(ApkbuildFormatter::new().reorder(false).format(input) == "pkgver=1.0\npkgname=foo\n");
+    assert!(ApkbuildFormatter::new().format(input) == "pkgname=foo\npkgver=1.0\n");
+}
+
+#[test]
+fn format_is_idempotent() {
+    let input = fs::read_to_string("../fixtures/aports/s6/APKBUILD").unwrap();
+    let formatter = ApkbuildFormatter::new();
+    let formatted = formatter.format(&input);
+
+    assert!(formatter.format(&formatted) == formatted);
+    assert!(formatter.check(&formatted).is_empty());
+}
+
+#[test]
+fn check() {
+    let input = indoc! {r#"
+        pkgname=foo
+        pkgver="1.0"
+        pkgrel=0
+        url=https://example.org
+    "#};
+
+    assert!(
+        ApkbuildFormatter::new().check(input)
+            == vec![
+                LineDiff {
+                    line: 2,
+                    removed: vec![S!("pkgver=\"1.0\"")],
+                    added: vec![S!("pkgver=1.0")],
+                },
+                LineDiff {
+                    line: 4,
+                    removed: vec![S!("url=https://example.org")],
+                    added: vec![S!("url=\"https://example.org\"")],
+                },
+            ]
+    );
+}
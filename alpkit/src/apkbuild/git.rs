@@ -0,0 +1,116 @@
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+use log::debug;
+use tar::Archive;
+
+use super::{Apkbuild, ApkbuildReader, Error};
+use crate::internal::macros::bail;
+
+impl ApkbuildReader {
+    /// Reads the APKBUILD at the `path` (relative to the repository root,
+    /// e.g. `main/foo/APKBUILD`) as of the given commit or ref `rev` of the
+    /// git repository (e.g. aports) at `repo`, without checking it out.
+    ///
+    /// The APKBUILD's directory (with the helper files, e.g. patches or
+    /// shared functions) is materialized from the git objects into a
+    /// temporary directory, which is removed afterwards. It requires the
+    /// `git` command (2.24 or newer).
+    ///
+    /// Example:
+    /// ```no_run
+    /// use alpkit::apkbuild::ApkbuildReader;
+    ///
+    /// let apkbuild = ApkbuildReader::new()
+    ///     .read_apkbuild_at_rev("aports", "3.18-stable~10", "main/zlib/APKBUILD")
+    ///     .unwrap();
+    /// ```
+    pub fn read_apkbuild_at_rev<P, Q>(&self, repo: P, rev: &str, path: Q) -> Result<Apkbuild, Error>
+    where
+        P: AsRef<Path>,
+        Q: AsRef<Path>,
+    {
+        let (repo, path) = (repo.as_ref(), path.as_ref());
+        debug!(
+            "reading APKBUILD {} at {rev} from {}",
+            path.display(),
+            repo.display()
+        );
+
+        let tempdir = tempfile::Builder::new()
+            .prefix("alpkit-git.")
+            .tempdir()
+            .map_err(|e| Error::Io(e, "creating temporary directory"))?;
+
+        let tarball = git(repo)
+            .args(["archive", "--format=tar", "--end-of-options", rev])
+            .arg(path.parent().unwrap_or(path))
+            .output_ok()?;
+
+        Archive::new(tarball.as_slice())
+            .unpack(tempdir.path())
+            .map_err(|e| Error::Io(e, "extracting files from git"))?;
+
+        self.read_apkbuild(tempdir.path().join(path))
+    }
+}
+
+/// Returns the hash of the last commit that touched the APKBUILD at the
+/// `filepath` in a git checkout (e.g. aports), i.e. the value that abuild puts
+/// into the `commit` field of `.PKGINFO`. Returns `None` if the file hasn't
+/// been committed yet. It requires the `git` command.
+///
+/// This can be used to fill the `commit` of the [expected
+/// PkgInfo](Apkbuild::expected_pkginfo), or to verify it in a built package
+/// (see [`check_commit`](crate::consistency::check_commit)).
+pub fn last_commit<P: AsRef<Path>>(filepath: P) -> Result<Option<String>, Error> {
+    let filepath = filepath.as_ref();
+    let (dir, filename) = match (filepath.parent(), filepath.file_name()) {
+        (Some(dir), Some(filename)) if dir != Path::new("") => (dir, filename),
+        (_, Some(filename)) => (Path::new("."), filename),
+        _ => bail!(Error::ReadFile(
+            io::Error::new(io::ErrorKind::InvalidInput, "not a file path"),
+            filepath.to_owned()
+        )),
+    };
+
+    let stdout = git(dir)
+        .args(["log", "-n1", "--format=%H", "--"])
+        .arg(filename)
+        .output_ok()?;
+    let commit = String::from_utf8_lossy(&stdout).trim().to_owned();
+
+    Ok((!commit.is_empty()).then_some(commit))
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Returns a `git` command with the working directory set to the `repo`.
+pub(crate) fn git(repo: &Path) -> Command {
+    let mut cmd = Command::new("git");
+    cmd.arg("-C").arg(repo);
+    cmd
+}
+
+pub(crate) trait CommandExt {
+    /// Executes the command and returns its stdout if it exited successfully.
+    fn output_ok(&mut self) -> Result<Vec<u8>, Error>;
+}
+
+impl CommandExt for Command {
+    fn output_ok(&mut self) -> Result<Vec<u8>, Error> {
+        let output = self.output().map_err(Error::SpawnGit)?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            bail!(Error::Git(stderr.trim_end().to_owned()));
+        }
+        Ok(output.stdout)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+#[path = "git.test.rs"]
+mod test;
@@ -0,0 +1,109 @@
+use std::fs;
+
+use indoc::indoc;
+
+use super::*;
+use crate::internal::test_utils::{assert, assert_let};
+
+fn apkbuild(pkgver: &str) -> String {
+    format!(
+        indoc! {r#"
+            pkgname=sample
+            pkgver={}
+            pkgrel=0
+            pkgdesc="A sample aport for testing"
+            url="https://example.org/sample"
+            arch="noarch"
+            license="MIT"
+            source="sample.initd"
+            . ./common.sh
+            sha512sums="$(common_checksum)"
+        "#},
+        pkgver
+    )
+}
+
+fn commit_all(repo: &Path, message: &str) {
+    for args in [&["add", "-A"][..], &["commit", "-q", "-m", message]] {
+        git(repo)
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.org"])
+            .args(args)
+            .output_ok()
+            .unwrap();
+    }
+}
+
+#[test]
+fn read_apkbuild_at_rev() {
+    let repo = tempfile::tempdir().unwrap();
+    let pkgdir = repo.path().join("main/sample");
+    fs::create_dir_all(&pkgdir).unwrap();
+    git(repo.path()).args(["init", "-q"]).output_ok().unwrap();
+
+    fs::write(pkgdir.join("APKBUILD"), apkbuild("1.0.0")).unwrap();
+    fs::write(
+        pkgdir.join("common.sh"),
+        "common_checksum() { echo 'abcd  sample.initd'; }\n",
+    )
+    .unwrap();
+    commit_all(repo.path(), "sample: new aport");
+
+    fs::write(pkgdir.join("APKBUILD"), apkbuild("2.0.0")).unwrap();
+    commit_all(repo.path(), "sample: upgrade to 2.0.0");
+
+    let reader = ApkbuildReader::new();
+
+    let old = reader
+        .read_apkbuild_at_rev(repo.path(), "HEAD~1", "main/sample/APKBUILD")
+        .unwrap();
+    assert!(old.pkgver == "1.0.0");
+    assert!(old.source[0].checksum == "abcd");
+
+    let new = reader
+        .read_apkbuild_at_rev(repo.path(), "HEAD", "main/sample/APKBUILD")
+        .unwrap();
+    assert!(new.pkgver == "2.0.0");
+
+    assert_let!(
+        Err(Error::Git(_)) =
+            reader.read_apkbuild_at_rev(repo.path(), "HEAD", "main/missing/APKBUILD")
+    );
+    assert_let!(
+        Err(Error::Git(_)) =
+            reader.read_apkbuild_at_rev(repo.path(), "--output=/tmp/x", "main/sample/APKBUILD")
+    );
+}
+
+#[test]
+fn last_commit_of_apkbuild() {
+    let repo = tempfile::tempdir().unwrap();
+    let pkgdir = repo.path().join("main/sample");
+    fs::create_dir_all(&pkgdir).unwrap();
+    git(repo.path()).args(["init", "-q"]).output_ok().unwrap();
+
+    fs::write(repo.path().join("README"), "unrelated").unwrap();
+    commit_all(repo.path(), "add README");
+
+    fs::write(pkgdir.join("APKBUILD"), apkbuild("1.0.0")).unwrap();
+    assert!(last_commit(pkgdir.join("APKBUILD")).unwrap() == None);
+
+    commit_all(repo.path(), "sample: new aport");
+    let head = git(repo.path())
+        .args(["rev-parse", "HEAD"])
+        .output_ok()
+        .unwrap();
+    let head = String::from_utf8(head).unwrap();
+
+    fs::write(repo.path().join("README"), "still unrelated").unwrap();
+    commit_all(repo.path(), "update README");
+
+    assert!(last_commit(pkgdir.join("APKBUILD")).unwrap().as_deref() == Some(head.trim()));
+}
+
+#[test]
+fn last_commit_invalid_path() {
+    for path in ["/", ".."] {
+        assert_let!(Err(Error::ReadFile(_, p)) = last_commit(path));
+        assert!(p == Path::new(path));
+    }
+}
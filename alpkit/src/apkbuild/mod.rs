@@ -0,0 +1,2225 @@
+mod editor;
+#[cfg(feature = "http")]
+mod fetch;
+mod formatter;
+#[cfg(feature = "git")]
+mod git;
+mod template;
+#[cfg(all(unix, feature = "shell-workers"))]
+mod worker;
+mod writer;
+
+use std::collections::{BTreeMap, HashMap};
+use std::convert::Infallible;
+use std::ffi::{OsStr, OsString};
+use std::fmt::{self, Write as _};
+use std::fs;
+use std::io::{self, Read, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command, Stdio};
+use std::str::FromStr;
+#[cfg(all(unix, feature = "shell-workers"))]
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+#[cfg(feature = "shell-timeout")]
+use std::time::Instant;
+
+use field_names::FieldNames;
+use log::{debug, trace};
+use md5::Md5;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256, Sha512};
+use thiserror::Error;
+
+#[cfg(feature = "shell-timeout")]
+use process_control::{ChildExt, Control};
+
+use crate::dependency::Dependency;
+use crate::internal::exit_status_error::{ExitStatusError, ExitStatusExt};
+use crate::internal::key_value_vec_map::{self, KeyValueLike};
+use crate::internal::macros::bail;
+#[cfg(feature = "shell-timeout")]
+use crate::internal::pipe_collector::PipeCollector;
+use crate::internal::serde_key_value;
+use crate::internal::std_ext::{ChunksExactIterator, Tap};
+use crate::license::{LicenseExpr, LicenseParseError};
+use crate::package::PkgInfo;
+
+pub use editor::*;
+#[cfg(feature = "http")]
+pub use fetch::*;
+pub use formatter::*;
+#[cfg(feature = "git")]
+pub use git::last_commit;
+pub use template::*;
+pub use writer::*;
+
+#[cfg(all(unix, feature = "shell-workers"))]
+use worker::ShellWorker;
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error(transparent)]
+    Decode(#[from] serde_key_value::Error),
+
+    #[error("shell exited unsuccessfully: '{1}'")]
+    Evaluate(#[source] ExitStatusError, String),
+
+    #[error("I/O error occurred when {1}")]
+    Io(#[source] io::Error, &'static str),
+
+    #[error("syntax error in secfixes on line {0}: '{1}'")]
+    MalformedSecfixes(usize, String),
+
+    #[error("checksum mismatch for '{0}': expected {1}, but got {2}")]
+    ChecksumMismatch(String, String, String),
+
+    #[cfg(feature = "http")]
+    #[error("failed to download '{1}'")]
+    Download(#[source] Box<ureq::Error>, String),
+
+    #[error("missing checksum for: '{0}'")]
+    MissingChecksum(String),
+
+    #[error("variable '{0}' is not assigned in APKBUILD")]
+    MissingVariable(String),
+
+    #[error("invalid value of variable '{0}': '{1}'")]
+    InvalidVariable(String, String),
+
+    #[error("invalid mailbox, expected 'Full Name <email@example.org>': '{0}'")]
+    InvalidMailbox(String),
+
+    #[error("failed to read file '{1}'")]
+    ReadFile(#[source] io::Error, PathBuf),
+
+    #[error("failed to execute shell '{1}'")]
+    SpawnShell(#[source] io::Error, String),
+
+    #[cfg(feature = "git")]
+    #[error("git exited unsuccessfully: '{0}'")]
+    Git(String),
+
+    #[cfg(feature = "git")]
+    #[error("failed to execute git")]
+    SpawnGit(#[source] io::Error),
+
+    /// The shell has been killed after exceeding the time limit. It contains
+    /// the elapsed time and stdout and stderr captured until then.
+    #[error("exceeded timeout {} ms", .elapsed.as_millis())]
+    Timeout {
+        elapsed: Duration,
+        stdout: String,
+        stderr: String,
+    },
+
+    #[error("failed to write file '{1}'")]
+    WriteFile(#[source] io::Error, PathBuf),
+}
+
+#[derive(Debug, Default, PartialEq, Deserialize, Serialize, FieldNames)]
+#[cfg_attr(feature = "schema-gen", derive(schemars::JsonSchema))]
+pub struct Apkbuild {
+    /// The name and email address of the package's maintainer. It should be in
+    /// the RFC5322 mailbox format, e.g. `Kevin Flynn <kevin.flynn@encom.com>`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[field_names(skip)] // parsed from comments
+    pub maintainer: Option<String>,
+
+    #[serde(default)]
+    #[field_names(skip)] // parsed from comments
+    pub contributors: Vec<String>,
+
+    /// Custom comment attributes (`# Key: value`) requested by
+    /// [`ApkbuildReader::comment_attributes`], keyed by the attribute name.
+    /// If an attribute occurs multiple times, all values are collected.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    #[field_names(skip)] // parsed from comments
+    pub attributes: BTreeMap<String, Vec<String>>,
+
+    /// The name of the main package built from this APKBUILD.
+    pub pkgname: String,
+
+    /// The version of the software being packaged.
+    pub pkgver: String,
+
+    /// Alpine package release number (starts at 0).
+    pub pkgrel: u32,
+
+    /// A brief, one-line description of the APKBUILD's main package.
+    pub pkgdesc: String,
+
+    /// Homepage of the software being packaged.
+    pub url: String,
+
+    /// Package architecture(s) to build for. It doesn't contain `all`, `noarch`
+    /// or negated architectures -- `arch` is resolved on APKBUILD parsing as
+    /// per [`ApkbuildReader::arch_all`].
+    #[serde(default)]
+    pub arch: Vec<String>,
+
+    /// The `arch` as specified in the APKBUILD, before resolving keywords and
+    /// negated architectures, e.g. `all !s390x` or `noarch`.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    #[field_names(skip)] // parsed from `arch`
+    pub arch_raw: String,
+
+    /// License(s) of the source code from which the main package (and typically
+    /// also all subpackages) is built. It should be a SPDX license expression
+    /// or a list of SPDX license identifiers separated by a space.
+    pub license: String,
+
+    /// Manually specified run-time dependencies of the main package. This
+    /// doesn't include dependencies that are autodiscovered by the `abuild`
+    /// tool during the build of the package (e.g. shared object dependencies).
+    #[serde(default, with = "key_value_vec_map")]
+    #[cfg_attr(feature = "schema-gen", schemars(with = "crate::schema::Dependencies"))]
+    pub depends: Vec<Dependency>,
+
+    /// Build-time dependencies.
+    #[serde(default, with = "key_value_vec_map")]
+    #[cfg_attr(feature = "schema-gen", schemars(with = "crate::schema::Dependencies"))]
+    pub makedepends: Vec<Dependency>,
+
+    #[serde(default, with = "key_value_vec_map")]
+    #[cfg_attr(feature = "schema-gen", schemars(with = "crate::schema::Dependencies"))]
+    pub makedepends_build: Vec<Dependency>,
+
+    #[serde(default, with = "key_value_vec_map")]
+    #[cfg_attr(feature = "schema-gen", schemars(with = "crate::schema::Dependencies"))]
+    pub makedepends_host: Vec<Dependency>,
+
+    /// Dependencies that are only required during the check phase (i.e. for
+    /// running tests).
+    #[serde(default, with = "key_value_vec_map")]
+    #[cfg_attr(feature = "schema-gen", schemars(with = "crate::schema::Dependencies"))]
+    pub checkdepends: Vec<Dependency>,
+
+    /// Run-time dependencies of subpackages specified in the `depends_<suffix>`
+    /// variables (e.g. `depends_dev`, `depends_doc`), keyed by the suffix.
+    /// These are used by the abuild's default split functions of the
+    /// `$pkgname-<suffix>` subpackages.
+    #[serde(default, with = "key_value_vec_map::nested")]
+    #[cfg_attr(
+        feature = "schema-gen",
+        schemars(with = "std::collections::BTreeMap<String, crate::schema::Dependencies>")
+    )]
+    #[field_names(skip)] // evaluated dynamically
+    pub subpackage_depends: BTreeMap<String, Vec<Dependency>>,
+
+    /// A set of dependencies that, if all installed, induce installation of the
+    /// APKBUILD's main package. `install_if` can be used when a package needs
+    /// to be installed when some packages are already installed or are in the
+    /// dependency tree.
+    #[serde(default, with = "key_value_vec_map")]
+    #[cfg_attr(feature = "schema-gen", schemars(with = "crate::schema::Dependencies"))]
+    pub install_if: Vec<Dependency>,
+
+    /// System users to be created when building the package(s).
+    #[serde(default)]
+    pub pkgusers: Vec<String>,
+
+    /// System groups to be created when building the package(s).
+    #[serde(default)]
+    pub pkggroups: Vec<String>,
+
+    /// Providers (packages) that the APKBUILD's main package provides.
+    #[serde(default, with = "key_value_vec_map")]
+    #[cfg_attr(feature = "schema-gen", schemars(with = "crate::schema::Dependencies"))]
+    pub provides: Vec<Dependency>,
+
+    /// A numeric value which is used by apk-tools to break ties when choosing
+    /// a virtual package to satisfy a dependency. Higher values have higher
+    /// priority.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider_priority: Option<u32>,
+
+    /// The prefix for all providers derived by parsing pkg-config's name or
+    /// `Requires:`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pcprefix: Option<String>,
+
+    /// The prefix for all providers derived by parsing shared objects.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sonameprefix: Option<String>,
+
+    /// The packages whose files the APKBUILD's main package is allowed to
+    /// overwrite (i.e. both can be installed even if they have conflicting
+    /// files).
+    #[serde(default, with = "key_value_vec_map")]
+    #[cfg_attr(feature = "schema-gen", schemars(with = "crate::schema::Dependencies"))]
+    pub replaces: Vec<Dependency>,
+
+    /// The priority of the `replaces`. If multiple packages replace files of
+    /// each other, then the package with the highest `replaces_priority` wins.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replaces_priority: Option<u32>,
+
+    #[serde(default)]
+    pub install: Vec<String>,
+
+    /// Triggers installed `<pkgname>.trigger=<dir1>[:<dir2>...​]`
+    #[serde(default)]
+    pub triggers: Vec<String>,
+
+    /// Subpackages (names) built from this APKBUILD.
+    #[serde(default)]
+    pub subpackages: Vec<String>,
+
+    /// Both remote and local source files needed for building the package(s).
+    #[serde(default, rename = "sources")]
+    pub source: Vec<Source>,
+
+    /// Build-time options for the `abuild` tool.
+    #[serde(default)]
+    pub options: Vec<AbuildOption>,
+
+    /// A map of security vulnerabilities (CVE identifier) fixed in each version
+    /// of the APKBUILD's package(s).
+    #[serde(default, with = "key_value_vec_map")]
+    #[cfg_attr(feature = "schema-gen", schemars(with = "crate::schema::Secfixes"))]
+    #[field_names(skip)] // parsed from comments
+    pub secfixes: Vec<Secfix>,
+
+    /// Metadata of the subpackages evaluated by running their split functions.
+    /// Only subpackages whose split function is defined in the APKBUILD are
+    /// included. This is populated only if enabled by
+    /// [`ApkbuildReader::eval_split_functions`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[field_names(skip)]
+    pub subpackage_info: Vec<Subpackage>,
+
+    /// Values of the private (underscore-prefixed) variables, e.g. `_gitrev`,
+    /// keyed by the variable name (including the underscore). Variables that
+    /// are unset or empty after evaluating the APKBUILD are omitted. This is
+    /// populated only if enabled by [`ApkbuildReader::private_vars`].
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    #[field_names(skip)]
+    pub private_vars: BTreeMap<String, String>,
+
+    /// Files sourced (included) by the APKBUILD when evaluated, e.g. shared
+    /// functions, relative to the APKBUILD's directory (or absolute). This is
+    /// populated only if enabled by [`ApkbuildReader::track_includes`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[field_names(skip)]
+    pub includes: Vec<PathBuf>,
+
+    /// Raw values of the evaluated fields (and optionally other variables) as
+    /// produced by the shell, before splitting and parsing. This is populated
+    /// only if enabled by [`ApkbuildReader::keep_raw`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[field_names(skip)]
+    pub raw: Vec<(String, String)>,
+
+    /// Non-fatal problems found when reading the APKBUILD.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[field_names(skip)]
+    pub warnings: Vec<Warning>,
+}
+
+impl Apkbuild {
+    /// Returns `true` if the `options` contains the given option. The option
+    /// may be negated, e.g. `has_option("!check")` checks if tests are
+    /// disabled, whereas `has_option("check")` would be `false` in that case.
+    pub fn has_option(&self, option: &str) -> bool {
+        let option = AbuildOption::from_str(option).unwrap(); // this cannot fail
+        self.options.contains(&option)
+    }
+
+    /// Parses the `license` field into a SPDX license expression.
+    pub fn license_expr(&self) -> Result<LicenseExpr, LicenseParseError> {
+        self.license.parse()
+    }
+
+    /// Returns the full version of the package(s), i.e. `<pkgver>-r<pkgrel>`.
+    pub fn full_pkgver(&self) -> String {
+        format!("{}-r{}", self.pkgver, self.pkgrel)
+    }
+
+    /// Predicts the `.PKGINFO` of the main package built from this APKBUILD
+    /// for the given `arch`, as far as it can be determined without building
+    /// it. Only the manually specified `depends` are included (build-time
+    /// dependencies are not, nor the dependencies autodiscovered by abuild),
+    /// and the build-specific fields (`builddate`, `packager`, `size`,
+    /// `datahash` and `commit`) are left empty.
+    pub fn expected_pkginfo(&self, arch: &str) -> PkgInfo {
+        let (conflicts, depends) = self
+            .depends
+            .iter()
+            .filter(|dep| dep.name != self.pkgname)
+            .cloned()
+            .partition(|dep| dep.conflict);
+
+        let trigger_prefix = format!("{}.trigger=", self.pkgname);
+        let triggers = self
+            .triggers
+            .iter()
+            .filter_map(|s| s.strip_prefix(&trigger_prefix))
+            .flat_map(|dirs| dirs.split(':'))
+            .filter(|s| !s.is_empty())
+            .map(String::from)
+            .collect();
+
+        PkgInfo {
+            maintainer: self.maintainer.clone(),
+            pkgname: self.pkgname.clone(),
+            pkgver: self.full_pkgver(),
+            pkgdesc: self.pkgdesc.clone(),
+            url: self.url.clone(),
+            arch: arch.to_owned(),
+            license: self.license.clone(),
+            depends,
+            conflicts: conflicts
+                .into_iter()
+                .map(|dep| Dependency {
+                    conflict: false,
+                    ..dep
+                })
+                .collect(),
+            install_if: self.install_if.clone(),
+            provides: self.provides.clone(),
+            provider_priority: self.provider_priority.and_then(|n| u16::try_from(n).ok()),
+            replaces: self.replaces.clone(),
+            replaces_priority: self.replaces_priority.and_then(|n| u16::try_from(n).ok()),
+            triggers,
+            origin: self.pkgname.clone(),
+            ..Default::default()
+        }
+    }
+
+    /// Verifies checksums of the source files, as `abuild verify` does. Local
+    /// files are looked up in the `startdir` (the APKBUILD's directory) and
+    /// remote files in the `srcdest` (the directory with downloaded files).
+    ///
+    /// It returns a status for each source; a file that doesn't exist is
+    /// reported as [`SourceStatus::Missing`], not as an error.
+    pub fn verify_sources<P, Q>(
+        &self,
+        startdir: P,
+        srcdest: Q,
+    ) -> Result<Vec<(&Source, SourceStatus)>, Error>
+    where
+        P: AsRef<Path>,
+        Q: AsRef<Path>,
+    {
+        self.source
+            .iter()
+            .map(|source| {
+                let path = if source.is_remote() {
+                    srcdest.as_ref().join(&source.name)
+                } else {
+                    startdir.as_ref().join(&source.uri)
+                };
+                source.verify_file(&path).map(|status| (source, status))
+            })
+            .collect()
+    }
+
+    /// Computes SHA-512 checksums of the source files, as `abuild checksum`
+    /// does. The files are looked up the same way as in
+    /// [`Apkbuild::verify_sources`]; all of them must exist.
+    ///
+    /// It returns pairs of the source file name and its hex-encoded checksum.
+    pub fn compute_checksums<P, Q>(
+        &self,
+        startdir: P,
+        srcdest: Q,
+    ) -> Result<Vec<(&str, String)>, Error>
+    where
+        P: AsRef<Path>,
+        Q: AsRef<Path>,
+    {
+        self.source
+            .iter()
+            .map(|source| {
+                let path = if source.is_remote() {
+                    srcdest.as_ref().join(&source.name)
+                } else {
+                    startdir.as_ref().join(&source.uri)
+                };
+                fs::File::open(&path)
+                    .and_then(|file| ChecksumAlg::Sha512.digest(file))
+                    .map(|checksum| (source.name.as_str(), checksum))
+                    .map_err(|e| Error::ReadFile(e, path))
+            })
+            .collect()
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schema-gen", derive(schemars::JsonSchema))]
+pub struct Source {
+    /// The file name.
+    pub name: String,
+
+    /// URI of the file. This is either URL of the remote file or path of the
+    /// local file relative to the APKBUILD's directory.
+    pub uri: String,
+
+    /// Checksum of the file (hex-encoded).
+    pub checksum: String,
+
+    /// The algorithm of the `checksum`. This is SHA-512 unless the APKBUILD
+    /// uses the legacy `sha256sums` or `md5sums` variable.
+    #[serde(default, skip_serializing_if = "ChecksumAlg::is_sha512")]
+    pub checksum_alg: ChecksumAlg,
+}
+
+impl Source {
+    pub fn new<N, U, C>(name: N, uri: U, checksum: C) -> Self
+    where
+        N: ToString,
+        U: ToString,
+        C: ToString,
+    {
+        Source {
+            name: name.to_string(),
+            uri: uri.to_string(),
+            checksum: checksum.to_string(),
+            checksum_alg: ChecksumAlg::Sha512,
+        }
+    }
+
+    /// Sets the checksum algorithm.
+    pub fn checksum_alg(mut self, alg: ChecksumAlg) -> Self {
+        self.checksum_alg = alg;
+        self
+    }
+
+    /// Returns `true` if this is a remote file (i.e. the `uri` is URL).
+    pub fn is_remote(&self) -> bool {
+        self.uri.contains("://")
+    }
+
+    /// Computes the checksum of the file at the given path and compares it
+    /// with the expected `checksum`.
+    pub fn verify_file<P: AsRef<Path>>(&self, path: P) -> Result<SourceStatus, Error> {
+        let path = path.as_ref();
+
+        let file = match fs::File::open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(SourceStatus::Missing),
+            Err(e) => bail!(Error::ReadFile(e, path.to_owned())),
+        };
+        let actual = self
+            .checksum_alg
+            .digest(file)
+            .map_err(|e| Error::ReadFile(e, path.to_owned()))?;
+
+        if actual == self.checksum {
+            Ok(SourceStatus::Ok)
+        } else {
+            Ok(SourceStatus::Mismatch(actual))
+        }
+    }
+}
+
+/// A result of the source file verification.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SourceStatus {
+    /// The file's checksum matches.
+    Ok,
+    /// The file's checksum doesn't match; contains the actual checksum.
+    Mismatch(String),
+    /// The file doesn't exist (e.g. it hasn't been downloaded yet).
+    Missing,
+}
+
+/// A hash algorithm used for the source checksums.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schema-gen", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum ChecksumAlg {
+    /// `sha512sums`
+    #[default]
+    Sha512,
+    /// `sha256sums` (legacy)
+    Sha256,
+    /// `md5sums` (legacy)
+    Md5,
+}
+
+impl ChecksumAlg {
+    /// Computes a hex-encoded digest of the data read from the given reader.
+    pub fn digest<R: Read>(self, mut reader: R) -> io::Result<String> {
+        let mut hasher = Hasher::new(self);
+        io::copy(&mut reader, &mut hasher)?;
+        Ok(hasher.finish_hex())
+    }
+
+    fn is_sha512(&self) -> bool {
+        *self == ChecksumAlg::Sha512
+    }
+}
+
+/// An incremental hasher computing a digest using the [`ChecksumAlg`].
+pub(crate) enum Hasher {
+    Sha512(Sha512),
+    Sha256(Sha256),
+    Md5(Md5),
+}
+
+impl Hasher {
+    pub(crate) fn new(alg: ChecksumAlg) -> Self {
+        match alg {
+            ChecksumAlg::Sha512 => Hasher::Sha512(Sha512::new()),
+            ChecksumAlg::Sha256 => Hasher::Sha256(Sha256::new()),
+            ChecksumAlg::Md5 => Hasher::Md5(Md5::new()),
+        }
+    }
+
+    /// Returns the hex-encoded digest.
+    pub(crate) fn finish_hex(self) -> String {
+        let digest = match self {
+            Hasher::Sha512(h) => h.finalize().to_vec(),
+            Hasher::Sha256(h) => h.finalize().to_vec(),
+            Hasher::Md5(h) => h.finalize().to_vec(),
+        };
+        digest.iter().fold(String::new(), |mut acc, b| {
+            let _ = write!(acc, "{b:02x}");
+            acc
+        })
+    }
+}
+
+impl Write for Hasher {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Hasher::Sha512(h) => h.update(buf),
+            Hasher::Sha256(h) => h.update(buf),
+            Hasher::Md5(h) => h.update(buf),
+        };
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Metadata of a subpackage set in its split function.
+#[derive(Debug, Default, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schema-gen", derive(schemars::JsonSchema))]
+pub struct Subpackage {
+    /// The subpackage name.
+    pub name: String,
+
+    /// The name of the split function.
+    pub split_func: String,
+
+    /// The architecture specified in `subpackages` (`<name>:<func>:<arch>`),
+    /// typically `noarch`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arch: Option<String>,
+
+    /// A brief, one-line description of the subpackage.
+    pub pkgdesc: String,
+
+    /// Run-time dependencies of the subpackage.
+    #[serde(default, with = "key_value_vec_map")]
+    #[cfg_attr(feature = "schema-gen", schemars(with = "crate::schema::Dependencies"))]
+    pub depends: Vec<Dependency>,
+
+    /// Providers (packages) that the subpackage provides.
+    #[serde(default, with = "key_value_vec_map")]
+    #[cfg_attr(feature = "schema-gen", schemars(with = "crate::schema::Dependencies"))]
+    pub provides: Vec<Dependency>,
+
+    /// A set of dependencies that, if all installed, induce installation of
+    /// the subpackage.
+    #[serde(default, with = "key_value_vec_map")]
+    #[cfg_attr(feature = "schema-gen", schemars(with = "crate::schema::Dependencies"))]
+    pub install_if: Vec<Dependency>,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[cfg_attr(feature = "schema-gen", derive(schemars::JsonSchema))]
+pub struct Secfix {
+    /// A full version of the package that _fixes_ the vulnerabilities.
+    pub version: String,
+
+    /// A set of CVE identifiers.
+    pub fixes: Vec<String>,
+}
+
+impl Secfix {
+    pub fn new<S: ToString>(version: S, fixes: Vec<String>) -> Self {
+        Secfix {
+            version: version.to_string(),
+            fixes,
+        }
+    }
+}
+
+impl<'a> KeyValueLike<'a> for Secfix {
+    type Key = &'a str;
+    type Value = Vec<String>;
+    type Err = Infallible;
+
+    fn from_key_value(key: Self::Key, value: Self::Value) -> Result<Self, Self::Err> {
+        Ok(Secfix::new(key, value))
+    }
+
+    fn to_key_value(&'a self) -> (Self::Key, Self::Value) {
+        (&self.version, self.fixes.clone())
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// A non-fatal problem found when reading an APKBUILD.
+#[derive(Debug, Clone, PartialEq, Eq, Error, Deserialize, Serialize)]
+#[cfg_attr(feature = "schema-gen", derive(schemars::JsonSchema))]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum Warning {
+    /// A line in the secfixes block that has been skipped or recovered in the
+    /// lenient mode (see [`ApkbuildReader::lenient_secfixes`]).
+    #[error("malformed secfixes on line {line}: '{text}'")]
+    MalformedSecfixes { line: usize, text: String },
+
+    /// A required field that is missing in a partial APKBUILD and has been set
+    /// to a default value (see [`ApkbuildReader::allow_partial`]).
+    #[error("missing required field '{field}'")]
+    MissingField { field: String },
+
+    /// A field with an invalid value in a partial APKBUILD that has been left
+    /// empty (see [`ApkbuildReader::allow_partial`]).
+    #[error("invalid field '{field}': {message}")]
+    InvalidField { field: String, message: String },
+
+    /// A command executed at the top level of the APKBUILD, i.e. when it's
+    /// sourced (see [`ApkbuildReader::detect_side_effects`]).
+    #[error("command executed at the top level: '{command}'")]
+    SideEffect { command: String },
+
+    /// A remote source whose URI contains the literal `pkgver` instead of
+    /// referencing the `$pkgver` variable, so bumping `pkgver` alone won't
+    /// update it (see [`ApkbuildReader::detect_hardcoded_version`]).
+    #[error("source has hardcoded version: '{uri}'")]
+    HardcodedVersion { uri: String },
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// A build-time option for the `abuild` tool, e.g. `!check` or `net`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AbuildOption {
+    /// `!archcheck` - don't check if the binaries match the target arch.
+    Archcheck(bool),
+
+    /// `!check` - don't run the test suite (`check()`).
+    Check(bool),
+
+    /// `checkroot` - run the test suite under fakeroot.
+    Checkroot(bool),
+
+    /// `chmod-clean` - make the build directory writable before cleaning.
+    ChmodClean(bool),
+
+    /// `!dbg` - don't build the `-dbg` subpackage even if `DEFAULT_DBG` is set.
+    Dbg(bool),
+
+    /// `!fhs` - allow installing files outside of the FHS paths.
+    Fhs(bool),
+
+    /// `ldpath-recursive` - scan for shared object dependencies recursively.
+    LdpathRecursive(bool),
+
+    /// `lib64` - allow installing files into `/lib64` or `/usr/lib64`.
+    Lib64(bool),
+
+    /// `net` - allow network access during the build.
+    Net(bool),
+
+    /// `setcap` - allow setting file capabilities.
+    Setcap(bool),
+
+    /// `sover-namecheck` - check that the soname matches the package version.
+    SoverNamecheck(bool),
+
+    /// `!spdx` - don't check if the license is a valid SPDX expression.
+    Spdx(bool),
+
+    /// `!strip` - don't strip binaries.
+    Strip(bool),
+
+    /// `suid` - allow setuid/setgid binaries.
+    Suid(bool),
+
+    /// `textrels` - allow text relocations.
+    Textrels(bool),
+
+    /// `toolchain` - the package is part of the toolchain.
+    Toolchain(bool),
+
+    /// `!tracedeps` - don't trace shared object dependencies.
+    Tracedeps(bool),
+
+    /// Any other (unknown) option with its name (without `!`).
+    Other(String, bool),
+}
+
+impl AbuildOption {
+    /// Returns the option name without the negation prefix (`!`).
+    pub fn name(&self) -> &str {
+        use AbuildOption::*;
+
+        match self {
+            Archcheck(_) => "archcheck",
+            Check(_) => "check",
+            Checkroot(_) => "checkroot",
+            ChmodClean(_) => "chmod-clean",
+            Dbg(_) => "dbg",
+            Fhs(_) => "fhs",
+            LdpathRecursive(_) => "ldpath-recursive",
+            Lib64(_) => "lib64",
+            Net(_) => "net",
+            Setcap(_) => "setcap",
+            SoverNamecheck(_) => "sover-namecheck",
+            Spdx(_) => "spdx",
+            Strip(_) => "strip",
+            Suid(_) => "suid",
+            Textrels(_) => "textrels",
+            Toolchain(_) => "toolchain",
+            Tracedeps(_) => "tracedeps",
+            Other(name, _) => name,
+        }
+    }
+
+    /// Returns `true` if the option is negated (prefixed with `!`).
+    pub fn is_negated(&self) -> bool {
+        use AbuildOption::*;
+
+        match self {
+            Archcheck(neg)
+            | Check(neg)
+            | Checkroot(neg)
+            | ChmodClean(neg)
+            | Dbg(neg)
+            | Fhs(neg)
+            | LdpathRecursive(neg)
+            | Lib64(neg)
+            | Net(neg)
+            | Setcap(neg)
+            | SoverNamecheck(neg)
+            | Spdx(neg)
+            | Strip(neg)
+            | Suid(neg)
+            | Textrels(neg)
+            | Toolchain(neg)
+            | Tracedeps(neg)
+            | Other(_, neg) => *neg,
+        }
+    }
+}
+
+impl FromStr for AbuildOption {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use AbuildOption::*;
+
+        let (name, neg) = s.strip_prefix('!').map_or((s, false), |s| (s, true));
+
+        Ok(match name {
+            "archcheck" => Archcheck(neg),
+            "check" => Check(neg),
+            "checkroot" => Checkroot(neg),
+            "chmod-clean" => ChmodClean(neg),
+            "dbg" => Dbg(neg),
+            "fhs" => Fhs(neg),
+            "ldpath-recursive" => LdpathRecursive(neg),
+            "lib64" => Lib64(neg),
+            "net" => Net(neg),
+            "setcap" => Setcap(neg),
+            "sover-namecheck" => SoverNamecheck(neg),
+            "spdx" => Spdx(neg),
+            "strip" => Strip(neg),
+            "suid" => Suid(neg),
+            "textrels" => Textrels(neg),
+            "toolchain" => Toolchain(neg),
+            "tracedeps" => Tracedeps(neg),
+            name => Other(name.to_owned(), neg),
+        })
+    }
+}
+
+impl fmt::Display for AbuildOption {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_negated() {
+            f.write_str("!")?;
+        }
+        f.write_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for AbuildOption {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Ok(AbuildOption::from_str(&s).unwrap()) // this cannot fail
+    }
+}
+
+impl Serialize for AbuildOption {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// The default list of CPU architectures (arch) to which the `all` and `noarch`
+/// keywords are expanded.
+pub const ARCH_ALL: &[&str] = &[
+    "aarch64", "armhf", "armv7", "ppc64le", "riscv64", "s390x", "x86", "x86_64",
+];
+
+/// The `PS4` used for tracing the shell execution when detecting side effects.
+const XTRACE_PREFIX: &str = "\x1F+ ";
+
+/// Shell builtins (and keywords) that are not considered as side effects when
+/// executed at the top level of an APKBUILD.
+const HARMLESS_COMMANDS: &[&str] = &[
+    ".", ":", "[", "break", "continue", "echo", "eval", "export", "false", "local", "printf",
+    "read", "readonly", "return", "set", "shift", "source", "test", "true", "unset",
+];
+
+/// A shell expression that runs the split function (if defined) of each
+/// subpackage in a subshell and prints a record for each subpackage terminated
+/// by `\x1D`: the subpackage name, split function, and if the function has
+/// been run, `pkgdesc`, `depends`, `provides` and `install_if`, separated by
+/// `\x1F`.
+const SPLIT_FUNCS_SCRIPT: &str = "$(\
+for _sp in $subpackages; do \
+  _sp_name=${_sp%%:*}; \
+  case $_sp in *:*) _sp_func=${_sp#*:}; _sp_func=${_sp_func%%:*};; *) _sp_func=;; esac; \
+  [ -n \"$_sp_func\" ] || _sp_func=${_sp_name##*-}; \
+  printf '%s\x1F%s' \"$_sp_name\" \"$_sp_func\"; \
+  case $(type \"$_sp_func\" 2>/dev/null) in *function*) ( \
+    _sp_tmp=$(mktemp -d) || exit 0; \
+    pkgdir=$_sp_tmp/pkg; subpkgdir=$_sp_tmp/subpkg; subpkgname=$_sp_name; \
+    srcdir=$_sp_tmp/src; builddir=$srcdir; \
+    mkdir -p \"$pkgdir\" \"$subpkgdir\" \"$srcdir\"; cd \"$_sp_tmp\"; \
+    amove() { :; }; default_dev() { :; }; default_doc() { :; }; default_libs() { :; }; \
+    default_openrc() { :; }; default_static() { :; }; default_pyc() { :; }; \
+    depends=; provides=; install_if=; \
+    $_sp_func </dev/null >/dev/null 2>&1; \
+    rm -rf \"$_sp_tmp\"; \
+    printf '\x1F%s\x1F%s\x1F%s\x1F%s' \"$pkgdesc\" \"$depends\" \"$provides\" \"$install_if\" \
+  );; esac; \
+  printf '\x1D'; \
+done)";
+
+/// The output of the shell evaluating an APKBUILD.
+#[cfg(feature = "shell-timeout")]
+type ShellOutput = process_control::Output;
+#[cfg(not(feature = "shell-timeout"))]
+type ShellOutput = process::Output;
+
+/// A value assigned to `pkgver` when evaluating the unexpanded `source` to find
+/// a hardcoded version.
+const PKGVER_PLACEHOLDER: &str = "@pkgver@";
+
+pub struct ApkbuildReader {
+    abuild_conf: Option<PathBuf>,
+    allow_partial: bool,
+    arch_all: Vec<String>,
+    comment_attributes: Vec<String>,
+    contributors_scan_limit: Option<usize>,
+    detect_hardcoded_version: bool,
+    detect_side_effects: bool,
+    eval_split_functions: bool,
+    track_includes: bool,
+    env: HashMap<OsString, OsString>,
+    inherit_env: bool,
+    keep_raw: RawValues,
+    lenient_secfixes: bool,
+    private_vars: bool,
+    shell_cmd: OsString,
+    shell_workers: usize,
+    #[allow(unused)]
+    time_limit: Duration,
+
+    eval_fields: Vec<&'static str>,
+    eval_script: Vec<u8>,
+    #[cfg(all(unix, feature = "shell-workers"))]
+    idle_workers: Mutex<Vec<ShellWorker>>,
+}
+
+impl ApkbuildReader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the path of an `abuild.conf` file to be sourced before the APKBUILD,
+    /// so that the variables set in it (e.g. `DISTFILES_MIRROR` or `CARCH`)
+    /// influence the evaluation the same way they do for abuild. A relative
+    /// path is resolved against the current working directory.
+    pub fn abuild_conf<P: AsRef<Path>>(&mut self, path: P) -> &mut Self {
+        self.abuild_conf = Some(path.as_ref().to_owned());
+        self
+    }
+
+    /// Sets if an incomplete or broken APKBUILD should be read as far as
+    /// possible, or the reading should fail on the first missing or invalid
+    /// field (default).
+    ///
+    /// If allowed, missing required fields are set to default values and
+    /// invalid fields are left empty; each of these is reported in
+    /// [`Apkbuild::warnings`]. This is useful for editors and similar tools
+    /// that work with APKBUILDs being edited.
+    pub fn allow_partial(&mut self, cond: bool) -> &mut Self {
+        self.allow_partial = cond;
+        self
+    }
+
+    /// Changes the list of CPU architectures (arch) to which the `all` and
+    /// `noarch` keywords are expanded. The default is [`ARCH_ALL`].
+    pub fn arch_all<S: ToString>(&mut self, arches: &[S]) -> &mut Self {
+        self.arch_all.extend(arches.iter().map(|s| s.to_string()));
+        self
+    }
+
+    /// Sets names of custom comment attributes (e.g. `Patch-source` for
+    /// `# Patch-source: <value>`) to be extracted into
+    /// [`Apkbuild::attributes`].
+    pub fn comment_attributes<S: ToString>(&mut self, names: &[S]) -> &mut Self {
+        self.comment_attributes = names.iter().map(|s| s.to_string()).collect();
+        self
+    }
+
+    /// Changes the number of lines at the beginning of the APKBUILD in which
+    /// the `# Contributor:` comments are searched for. If `None`, the whole
+    /// leading comment block is searched (i.e. until the first line that is
+    /// neither a comment nor empty). The default is 10 lines.
+    pub fn contributors_scan_limit(&mut self, limit: Option<usize>) -> &mut Self {
+        self.contributors_scan_limit = limit;
+        self
+    }
+
+    /// Sets if remote sources should be checked for a hardcoded version, i.e.
+    /// whether the URI contains the literal value of `pkgver` instead of
+    /// referencing the variable. Each such source is reported in
+    /// [`Apkbuild::warnings`]. This is disabled by default.
+    ///
+    /// This is done by evaluating the unexpanded `source` string once again
+    /// with `pkgver` set to a placeholder.
+    pub fn detect_hardcoded_version(&mut self, cond: bool) -> &mut Self {
+        self.detect_hardcoded_version = cond;
+        self
+    }
+
+    /// Sets if the APKBUILD should be checked for commands executed at the top
+    /// level, i.e. when the APKBUILD is sourced (e.g. `$(uname -m)` or
+    /// `cd somewhere`). Each such command is reported in
+    /// [`Apkbuild::warnings`]. This is disabled by default.
+    ///
+    /// This is done by tracing the shell execution (`set -x`). Shell builtins
+    /// without side effects, such as `[`, `echo` or `export`, are not
+    /// reported.
+    pub fn detect_side_effects(&mut self, cond: bool) -> &mut Self {
+        self.detect_side_effects = cond;
+        self
+    }
+
+    /// Sets if the split functions of subpackages defined in the APKBUILD should
+    /// be run to evaluate the `pkgdesc`, `depends`, `provides` and `install_if`
+    /// they set, and collected into [`Apkbuild::subpackage_info`]. This is
+    /// disabled by default.
+    ///
+    /// Each split function is run in a subshell inside a temporary directory
+    /// with `pkgdir`, `subpkgdir`, `srcdir` and `builddir` pointing to empty
+    /// directories in it and abuild helpers (e.g. `amove`) stubbed out.
+    /// However, they're still shell functions that can do anything!
+    pub fn eval_split_functions(&mut self, cond: bool) -> &mut Self {
+        self.eval_split_functions = cond;
+        self
+    }
+
+    /// Inserts or updates an environment variable mapping.
+    pub fn env<K, V>(&mut self, key: K, val: V) -> &mut Self
+    where
+        K: AsRef<OsStr>,
+        V: AsRef<OsStr>,
+    {
+        self.env.insert(OsString::from(&key), OsString::from(&val));
+        self.reset_workers()
+    }
+
+    /// Adds or updates multiple environment variable mappings.
+    pub fn envs<I, K, V>(&mut self, vars: I) -> &mut Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<OsStr>,
+        V: AsRef<OsStr>,
+    {
+        for (ref key, ref val) in vars {
+            self.env.insert(OsString::from(&key), OsString::from(&val));
+        }
+        self.reset_workers()
+    }
+
+    /// Sets if the spawned shell process should inherit environment variables
+    /// from the parent process, or the environment should be cleared (default).
+    pub fn inherit_env(&mut self, cond: bool) -> &mut Self {
+        self.inherit_env = cond;
+        self.reset_workers()
+    }
+
+    /// Sets which raw values (as produced by the shell) should be kept in
+    /// [`Apkbuild::raw`]. This is useful for debugging when the parsed values
+    /// are not as expected. The default is [`RawValues::None`].
+    pub fn keep_raw(&mut self, values: RawValues) -> &mut Self {
+        self.keep_raw = values;
+        self
+    }
+
+    /// Sets if the secfixes comment block should be parsed in a lenient mode,
+    /// or the reading should fail on the first malformed line (default).
+    ///
+    /// In the lenient mode, common mistakes such as tabs, wrong indentation,
+    /// a missing space after `-` or a missing trailing colon after version are
+    /// tolerated, unrecoverable lines are skipped and each of these lines is
+    /// reported in [`Apkbuild::warnings`].
+    pub fn lenient_secfixes(&mut self, cond: bool) -> &mut Self {
+        self.lenient_secfixes = cond;
+        self
+    }
+
+    /// Sets if the private (underscore-prefixed) variables assigned in the
+    /// APKBUILD, such as `_gitrev` or `_llvmver`, should be evaluated into
+    /// [`Apkbuild::private_vars`]. This is disabled by default.
+    pub fn private_vars(&mut self, cond: bool) -> &mut Self {
+        self.private_vars = cond;
+        self
+    }
+
+    /// Changes the shell command used to evaluate an APKBUILD.
+    pub fn shell_cmd<S: AsRef<OsStr>>(&mut self, cmd: S) -> &mut Self {
+        self.shell_cmd = OsString::from(&cmd);
+        self.reset_workers()
+    }
+
+    /// Sets the number of long-lived shell processes (workers) to be kept for
+    /// evaluating APKBUILDs, instead of spawning a new shell for each one.
+    /// The default is 0, i.e. no workers.
+    ///
+    /// Each APKBUILD is evaluated in a subshell of a worker, so it cannot
+    /// affect the evaluation of the others. This considerably speeds up
+    /// reading a lot of APKBUILDs (e.g. the whole aports tree); it makes
+    /// sense to set it to the number of threads calling
+    /// [`ApkbuildReader::read_apkbuild`]. If more threads read at the same
+    /// time, additional workers are spawned, but only this number of them is
+    /// kept. The [`shell_cmd`](ApkbuildReader::shell_cmd) must support the
+    /// `-c` option.
+    ///
+    /// The workers are supported only on Unix; on other platforms, this
+    /// option is ignored.
+    #[cfg(feature = "shell-workers")]
+    pub fn shell_workers(&mut self, count: usize) -> &mut Self {
+        self.shell_workers = count;
+        self.reset_workers()
+    }
+
+    /// Sets the target architecture for the APKBUILD evaluation, i.e. the
+    /// `CARCH` and `CHOST` variables as abuild does, so that arch-conditional
+    /// variables (e.g. `case "$CARCH" in ...`) are evaluated for it.
+    pub fn target_arch<S: AsRef<str>>(&mut self, arch: S) -> &mut Self {
+        let arch = arch.as_ref();
+        self.env("CARCH", arch).env("CHOST", arch_to_hostspec(arch))
+    }
+
+    /// Sets if the files sourced by the APKBUILD (e.g. `. ./common.sh`) should
+    /// be collected into [`Apkbuild::includes`], e.g. for tracking changes of
+    /// files the APKBUILD depends on. This is disabled by default.
+    ///
+    /// This is done by tracing the shell execution (`set -x`).
+    pub fn track_includes(&mut self, cond: bool) -> &mut Self {
+        self.track_includes = cond;
+        self
+    }
+
+    #[cfg(feature = "shell-timeout")]
+    pub fn time_limit(&mut self, limit: Duration) -> &mut Self {
+        self.time_limit = limit;
+        self
+    }
+
+    pub fn read_apkbuild<P: AsRef<Path>>(&self, filepath: P) -> Result<Apkbuild, Error> {
+        let filepath = filepath.as_ref();
+        debug!("reading APKBUILD {}", filepath.display());
+
+        let apkbuild_str =
+            fs::read_to_string(filepath).map_err(|e| Error::ReadFile(e, filepath.to_owned()))?;
+
+        let subpkg_depends_vars = find_variables_with_prefix(&apkbuild_str, "depends_");
+        let raw_source = self
+            .detect_hardcoded_version
+            .then(|| find_raw_variable(&apkbuild_str, "source"))
+            .flatten();
+        let other_vars: Vec<_> = if self.keep_raw == RawValues::AllVariables {
+            find_variables_with_prefix(&apkbuild_str, "")
+                .into_iter()
+                .filter(|var| !self.eval_fields.contains(var) && !subpkg_depends_vars.contains(var))
+                .collect()
+        } else {
+            vec![]
+        };
+        let private_vars = if self.private_vars {
+            find_variables_with_prefix(&apkbuild_str, "_")
+        } else {
+            vec![]
+        };
+
+        let mut extra_exprs: Vec<_> = subpkg_depends_vars
+            .iter()
+            .chain(&other_vars)
+            .chain(&private_vars)
+            .map(|var| format!("${var}"))
+            .collect();
+        if self.eval_split_functions {
+            extra_exprs.push(SPLIT_FUNCS_SCRIPT.to_owned());
+        }
+        if let Some((quote, value)) = raw_source {
+            extra_exprs.push(format!(
+                "$(pkgver={PKGVER_PLACEHOLDER}; echo {quote}{value}{quote})"
+            ));
+        }
+
+        let (values, stderr) = self.evaluate(filepath, &extra_exprs)?;
+        let values: Vec<_> = values.trim_end().split_terminator('\x1E').collect();
+        let mut rest = values.iter().copied().skip(self.eval_fields.len());
+
+        let mut arch: Option<&str> = None;
+        let mut sha512sums: Option<&str> = None;
+        let mut sha256sums: Option<&str> = None;
+        let mut md5sums: Option<&str> = None;
+        let mut source: Option<&str> = None;
+
+        let parsed = self.eval_fields.iter().zip(values.iter().copied()).fold(
+            Vec::with_capacity(64),
+            |mut acc, (key, val)| {
+                match *key {
+                    "arch" => arch = Some(val),
+                    "source" => source = Some(val),
+                    "sha512sums" => sha512sums = Some(val),
+                    "sha256sums" => sha256sums = Some(val),
+                    "md5sums" => md5sums = Some(val),
+                    "license" | "pkgdesc" | "pkgver" | "url" => {
+                        acc.push((*key, val));
+                    }
+                    _ => {
+                        for mut word in val.split_ascii_whitespace() {
+                            if *key == "subpackages" {
+                                word = word.split(':').next().unwrap(); // this cannot panic
+                            }
+                            acc.push((*key, word));
+                        }
+                    }
+                };
+                acc
+            },
+        );
+
+        let mut warnings = Vec::new();
+        let mut apkbuild: Apkbuild = if self.allow_partial {
+            decode_partial(parsed, &mut warnings)?
+        } else {
+            serde_key_value::from_ordered_pairs(parsed)?
+        };
+
+        if let Some(arch) = arch {
+            apkbuild.arch = parse_and_expand_arch(arch, &self.arch_all);
+            apkbuild.arch_raw = arch.trim().to_owned();
+        }
+        if let Some(source) = source {
+            let result = decode_source_and_checksums(
+                source,
+                &[
+                    (ChecksumAlg::Sha512, sha512sums.unwrap_or("")),
+                    (ChecksumAlg::Sha256, sha256sums.unwrap_or("")),
+                    (ChecksumAlg::Md5, md5sums.unwrap_or("")),
+                ],
+            );
+            apkbuild.source = self
+                .recover(result, "source", &mut warnings)?
+                .unwrap_or_default();
+        }
+        for (var, val) in subpkg_depends_vars.iter().zip(rest.by_ref()) {
+            let depends = self
+                .recover(parse_dependencies(var, val), var, &mut warnings)?
+                .unwrap_or_default();
+            if !depends.is_empty() {
+                let suffix = &var["depends_".len()..];
+                apkbuild
+                    .subpackage_depends
+                    .insert(suffix.to_owned(), depends);
+            }
+        }
+
+        rest.by_ref().take(other_vars.len()).for_each(drop);
+
+        for (var, val) in private_vars.iter().zip(rest.by_ref()) {
+            if !val.is_empty() {
+                apkbuild
+                    .private_vars
+                    .insert(var.to_string(), val.to_owned());
+            }
+        }
+
+        if let Some(value) = self.eval_split_functions.then(|| rest.next()).flatten() {
+            let specs = self
+                .eval_fields
+                .iter()
+                .position(|f| *f == "subpackages")
+                .and_then(|i| values.get(i))
+                .copied()
+                .unwrap_or("");
+            apkbuild.subpackage_info = self
+                .recover(
+                    decode_subpackages(specs, value),
+                    "subpackages",
+                    &mut warnings,
+                )?
+                .unwrap_or_default();
+        }
+
+        if let Some(unexpanded) = rest.next().filter(|_| raw_source.is_some()) {
+            warnings.extend(find_hardcoded_version(&apkbuild, unexpanded));
+        }
+
+        if self.detect_side_effects {
+            warnings.extend(find_side_effects(&stderr));
+        }
+        if self.track_includes {
+            apkbuild.includes = find_includes(&stderr);
+        }
+
+        if self.keep_raw != RawValues::None {
+            apkbuild.raw = self
+                .eval_fields
+                .iter()
+                .chain(&subpkg_depends_vars)
+                .chain(&other_vars)
+                .zip(&values)
+                .map(|(var, val)| (var.to_string(), val.to_string()))
+                .collect();
+        }
+
+        apkbuild.maintainer = parse_maintainer(&apkbuild_str).map(|s| s.to_owned());
+        apkbuild.contributors = parse_contributors(&apkbuild_str, self.contributors_scan_limit)
+            .map(|s| s.to_owned())
+            .collect();
+        if self.lenient_secfixes {
+            let (secfixes, secfixes_warnings) = parse_secfixes_lenient(&apkbuild_str);
+            apkbuild.secfixes = secfixes;
+            warnings.extend(secfixes_warnings);
+        } else {
+            apkbuild.secfixes = self
+                .recover(parse_secfixes(&apkbuild_str), "secfixes", &mut warnings)?
+                .unwrap_or_default();
+        }
+        apkbuild.attributes = parse_comment_attributes(&apkbuild_str, &self.comment_attributes);
+        apkbuild.warnings = warnings;
+
+        Ok(apkbuild)
+    }
+
+    /// If partial APKBUILDs are allowed, converts the error into a warning
+    /// about the invalid `field` and returns `None`, otherwise returns the
+    /// error.
+    fn recover<T, E: Into<Error>>(
+        &self,
+        result: Result<T, E>,
+        field: &str,
+        warnings: &mut Vec<Warning>,
+    ) -> Result<Option<T>, Error> {
+        match result {
+            Ok(value) => Ok(Some(value)),
+            Err(e) if self.allow_partial => {
+                warnings.push(Warning::InvalidField {
+                    field: field.to_owned(),
+                    message: error_message(&e.into()),
+                });
+                Ok(None)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Evaluates the APKBUILD and returns stdout with values of the
+    /// `eval_fields` followed by the `extra_exprs` (shell expressions), each
+    /// terminated by `\x1E`, and stderr.
+    fn evaluate(&self, filepath: &Path, extra_exprs: &[String]) -> Result<(String, String), Error> {
+        // filepath is validated in `.read_apkbuild`.
+        let startdir = filepath
+            .parent()
+            .unwrap_or_else(|| panic!("invalid APKBUILD path: `{filepath:?}`"));
+        let filename = filepath
+            .file_name()
+            .unwrap_or_else(|| panic!("invalid APKBUILD path: `{filepath:?}`"));
+
+        let abuild_conf = match &self.abuild_conf {
+            Some(path) if path.is_relative() => Some(
+                std::env::current_dir()
+                    .map_err(|e| Error::Io(e, "resolving path of abuild.conf"))?
+                    .join(path),
+            ),
+            path => path.clone(),
+        };
+
+        let mut script_prefix = String::new();
+        if abuild_conf.is_some() {
+            script_prefix += r#". "$ABUILD_CONF" >/dev/null; "#;
+        }
+        if self.detect_side_effects || self.track_includes {
+            script_prefix +=
+                &format!(r#"PS4='{XTRACE_PREFIX}'; set -x; . ./"$APKBUILD" >/dev/null; set +x; "#);
+        } else {
+            script_prefix += r#". ./"$APKBUILD" >/dev/null; "#;
+        }
+        let eval_script = extra_exprs.iter().fold(
+            [script_prefix.as_bytes(), &self.eval_script].concat(),
+            |mut acc, expr| {
+                acc.extend_from_slice(format!("{expr}\x1E").as_bytes());
+                acc
+            },
+        );
+
+        trace!(
+            "evaluating {} with {}{} (time limit {:?})",
+            filepath.display(),
+            self.shell_cmd.to_string_lossy(),
+            if cfg!(all(unix, feature = "shell-workers")) && self.shell_workers > 0 {
+                " worker"
+            } else {
+                ""
+            },
+            self.time_limit
+        );
+        let output = match () {
+            #[cfg(all(unix, feature = "shell-workers"))]
+            _ if self.shell_workers > 0 => {
+                let output = self.evaluate_in_worker(
+                    startdir,
+                    filename,
+                    abuild_conf.as_deref(),
+                    &eval_script,
+                )?;
+                #[cfg(feature = "shell-timeout")]
+                let output = ShellOutput::from(output);
+                output
+            }
+            _ => {
+                self.evaluate_in_process(startdir, filename, abuild_conf.as_deref(), &eval_script)?
+            }
+        };
+
+        let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+
+        output.status.exit_ok().map_err(|e| {
+            let stderr = stderr
+                .lines()
+                .filter(|line| !line.starts_with(XTRACE_PREFIX))
+                .fold(String::new(), |acc, line| acc + line + "\n");
+            Error::Evaluate(e, stderr)
+        })?;
+
+        let stdout = String::from_utf8(output.stdout).map_err(|e| {
+            Error::Io(
+                io::Error::new(io::ErrorKind::InvalidData, e),
+                "reading shell stdout",
+            )
+        })?;
+        Ok((stdout, stderr))
+    }
+
+    /// Evaluates the `eval_script` in a new shell process.
+    fn evaluate_in_process(
+        &self,
+        startdir: &Path,
+        filename: &OsStr,
+        abuild_conf: Option<&Path>,
+        eval_script: &[u8],
+    ) -> Result<ShellOutput, Error> {
+        let mut child = self
+            .shell_command()
+            .env("APKBUILD", filename)
+            .tap_mut_if(abuild_conf.is_some(), |cmd| {
+                cmd.env("ABUILD_CONF", abuild_conf.unwrap());
+            })
+            .tap_mut_if(!startdir.as_os_str().is_empty(), |cmd| {
+                cmd.current_dir(startdir);
+            })
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| Error::SpawnShell(e, self.shell_cmd.to_string_lossy().into_owned()))?;
+
+        let mut stdin = child.stdin.take().unwrap(); // this should never fail
+        stdin
+            .write_all(eval_script)
+            .map_err(|e| Error::Io(e, "writing data to stdin of shell"))?;
+        drop(stdin);
+
+        #[cfg(feature = "shell-timeout")]
+        let output = {
+            // Pipes are read in background threads (instead of using
+            // `controlled_with_output`) to keep the partial output on timeout.
+            let started = Instant::now();
+            let stdout = PipeCollector::spawn(child.stdout.take().unwrap());
+            let stderr = PipeCollector::spawn(child.stderr.take().unwrap());
+
+            let status = child
+                .controlled()
+                .pipe_if(!self.time_limit.is_zero(), |ctrl| {
+                    ctrl.terminate_for_timeout().time_limit(self.time_limit)
+                })
+                .wait()
+                .map_err(|e| Error::Io(e, "waiting on shell process"))?;
+
+            let status = match status {
+                Some(status) => status,
+                None => bail!(Error::Timeout {
+                    elapsed: started.elapsed(),
+                    stdout: String::from_utf8_lossy(&stdout.partial()).into(),
+                    stderr: String::from_utf8_lossy(&stderr.partial()).into(),
+                }),
+            };
+            process_control::Output {
+                status,
+                stdout: stdout
+                    .finish()
+                    .map_err(|e| Error::Io(e, "reading shell stdout"))?,
+                stderr: stderr
+                    .finish()
+                    .map_err(|e| Error::Io(e, "reading shell stderr"))?,
+            }
+        };
+
+        #[cfg(not(feature = "shell-timeout"))]
+        let output = child
+            .wait_with_output()
+            .map_err(|e| Error::Io(e, "waiting on shell process"))?;
+
+        Ok(output)
+    }
+
+    /// Evaluates the `eval_script` in a subshell of an idle shell worker, or
+    /// a newly spawned one if there's none.
+    #[cfg(all(unix, feature = "shell-workers"))]
+    fn evaluate_in_worker(
+        &self,
+        startdir: &Path,
+        filename: &OsStr,
+        abuild_conf: Option<&Path>,
+        eval_script: &[u8],
+    ) -> Result<process::Output, Error> {
+        // The worker is shared by all APKBUILDs, so the working directory and
+        // variables are set in the subshell.
+        let startdir = std::env::current_dir()
+            .map_err(|e| Error::Io(e, "resolving path of APKBUILD"))?
+            .join(startdir);
+
+        let mut script = b"cd ".to_vec();
+        worker::push_quoted(&mut script, startdir.as_os_str());
+        script.extend_from_slice(b" || exit 1; APKBUILD=");
+        worker::push_quoted(&mut script, filename);
+        script.extend_from_slice(b"; export APKBUILD; ");
+        if let Some(abuild_conf) = abuild_conf {
+            script.extend_from_slice(b"ABUILD_CONF=");
+            worker::push_quoted(&mut script, abuild_conf.as_os_str());
+            script.extend_from_slice(b"; export ABUILD_CONF; ");
+        }
+        script.extend_from_slice(eval_script);
+
+        #[cfg(feature = "shell-timeout")]
+        let time_limit = Some(self.time_limit).filter(|limit| !limit.is_zero());
+        #[cfg(not(feature = "shell-timeout"))]
+        let time_limit = None;
+
+        let idle = self.lock_idle_workers().pop();
+        let mut worker = match idle {
+            Some(worker) => worker,
+            None => ShellWorker::spawn(self.shell_command())?,
+        };
+        // If it fails, the worker is dropped (killed).
+        let output = worker.evaluate(&script, time_limit)?;
+
+        let mut idle_workers = self.lock_idle_workers();
+        if idle_workers.len() < self.shell_workers {
+            idle_workers.push(worker);
+        }
+        Ok(output)
+    }
+
+    /// Returns a command for spawning the shell with the configured
+    /// environment.
+    fn shell_command(&self) -> Command {
+        let mut cmd = Command::new(&self.shell_cmd);
+        if !self.inherit_env {
+            cmd.env_clear();
+        }
+        cmd.envs(self.env.iter());
+        cmd
+    }
+
+    #[cfg(all(unix, feature = "shell-workers"))]
+    fn lock_idle_workers(&self) -> std::sync::MutexGuard<'_, Vec<ShellWorker>> {
+        self.idle_workers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Drops the idle shell workers, so that new ones are spawned with the
+    /// current configuration.
+    fn reset_workers(&mut self) -> &mut Self {
+        #[cfg(all(unix, feature = "shell-workers"))]
+        self.idle_workers
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+        self
+    }
+}
+
+impl Default for ApkbuildReader {
+    fn default() -> Self {
+        // TODO: Remove PATH?
+        let path = std::env::var_os("PATH").unwrap_or_else(|| "/usr/bin:/bin".into());
+
+        // `*sums` are not in Apkbuild struct, because they're merged into `source`.
+        let eval_fields: Vec<_> = Apkbuild::FIELDS
+            .into_iter()
+            .chain(["sha512sums", "sha256sums", "md5sums"])
+            .collect();
+
+        let eval_script = eval_fields
+            .iter()
+            .fold("echo ".to_owned(), |acc, field| acc + "$" + field + "\x1E")
+            .into_bytes();
+
+        Self {
+            abuild_conf: None,
+            allow_partial: false,
+            arch_all: ARCH_ALL.iter().map(|s| s.to_string()).collect(), // this is suboptiomal :/
+            comment_attributes: vec![],
+            contributors_scan_limit: Some(10),
+            detect_hardcoded_version: false,
+            detect_side_effects: false,
+            eval_split_functions: false,
+            track_includes: false,
+            shell_cmd: "/bin/sh".into(),
+            shell_workers: 0,
+            env: HashMap::from([("PATH".into(), path)]),
+            inherit_env: false,
+            keep_raw: RawValues::None,
+            lenient_secfixes: false,
+            private_vars: false,
+            time_limit: Duration::from_millis(500),
+            eval_fields,
+            eval_script,
+            #[cfg(all(unix, feature = "shell-workers"))]
+            idle_workers: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+/// Specifies which raw values are kept in [`Apkbuild::raw`], see
+/// [`ApkbuildReader::keep_raw`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RawValues {
+    /// Don't keep any raw values.
+    #[default]
+    None,
+
+    /// Keep values of the evaluated fields (including `*sums` and
+    /// `depends_<suffix>`).
+    Fields,
+
+    /// Keep values of the evaluated fields and all other variables assigned in
+    /// the APKBUILD.
+    AllVariables,
+}
+
+/// Finds all APKBUILD files in the given aports tree, either a repository
+/// directory (e.g. `aports/main`, i.e. `<dir>/*/APKBUILD`) or the whole tree
+/// with repositories (`<dir>/*/*/APKBUILD`). The returned paths are sorted.
+pub fn find_apkbuilds<P: AsRef<Path>>(dir: P) -> io::Result<Vec<PathBuf>> {
+    fn subdirs(dir: &Path) -> io::Result<Vec<PathBuf>> {
+        let mut dirs = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            if entry.file_type()?.is_dir() && !entry.file_name().to_string_lossy().starts_with('.')
+            {
+                dirs.push(entry.path());
+            }
+        }
+        Ok(dirs)
+    }
+
+    let mut found = Vec::new();
+    for subdir in subdirs(dir.as_ref())? {
+        let apkbuild = subdir.join("APKBUILD");
+        if apkbuild.is_file() {
+            found.push(apkbuild);
+        } else {
+            for pkgdir in subdirs(&subdir)? {
+                let apkbuild = pkgdir.join("APKBUILD");
+                if apkbuild.is_file() {
+                    found.push(apkbuild);
+                }
+            }
+        }
+    }
+    found.sort();
+
+    Ok(found)
+}
+
+fn parse_and_expand_arch<'v, 's: 'v>(value: &'v str, arch_all: &'s [String]) -> Vec<String> {
+    value
+        .split_ascii_whitespace()
+        .fold(vec![], |mut acc, token| {
+            match token {
+                "all" | "noarch" => acc.extend(arch_all.iter().map(String::clone)),
+                s if s.starts_with('!') => acc.retain(|arch| arch != &s[1..]),
+                s => acc.push(s.to_owned()),
+            };
+            acc
+        })
+        .tap_mut(|v| {
+            v.sort();
+            v.dedup();
+        })
+}
+
+/// Converts the Alpine architecture name to the GNU host triplet, as
+/// `arch_to_hostspec` in abuild.
+fn arch_to_hostspec(arch: &str) -> String {
+    let spec = match arch {
+        "aarch64" => "aarch64-alpine-linux-musl",
+        "armel" => "armv5-alpine-linux-musleabi",
+        "armhf" => "armv6-alpine-linux-musleabihf",
+        "armv7" => "armv7-alpine-linux-musleabihf",
+        "loongarch64" => "loongarch64-alpine-linux-musl",
+        "mips" => "mips-alpine-linux-musl",
+        "mips64" => "mips64-alpine-linux-musl",
+        "mipsel" => "mipsel-alpine-linux-musl",
+        "mips64el" => "mips64el-alpine-linux-musl",
+        "ppc" => "powerpc-alpine-linux-musl",
+        "ppc64" => "powerpc64-alpine-linux-musl",
+        "ppc64le" => "powerpc64le-alpine-linux-musl",
+        "riscv32" => "riscv32-alpine-linux-musl",
+        "riscv64" => "riscv64-alpine-linux-musl",
+        "s390x" => "s390x-alpine-linux-musl",
+        "x86" => "i586-alpine-linux-musl",
+        "x86_64" => "x86_64-alpine-linux-musl",
+        _ => return format!("{arch}-alpine-linux-musl"),
+    };
+    spec.to_owned()
+}
+
+/// Returns warnings for the commands found in the shell trace (stderr) that
+/// aren't variable assignments or [`HARMLESS_COMMANDS`].
+fn find_side_effects(stderr: &str) -> Vec<Warning> {
+    stderr
+        .lines()
+        .filter_map(|line| line.strip_prefix(XTRACE_PREFIX))
+        .filter(|cmd| {
+            let word = cmd.split_ascii_whitespace().next().unwrap_or("");
+            let is_assignment = word
+                .split_once('=')
+                .map_or(false, |(name, _)| is_variable_name(name));
+
+            !word.is_empty() && !is_assignment && !HARMLESS_COMMANDS.contains(&word)
+        })
+        .map(|cmd| Warning::SideEffect {
+            command: cmd.trim_end().to_owned(),
+        })
+        .collect()
+}
+
+/// Returns paths of the files sourced by the APKBUILD found in the shell trace
+/// (stderr), in order of appearance, deduplicated.
+fn find_includes(stderr: &str) -> Vec<PathBuf> {
+    stderr
+        .lines()
+        .filter_map(|line| line.strip_prefix(XTRACE_PREFIX))
+        .filter_map(|cmd| {
+            let mut words = cmd.split_ascii_whitespace();
+            matches!(words.next(), Some("." | "source"))
+                .then(|| words.next())
+                .flatten()
+        })
+        .skip(1) // the APKBUILD itself
+        .map(|path| Path::new(path.strip_prefix("./").unwrap_or(path)).to_owned())
+        .fold(Vec::new(), |mut acc, path| {
+            if !acc.contains(&path) {
+                acc.push(path);
+            }
+            acc
+        })
+}
+
+pub(crate) fn is_variable_name(s: &str) -> bool {
+    !s.is_empty()
+        && !s.starts_with(|c: char| c.is_ascii_digit())
+        && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Returns `true` if the line starts a function definition, e.g. `build() {`.
+fn is_function_start(line: &str) -> bool {
+    line.split_once("()")
+        .map(|(name, _)| is_variable_name(name.trim_end()))
+        .unwrap_or(false)
+}
+
+/// Decodes subpackages from the output of [`SPLIT_FUNCS_SCRIPT`]. `specs` is
+/// the value of `subpackages` (`<name>[:<func>[:<arch>]]`).
+fn decode_subpackages(specs: &str, value: &str) -> Result<Vec<Subpackage>, Error> {
+    let arches: HashMap<_, _> = specs
+        .split_ascii_whitespace()
+        .filter_map(|spec| {
+            let mut parts = spec.splitn(3, ':');
+            let name = parts.next()?;
+            parts.nth(1).map(|arch| (name, arch))
+        })
+        .collect();
+
+    value
+        .split_terminator('\x1D')
+        .map(|record| {
+            record
+                .trim()
+                .split('\x1F')
+                .map(str::trim)
+                .collect::<Vec<_>>()
+        })
+        .filter(|fields| fields.len() == 6)
+        .map(|fields| {
+            Ok(Subpackage {
+                name: fields[0].to_owned(),
+                split_func: fields[1].to_owned(),
+                arch: arches.get(fields[0]).map(|s| s.to_string()),
+                pkgdesc: fields[2].to_owned(),
+                depends: parse_dependencies("depends", fields[3])?,
+                provides: parse_dependencies("provides", fields[4])?,
+                install_if: parse_dependencies("install_if", fields[5])?,
+            })
+        })
+        .collect()
+}
+
+/// Decodes the `Apkbuild` from the given pairs like
+/// [`serde_key_value::from_ordered_pairs`], but recovers from missing and
+/// invalid fields by using default values and reporting them in `warnings`.
+fn decode_partial<'a>(
+    mut pairs: Vec<(&'a str, &'a str)>,
+    warnings: &mut Vec<Warning>,
+) -> Result<Apkbuild, Error> {
+    let mut recovered: Vec<Warning> = vec![];
+
+    loop {
+        let warning = match serde_key_value::from_ordered_pairs(pairs.iter().copied()) {
+            Ok(apkbuild) => {
+                warnings.extend(recovered);
+                return Ok(apkbuild);
+            }
+            Err(serde_key_value::Error::MissingField(field)) => {
+                pairs.push((field, if field == "pkgrel" { "0" } else { "" }));
+
+                // A required field that has been removed as invalid.
+                if recovered
+                    .iter()
+                    .any(|w| matches!(w, Warning::InvalidField { field: f, .. } if f == field))
+                {
+                    continue;
+                }
+                Warning::MissingField {
+                    field: field.to_owned(),
+                }
+            }
+            Err(serde_key_value::Error::InvalidField(e, field)) => {
+                pairs.retain(|(key, _)| *key != field);
+                Warning::InvalidField {
+                    field,
+                    message: e.to_string(),
+                }
+            }
+            Err(e) => bail!(Error::Decode(e)),
+        };
+        // The same problem repeated means that it cannot be recovered.
+        if recovered.contains(&warning) {
+            bail!(Error::Decode(serde_key_value::Error::Other(
+                warning.to_string()
+            )));
+        }
+        recovered.push(warning);
+    }
+}
+
+/// Returns the message of the error's source (cause), or of the error itself
+/// if it has no source.
+fn error_message(error: &Error) -> String {
+    match error {
+        Error::Decode(serde_key_value::Error::InvalidField(e, _)) => e.to_string(),
+        e => e.to_string(),
+    }
+}
+
+/// Returns names of the variables starting with the given prefix that are
+/// assigned anywhere in the APKBUILD (in order of appearance, deduplicated).
+fn find_variables_with_prefix<'a>(apkbuild: &'a str, prefix: &str) -> Vec<&'a str> {
+    apkbuild
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim_start();
+            let name_len = line
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(line.len());
+            let (name, rest) = line.split_at(name_len);
+
+            (rest.starts_with('=') && name.len() > prefix.len() && name.starts_with(prefix))
+                .then_some(name)
+        })
+        .fold(Vec::new(), |mut acc, name| {
+            if !acc.contains(&name) {
+                acc.push(name);
+            }
+            acc
+        })
+}
+
+/// The conventional order of the APKBUILD variables (as `newapkbuild` writes
+/// them).
+pub(crate) const VARIABLES_ORDER: &[&str] = &[
+    "pkgname",
+    "pkgver",
+    "pkgrel",
+    "pkgdesc",
+    "url",
+    "arch",
+    "license",
+    "depends",
+    "makedepends",
+    "makedepends_build",
+    "makedepends_host",
+    "checkdepends",
+    "install_if",
+    "pkgusers",
+    "pkggroups",
+    "provides",
+    "provider_priority",
+    "pcprefix",
+    "sonameprefix",
+    "replaces",
+    "replaces_priority",
+    "install",
+    "triggers",
+    "subpackages",
+    "source",
+    "builddir",
+    "options",
+];
+
+/// Location of a top-level variable assignment in the APKBUILD text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Assignment {
+    /// Byte offset of the start of the line with the assignment.
+    start: usize,
+    /// Byte range of the raw value (without quotes).
+    value: Range<usize>,
+    /// Byte offset just after the assignment (including the closing quote).
+    end: usize,
+    /// The quote character (`"`, `'`) or `None` if the value is not quoted.
+    quote: Option<char>,
+}
+
+/// Finds the first top-level assignment of the given variable.
+fn find_assignment(apkbuild: &str, name: &str) -> Option<Assignment> {
+    let start = apkbuild.match_indices(name).map(|(i, _)| i).find(|&i| {
+        (i == 0 || apkbuild.as_bytes()[i - 1] == b'\n')
+            && apkbuild[i + name.len()..].starts_with('=')
+    })?;
+    assignment_at(apkbuild, start, name)
+}
+
+/// Parses the assignment of the given variable starting at byte offset `start`
+/// (i.e. `apkbuild[start..]` must start with `<name>=`).
+fn assignment_at(apkbuild: &str, start: usize, name: &str) -> Option<Assignment> {
+    let value_start = start + name.len() + 1;
+    let rest = &apkbuild[value_start..];
+
+    match rest.chars().next() {
+        Some(quote @ ('"' | '\'')) => {
+            let mut escaped = false;
+            let len = rest[1..].find(|c| {
+                let found = c == quote && !(escaped && quote == '"');
+                escaped = c == '\\' && !escaped;
+                found
+            })?;
+            Some(Assignment {
+                start,
+                value: value_start + 1..value_start + 1 + len,
+                end: value_start + len + 2,
+                quote: Some(quote),
+            })
+        }
+        _ => {
+            let len = rest
+                .find(|c: char| c.is_ascii_whitespace() || c == ';')
+                .unwrap_or(rest.len());
+            Some(Assignment {
+                start,
+                value: value_start..value_start + len,
+                end: value_start + len,
+                quote: None,
+            })
+        }
+    }
+}
+
+/// Finds the first top-level assignment of the given variable and returns the
+/// quote character (`"`, `'` or an empty string) and the raw, unexpanded value.
+fn find_raw_variable<'a>(apkbuild: &'a str, name: &str) -> Option<(&'a str, &'a str)> {
+    find_assignment(apkbuild, name).map(|a| {
+        let quote = if a.quote.is_some() {
+            &apkbuild[a.value.start - 1..a.value.start]
+        } else {
+            ""
+        };
+        (quote, &apkbuild[a.value])
+    })
+}
+
+/// Compares the remote sources with the `source` evaluated with `pkgver` set
+/// to [`PKGVER_PLACEHOLDER`] and returns warnings for the sources that still
+/// contain the literal `pkgver`.
+fn find_hardcoded_version(apkbuild: &Apkbuild, unexpanded: &str) -> Vec<Warning> {
+    let unexpanded: Vec<_> = unexpanded.split_ascii_whitespace().collect();
+
+    if apkbuild.pkgver.is_empty() || unexpanded.len() != apkbuild.source.len() {
+        return vec![];
+    }
+    apkbuild
+        .source
+        .iter()
+        .zip(unexpanded)
+        .filter(|(source, raw)| source.is_remote() && raw.contains(&apkbuild.pkgver))
+        .map(|(source, _)| Warning::HardcodedVersion {
+            uri: source.uri.clone(),
+        })
+        .collect()
+}
+
+fn parse_dependencies(var: &str, value: &str) -> Result<Vec<Dependency>, Error> {
+    value
+        .split_ascii_whitespace()
+        .map(Dependency::from_str)
+        .collect::<Result<_, _>>()
+        .map_err(|e| serde_key_value::Error::InvalidField(Box::new(e), var.to_owned()).into())
+}
+
+fn parse_comment_attribute<'a>(name: &str, line: &'a str) -> Option<&'a str> {
+    line.trim()
+        .strip_prefix("# ")
+        .and_then(|s| s.trim_start().strip_prefix(name))
+        .map(str::trim_start)
+        .and_then(|s| (!s.is_empty()).then_some(s))
+}
+
+fn parse_maintainer(apkbuild: &str) -> Option<&str> {
+    apkbuild
+        .lines()
+        .find_map(|s| parse_comment_attribute("Maintainer:", s))
+}
+
+fn parse_contributors(apkbuild: &str, limit: Option<usize>) -> impl Iterator<Item = &str> {
+    apkbuild
+        .lines()
+        .take(limit.unwrap_or(usize::MAX))
+        .take_while(move |s| {
+            limit.is_some() || s.trim().is_empty() || s.trim_start().starts_with('#')
+        })
+        .filter_map(|s| parse_comment_attribute("Contributor:", s))
+}
+
+fn parse_comment_attributes<S: AsRef<str>>(
+    apkbuild: &str,
+    names: &[S],
+) -> BTreeMap<String, Vec<String>> {
+    let names: Vec<_> = names
+        .iter()
+        .map(|name| (name.as_ref(), format!("{}:", name.as_ref())))
+        .collect();
+
+    apkbuild.lines().fold(BTreeMap::new(), |mut acc, line| {
+        for (name, prefix) in &names {
+            if let Some(value) = parse_comment_attribute(prefix, line) {
+                acc.entry(name.to_string())
+                    .or_insert_with(Vec::new)
+                    .push(value.to_owned());
+            }
+        }
+        acc
+    })
+}
+
+fn parse_secfixes(apkbuild: &str) -> Result<Vec<Secfix>, Error> {
+    let mut lines = apkbuild.lines().enumerate();
+    let mut secfixes: Vec<Secfix> = vec![];
+
+    if !lines.any(|(_, s)| s.starts_with("# secfixes:")) {
+        return Ok(secfixes);
+    }
+
+    for pair in lines.map_while(|(i, s)| s.strip_prefix("#   ").map(|s| (i, s))) {
+        let line_no = pair.0 + 1;
+        let line = pair.1.split(" #").next().unwrap().trim(); // this cannot panic
+
+        if let Some(line) = line.strip_prefix("- ") {
+            if let Some(Secfix { fixes, .. }) = secfixes.last_mut() {
+                fixes.push(line.trim_start().to_string());
+            } else {
+                bail!(Error::MalformedSecfixes(line_no, pair.1.to_owned()));
+            }
+        } else if let Some(key) = line.strip_suffix(':') {
+            secfixes.push(Secfix {
+                version: key.to_owned(),
+                fixes: Vec::with_capacity(3),
+            });
+        } else {
+            bail!(Error::MalformedSecfixes(line_no, pair.1.to_owned()));
+        }
+    }
+    Ok(secfixes)
+}
+
+fn parse_secfixes_lenient(apkbuild: &str) -> (Vec<Secfix>, Vec<Warning>) {
+    let mut lines = apkbuild.lines().enumerate();
+    let mut secfixes: Vec<Secfix> = vec![];
+    let mut warnings: Vec<Warning> = vec![];
+
+    /// Splits the line into the comment text (without the leading `#` and
+    /// a trailing comment) and a flag if the `#` is preceded by whitespace.
+    fn comment(s: &str) -> Option<(&str, bool)> {
+        let rest = s.trim_start();
+        rest.strip_prefix('#')
+            .map(|c| (c.split(" #").next().unwrap(), rest.len() != s.len())) // this cannot panic
+    }
+
+    let start = lines.find(|(_, s)| {
+        comment(s).map_or(false, |(c, _)| matches!(c.trim(), "secfixes:" | "secfixes"))
+    });
+    match start {
+        Some((i, s)) if s != "# secfixes:" => warnings.push(Warning::MalformedSecfixes {
+            line: i + 1,
+            text: s.trim().to_owned(),
+        }),
+        Some(_) => (),
+        None => return (secfixes, warnings),
+    }
+
+    let block = lines.map_while(|(i, s)| {
+        comment(s)
+            .map(|(raw, indented)| (i, raw.trim_end(), indented))
+            .filter(|(_, raw, _)| !raw.trim_start().is_empty())
+    });
+    for (i, raw, indented) in block {
+        let line = raw.trim_start();
+        let mut warn = || {
+            warnings.push(Warning::MalformedSecfixes {
+                line: i + 1,
+                text: line.to_owned(),
+            })
+        };
+
+        // The well-formed lines are `#   <version>:` and `#     - <id>`.
+        if let Some(ids) = line.strip_prefix('-') {
+            let well_formed = raw
+                .strip_prefix("     - ")
+                .map_or(false, |ids| !ids.starts_with(char::is_whitespace));
+
+            match secfixes.last_mut() {
+                Some(_) if ids.trim().is_empty() => warn(),
+                Some(Secfix { fixes, .. }) => {
+                    fixes.extend(ids.split_ascii_whitespace().map(str::to_owned));
+                    if indented || !well_formed {
+                        warn();
+                    }
+                }
+                None => warn(),
+            }
+        } else if let Some(key) = line.strip_suffix(':') {
+            if indented || raw.strip_prefix("   ") != Some(line) || key.trim_end() != key {
+                warn();
+            }
+            secfixes.push(Secfix::new(key.trim_end(), Vec::with_capacity(3)));
+        } else if line.starts_with(|c: char| c.is_ascii_digit()) && !line.contains(' ') {
+            // Version with a missing colon.
+            warn();
+            secfixes.push(Secfix::new(line, Vec::with_capacity(3)));
+        } else {
+            warn();
+        }
+    }
+    (secfixes, warnings)
+}
+
+/// Decodes `source` and the checksums of the given algorithms (in order of
+/// preference) into a list of [`Source`]s. The legacy `sha256sums` and
+/// `md5sums` are used only for sources missing in the `sha512sums`.
+fn decode_source_and_checksums(
+    source: &str,
+    checksums: &[(ChecksumAlg, &str)],
+) -> Result<Vec<Source>, Error> {
+    let mut checksums: Vec<(ChecksumAlg, HashMap<&str, &str>)> = checksums
+        .iter()
+        .map(|(alg, sums)| {
+            let sums = sums
+                .split_ascii_whitespace()
+                .chunks_exact()
+                .map(|[a, b]| (b, a))
+                .collect();
+            (*alg, sums)
+        })
+        .collect();
+
+    source
+        .split_ascii_whitespace()
+        .map(|item| {
+            let (name, uri) = if let Some((name, uri)) = item.split_once("::") {
+                (name, uri)
+            } else if let Some((_, name)) = item.rsplit_once('/') {
+                (name, item)
+            } else {
+                (item, item)
+            };
+            checksums
+                .iter_mut()
+                .find_map(|(alg, sums)| sums.remove(name).map(|sum| (*alg, sum)))
+                .map(|(alg, checksum)| Source::new(name, uri, checksum).checksum_alg(alg))
+                .ok_or_else(|| Error::MissingChecksum(name.to_owned()))
+        })
+        .collect()
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+#[path = "mod.test.rs"]
+mod test;
@@ -0,0 +1,1007 @@
+use indoc::indoc;
+use serde_json::json;
+
+use super::*;
+use crate::internal::test_utils::{assert, assert_from_to_json, assert_let, dependency, S};
+
+fn sample_apkbuild() -> Apkbuild {
+    Apkbuild {
+        maintainer: Some(S!("Jakub Jirutka <jakub@jirutka.cz>")),
+        contributors: vec![
+            S!("Francesco Colista <fcolista@alpinelinux.org>"),
+            S!("Natanael Copa <ncopa@alpinelinux.org>")
+        ],
+        attributes: BTreeMap::new(),
+        pkgname: S!("sample"),
+        pkgver: S!("1.2.3"),
+        pkgrel: 2,
+        pkgdesc: S!("A sample aport for testing"),
+        url: S!("https://example.org/sample"),
+        arch: ARCH_ALL
+            .iter()
+            .filter(|s| !matches!(**s, "riscv64" | "s390x"))
+            .map(ToString::to_string)
+            .collect(),
+        arch_raw: S!("all !riscv64 !s390x"),
+        license: S!("ISC and BSD-2-Clause and BSD-3-Clause"),
+        depends: vec![
+            dependency("ruby>=3.0"),
+            dependency("!sample-legacy"),
+        ],
+        makedepends: vec![
+            dependency("openssl-dev>3"),
+            dependency("zlib-dev"),
+        ],
+        makedepends_build: vec![],
+        makedepends_host: vec![],
+        checkdepends: vec![
+            dependency("ruby-rspec"),
+        ],
+        subpackage_depends: BTreeMap::from([
+            (S!("dev"), vec![dependency("openssl-dev>3"), dependency("zlib-dev")]),
+        ]),
+        install_if: vec![],
+        pkgusers: vec![],
+        pkggroups: vec![],
+        provides: vec![
+            dependency("sample2=1.2.3-r2"),
+        ],
+        provider_priority: Some(100),
+        pcprefix: None,
+        sonameprefix: Some(S!("smpl")),
+        replaces: vec![
+            dependency("sample2"),
+        ],
+        replaces_priority: None,
+        install: vec![S!("sample.post-install"), S!("sample.post-upgrade")],
+        triggers: vec![S!("sample.trigger=/usr/share/sample/*")],
+        subpackages: vec![
+            S!("sample-doc"),
+            S!("sample-dev"),
+        ],
+        source: vec![
+            Source::new("sample-1.2.3.tar.gz", "https://example.org/sample/sample-1.2.3.tar.gz", "54286070812a47b629f68757046d3c9a1bdd2b5d1c3b84a5c8e4cb92f1331afa745443f7238175835d8cfbe5b8dd442e00c75c3a5b5b8f8efd8d2ec8f636dad4"),
+            Source::new("sample.initd", "sample.initd", "b512bcb8bae11853a3006e2122d7e652806d4bf2234638d8809fd823375b5b0bd590f7d6a90412baffcc3b7b6a0f197a10986728a70f24fe628f91bfb651d266"),
+            Source::new("sample.confd", "sample.confd", "6eda39920cccb1238b104bb90ac4be2c32883897c72363560d8d39345819cdeff535680e78396052b2b8f981e169ad9b3c30da724def80a1501785d82ce7fa25")
+        ],
+        options: vec![AbuildOption::Check(true)],
+        secfixes: vec![
+            Secfix::new("1.2.3-r2", vec![S!("CVE-2022-12347"), S!("CVE-2022-12346")]),
+            Secfix::new("1.2.0-r0", vec![S!("CVE-2021-12345")]),
+        ],
+        subpackage_info: vec![],
+        private_vars: BTreeMap::new(),
+        includes: vec![],
+        raw: vec![],
+        warnings: vec![],
+    }
+}
+
+#[test]
+fn read_apkbuild() {
+    let fixture = Path::new("../fixtures/aports/sample/APKBUILD");
+    assert!(ApkbuildReader::new().read_apkbuild(fixture).unwrap() == sample_apkbuild());
+}
+
+#[test]
+#[cfg(all(unix, feature = "shell-workers"))]
+fn read_apkbuild_shell_workers() {
+    let dir = tempfile::tempdir().unwrap();
+    let filepath = dir.path().join("APKBUILD");
+    fs::write(&filepath, "pkgname=foo\nexit 3\n").unwrap();
+
+    let fixture = Path::new("../fixtures/aports/sample/APKBUILD");
+    let mut reader = ApkbuildReader::new();
+    reader.shell_workers(1);
+
+    assert!(reader.read_apkbuild(fixture).unwrap() == sample_apkbuild());
+    assert_let!(Err(Error::Evaluate(status, _)) = reader.read_apkbuild(&filepath));
+    assert!(status.code() == Some(3));
+    // The worker is reused and not affected by the previous APKBUILD.
+    assert!(reader.read_apkbuild(fixture).unwrap() == sample_apkbuild());
+    assert!(reader.lock_idle_workers().len() == 1);
+}
+
+#[test]
+#[rustfmt::skip]
+fn abuild_option_from_str_and_display() {
+    for (s              , option) in [
+        ("!check"       , AbuildOption::Check(true)                    ),
+        ("net"          , AbuildOption::Net(false)                     ),
+        ("chmod-clean"  , AbuildOption::ChmodClean(false)              ),
+        ("!tracedeps"   , AbuildOption::Tracedeps(true)                ),
+        ("foo"          , AbuildOption::Other(S!("foo"), false)        ),
+        ("!foo-bar"     , AbuildOption::Other(S!("foo-bar"), true)     ),
+    ] {
+        assert!(AbuildOption::from_str(s).unwrap() == option);
+        assert!(option.to_string() == s);
+    }
+}
+
+#[test]
+fn apkbuild_has_option() {
+    let apkbuild = Apkbuild {
+        options: vec![AbuildOption::Check(true), AbuildOption::Net(false)],
+        ..Default::default()
+    };
+    assert!(apkbuild.has_option("!check"));
+    assert!(apkbuild.has_option("net"));
+    assert!(!apkbuild.has_option("check"));
+    assert!(!apkbuild.has_option("suid"));
+}
+
+#[test]
+fn apkbuild_expected_pkginfo() {
+    let expected = PkgInfo {
+        maintainer: Some(S!("Jakub Jirutka <jakub@jirutka.cz>")),
+        pkgname: S!("sample"),
+        pkgver: S!("1.2.3-r2"),
+        pkgdesc: S!("A sample aport for testing"),
+        url: S!("https://example.org/sample"),
+        arch: S!("x86_64"),
+        license: S!("ISC and BSD-2-Clause and BSD-3-Clause"),
+        depends: vec![dependency("ruby>=3.0")],
+        conflicts: vec![dependency("sample-legacy")],
+        provides: vec![dependency("sample2=1.2.3-r2")],
+        provider_priority: Some(100),
+        replaces: vec![dependency("sample2")],
+        triggers: vec![S!("/usr/share/sample/*")],
+        origin: S!("sample"),
+        ..Default::default()
+    };
+    assert!(sample_apkbuild().expected_pkginfo("x86_64") == expected);
+}
+
+#[test]
+fn test_find_variables_with_prefix() {
+    let input = indoc! {r#"
+        pkgname=sample
+        depends_dev="foo"
+        depends="bar"
+        depends_doc="baz"
+        	depends_dev="$depends_dev qux"
+        depends_static
+    "#};
+    assert!(find_variables_with_prefix(input, "depends_") == vec!["depends_dev", "depends_doc"]);
+}
+
+#[test]
+fn read_apkbuild_hardcoded_version() {
+    let dir = tempfile::tempdir().unwrap();
+    let filepath = dir.path().join("APKBUILD");
+    fs::write(
+        &filepath,
+        indoc! {r#"
+            pkgname=foo
+            pkgver=1.2.3
+            pkgrel=0
+            pkgdesc="Foo"
+            url="https://example.org/foo"
+            license="MIT"
+            _majorver=${pkgver%.*}
+            source="https://example.org/foo/$_majorver/foo-$pkgver.tar.gz
+            	https://example.org/patches/foo-1.2.3-fix.patch
+            	foo-v1.2.3.tar.gz::https://example.org/foo/v$pkgver.tar.gz
+            	foo.initd
+            	"
+            sha512sums="
+            aaaa  foo-1.2.3.tar.gz
+            bbbb  foo-1.2.3-fix.patch
+            cccc  foo-v1.2.3.tar.gz
+            dddd  foo.initd
+            "
+        "#},
+    )
+    .unwrap();
+
+    let apkbuild = ApkbuildReader::new()
+        .detect_hardcoded_version(true)
+        .read_apkbuild(&filepath)
+        .unwrap();
+
+    assert!(
+        apkbuild.warnings
+            == vec![
+                Warning::HardcodedVersion {
+                    uri: S!("https://example.org/patches/foo-1.2.3-fix.patch")
+                },
+                Warning::HardcodedVersion {
+                    uri: S!("https://example.org/foo/v1.2.3.tar.gz")
+                },
+            ]
+    );
+
+    let apkbuild = ApkbuildReader::new().read_apkbuild(&filepath).unwrap();
+    assert!(apkbuild.warnings.is_empty());
+}
+
+#[test]
+fn read_apkbuild_keep_raw() {
+    let fixture = Path::new("../fixtures/aports/sample/APKBUILD");
+
+    let apkbuild = ApkbuildReader::new()
+        .keep_raw(RawValues::Fields)
+        .read_apkbuild(fixture)
+        .unwrap();
+
+    let raw: HashMap<_, _> = apkbuild
+        .raw
+        .iter()
+        .map(|(k, v)| (k.as_str(), v.as_str()))
+        .collect();
+    assert!(raw["pkgname"] == "sample");
+    assert!(raw["arch"] == "all !riscv64 !s390x");
+    assert!(raw["provides"] == "sample2=1.2.3-r2");
+    assert!(raw["depends_dev"] == "openssl-dev>3 zlib-dev");
+    assert!(raw["pcprefix"].is_empty());
+    assert!(apkbuild.raw.first() == Some(&(S!("pkgname"), S!("sample"))));
+
+    let apkbuild = Apkbuild {
+        raw: vec![],
+        ..apkbuild
+    };
+    assert!(apkbuild == sample_apkbuild());
+}
+
+#[test]
+fn read_apkbuild_keep_raw_all_variables() {
+    let dir = tempfile::tempdir().unwrap();
+    let filepath = dir.path().join("APKBUILD");
+    fs::write(
+        &filepath,
+        indoc! {r#"
+            pkgname=foo
+            pkgver=1.2.3
+            pkgrel=0
+            pkgdesc="Foo"
+            url="https://example.org/foo"
+            license="MIT"
+            _majorver=${pkgver%.*}
+            _flavor=lts
+        "#},
+    )
+    .unwrap();
+
+    let apkbuild = ApkbuildReader::new()
+        .keep_raw(RawValues::AllVariables)
+        .read_apkbuild(&filepath)
+        .unwrap();
+
+    assert!(apkbuild.raw.len() == Apkbuild::FIELDS.len() + 3 + 2);
+    assert!(
+        apkbuild.raw[apkbuild.raw.len() - 2..]
+            == [(S!("_majorver"), S!("1.2")), (S!("_flavor"), S!("lts"))]
+    );
+
+    let apkbuild = ApkbuildReader::new().read_apkbuild(&filepath).unwrap();
+    assert!(apkbuild.raw.is_empty());
+}
+
+#[cfg(feature = "shell-timeout")]
+#[test]
+fn read_apkbuild_timeout() {
+    let dir = tempfile::tempdir().unwrap();
+    let filepath = dir.path().join("APKBUILD");
+    fs::write(&filepath, "echo 'fetching...' >&2\nsleep 2\npkgname=foo\n").unwrap();
+
+    let result = ApkbuildReader::new()
+        .time_limit(Duration::from_millis(200))
+        .read_apkbuild(&filepath);
+
+    assert_let!(
+        Err(Error::Timeout {
+            elapsed,
+            stdout,
+            stderr
+        }) = result
+    );
+    assert!(elapsed >= Duration::from_millis(200));
+    assert!(stdout.is_empty());
+    assert!(stderr == "fetching...\n");
+}
+
+#[test]
+fn read_apkbuild_partial() {
+    let dir = tempfile::tempdir().unwrap();
+    let filepath = dir.path().join("APKBUILD");
+    fs::write(
+        &filepath,
+        indoc! {r#"
+            pkgver=1.2.3
+            pkgrel=x
+            depends="ruby>=3.0 foo=="
+            source="https://example.org/foo-$pkgver.tar.gz"
+        "#},
+    )
+    .unwrap();
+
+    assert_let!(Err(Error::Decode(_)) = ApkbuildReader::new().read_apkbuild(&filepath));
+
+    let apkbuild = ApkbuildReader::new()
+        .allow_partial(true)
+        .read_apkbuild(&filepath)
+        .unwrap();
+
+    assert!(apkbuild.pkgname.is_empty());
+    assert!(apkbuild.pkgver == "1.2.3");
+    assert!(apkbuild.pkgrel == 0);
+    assert!(apkbuild.depends.is_empty());
+    assert!(apkbuild.source.is_empty());
+
+    let fields: Vec<_> = apkbuild
+        .warnings
+        .iter()
+        .map(|w| match w {
+            Warning::MissingField { field } => format!("missing {field}"),
+            Warning::InvalidField { field, .. } => format!("invalid {field}"),
+            w => panic!("unexpected warning: {w}"),
+        })
+        .collect();
+    assert!(
+        fields
+            == [
+                "invalid pkgrel",
+                "invalid depends",
+                "missing pkgname",
+                "invalid source"
+            ]
+    );
+}
+
+#[test]
+fn read_apkbuild_side_effects() {
+    let dir = tempfile::tempdir().unwrap();
+    let filepath = dir.path().join("APKBUILD");
+    fs::write(
+        &filepath,
+        indoc! {r#"
+            pkgname=foo
+            pkgver=1.2.3
+            pkgrel=0
+            pkgdesc="Foo with  spaces"
+            url="https://example.org/foo"
+            license="MIT"
+            _arch=$(uname -m)
+            [ "$_arch" = x86_64 ] && echo "x86_64"
+            touch .stamp
+            build() {
+            	rm -rf build
+            }
+        "#},
+    )
+    .unwrap();
+
+    let apkbuild = ApkbuildReader::new()
+        .detect_side_effects(true)
+        .read_apkbuild(&filepath)
+        .unwrap();
+
+    assert!(
+        apkbuild.warnings
+            == vec![
+                Warning::SideEffect {
+                    command: S!("uname -m")
+                },
+                Warning::SideEffect {
+                    command: S!("touch .stamp")
+                },
+            ]
+    );
+
+    let apkbuild = ApkbuildReader::new().read_apkbuild(&filepath).unwrap();
+    assert!(apkbuild.warnings.is_empty());
+}
+
+#[test]
+fn read_apkbuild_track_includes() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir(dir.path().join("foo")).unwrap();
+    fs::write(dir.path().join("version.sh"), "_ver=1.2.3\n").unwrap();
+    fs::write(dir.path().join("foo/common.sh"), ". ../version.sh\n").unwrap();
+
+    let filepath = dir.path().join("foo/APKBUILD");
+    fs::write(
+        &filepath,
+        indoc! {r#"
+            . ./common.sh
+            source ../version.sh
+            pkgname=foo
+            pkgver=$_ver
+            pkgrel=0
+            pkgdesc="Foo"
+            url="https://example.org/foo"
+            license="MIT"
+        "#},
+    )
+    .unwrap();
+
+    let apkbuild = ApkbuildReader::new()
+        .track_includes(true)
+        .read_apkbuild(&filepath)
+        .unwrap();
+
+    assert!(apkbuild.pkgver == "1.2.3");
+    assert!(apkbuild.includes == [PathBuf::from("common.sh"), PathBuf::from("../version.sh")]);
+    assert!(apkbuild.warnings.is_empty());
+}
+
+#[test]
+fn read_apkbuild_private_vars() {
+    let dir = tempfile::tempdir().unwrap();
+    let filepath = dir.path().join("APKBUILD");
+    fs::write(
+        &filepath,
+        indoc! {r#"
+            pkgname=foo
+            pkgver=1.2.3
+            pkgrel=0
+            pkgdesc="Foo"
+            url="https://example.org/foo"
+            license="MIT"
+            _llvmver=15
+            _gitrev=abc123
+            _empty=
+            depends="llvm$_llvmver"
+
+            build() {
+            	local _jobs=4
+            	_builddir="$builddir/build"
+            }
+        "#},
+    )
+    .unwrap();
+
+    let apkbuild = ApkbuildReader::new()
+        .private_vars(true)
+        .read_apkbuild(&filepath)
+        .unwrap();
+
+    assert!(
+        apkbuild.private_vars
+            == BTreeMap::from([(S!("_gitrev"), S!("abc123")), (S!("_llvmver"), S!("15"))])
+    );
+    assert!(apkbuild.depends == [dependency("llvm15")]);
+}
+
+#[test]
+fn read_apkbuild_abuild_conf() {
+    let dir = tempfile::tempdir().unwrap();
+    let conf_path = dir.path().join("abuild.conf");
+    fs::write(&conf_path, "DISTFILES_MIRROR=https://mirror.example.org\n").unwrap();
+
+    fs::create_dir(dir.path().join("foo")).unwrap();
+    let filepath = dir.path().join("foo/APKBUILD");
+    fs::write(
+        &filepath,
+        indoc! {r#"
+            pkgname=foo
+            pkgver=1.2.3
+            pkgrel=0
+            pkgdesc="Foo"
+            url="${DISTFILES_MIRROR:-https://example.org}/foo"
+            license="MIT"
+        "#},
+    )
+    .unwrap();
+
+    let apkbuild = ApkbuildReader::new()
+        .abuild_conf(&conf_path)
+        .track_includes(true)
+        .read_apkbuild(&filepath)
+        .unwrap();
+
+    assert!(apkbuild.url == "https://mirror.example.org/foo");
+    assert!(apkbuild.includes.is_empty());
+
+    let apkbuild = ApkbuildReader::new().read_apkbuild(&filepath).unwrap();
+    assert!(apkbuild.url == "https://example.org/foo");
+}
+
+#[test]
+fn read_apkbuild_target_arch() {
+    let dir = tempfile::tempdir().unwrap();
+    let filepath = dir.path().join("APKBUILD");
+    fs::write(
+        &filepath,
+        indoc! {r#"
+            pkgname=foo
+            pkgver=1.2.3
+            pkgrel=0
+            pkgdesc="Foo for $CHOST"
+            url="https://example.org/foo"
+            arch="all"
+            license="MIT"
+            depends="bar"
+            case "$CARCH" in
+            	x86_64) depends="$depends baz";;
+            esac
+        "#},
+    )
+    .unwrap();
+
+    let mut reader = ApkbuildReader::new();
+
+    let apkbuild = reader
+        .target_arch("x86_64")
+        .read_apkbuild(&filepath)
+        .unwrap();
+    assert!(apkbuild.pkgdesc == "Foo for x86_64-alpine-linux-musl");
+    assert!(apkbuild.depends == [dependency("bar"), dependency("baz")]);
+
+    let apkbuild = reader.target_arch("x86").read_apkbuild(&filepath).unwrap();
+    assert!(apkbuild.pkgdesc == "Foo for i586-alpine-linux-musl");
+    assert!(apkbuild.depends == [dependency("bar")]);
+}
+
+#[test]
+fn read_apkbuild_eval_split_functions() {
+    let dir = tempfile::tempdir().unwrap();
+    let filepath = dir.path().join("APKBUILD");
+    fs::write(
+        &filepath,
+        indoc! {r#"
+            pkgname=foo
+            pkgver=1.2.3
+            pkgrel=0
+            pkgdesc="Foo"
+            url="https://example.org/foo"
+            license="MIT"
+            depends="bar"
+            subpackages="
+            	$pkgname-doc
+            	$pkgname-bash-completion:bashcomp:noarch
+            	py3-$pkgname:_py3
+            	"
+
+            bashcomp() {
+            	pkgdesc="$pkgdesc (bash completion)"
+            	install_if="$pkgname=$pkgver-r$pkgrel bash-completion"
+            	amove usr/share/bash-completion
+            }
+
+            _py3() {
+            	pkgdesc="$pkgdesc (Python bindings)"
+            	depends="$pkgname=$pkgver-r$pkgrel python3"
+            	provides="py-$pkgname"
+            	mkdir -p "$subpkgdir"/usr/lib
+            	mv "$pkgdir"/usr/lib/python* "$subpkgdir"/usr/lib/
+            }
+        "#},
+    )
+    .unwrap();
+
+    let apkbuild = ApkbuildReader::new()
+        .eval_split_functions(true)
+        .read_apkbuild(&filepath)
+        .unwrap();
+
+    assert!(
+        apkbuild.subpackage_info
+            == vec![
+                Subpackage {
+                    name: S!("foo-bash-completion"),
+                    split_func: S!("bashcomp"),
+                    arch: Some(S!("noarch")),
+                    pkgdesc: S!("Foo (bash completion)"),
+                    install_if: vec![dependency("foo=1.2.3-r0"), dependency("bash-completion")],
+                    ..Default::default()
+                },
+                Subpackage {
+                    name: S!("py3-foo"),
+                    split_func: S!("_py3"),
+                    arch: None,
+                    pkgdesc: S!("Foo (Python bindings)"),
+                    depends: vec![dependency("foo=1.2.3-r0"), dependency("python3")],
+                    provides: vec![dependency("py-foo")],
+                    ..Default::default()
+                },
+            ]
+    );
+    assert!(apkbuild.depends == [dependency("bar")]);
+}
+
+#[test]
+#[rustfmt::skip]
+fn test_find_raw_variable() {
+    for (input                                  , expected                          ) in [
+        ("source=\"a $pkgver\nb\"\n"             , Some(("\"", "a $pkgver\nb"))     ),
+        ("source=\"a \\\"q\\\" b\""               , Some(("\"", "a \\\"q\\\" b"))     ),
+        ("source='a $pkgver'"                   , Some(("'", "a $pkgver"))          ),
+        ("x=1\nsource=$pkgname.tar.gz; y=2"     , Some(("", "$pkgname.tar.gz"))     ),
+        ("_source=\"a\"\n\tsource=\"b\""          , None                              ),
+        ("source=\"unterminated"                , None                              ),
+    ] {
+        assert!(find_raw_variable(input, "source") == expected, "input: {input:?}");
+    }
+}
+
+#[test]
+#[rustfmt::skip]
+fn test_parse_maintainer() {
+    for (input, expected) in [
+        ("\n# sample\n# Maintainer: Kevin Flynn\n", Some("Kevin Flynn")            ),
+        ("#   Maintainer:  Kevin Flynn  \n"       , Some("Kevin Flynn")            ),
+        ("# Maintainer: Flynn <flynn@encom.com>\n", Some("Flynn <flynn@encom.com>")),
+        ("#Maintainer: No One\n"                  , None                           ),
+        ("# Some comment\n\npkgname=sample\n"     , None                           ),
+    ] {
+        assert!(parse_maintainer(input) == expected);
+    }
+}
+
+#[test]
+#[rustfmt::skip]
+fn test_parse_contributors() {
+    for (input, expected) in [
+        ("\n# sample\n#  Contributor: Kevin Flynn\n"         , vec!["Kevin Flynn"]),
+        ("# Contributor: KF\n# Contributor: AB\n"            , vec!["KF", "AB"]   ),
+        ("# Contributor: KF\n\n# sample\n# Contributor: AB\n", vec!["KF", "AB"]   ),
+        ("# Maintainer: No One"                              , vec![]             ),
+    ] {
+        assert!(parse_contributors(input, Some(10)).collect::<Vec<_>>() == expected);
+    }
+}
+
+#[test]
+fn test_parse_contributors_limit() {
+    let input = indoc! {"
+        # Contributor: KF
+        # This is a long license banner
+        #
+        #
+        # Contributor: AB
+        pkgname=sample
+        # Contributor: XY
+    "};
+    assert!(parse_contributors(input, Some(2)).collect::<Vec<_>>() == vec!["KF"]);
+    assert!(parse_contributors(input, None).collect::<Vec<_>>() == vec!["KF", "AB"]);
+}
+
+#[test]
+fn test_parse_comment_attributes() {
+    let input = indoc! {"
+        # Maintainer: Kevin Flynn
+        # Patch-source: https://example.org/a.patch
+        # Patch-source: https://example.org/b.patch
+        # Upstream-Issue:  https://example.org/issues/1
+        # Other: ignored
+        pkgname=sample
+    "};
+    let expected = BTreeMap::from([
+        (
+            S!("Patch-source"),
+            vec![
+                S!("https://example.org/a.patch"),
+                S!("https://example.org/b.patch"),
+            ],
+        ),
+        (
+            S!("Upstream-Issue"),
+            vec![S!("https://example.org/issues/1")],
+        ),
+    ]);
+    assert!(parse_comment_attributes(input, &["Patch-source", "Upstream-Issue"]) == expected);
+}
+
+#[test]
+fn test_parse_secfixes() {
+    let input = indoc! {"
+        # Maintainer: me
+        pkgname=sample
+
+        # secfixes:
+        #   1.1-r0:
+        #   - CVE-2022-1236  # comment
+        #   1.0-r0:
+        #     - CVE-2022-1235
+        #      -  CVE-2022-1234
+        #
+    "};
+    let expected = vec![
+        Secfix::new("1.1-r0", vec!["CVE-2022-1236".to_owned()]),
+        Secfix::new(
+            "1.0-r0",
+            vec!["CVE-2022-1235".to_owned(), "CVE-2022-1234".to_owned()],
+        ),
+    ];
+    assert!(parse_secfixes(input).unwrap() == expected);
+
+    let input = indoc! {"
+        # Maintainer: me
+        pkgname=sample
+    "};
+    assert!(parse_secfixes(input).unwrap() == vec![]);
+
+    let input = indoc! {"
+        # secfixes:
+        #   - CVE-2022-1236
+        #   - CVE-2022-1235
+    "};
+    assert_let!(Err(err @ Error::MalformedSecfixes(..)) = parse_secfixes(input));
+    assert!(format!("{}", err) == "syntax error in secfixes on line 2: '- CVE-2022-1236'");
+
+    let input = indoc! {"
+        # secfixes:
+        #   1.2-r0:
+        #     - CVE-2022-1235
+        #   1.1-r0
+        #     - CVE-2022-1234
+    "};
+    assert_let!(Err(err @ Error::MalformedSecfixes(..)) = parse_secfixes(input));
+    assert!(format!("{}", err) == "syntax error in secfixes on line 4: '1.1-r0'");
+}
+
+#[test]
+fn test_parse_secfixes_lenient() {
+    let input = indoc! {"
+        pkgname=sample
+
+        #  secfixes:
+        #	1.2-r0:
+        #	  - CVE-2022-1237 CVE-2022-1238
+        #   1.1-r0
+        #     -CVE-2022-1236  # comment
+        #   what is this
+        #   1.0-r0 :
+        #     - CVE-2022-1235
+        #     -
+        #
+        # - CVE-2022-1234
+    "};
+    let (secfixes, warnings) = parse_secfixes_lenient(input);
+    let warning = |line: usize, text: &str| Warning::MalformedSecfixes {
+        line,
+        text: text.to_owned(),
+    };
+
+    assert!(
+        secfixes
+            == vec![
+                Secfix::new("1.2-r0", vec![S!("CVE-2022-1237"), S!("CVE-2022-1238")]),
+                Secfix::new("1.1-r0", vec![S!("CVE-2022-1236")]),
+                Secfix::new("1.0-r0", vec![S!("CVE-2022-1235")]),
+            ]
+    );
+    assert!(
+        warnings
+            == vec![
+                warning(3, "#  secfixes:"),
+                warning(4, "1.2-r0:"),
+                warning(5, "- CVE-2022-1237 CVE-2022-1238"),
+                warning(6, "1.1-r0"),
+                warning(7, "-CVE-2022-1236"),
+                warning(8, "what is this"),
+                warning(9, "1.0-r0 :"),
+                warning(11, "-"),
+            ]
+    );
+
+    let input = indoc! {"
+        # secfixes:
+        #   1.1-r0:
+        #     - CVE-2022-1236
+        #     - CVE-2022-1237 CVE-2022-1238
+        #   1.0-r0:
+        #       - CVE-2022-1235
+        	#   0.9-r0:
+    "};
+    let (secfixes, warnings) = parse_secfixes_lenient(input);
+    assert!(secfixes.len() == 3);
+    assert!(warnings == vec![warning(6, "- CVE-2022-1235"), warning(7, "0.9-r0:")]);
+
+    let input = indoc! {"
+        # secfixes:
+        #   - CVE-2022-1236
+    "};
+    let (secfixes, warnings) = parse_secfixes_lenient(input);
+    assert!(secfixes.is_empty());
+    assert!(warnings.len() == 1);
+}
+
+#[test]
+fn test_decode_source_and_checksums() {
+    let source = indoc! {"
+        https://example.org/sample-1.2.3.tar.gz
+        bar-1.2.tar.gz::https://example.org/bar/1.2.tar.gz
+        sample.initd
+    "};
+    let sha512sums = indoc! {"
+        1d468dcfa9bbd348b8a5dc514ac1428a789e73a92384c039b73a51ce376785f74bf942872c5594a9fcda6bbf44758bd727ce15ac2395f1aa989c507014647dcc sample-1.2.3.tar.gz
+        0acd8bf9aedeabeef590909c83ad9057063b4d3165fe5e0b0ff2205df6e0d1b97f3fcfd27384a55b4816bbe975e93a737e58df9c6ee01baf7e46ceaabc43c64a bar-1.2.tar.gz
+        ee10a5687740dde0c3d18d8b3555f49fcdc6abfc0a3bc2de1de3be0e99951a346fe8027d916aab73071ecd4e2c50871e7c867aca3a7a0fd16e3374c5caed1c57 sample.initd
+    "};
+    let expected = vec!(
+        Source::new("sample-1.2.3.tar.gz", "https://example.org/sample-1.2.3.tar.gz", "1d468dcfa9bbd348b8a5dc514ac1428a789e73a92384c039b73a51ce376785f74bf942872c5594a9fcda6bbf44758bd727ce15ac2395f1aa989c507014647dcc"),
+        Source::new("bar-1.2.tar.gz", "https://example.org/bar/1.2.tar.gz", "0acd8bf9aedeabeef590909c83ad9057063b4d3165fe5e0b0ff2205df6e0d1b97f3fcfd27384a55b4816bbe975e93a737e58df9c6ee01baf7e46ceaabc43c64a"),
+        Source::new("sample.initd", "sample.initd", "ee10a5687740dde0c3d18d8b3555f49fcdc6abfc0a3bc2de1de3be0e99951a346fe8027d916aab73071ecd4e2c50871e7c867aca3a7a0fd16e3374c5caed1c57"),
+    );
+
+    assert!(
+        decode_source_and_checksums(source, &[(ChecksumAlg::Sha512, sha512sums)]).unwrap()
+            == expected
+    );
+
+    let sha512sums = indoc! {"
+        1d468dcfa9bbd348b8a5dc514ac1428a789e73a92384c039b73a51ce376785f74bf942872c5594a9fcda6bbf44758bd727ce15ac2395f1aa989c507014647dcc sample-1.2.3.tar.gz
+        ee10a5687740dde0c3d18d8b3555f49fcdc6abfc0a3bc2de1de3be0e99951a346fe8027d916aab73071ecd4e2c50871e7c867aca3a7a0fd16e3374c5caed1c57 sample.initd
+    "};
+
+    assert_let!(Err(err @ Error::MissingChecksum(..)) = decode_source_and_checksums(source, &[(ChecksumAlg::Sha512, sha512sums)]));
+    assert!(
+        format!("{err}").contains("bar-1.2.tar.gz"),
+        "error message should contain name of the missing checksum"
+    );
+}
+
+#[test]
+fn test_decode_source_and_checksums_legacy() {
+    let source = "https://example.org/sample-1.2.3.tar.gz sample.initd";
+    let sha512sums = "ee10a5687740dde0c3d18d8b3555f49fcdc6abfc0a3bc2de1de3be0e99951a346fe8027d916aab73071ecd4e2c50871e7c867aca3a7a0fd16e3374c5caed1c57 sample.initd";
+    let sha256sums =
+        "8a3a0ac8bb4d01a8cae4bb2c2a1dfb3d2f5a4be2a8c1b3c3d1c4a2e0b7b3c0f1 sample-1.2.3.tar.gz";
+    let md5sums = "d41d8cd98f00b204e9800998ecf8427e sample-1.2.3.tar.gz";
+
+    let expected = vec![
+        Source::new("sample-1.2.3.tar.gz", "https://example.org/sample-1.2.3.tar.gz", "8a3a0ac8bb4d01a8cae4bb2c2a1dfb3d2f5a4be2a8c1b3c3d1c4a2e0b7b3c0f1")
+            .checksum_alg(ChecksumAlg::Sha256),
+        Source::new("sample.initd", "sample.initd", "ee10a5687740dde0c3d18d8b3555f49fcdc6abfc0a3bc2de1de3be0e99951a346fe8027d916aab73071ecd4e2c50871e7c867aca3a7a0fd16e3374c5caed1c57"),
+    ];
+    let checksums = [
+        (ChecksumAlg::Sha512, sha512sums),
+        (ChecksumAlg::Sha256, sha256sums),
+        (ChecksumAlg::Md5, md5sums),
+    ];
+    assert!(decode_source_and_checksums(source, &checksums).unwrap() == expected);
+
+    let expected = Source::new(
+        "sample-1.2.3.tar.gz",
+        "https://example.org/sample-1.2.3.tar.gz",
+        "d41d8cd98f00b204e9800998ecf8427e",
+    )
+    .checksum_alg(ChecksumAlg::Md5);
+    let checksums = [
+        (ChecksumAlg::Sha512, sha512sums),
+        (ChecksumAlg::Md5, md5sums),
+    ];
+    assert!(decode_source_and_checksums(source, &checksums).unwrap()[0] == expected);
+}
+
+#[test]
+fn source_json_legacy_checksum() {
+    assert_from_to_json!(
+        Source::new(
+            "foo.tar.gz",
+            "https://example.org/foo.tar.gz",
+            "d41d8cd98f00b204e9800998ecf8427e"
+        )
+        .checksum_alg(ChecksumAlg::Md5),
+        json!({
+            "name": "foo.tar.gz",
+            "uri": "https://example.org/foo.tar.gz",
+            "checksum": "d41d8cd98f00b204e9800998ecf8427e",
+            "checksum_alg": "md5"
+        }),
+    );
+}
+
+#[test]
+fn apkbuild_verify_sources() {
+    let startdir = Path::new("../fixtures/aports/s6");
+    let mut apkbuild = ApkbuildReader::new()
+        .read_apkbuild(startdir.join("APKBUILD"))
+        .unwrap();
+
+    let statuses = apkbuild.verify_sources(startdir, startdir).unwrap();
+    let statuses = statuses
+        .iter()
+        .map(|(src, status)| (src.name.as_str(), status.clone()))
+        .collect::<Vec<_>>();
+    assert!(
+        statuses
+            == vec![
+                ("s6-2.11.1.2.tar.gz", SourceStatus::Missing),
+                ("s6-svscanboot", SourceStatus::Ok),
+                ("s6.initd", SourceStatus::Ok),
+            ]
+    );
+
+    apkbuild.source[2].checksum = S!("f00");
+    assert_let!(Ok(statuses) = apkbuild.verify_sources(startdir, startdir));
+    assert_let!(SourceStatus::Mismatch(actual) = &statuses[2].1);
+    assert!(actual.starts_with("e7f4fcdc04fc7f72df4419018d048e14f80d50d01ebc284b6d208d6bada3201"));
+}
+
+#[test]
+#[rustfmt::skip]
+fn checksum_alg_digest() {
+    for (alg, expected) in [
+        (ChecksumAlg::Md5   , "5d41402abc4b2a76b9719d911017c592"),
+        (ChecksumAlg::Sha256, "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"),
+        (ChecksumAlg::Sha512, "9b71d224bd62f3785d96d46ad3ea3d73319bfbc2890caadae2dff72519673ca72323c3d99ba5c11d7c7acc6e14b8c5da0c4663475c2e5c3adef46f73bcdec043"),
+    ] {
+        assert!(alg.digest(&b"hello"[..]).unwrap() == expected);
+    }
+}
+
+#[test]
+fn apkbuild_json() {
+    assert_from_to_json!(
+        sample_apkbuild(),
+        json!({
+            "maintainer": "Jakub Jirutka <jakub@jirutka.cz>",
+            "contributors": [
+                "Francesco Colista <fcolista@alpinelinux.org>",
+                "Natanael Copa <ncopa@alpinelinux.org>"
+            ],
+            "pkgname": "sample",
+            "pkgver": "1.2.3",
+            "pkgrel": 2,
+            "pkgdesc": "A sample aport for testing",
+            "url": "https://example.org/sample",
+            "arch": [ "aarch64", "armhf", "armv7", "ppc64le", "x86", "x86_64" ],
+            "arch_raw": "all !riscv64 !s390x",
+            "license": "ISC and BSD-2-Clause and BSD-3-Clause",
+            "depends": {
+                "ruby": ">= 3.0",
+                "sample-legacy": "!"
+            },
+            "makedepends": {
+                "openssl-dev": "> 3",
+                "zlib-dev": "*"
+            },
+            "makedepends_build": {},
+            "makedepends_host": {},
+            "checkdepends": {
+                "ruby-rspec": "*"
+            },
+            "subpackage_depends": {
+                "dev": {
+                    "openssl-dev": "> 3",
+                    "zlib-dev": "*"
+                }
+            },
+            "install_if": {},
+            "pkgusers": [],
+            "pkggroups": [],
+            "provides": {
+                "sample2": "= 1.2.3-r2"
+            },
+            "provider_priority": 100,
+            "sonameprefix": "smpl",
+            "replaces": {
+                "sample2": "*"
+            },
+            "install": [ "sample.post-install", "sample.post-upgrade" ],
+            "triggers": [ "sample.trigger=/usr/share/sample/*" ],
+            "subpackages": [ "sample-doc", "sample-dev" ],
+            "sources": [{
+                "name": "sample-1.2.3.tar.gz",
+                "uri": "https://example.org/sample/sample-1.2.3.tar.gz",
+                "checksum": "54286070812a47b629f68757046d3c9a1bdd2b5d1c3b84a5c8e4cb92f1331afa745443f7238175835d8cfbe5b8dd442e00c75c3a5b5b8f8efd8d2ec8f636dad4"
+            }, {
+                "name": "sample.initd",
+                "uri": "sample.initd",
+                "checksum": "b512bcb8bae11853a3006e2122d7e652806d4bf2234638d8809fd823375b5b0bd590f7d6a90412baffcc3b7b6a0f197a10986728a70f24fe628f91bfb651d266"
+            }, {
+                "name": "sample.confd",
+                "uri": "sample.confd",
+                "checksum": "6eda39920cccb1238b104bb90ac4be2c32883897c72363560d8d39345819cdeff535680e78396052b2b8f981e169ad9b3c30da724def80a1501785d82ce7fa25"
+            }],
+            "options": [ "!check" ],
+            "secfixes": {
+                "1.2.3-r2": [ "CVE-2022-12347", "CVE-2022-12346" ],
+                "1.2.0-r0": [ "CVE-2021-12345" ]
+            }
+        }),
+    );
+}
+
+#[test]
+fn test_find_apkbuilds() {
+    let expected = vec![
+        PathBuf::from("../fixtures/aports/s6/APKBUILD"),
+        PathBuf::from("../fixtures/aports/sample/APKBUILD"),
+    ];
+    assert!(find_apkbuilds("../fixtures/aports").unwrap() == expected);
+    assert!(find_apkbuilds("../fixtures").unwrap() == expected);
+}
@@ -0,0 +1,244 @@
+use std::fmt::{self, Write as _};
+use std::fs;
+use std::path::Path;
+
+use super::writer::escape;
+use super::Error;
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// File extensions of source archives recognized by
+/// [`ApkbuildTemplate::from_source_url`].
+const ARCHIVE_EXTS: &[&str] = &[
+    ".tar.gz", ".tar.bz2", ".tar.xz", ".tar.zst", ".tar.lz", ".tgz", ".tbz2", ".txz", ".zip",
+];
+
+/// A descriptor of a new package used to generate a skeleton APKBUILD, the
+/// equivalent of `newapkbuild`.
+///
+/// Example:
+/// ```no_run
+/// use alpkit::apkbuild::ApkbuildTemplate;
+///
+/// let template = ApkbuildTemplate {
+///     maintainer: Some("Kevin Flynn <kevin.flynn@encom.com>".to_owned()),
+///     license: "MIT".to_owned(),
+///     ..ApkbuildTemplate::from_source_url("https://example.org/dist/foo-1.2.3.tar.gz")
+/// };
+/// template.write_file("aports/testing/foo/APKBUILD").unwrap();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApkbuildTemplate {
+    /// The name and email address of the package's maintainer.
+    pub maintainer: Option<String>,
+
+    /// The package name.
+    pub pkgname: String,
+
+    /// The version of the packaged software.
+    pub pkgver: String,
+
+    /// A brief, one-line description of the package.
+    pub pkgdesc: String,
+
+    /// The homepage of the packaged software.
+    pub url: String,
+
+    /// The `arch` value, `all` by default.
+    pub arch: String,
+
+    /// License of the packaged software (SPDX license expression).
+    pub license: String,
+
+    /// Source entries, written literally (i.e. they may contain e.g.
+    /// `$pkgver`).
+    pub source: Vec<String>,
+}
+
+impl Default for ApkbuildTemplate {
+    fn default() -> Self {
+        Self {
+            maintainer: None,
+            pkgname: String::new(),
+            pkgver: String::new(),
+            pkgdesc: String::new(),
+            url: String::new(),
+            arch: "all".to_owned(),
+            license: String::new(),
+            source: Vec::new(),
+        }
+    }
+}
+
+impl ApkbuildTemplate {
+    /// Creates a template pre-filled from the given URL of a source archive,
+    /// e.g. `https://example.org/dist/foo-1.2.3.tar.gz`.
+    ///
+    /// The `pkgname` and `pkgver` are guessed from the file name (or, for
+    /// GitHub archives, from the repository name and tag) and replaced with
+    /// `$pkgname` and `$pkgver` in the file name and in the path segments of
+    /// the `source` entry that consist only of the version (optionally
+    /// prefixed with `v`). If they cannot be guessed, they are left empty and
+    /// the URL is used as is (escaped).
+    pub fn from_source_url(source_url: &str) -> Self {
+        let (base, file) = source_url.rsplit_once('/').unwrap_or(("", source_url));
+        let (stem, ext) = ARCHIVE_EXTS
+            .iter()
+            .find_map(|ext| file.strip_suffix(ext).map(|stem| (stem, *ext)))
+            .unwrap_or((file, ""));
+
+        let mut template = Self {
+            url: homepage_url(source_url),
+            ..Default::default()
+        };
+
+        // https://github.com/<owner>/<repo>/archive/[refs/tags/]<tag>.tar.gz
+        if let Some(repo_url) = base
+            .strip_suffix("/archive/refs/tags")
+            .or_else(|| base.strip_suffix("/archive"))
+            .filter(|_| source_url.starts_with("https://github.com/"))
+        {
+            let tag_prefix = if stem.starts_with('v') { "v" } else { "" };
+            template.pkgname = repo_url.rsplit('/').next().unwrap_or("").to_lowercase();
+            template.pkgver = stem[tag_prefix.len()..].to_owned();
+            template.url = repo_url.to_owned();
+            template.source = vec![format!(
+                "$pkgname-$pkgver{ext}::{}/{tag_prefix}$pkgver{ext}",
+                escape(base)
+            )];
+        } else if let Some((name, ver)) = split_name_version(stem) {
+            template.pkgname = name.to_lowercase();
+            template.pkgver = ver.to_owned();
+            let name = if name == template.pkgname {
+                "$pkgname".to_owned()
+            } else {
+                escape(name)
+            };
+            let prefix = &source_url[..source_url.len() - file.len()];
+            template.source = vec![format!(
+                "{}{name}-$pkgver{ext}",
+                substitute_version(prefix, ver)
+            )];
+        } else {
+            template.source = vec![escape(source_url)];
+        }
+        template
+    }
+
+    /// Renders the APKBUILD.
+    pub fn render(&self) -> String {
+        let mut out = String::with_capacity(1024);
+        self.render_fmt(&mut out).unwrap(); // writing to String cannot fail
+        out
+    }
+
+    /// Renders the APKBUILD into a file at the given path.
+    pub fn write_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let path = path.as_ref();
+        fs::write(path, self.render()).map_err(|e| Error::WriteFile(e, path.to_owned()))
+    }
+
+    fn render_fmt(&self, out: &mut String) -> fmt::Result {
+        if let Some(maintainer) = &self.maintainer {
+            writeln!(out, "# Contributor: {maintainer}")?;
+            writeln!(out, "# Maintainer: {maintainer}")?;
+        }
+        writeln!(out, "pkgname={}", quote(&self.pkgname))?;
+        writeln!(out, "pkgver={}", quote(&self.pkgver))?;
+        writeln!(out, "pkgrel=0")?;
+        writeln!(out, "pkgdesc=\"{}\"", escape(&self.pkgdesc))?;
+        writeln!(out, "url=\"{}\"", escape(&self.url))?;
+        writeln!(out, "arch=\"{}\"", escape(&self.arch))?;
+        writeln!(out, "license=\"{}\"", escape(&self.license))?;
+        writeln!(out, "subpackages=\"$pkgname-doc\"")?;
+        match self.source.as_slice() {
+            [] => (),
+            [source] => writeln!(out, "source=\"{source}\"")?,
+            sources => {
+                writeln!(out, "source=\"")?;
+                for source in sources {
+                    writeln!(out, "\t{source}")?;
+                }
+                writeln!(out, "\t\"")?;
+            }
+        }
+        out.push_str(concat!(
+            "\n",
+            "build() {\n",
+            "\tmake\n",
+            "}\n",
+            "\n",
+            "check() {\n",
+            "\tmake check\n",
+            "}\n",
+            "\n",
+            "package() {\n",
+            "\tmake DESTDIR=\"$pkgdir\" install\n",
+            "}\n",
+        ));
+        Ok(())
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Splits the archive file stem (e.g. `foo-bar-1.2.3`) into the name and
+/// version at the last `-` followed by a digit.
+fn split_name_version(stem: &str) -> Option<(&str, &str)> {
+    stem.rmatch_indices('-')
+        .map(|(i, _)| i)
+        .find(|&i| stem[i + 1..].starts_with(|c: char| c.is_ascii_digit()))
+        .map(|i| (&stem[..i], &stem[i + 1..]))
+        .filter(|(name, _)| !name.is_empty())
+}
+
+/// Replaces the path segments of the `url` that are equal to the `ver`
+/// (optionally prefixed with `v`) with `$pkgver` and escapes the rest.
+fn substitute_version(url: &str, ver: &str) -> String {
+    let path_start = url
+        .find("://")
+        .map(|i| i + 3)
+        .and_then(|start| url[start..].find('/').map(|i| start + i))
+        .unwrap_or(0);
+    let (host, path) = url.split_at(path_start);
+
+    let path: Vec<_> = path
+        .split('/')
+        .map(
+            |segment| match segment.strip_prefix('v').unwrap_or(segment) {
+                s if s == ver => format!("{}$pkgver", &segment[..segment.len() - s.len()]),
+                _ => escape(segment),
+            },
+        )
+        .collect();
+
+    escape(host) + &path.join("/")
+}
+
+/// Returns the value as is if it's safe to use unquoted in the shell,
+/// otherwise quotes and escapes it.
+fn quote(value: &str) -> String {
+    let is_safe = |c: char| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '+' | '-');
+    if !value.is_empty() && value.chars().all(is_safe) {
+        value.to_owned()
+    } else {
+        format!("\"{}\"", escape(value))
+    }
+}
+
+/// Returns the scheme and host part of the URL (e.g. `https://example.org`).
+fn homepage_url(url: &str) -> String {
+    url.find("://")
+        .map(|i| i + 3)
+        .map(|start| match url[start..].find('/') {
+            Some(end) => url[..start + end].to_owned(),
+            None => url.to_owned(),
+        })
+        .unwrap_or_default()
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+#[path = "template.test.rs"]
+mod test;
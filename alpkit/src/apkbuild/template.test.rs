@@ -0,0 +1,110 @@
+use std::fs;
+
+use indoc::indoc;
+
+use super::*;
+use crate::apkbuild::ApkbuildReader;
+use crate::internal::test_utils::{assert, S};
+use crate::lint::{lint, Rule};
+
+#[rustfmt::skip]
+#[test]
+fn from_source_url() {
+    for (url, pkgname, pkgver, homepage, source) in [
+        ("https://example.org/dist/foo-1.2.3.tar.gz", "foo", "1.2.3", "https://example.org", "https://example.org/dist/$pkgname-$pkgver.tar.gz"),
+        ("https://example.org/dist/1.2/foo-bar-1.2.3.tar.xz", "foo-bar", "1.2.3", "https://example.org", "https://example.org/dist/1.2/$pkgname-$pkgver.tar.xz"),
+        ("https://example.org/dist/v1.2.3/Foo-1.2.3.zip", "foo", "1.2.3", "https://example.org", "https://example.org/dist/v$pkgver/Foo-$pkgver.zip"),
+        ("https://github.com/jirutka/esh/archive/v0.3.2.tar.gz", "esh", "0.3.2", "https://github.com/jirutka/esh", "$pkgname-$pkgver.tar.gz::https://github.com/jirutka/esh/archive/v$pkgver.tar.gz"),
+        ("https://github.com/jirutka/Foo/archive/refs/tags/1.0.tar.gz", "foo", "1.0", "https://github.com/jirutka/Foo", "$pkgname-$pkgver.tar.gz::https://github.com/jirutka/Foo/archive/refs/tags/$pkgver.tar.gz"),
+        ("https://example.org/latest.tar.gz", "", "", "https://example.org", "https://example.org/latest.tar.gz"),
+        ("https://1.2.example.org/1.2-beta/foo-1.2.tar.gz", "foo", "1.2", "https://1.2.example.org", "https://1.2.example.org/1.2-beta/$pkgname-$pkgver.tar.gz"),
+        ("https://example.org/1.2/v1.2/foo-1.2.tgz", "foo", "1.2", "https://example.org", "https://example.org/$pkgver/v$pkgver/$pkgname-$pkgver.tgz"),
+        ("https://example.org/$dir/Foo-1.0.zip", "foo", "1.0", "https://example.org", "https://example.org/\\$dir/Foo-$pkgver.zip"),
+        ("https://example.org/dist/$latest.tar.gz", "", "", "https://example.org", "https://example.org/dist/\\$latest.tar.gz"),
+    ] {
+        let template = ApkbuildTemplate::from_source_url(url);
+        assert!(template.pkgname == pkgname, "{url}");
+        assert!(template.pkgver == pkgver, "{url}");
+        assert!(template.url == homepage, "{url}");
+        assert!(template.source == vec![S!(source)], "{url}");
+    }
+}
+
+#[test]
+fn render() {
+    let template = ApkbuildTemplate {
+        maintainer: Some(S!("Kevin Flynn <kevin.flynn@encom.com>")),
+        pkgdesc: S!("A \"foo\" tool"),
+        license: S!("MIT"),
+        ..ApkbuildTemplate::from_source_url("https://example.org/dist/foo-1.2.3.tar.gz")
+    };
+
+    assert!(
+        template.render()
+            == indoc! {r#"
+        # Contributor: Kevin Flynn <kevin.flynn@encom.com>
+        # Maintainer: Kevin Flynn <kevin.flynn@encom.com>
+        pkgname=foo
+        pkgver=1.2.3
+        pkgrel=0
+        pkgdesc="A \"foo\" tool"
+        url="https://example.org"
+        arch="all"
+        license="MIT"
+        subpackages="$pkgname-doc"
+        source="https://example.org/dist/$pkgname-$pkgver.tar.gz"
+
+        build() {
+        	make
+        }
+
+        check() {
+        	make check
+        }
+
+        package() {
+        	make DESTDIR="$pkgdir" install
+        }
+    "#}
+    );
+}
+
+#[test]
+fn render_quotes_pkgname_and_pkgver() {
+    let template = ApkbuildTemplate {
+        pkgname: S!("foo"),
+        pkgver: S!("1.0 $(x)"),
+        ..Default::default()
+    };
+    let rendered = template.render();
+
+    assert!(rendered.contains("pkgname=foo\n"));
+    assert!(rendered.contains("pkgver=\"1.0 \\$(x)\"\n"));
+}
+
+#[test]
+fn write_file_is_readable() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("APKBUILD");
+    let template = ApkbuildTemplate {
+        pkgdesc: S!("A foo tool"),
+        license: S!("MIT"),
+        ..ApkbuildTemplate::from_source_url("https://example.org/dist/foo-1.2.3.tar.gz")
+    };
+    template.write_file(&path).unwrap();
+
+    let mut reader = ApkbuildReader::new();
+    reader.allow_partial(true);
+    let apkbuild = reader.read_apkbuild(&path).unwrap();
+
+    assert!(apkbuild.pkgname == "foo");
+    assert!(apkbuild.pkgver == "1.2.3");
+    assert!(apkbuild.subpackages == vec![S!("foo-doc")]);
+
+    let text = fs::read_to_string(&path).unwrap();
+    let findings = lint(&apkbuild, &text, Some("foo"));
+    assert!(
+        findings.iter().all(|f| f.rule != Rule::EmptyVariable),
+        "{findings:?}"
+    );
+}
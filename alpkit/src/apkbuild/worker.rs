@@ -0,0 +1,208 @@
+use std::ffi::OsStr;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::process::{CommandExt, ExitStatusExt};
+use std::process::{Child, ChildStdin, Command, ExitStatus, Output, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use tempfile::TempDir;
+
+use super::Error;
+use crate::internal::macros::bail;
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// The main loop of a shell worker. For each line read from stdin, it sources
+/// the `script` file in the worker's directory (`$1`) in a subshell, with
+/// stderr redirected to the `stderr` file, and then prints `\x1C`, the exit
+/// status of the subshell and a newline.
+const WORKER_SCRIPT: &str = "\
+_alpkit_dir=$1; shift; \
+while read -r _; do \
+  ( cd \"$_alpkit_dir\" && unset _alpkit_dir && . ./script ) </dev/null 2>\"$_alpkit_dir\"/stderr; \
+  printf '\\034%d\\n' $?; \
+done";
+
+/// The byte that terminates the stdout of a script evaluated by the worker.
+const RESPONSE_END: u8 = b'\x1C';
+
+/// A long-lived shell process that evaluates scripts, each in an isolated
+/// subshell. This amortizes the cost of spawning a new shell for each
+/// APKBUILD.
+///
+/// The scripts are passed via a file in a private temporary directory owned
+/// by the worker, because they may contain any characters. The shell runs in
+/// its own process group, which is killed together with any processes left
+/// behind by the scripts when the worker is dropped. Then the directory is
+/// removed.
+pub(super) struct ShellWorker {
+    child: Child,
+    stdin: ChildStdin,
+    responses: Receiver<io::Result<(Vec<u8>, ExitStatus)>>,
+    dir: TempDir,
+}
+
+impl ShellWorker {
+    /// Spawns a worker using the given shell `cmd`, which must support the
+    /// `-c` option like `/bin/sh` does.
+    pub(super) fn spawn(mut cmd: Command) -> Result<Self, Error> {
+        let dir = tempfile::Builder::new()
+            .prefix("alpkit-sh.")
+            .permissions(fs::Permissions::from_mode(0o700))
+            .tempdir()
+            .map_err(|e| Error::Io(e, "creating directory for shell worker"))?;
+
+        let shell = cmd.get_program().to_string_lossy().into_owned();
+        let mut child = cmd
+            .args([
+                OsStr::new("-c"),
+                OsStr::new(WORKER_SCRIPT),
+                OsStr::new("sh"),
+            ])
+            .arg(dir.path())
+            .process_group(0)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| Error::SpawnShell(e, shell))?;
+        let stdin = child.stdin.take().unwrap(); // this should never fail
+        let mut stdout = BufReader::new(child.stdout.take().unwrap());
+
+        // The responses are read in a background thread to be able to stop
+        // waiting for them after a timeout.
+        let (sender, responses) = mpsc::channel();
+        thread::spawn(move || {
+            while let Some(response) = read_response(&mut stdout).transpose() {
+                let failed = response.is_err();
+                if sender.send(response).is_err() || failed {
+                    break;
+                }
+            }
+        });
+
+        Ok(ShellWorker {
+            child,
+            stdin,
+            responses,
+            dir,
+        })
+    }
+
+    /// Evaluates the `script` in a subshell and returns its exit status,
+    /// stdout and stderr. If the `time_limit` is exceeded, it returns
+    /// [`Error::Timeout`] and the worker should be dropped.
+    pub(super) fn evaluate(
+        &mut self,
+        script: &[u8],
+        time_limit: Option<Duration>,
+    ) -> Result<Output, Error> {
+        fs::write(self.dir.path().join("script"), script)
+            .map_err(|e| Error::Io(e, "writing script for shell worker"))?;
+        self.stdin
+            .write_all(b"\n")
+            .and_then(|_| self.stdin.flush())
+            .map_err(|e| Error::Io(e, "writing data to stdin of shell"))?;
+
+        let started = Instant::now();
+        let response = match time_limit {
+            Some(limit) => match self.responses.recv_timeout(limit) {
+                Ok(response) => Some(response),
+                Err(RecvTimeoutError::Timeout) => bail!(Error::Timeout {
+                    elapsed: started.elapsed(),
+                    stdout: String::new(),
+                    stderr: String::from_utf8_lossy(&self.read_stderr().unwrap_or_default()).into(),
+                }),
+                Err(RecvTimeoutError::Disconnected) => None,
+            },
+            None => self.responses.recv().ok(),
+        };
+        let (stdout, status) = response
+            .unwrap_or_else(|| {
+                Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "shell worker exited unexpectedly",
+                ))
+            })
+            .map_err(|e| Error::Io(e, "reading shell stdout"))?;
+
+        let stderr = self
+            .read_stderr()
+            .map_err(|e| Error::Io(e, "reading shell stderr"))?;
+
+        Ok(Output {
+            status,
+            stdout,
+            stderr,
+        })
+    }
+
+    fn read_stderr(&self) -> io::Result<Vec<u8>> {
+        fs::read(self.dir.path().join("stderr"))
+    }
+}
+
+impl Drop for ShellWorker {
+    fn drop(&mut self) {
+        // The worker is the leader of its process group, so the group ID is
+        // the same as its PID.
+        if let Ok(pgid) = libc::pid_t::try_from(self.child.id()) {
+            // SAFETY: kill(2) has no memory safety preconditions.
+            unsafe { libc::kill(-pgid, libc::SIGKILL) };
+        }
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        // The directory is removed when dropping the TempDir.
+    }
+}
+
+/// Reads the stdout of one script and its exit status as printed by the
+/// [`WORKER_SCRIPT`]. Returns `None` if the worker has exited.
+fn read_response<R: BufRead>(reader: &mut R) -> io::Result<Option<(Vec<u8>, ExitStatus)>> {
+    let mut stdout = Vec::new();
+    if reader.read_until(RESPONSE_END, &mut stdout)? == 0 {
+        return Ok(None);
+    }
+    if stdout.pop() != Some(RESPONSE_END) {
+        bail!(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "shell worker exited unexpectedly"
+        ));
+    }
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+
+    let code: i32 = line.trim_end().parse().map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "invalid exit status from shell worker: '{}'",
+                line.trim_end()
+            ),
+        )
+    })?;
+    Ok(Some((stdout, ExitStatus::from_raw(code << 8))))
+}
+
+/// Appends the `value` quoted for the shell to the `buf`.
+pub(super) fn push_quoted(buf: &mut Vec<u8>, value: &OsStr) {
+    buf.push(b'\'');
+    for &byte in value.as_bytes() {
+        if byte == b'\'' {
+            buf.extend_from_slice(b"'\\''");
+        } else {
+            buf.push(byte);
+        }
+    }
+    buf.push(b'\'');
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+#[path = "worker.test.rs"]
+mod test;
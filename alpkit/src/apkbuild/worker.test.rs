@@ -0,0 +1,95 @@
+use std::ffi::OsStr;
+
+use super::*;
+use crate::internal::test_utils::{assert, assert_let};
+
+fn spawn_worker() -> ShellWorker {
+    let mut cmd = Command::new("/bin/sh");
+    cmd.env_clear().env("PATH", "/usr/bin:/bin");
+    ShellWorker::spawn(cmd).unwrap()
+}
+
+#[test]
+fn shell_worker_evaluate() {
+    let mut worker = spawn_worker();
+
+    let output = worker
+        .evaluate(b"foo=42; printf 'a\\nb\\036'; echo oops >&2", None)
+        .unwrap();
+    assert!(output.status.success());
+    assert!(output.stdout == b"a\nb\x1E");
+    assert!(output.stderr == b"oops\n");
+
+    // Variables don't leak between evaluations.
+    let output = worker.evaluate(b"echo \"[$foo]\"; exit 3", None).unwrap();
+    assert!(output.status.code() == Some(3));
+    assert!(output.stdout == b"[]\n");
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn shell_worker_timeout() {
+    let mut worker = spawn_worker();
+
+    let result = worker.evaluate(
+        b"echo 'fetching...' >&2; sleep 2",
+        Some(Duration::from_millis(200)),
+    );
+    assert_let!(
+        Err(Error::Timeout {
+            elapsed,
+            stderr,
+            ..
+        }) = result
+    );
+    assert!(elapsed >= Duration::from_millis(200));
+    assert!(stderr == "fetching...\n");
+}
+
+#[test]
+fn shell_worker_private_dir() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let worker = spawn_worker();
+
+    let mode = fs::metadata(worker.dir.path())
+        .unwrap()
+        .permissions()
+        .mode();
+    assert!(mode & 0o777 == 0o700);
+}
+
+#[test]
+fn shell_worker_drop_kills_process_group() {
+    let mut worker = spawn_worker();
+
+    let output = worker
+        .evaluate(b"sleep 10 >/dev/null 2>&1 & echo $!", None)
+        .unwrap();
+    let pid: libc::pid_t = String::from_utf8(output.stdout)
+        .unwrap()
+        .trim()
+        .parse()
+        .unwrap();
+    drop(worker);
+
+    // The signal is delivered asynchronously and the killed process may stay
+    // a zombie until it's reaped by init.
+    let is_dead = || {
+        fs::read_to_string(format!("/proc/{pid}/status"))
+            .map(|status| status.contains("State:\tZ"))
+            .unwrap_or(true)
+    };
+    let started = Instant::now();
+    while !is_dead() && started.elapsed() < Duration::from_secs(2) {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert!(is_dead());
+}
+
+#[test]
+fn test_push_quoted() {
+    let mut buf = b"echo ".to_vec();
+    push_quoted(&mut buf, OsStr::new("it's $HOME"));
+    assert!(buf == b"echo 'it'\\''s $HOME'");
+}
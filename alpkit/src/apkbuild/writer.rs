@@ -0,0 +1,224 @@
+use std::fmt::{self, Display, Write as _};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use super::{Apkbuild, ChecksumAlg, Error, Source};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// The maximum length of a variable assignment line; longer lists are wrapped
+/// one item per line.
+const MAX_LINE_LEN: usize = 80;
+
+/// A writer that serializes an [`Apkbuild`] into an APKBUILD file, the inverse
+/// of [`ApkbuildReader`](super::ApkbuildReader).
+///
+/// Variables are written in the conventional order (as `newapkbuild` does),
+/// empty variables are omitted. Values are written literally, i.e. without
+/// substituting e.g. `$pkgver`.
+///
+/// Example:
+/// ```no_run
+/// use alpkit::apkbuild::{Apkbuild, ApkbuildWriter};
+///
+/// let apkbuild = Apkbuild {
+///     pkgname: "foo".to_owned(),
+///     pkgver: "1.0".to_owned(),
+///     ..Default::default()
+/// };
+/// ApkbuildWriter::new()
+///     .function("package", "make DESTDIR=\"$pkgdir\" install")
+///     .write_file(&apkbuild, "aports/testing/foo/APKBUILD")
+///     .unwrap();
+/// ```
+#[derive(Debug, Default)]
+pub struct ApkbuildWriter {
+    functions: Vec<(String, String)>,
+}
+
+impl ApkbuildWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a shell function with the given name and body (without braces) to
+    /// be written after the variables, e.g. `build` or `package`. The body is
+    /// indented with a tab.
+    pub fn function<N: ToString, B: AsRef<str>>(&mut self, name: N, body: B) -> &mut Self {
+        self.functions
+            .push((name.to_string(), body.as_ref().to_owned()));
+        self
+    }
+
+    /// Serializes the `apkbuild` into a string.
+    pub fn to_string(&self, apkbuild: &Apkbuild) -> String {
+        let mut out = String::with_capacity(1024);
+        self.write_fmt(apkbuild, &mut out).unwrap(); // writing to String cannot fail
+        out
+    }
+
+    /// Serializes the `apkbuild` into the given writer.
+    pub fn write<W: io::Write>(&self, apkbuild: &Apkbuild, mut writer: W) -> io::Result<()> {
+        writer.write_all(self.to_string(apkbuild).as_bytes())
+    }
+
+    /// Serializes the `apkbuild` into a file at the given path.
+    pub fn write_file<P: AsRef<Path>>(&self, apkbuild: &Apkbuild, path: P) -> Result<(), Error> {
+        let path = path.as_ref();
+        fs::write(path, self.to_string(apkbuild)).map_err(|e| Error::WriteFile(e, path.to_owned()))
+    }
+
+    fn write_fmt(&self, a: &Apkbuild, out: &mut String) -> fmt::Result {
+        for contributor in &a.contributors {
+            writeln!(out, "# Contributor: {contributor}")?;
+        }
+        if let Some(maintainer) = &a.maintainer {
+            writeln!(out, "# Maintainer: {maintainer}")?;
+        }
+        for (name, values) in &a.attributes {
+            for value in values {
+                writeln!(out, "# {name}: {value}")?;
+            }
+        }
+
+        writeln!(out, "pkgname={}", a.pkgname)?;
+        writeln!(out, "pkgver={}", a.pkgver)?;
+        writeln!(out, "pkgrel={}", a.pkgrel)?;
+        write_var(out, "pkgdesc", [&a.pkgdesc])?;
+        write_var(out, "url", [&a.url])?;
+        if a.arch_raw.is_empty() {
+            write_var(out, "arch", &a.arch)?;
+        } else {
+            write_var(out, "arch", [&a.arch_raw])?;
+        }
+        write_var(out, "license", [&a.license])?;
+        write_var(out, "depends", &a.depends)?;
+        write_var(out, "makedepends", &a.makedepends)?;
+        write_var(out, "makedepends_build", &a.makedepends_build)?;
+        write_var(out, "makedepends_host", &a.makedepends_host)?;
+        write_var(out, "checkdepends", &a.checkdepends)?;
+        for (suffix, depends) in &a.subpackage_depends {
+            write_var(out, &format!("depends_{suffix}"), depends)?;
+        }
+        write_var(out, "install_if", &a.install_if)?;
+        write_var(out, "pkgusers", &a.pkgusers)?;
+        write_var(out, "pkggroups", &a.pkggroups)?;
+        write_var(out, "provides", &a.provides)?;
+        write_var(out, "provider_priority", a.provider_priority)?;
+        write_var(out, "pcprefix", &a.pcprefix)?;
+        write_var(out, "sonameprefix", &a.sonameprefix)?;
+        write_var(out, "replaces", &a.replaces)?;
+        write_var(out, "replaces_priority", a.replaces_priority)?;
+        write_var(out, "install", &a.install)?;
+        write_var(out, "triggers", &a.triggers)?;
+        write_var(out, "subpackages", &a.subpackages)?;
+        write_var(out, "source", a.source.iter().map(source_entry))?;
+        write_var(out, "options", &a.options)?;
+
+        if !a.secfixes.is_empty() {
+            writeln!(out, "\n# secfixes:")?;
+            for secfix in &a.secfixes {
+                writeln!(out, "#   {}:", secfix.version)?;
+                for fix in &secfix.fixes {
+                    writeln!(out, "#     - {fix}")?;
+                }
+            }
+        }
+
+        for (name, body) in &self.functions {
+            writeln!(out, "\n{name}() {{")?;
+            for line in body.lines() {
+                if line.is_empty() {
+                    writeln!(out)?;
+                } else {
+                    writeln!(out, "\t{line}")?;
+                }
+            }
+            writeln!(out, "}}")?;
+        }
+
+        write_checksums(out, &a.source)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Writes `name="value"` if the items are not empty. The items are written on
+/// one line separated by a space if it fits into [`MAX_LINE_LEN`], otherwise
+/// (and for `source` with multiple items) one item per line indented by a tab.
+fn write_var<I>(out: &mut String, name: &str, items: I) -> fmt::Result
+where
+    I: IntoIterator,
+    I::Item: Display,
+{
+    let items: Vec<_> = items
+        .into_iter()
+        .map(|item| escape(&item.to_string()))
+        .filter(|s| !s.is_empty())
+        .collect();
+
+    if items.is_empty() {
+        return Ok(());
+    }
+    let oneline = items.join(" ");
+    // Sources are conventionally always written one per line.
+    if items.len() == 1 || (name != "source" && name.len() + oneline.len() + 3 <= MAX_LINE_LEN) {
+        writeln!(out, "{name}=\"{oneline}\"")
+    } else {
+        writeln!(out, "{name}=\"")?;
+        for item in items {
+            writeln!(out, "\t{item}")?;
+        }
+        writeln!(out, "\t\"")
+    }
+}
+
+/// Writes the `<alg>sums` blocks for all sources.
+fn write_checksums(out: &mut String, sources: &[Source]) -> fmt::Result {
+    for (alg, var) in [
+        (ChecksumAlg::Sha512, "sha512sums"),
+        (ChecksumAlg::Sha256, "sha256sums"),
+        (ChecksumAlg::Md5, "md5sums"),
+    ] {
+        let mut sources = sources.iter().filter(|s| s.checksum_alg == alg).peekable();
+        if sources.peek().is_none() {
+            continue;
+        }
+        writeln!(out, "\n{var}=\"")?;
+        for source in sources {
+            writeln!(out, "{}  {}", source.checksum, source.name)?;
+        }
+        writeln!(out, "\"")?;
+    }
+    Ok(())
+}
+
+/// Returns the `source` entry: the URI, prefixed with `<name>::` if the name
+/// differs from the URI's file name.
+fn source_entry(source: &Source) -> String {
+    let basename = source.uri.rsplit('/').next().unwrap_or(&source.uri);
+    if basename == source.name {
+        source.uri.clone()
+    } else {
+        format!("{}::{}", source.name, source.uri)
+    }
+}
+
+/// Escapes characters that have a special meaning inside double quotes.
+pub(super) fn escape(s: &str) -> String {
+    s.chars()
+        .fold(String::with_capacity(s.len()), |mut acc, c| {
+            if matches!(c, '"' | '$' | '`' | '\\') {
+                acc.push('\\');
+            }
+            acc.push(c);
+            acc
+        })
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+#[path = "writer.test.rs"]
+mod test;
@@ -0,0 +1,105 @@
+use std::path::Path;
+
+use indoc::indoc;
+
+use super::*;
+use crate::apkbuild::{ApkbuildReader, Secfix};
+use crate::internal::test_utils::{assert, dependency, S};
+
+#[test]
+fn to_string() {
+    let apkbuild = Apkbuild {
+        maintainer: Some(S!("Kevin Flynn <kevin.flynn@encom.com>")),
+        pkgname: S!("foo"),
+        pkgver: S!("1.2.3"),
+        pkgrel: 1,
+        pkgdesc: S!("A \"foo\" tool for $HOME"),
+        url: S!("https://example.org/foo"),
+        arch_raw: S!("all !s390x"),
+        license: S!("MIT"),
+        depends: vec![dependency("bar>=1.0")],
+        makedepends: vec![
+            dependency("cargo"),
+            dependency("cargo-auditable"),
+            dependency("libgit2-dev"),
+            dependency("openssl-dev>3"),
+            dependency("sqlite-dev"),
+            dependency("zlib-dev"),
+        ],
+        subpackages: vec![S!("foo-doc")],
+        source: vec![
+            Source::new(
+                "foo-1.2.3.tar.gz",
+                "https://example.org/foo/v1.2.3.tar.gz",
+                "aaaa",
+            ),
+            Source::new("foo.initd", "foo.initd", "bbbb"),
+        ],
+        secfixes: vec![Secfix::new("1.2.3-r1", vec![S!("CVE-2022-1234")])],
+        ..Default::default()
+    };
+    let expected = indoc! {r#"
+        # Maintainer: Kevin Flynn <kevin.flynn@encom.com>
+        pkgname=foo
+        pkgver=1.2.3
+        pkgrel=1
+        pkgdesc="A \"foo\" tool for \$HOME"
+        url="https://example.org/foo"
+        arch="all !s390x"
+        license="MIT"
+        depends="bar>=1.0"
+        makedepends="
+        	cargo
+        	cargo-auditable
+        	libgit2-dev
+        	openssl-dev>3
+        	sqlite-dev
+        	zlib-dev
+        	"
+        subpackages="foo-doc"
+        source="
+        	foo-1.2.3.tar.gz::https://example.org/foo/v1.2.3.tar.gz
+        	foo.initd
+        	"
+
+        # secfixes:
+        #   1.2.3-r1:
+        #     - CVE-2022-1234
+
+        package() {
+        	make DESTDIR="$pkgdir" install
+
+        	install -D -m755 "$srcdir"/foo.initd "$pkgdir"/etc/init.d/foo
+        }
+
+        sha512sums="
+        aaaa  foo-1.2.3.tar.gz
+        bbbb  foo.initd
+        "
+    "#};
+
+    let actual = ApkbuildWriter::new()
+        .function(
+            "package",
+            "make DESTDIR=\"$pkgdir\" install\n\ninstall -D -m755 \"$srcdir\"/foo.initd \"$pkgdir\"/etc/init.d/foo",
+        )
+        .to_string(&apkbuild);
+
+    assert!(actual == expected);
+}
+
+#[test]
+fn write_and_read_roundtrip() {
+    let reader = ApkbuildReader::new();
+    let apkbuild = reader
+        .read_apkbuild(Path::new("../fixtures/aports/sample/APKBUILD"))
+        .unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    let filepath = dir.path().join("APKBUILD");
+    ApkbuildWriter::new()
+        .write_file(&apkbuild, &filepath)
+        .unwrap();
+
+    assert!(reader.read_apkbuild(&filepath).unwrap() == apkbuild);
+}
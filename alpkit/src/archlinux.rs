@@ -0,0 +1,269 @@
+//! Parsing and serialization of the Arch Linux `.PKGINFO` file and mapping
+//! between it and [`PkgInfo`], so that cross-distro analysis tools can use
+//! a single code path for packages of both distributions.
+//!
+//! The format is nearly identical to the Alpine's `.PKGINFO` (`key = value`
+//! lines), but some fields differ in name or meaning. Only the fields that
+//! have a counterpart in the other format are mapped:
+//!
+//! | Arch         | Alpine          | Note                                |
+//! |--------------|-----------------|-------------------------------------|
+//! | `pkgbase`    | `origin`        | `pkgname` if `pkgbase` is missing   |
+//! | `arch = any` | `arch = noarch` |                                     |
+//! | `license`    | `license`       | multiple values joined with ` AND ` |
+//! | `depend`     | `depend`        |                                     |
+//! | `conflict`   | `depend = !…`   | stored in [`PkgInfo::conflicts`]    |
+//! | `provides`   | `provides`      |                                     |
+//! | `replaces`   | `replaces`      |                                     |
+//!
+//! The `pkgver` is kept verbatim (i.e. `[epoch:]pkgver-pkgrel` for Arch and
+//! `pkgver-r<n>` for Alpine); the version schemes are not compatible.
+//!
+//! Example:
+//! ```
+//! use alpkit::archlinux::ArchPkgInfo;
+//! use alpkit::package::PkgInfo;
+//!
+//! let arch = ArchPkgInfo::parse("pkgname = bash\npkgver = 5.2.026-2\narch = x86_64\n").unwrap();
+//! let pkginfo = PkgInfo::from(&arch);
+//!
+//! assert_eq!(pkginfo.pkgname, "bash");
+//! assert_eq!(pkginfo.origin, "bash");
+//! ```
+use std::fmt;
+
+use thiserror::Error;
+
+use crate::dependency::{ConstraintParseError, Dependency};
+use crate::internal::macros::bail;
+use crate::package::PkgInfo;
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, Error)]
+pub enum ArchPkgInfoError {
+    #[error("syntax error on line {0}: missing ' = ' in '{1}'")]
+    Syntax(usize, String),
+
+    #[error("missing required field: {0}")]
+    MissingField(&'static str),
+
+    #[error("invalid value of {0}: '{1}'")]
+    InvalidValue(&'static str, String),
+
+    #[error(transparent)]
+    Dependency(#[from] ConstraintParseError),
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// This struct represents the `.PKGINFO` file of Arch Linux packages (as
+/// generated by makepkg).
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ArchPkgInfo {
+    /// The package name.
+    pub pkgname: String,
+
+    /// The name of the split package group (i.e. the PKGBUILD) from which the
+    /// package was built.
+    pub pkgbase: Option<String>,
+
+    /// Extra data in the `key=value` format (e.g. `pkgtype=pkg`).
+    pub xdata: Vec<String>,
+
+    /// A full version of the package: `[epoch:]pkgver-pkgrel`.
+    pub pkgver: String,
+
+    /// A brief, one-line description of the package.
+    pub pkgdesc: String,
+
+    /// The homepage of the packaged software.
+    pub url: String,
+
+    /// An unix timestamp of the package build date/time.
+    pub builddate: i64,
+
+    /// The name and email address of the person who built the package.
+    pub packager: String,
+
+    /// The installed-size of the package in bytes.
+    pub size: usize,
+
+    /// The architecture of the package (e.g. `x86_64`, or `any`).
+    pub arch: String,
+
+    /// License(s) of the packaged software.
+    pub license: Vec<String>,
+
+    pub replaces: Vec<Dependency>,
+
+    /// Groups the package belongs to (e.g. `base-devel`).
+    pub group: Vec<String>,
+
+    pub conflicts: Vec<Dependency>,
+
+    pub provides: Vec<Dependency>,
+
+    /// Paths of the configuration files to be preserved on upgrade.
+    pub backup: Vec<String>,
+
+    pub depends: Vec<Dependency>,
+
+    /// Optional dependencies in the format `name: description`.
+    pub optdepends: Vec<String>,
+
+    pub makedepends: Vec<Dependency>,
+
+    pub checkdepends: Vec<Dependency>,
+}
+
+impl ArchPkgInfo {
+    /// Parses the given `.PKGINFO` file contents. Unknown fields are ignored.
+    pub fn parse(s: &str) -> Result<Self, ArchPkgInfoError> {
+        let mut pkginfo = ArchPkgInfo::default();
+
+        for (lno, line) in s.lines().enumerate() {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = match line.split_once(" = ") {
+                Some(kv) => kv,
+                None => bail!(ArchPkgInfoError::Syntax(lno + 1, line.to_owned())),
+            };
+            let value = value.to_owned();
+
+            match key {
+                "pkgname" => pkginfo.pkgname = value,
+                "pkgbase" => pkginfo.pkgbase = Some(value),
+                "xdata" => pkginfo.xdata.push(value),
+                "pkgver" => pkginfo.pkgver = value,
+                "pkgdesc" => pkginfo.pkgdesc = value,
+                "url" => pkginfo.url = value,
+                "builddate" => pkginfo.builddate = parse_number("builddate", value)?,
+                "packager" => pkginfo.packager = value,
+                "size" => pkginfo.size = parse_number("size", value)?,
+                "arch" => pkginfo.arch = value,
+                "license" => pkginfo.license.push(value),
+                "replaces" => pkginfo.replaces.push(value.parse()?),
+                "group" => pkginfo.group.push(value),
+                "conflict" => pkginfo.conflicts.push(value.parse()?),
+                "provides" => pkginfo.provides.push(value.parse()?),
+                "backup" => pkginfo.backup.push(value),
+                "depend" => pkginfo.depends.push(value.parse()?),
+                "optdepend" => pkginfo.optdepends.push(value),
+                "makedepend" => pkginfo.makedepends.push(value.parse()?),
+                "checkdepend" => pkginfo.checkdepends.push(value.parse()?),
+                _ => (),
+            }
+        }
+
+        if pkginfo.pkgname.is_empty() {
+            bail!(ArchPkgInfoError::MissingField("pkgname"));
+        }
+        if pkginfo.pkgver.is_empty() {
+            bail!(ArchPkgInfoError::MissingField("pkgver"));
+        }
+        Ok(pkginfo)
+    }
+}
+
+/// Serializes into the `.PKGINFO` format with the fields in the same order as
+/// makepkg writes them.
+impl fmt::Display for ArchPkgInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "pkgname = {}", self.pkgname)?;
+        if let Some(pkgbase) = &self.pkgbase {
+            writeln!(f, "pkgbase = {pkgbase}")?;
+        }
+        write_all(f, "xdata", &self.xdata)?;
+        writeln!(f, "pkgver = {}", self.pkgver)?;
+        writeln!(f, "pkgdesc = {}", self.pkgdesc)?;
+        writeln!(f, "url = {}", self.url)?;
+        writeln!(f, "builddate = {}", self.builddate)?;
+        writeln!(f, "packager = {}", self.packager)?;
+        writeln!(f, "size = {}", self.size)?;
+        writeln!(f, "arch = {}", self.arch)?;
+        write_all(f, "license", &self.license)?;
+        write_all(f, "replaces", &self.replaces)?;
+        write_all(f, "group", &self.group)?;
+        write_all(f, "conflict", &self.conflicts)?;
+        write_all(f, "provides", &self.provides)?;
+        write_all(f, "backup", &self.backup)?;
+        write_all(f, "depend", &self.depends)?;
+        write_all(f, "optdepend", &self.optdepends)?;
+        write_all(f, "makedepend", &self.makedepends)?;
+        write_all(f, "checkdepend", &self.checkdepends)
+    }
+}
+
+impl From<&ArchPkgInfo> for PkgInfo {
+    fn from(arch: &ArchPkgInfo) -> Self {
+        PkgInfo {
+            pkgname: arch.pkgname.clone(),
+            pkgver: arch.pkgver.clone(),
+            pkgdesc: arch.pkgdesc.clone(),
+            url: arch.url.clone(),
+            arch: match arch.arch.as_str() {
+                "any" => "noarch".to_owned(),
+                s => s.to_owned(),
+            },
+            license: arch.license.join(" AND "),
+            depends: arch.depends.clone(),
+            conflicts: arch.conflicts.clone(),
+            provides: arch.provides.clone(),
+            replaces: arch.replaces.clone(),
+            origin: arch.pkgbase.clone().unwrap_or_else(|| arch.pkgname.clone()),
+            builddate: arch.builddate,
+            packager: arch.packager.clone(),
+            size: arch.size,
+            ..PkgInfo::default()
+        }
+    }
+}
+
+impl From<&PkgInfo> for ArchPkgInfo {
+    fn from(pkginfo: &PkgInfo) -> Self {
+        ArchPkgInfo {
+            pkgname: pkginfo.pkgname.clone(),
+            pkgbase: Some(pkginfo.origin.clone()),
+            pkgver: pkginfo.pkgver.clone(),
+            pkgdesc: pkginfo.pkgdesc.clone(),
+            url: pkginfo.url.clone(),
+            builddate: pkginfo.builddate,
+            packager: pkginfo.packager.clone(),
+            size: pkginfo.size,
+            arch: match pkginfo.arch.as_str() {
+                "noarch" => "any".to_owned(),
+                s => s.to_owned(),
+            },
+            license: vec![pkginfo.license.clone()],
+            replaces: pkginfo.replaces.clone(),
+            conflicts: pkginfo.conflicts.clone(),
+            provides: pkginfo.provides.clone(),
+            depends: pkginfo.depends.clone(),
+            ..ArchPkgInfo::default()
+        }
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(
+    key: &'static str,
+    value: String,
+) -> Result<T, ArchPkgInfoError> {
+    value
+        .parse()
+        .map_err(|_| ArchPkgInfoError::InvalidValue(key, value))
+}
+
+fn write_all<T: fmt::Display>(f: &mut fmt::Formatter<'_>, key: &str, values: &[T]) -> fmt::Result {
+    for value in values {
+        writeln!(f, "{key} = {value}")?;
+    }
+    Ok(())
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+#[path = "archlinux.test.rs"]
+mod test;
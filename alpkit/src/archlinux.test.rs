@@ -0,0 +1,105 @@
+use indoc::indoc;
+
+use super::*;
+use crate::dependency::{Constraint, Op};
+use crate::internal::test_utils::{assert, assert_let, S};
+
+const SAMPLE: &str = indoc! {"
+    # Generated by makepkg 6.0.2
+    # using fakeroot version 1.34
+    pkgname = bash
+    pkgbase = bash
+    xdata = pkgtype=pkg
+    pkgver = 5.2.026-2
+    pkgdesc = The GNU Bourne Again shell
+    url = https://www.gnu.org/software/bash/bash.html
+    builddate = 1708000000
+    packager = Jane Doe <jane@example.org>
+    size = 9371232
+    arch = x86_64
+    license = GPL-3.0-or-later
+    provides = sh
+    backup = etc/bash.bashrc
+    depend = readline
+    depend = libreadline.so=8-64
+    depend = glibc
+    optdepend = bash-completion: for tab completion
+"};
+
+#[test]
+fn parse_and_display() {
+    let pkginfo = ArchPkgInfo::parse(SAMPLE).unwrap();
+
+    assert!(pkginfo.pkgname == "bash");
+    assert!(pkginfo.pkgbase == Some(S!("bash")));
+    assert!(pkginfo.xdata == [S!("pkgtype=pkg")]);
+    assert!(pkginfo.builddate == 1708000000);
+    assert!(pkginfo.size == 9371232);
+    assert!(pkginfo.backup == [S!("etc/bash.bashrc")]);
+    assert!(
+        pkginfo.depends
+            == vec![
+                Dependency::new("readline", None),
+                Dependency::new("libreadline.so", Some(Constraint::new(Op::Equal, "8-64"))),
+                Dependency::new("glibc", None),
+            ]
+    );
+    assert!(pkginfo.optdepends == [S!("bash-completion: for tab completion")]);
+
+    let expected = SAMPLE
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .map(|line| format!("{line}\n"))
+        .collect::<String>();
+    assert!(pkginfo.to_string() == expected);
+}
+
+#[test]
+fn parse_invalid() {
+    assert_let!(Err(ArchPkgInfoError::Syntax(2, _)) = ArchPkgInfo::parse("pkgname = a\nfoo\n"));
+    assert_let!(
+        Err(ArchPkgInfoError::MissingField("pkgver")) = ArchPkgInfo::parse("pkgname = a\n")
+    );
+    assert_let!(
+        Err(ArchPkgInfoError::InvalidValue("size", _)) =
+            ArchPkgInfo::parse("pkgname = a\npkgver = 1-1\nsize = big\n")
+    );
+}
+
+#[test]
+fn pkginfo_from_arch() {
+    let arch = ArchPkgInfo {
+        pkgname: S!("python-foo"),
+        pkgver: S!("1:1.0-1"),
+        arch: S!("any"),
+        license: vec![S!("MIT"), S!("Apache-2.0")],
+        conflicts: vec![Dependency::new("python-foo-git", None)],
+        ..ArchPkgInfo::default()
+    };
+    let pkginfo = PkgInfo::from(&arch);
+
+    assert!(pkginfo.pkgver == "1:1.0-1");
+    assert!(pkginfo.arch == "noarch");
+    assert!(pkginfo.license == "MIT AND Apache-2.0");
+    assert!(pkginfo.origin == "python-foo");
+    assert!(pkginfo.conflicts == arch.conflicts);
+}
+
+#[test]
+fn arch_from_pkginfo() {
+    let pkginfo = PkgInfo {
+        pkgname: S!("sample-doc"),
+        pkgver: S!("1.2.3-r2"),
+        arch: S!("noarch"),
+        license: S!("MIT"),
+        origin: S!("sample"),
+        depends: vec![Dependency::new("sample", None)],
+        ..PkgInfo::default()
+    };
+    let arch = ArchPkgInfo::from(&pkginfo);
+
+    assert!(arch.pkgbase == Some(S!("sample")));
+    assert!(arch.arch == "any");
+    assert!(arch.license == [S!("MIT")]);
+    assert!(arch.depends == pkginfo.depends);
+}
@@ -0,0 +1,150 @@
+//! Generates JSON Schemas of the types (de)serialized by alpkit.
+use std::env;
+use std::fs;
+use std::io::{self, Write as _};
+use std::path::{Path, PathBuf};
+use std::process::exit;
+
+use alpkit::schema::{openapi_components, root_schema, Draft, ROOT_TYPES};
+use schemars::schema::RootSchema;
+use serde::Serialize;
+use serde_json::json;
+
+const PROG_NAME: &str = "schema-gen";
+
+struct Opts {
+    type_name: Option<String>,
+    draft: Draft,
+    openapi: bool,
+    out_dir: Option<PathBuf>,
+}
+
+fn main() {
+    let opts = match parse_args(env::args().skip(1)) {
+        Ok(Some(opts)) => opts,
+        Ok(None) => {
+            print_usage();
+            exit(0);
+        }
+        Err(e) => {
+            eprintln!("{PROG_NAME}: {e}\n");
+            print_usage();
+            exit(2);
+        }
+    };
+
+    if let Err(e) = run(opts) {
+        eprintln!("{PROG_NAME}: {e}");
+        exit(1);
+    }
+}
+
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Option<Opts>, String> {
+    let mut type_name = None;
+    let mut draft = Draft::default();
+    let mut openapi = false;
+    let mut out_dir = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "-d" | "--draft" => {
+                let value = args.next().ok_or("missing value for --draft")?;
+                draft = value.parse().map_err(|e| format!("{e}"))?;
+            }
+            "--openapi" => openapi = true,
+            "-o" | "--out-dir" => {
+                let dir = args.next().ok_or("missing value for --out-dir")?;
+                out_dir = Some(PathBuf::from(dir));
+            }
+            s if s.starts_with('-') => return Err(format!("unknown option: '{s}'")),
+            s if type_name.is_none() => type_name = Some(s.to_owned()),
+            s => return Err(format!("unexpected argument: '{s}'")),
+        }
+    }
+    match (&type_name, openapi) {
+        (None, false) => return Err("no type specified".to_owned()),
+        (Some(_), true) => return Err("--openapi cannot be used with a type".to_owned()),
+        _ => (),
+    }
+
+    Ok(Some(Opts {
+        type_name,
+        draft,
+        openapi,
+        out_dir,
+    }))
+}
+
+fn run(opts: Opts) -> Result<(), String> {
+    if opts.openapi {
+        let doc = json!({ "components": { "schemas": openapi_components() } });
+
+        return match opts.out_dir {
+            Some(out_dir) => {
+                create_dir(&out_dir)?;
+                write_json(&doc, &out_dir.join("openapi-components.json"))
+            }
+            None => print_json(&doc),
+        };
+    }
+
+    let type_name = opts.type_name.as_deref().unwrap_or_default();
+    let names = if type_name == "all" {
+        ROOT_TYPES.to_vec()
+    } else {
+        vec![type_name]
+    };
+
+    match opts.out_dir {
+        Some(out_dir) => {
+            create_dir(&out_dir)?;
+
+            for name in names {
+                write_json(
+                    &generate(name, opts.draft)?,
+                    &out_dir.join(format!("{name}.schema.json")),
+                )?;
+            }
+            Ok(())
+        }
+        None if names.len() > 1 => Err("--out-dir is required for 'all'".to_owned()),
+        None => print_json(&generate(names[0], opts.draft)?),
+    }
+}
+
+fn generate(name: &str, draft: Draft) -> Result<RootSchema, String> {
+    root_schema(name, draft).ok_or_else(|| format!("unknown type: '{name}'"))
+}
+
+fn create_dir(path: &Path) -> Result<(), String> {
+    fs::create_dir_all(path).map_err(|e| format!("cannot create '{}': {e}", path.display()))
+}
+
+fn print_json<T: ?Sized + Serialize>(value: &T) -> Result<(), String> {
+    serde_json::to_writer_pretty(io::stdout(), value).map_err(|e| e.to_string())?;
+    let _ = io::stdout().write(b"\n");
+    Ok(())
+}
+
+fn write_json<T: ?Sized + Serialize>(value: &T, path: &Path) -> Result<(), String> {
+    let mut json = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    json.push('\n');
+
+    fs::write(path, json).map_err(|e| format!("cannot write '{}': {e}", path.display()))
+}
+
+fn print_usage() {
+    println!("Usage: {PROG_NAME} [-d <draft>] [-o <dir>] <type>|all");
+    println!("       {PROG_NAME} [-o <dir>] --openapi\n");
+    println!("Print JSON Schema of the given type to stdout, or write schemas of the");
+    println!("given type or all types into <dir> as <type>.schema.json.\n");
+    println!("Options:");
+    println!("  -d, --draft <draft>  JSON Schema draft: draft-07 (default), 2019-09 or");
+    println!("                       2020-12.");
+    println!("      --openapi        Print all types as OpenAPI 3.1 components (or write");
+    println!("                       them into <dir>/openapi-components.json).");
+    println!("  -o, --out-dir <dir>  Write schema(s) into files in <dir>.");
+    println!("  -h, --help           Show this help message.\n");
+    println!("Types: {}", ROOT_TYPES.join(", "));
+}
@@ -0,0 +1,302 @@
+//! Export of file and dependency records of packages into [Apache Parquet]
+//! files for bulk analytics in data tools (e.g. DuckDB, Polars, Spark).
+//!
+//! Each record contains `pkgname`, `pkgver` and `arch` of the package it
+//! belongs to, so records of many packages (e.g. a whole repository) can be
+//! written into a single file and queried together. For example, all setuid
+//! files across the repository:
+//!
+//! ```sql
+//! SELECT pkgname, path FROM 'files.parquet' WHERE mode & 2048 != 0;
+//! ```
+//!
+//! Example:
+//! ```no_run
+//! # use std::fs::File;
+//! # use std::io::BufReader;
+//! use alpkit::columnar::FilesWriter;
+//! use alpkit::package::Package;
+//!
+//! let pkg = Package::load(File::open("example-1.0-r0.apk").map(BufReader::new).unwrap()).unwrap();
+//!
+//! let mut writer = FilesWriter::new(File::create("files.parquet").unwrap()).unwrap();
+//! writer.add_package(&pkg).unwrap();
+//! writer.finish().unwrap();
+//! ```
+//!
+//! [Apache Parquet]: https://parquet.apache.org
+use std::io::Write;
+use std::sync::Arc;
+
+use arrow_array::builder::{StringBuilder, UInt32Builder, UInt64Builder};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+
+use crate::dependency::Dependency;
+use crate::index::Index;
+use crate::package::{Package, PkgInfo};
+
+pub use parquet::errors::ParquetError as Error;
+
+/// The number of rows buffered before they are written as a row batch.
+const BATCH_ROWS: usize = 16 * 1024;
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// A writer of metadata of the packages' files into a Parquet file with the
+/// columns `pkgname`, `pkgver`, `arch`, `path`, `type`, `link_target`,
+/// `uname`, `gname`, `size`, `mode` and `digest`.
+pub struct FilesWriter<W: Write + Send> {
+    writer: ArrowWriter<W>,
+    schema: SchemaRef,
+    rows: usize,
+    pkgname: StringBuilder,
+    pkgver: StringBuilder,
+    arch: StringBuilder,
+    path: StringBuilder,
+    file_type: StringBuilder,
+    link_target: StringBuilder,
+    uname: StringBuilder,
+    gname: StringBuilder,
+    size: UInt64Builder,
+    mode: UInt32Builder,
+    digest: StringBuilder,
+}
+
+impl<W: Write + Send> FilesWriter<W> {
+    pub fn new(out: W) -> Result<Self, Error> {
+        let schema = Schema::new(vec![
+            Field::new("pkgname", DataType::Utf8, false),
+            Field::new("pkgver", DataType::Utf8, false),
+            Field::new("arch", DataType::Utf8, false),
+            Field::new("path", DataType::Utf8, false),
+            Field::new("type", DataType::Utf8, false),
+            Field::new("link_target", DataType::Utf8, true),
+            Field::new("uname", DataType::Utf8, false),
+            Field::new("gname", DataType::Utf8, false),
+            Field::new("size", DataType::UInt64, true),
+            Field::new("mode", DataType::UInt32, false),
+            Field::new("digest", DataType::Utf8, true),
+        ]);
+        let schema = Arc::new(schema);
+        Ok(FilesWriter {
+            writer: arrow_writer(out, schema.clone())?,
+            schema,
+            rows: 0,
+            pkgname: StringBuilder::new(),
+            pkgver: StringBuilder::new(),
+            arch: StringBuilder::new(),
+            path: StringBuilder::new(),
+            file_type: StringBuilder::new(),
+            link_target: StringBuilder::new(),
+            uname: StringBuilder::new(),
+            gname: StringBuilder::new(),
+            size: UInt64Builder::new(),
+            mode: UInt32Builder::new(),
+            digest: StringBuilder::new(),
+        })
+    }
+
+    /// Writes records of all files of the package. The package must be
+    /// loaded with files, otherwise nothing is written.
+    pub fn add_package(&mut self, pkg: &Package) -> Result<(), Error> {
+        let pkginfo = pkg.pkginfo();
+
+        for file in pkg.files_metadata() {
+            self.pkgname.append_value(&pkginfo.pkgname);
+            self.pkgver.append_value(&pkginfo.pkgver);
+            self.arch.append_value(&pkginfo.arch);
+            self.path.append_value(file.path.to_string_lossy());
+            self.file_type.append_value(file.file_type.code());
+            self.link_target
+                .append_option(file.link_target.as_ref().map(|p| p.to_string_lossy()));
+            self.uname.append_value(&file.uname);
+            self.gname.append_value(&file.gname);
+            self.size.append_option(file.size);
+            self.mode.append_value(file.mode);
+            self.digest.append_option(file.digest.as_ref());
+
+            self.rows += 1;
+            if self.rows >= BATCH_ROWS {
+                self.flush()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the buffered records and the file footer, and returns the
+    /// underlying writer.
+    pub fn finish(mut self) -> Result<W, Error> {
+        self.flush()?;
+        self.writer.into_inner()
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        if self.rows == 0 {
+            return Ok(());
+        }
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(self.pkgname.finish()),
+            Arc::new(self.pkgver.finish()),
+            Arc::new(self.arch.finish()),
+            Arc::new(self.path.finish()),
+            Arc::new(self.file_type.finish()),
+            Arc::new(self.link_target.finish()),
+            Arc::new(self.uname.finish()),
+            Arc::new(self.gname.finish()),
+            Arc::new(self.size.finish()),
+            Arc::new(self.mode.finish()),
+            Arc::new(self.digest.finish()),
+        ];
+        self.rows = 0;
+        self.writer
+            .write(&RecordBatch::try_new(self.schema.clone(), columns)?)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// A writer of the packages' dependencies into a Parquet file with the
+/// columns `pkgname`, `pkgver`, `arch`, `kind`, `name`, `op` and `version`.
+/// The `kind` is one of `depends`, `conflicts`, `install_if`, `provides` or
+/// `replaces`.
+pub struct DependsWriter<W: Write + Send> {
+    writer: ArrowWriter<W>,
+    schema: SchemaRef,
+    rows: usize,
+    pkgname: StringBuilder,
+    pkgver: StringBuilder,
+    arch: StringBuilder,
+    kind: StringBuilder,
+    name: StringBuilder,
+    op: StringBuilder,
+    version: StringBuilder,
+}
+
+impl<W: Write + Send> DependsWriter<W> {
+    pub fn new(out: W) -> Result<Self, Error> {
+        let schema = Schema::new(vec![
+            Field::new("pkgname", DataType::Utf8, false),
+            Field::new("pkgver", DataType::Utf8, false),
+            Field::new("arch", DataType::Utf8, false),
+            Field::new("kind", DataType::Utf8, false),
+            Field::new("name", DataType::Utf8, false),
+            Field::new("op", DataType::Utf8, true),
+            Field::new("version", DataType::Utf8, true),
+        ]);
+        let schema = Arc::new(schema);
+        Ok(DependsWriter {
+            writer: arrow_writer(out, schema.clone())?,
+            schema,
+            rows: 0,
+            pkgname: StringBuilder::new(),
+            pkgver: StringBuilder::new(),
+            arch: StringBuilder::new(),
+            kind: StringBuilder::new(),
+            name: StringBuilder::new(),
+            op: StringBuilder::new(),
+            version: StringBuilder::new(),
+        })
+    }
+
+    /// Writes records of all dependencies of the package.
+    pub fn add_pkginfo(&mut self, pkginfo: &PkgInfo) -> Result<(), Error> {
+        self.add(
+            [&pkginfo.pkgname, &pkginfo.pkgver, &pkginfo.arch],
+            [
+                ("depends", &pkginfo.depends),
+                ("conflicts", &pkginfo.conflicts),
+                ("install_if", &pkginfo.install_if),
+                ("provides", &pkginfo.provides),
+                ("replaces", &pkginfo.replaces),
+            ],
+        )
+    }
+
+    /// Writes records of all dependencies of all the packages in the index.
+    pub fn add_index(&mut self, index: &Index) -> Result<(), Error> {
+        for entry in &index.entries {
+            self.add(
+                [&entry.pkgname, &entry.pkgver, &entry.arch],
+                [
+                    ("depends", &entry.depends),
+                    ("conflicts", &entry.conflicts),
+                    ("install_if", &entry.install_if),
+                    ("provides", &entry.provides),
+                    ("replaces", &entry.replaces),
+                ],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Writes the buffered records and the file footer, and returns the
+    /// underlying writer.
+    pub fn finish(mut self) -> Result<W, Error> {
+        self.flush()?;
+        self.writer.into_inner()
+    }
+
+    fn add(
+        &mut self,
+        [pkgname, pkgver, arch]: [&str; 3],
+        deps: [(&str, &[Dependency]); 5],
+    ) -> Result<(), Error> {
+        for (kind, deps) in deps {
+            for dep in deps {
+                self.pkgname.append_value(pkgname);
+                self.pkgver.append_value(pkgver);
+                self.arch.append_value(arch);
+                self.kind.append_value(kind);
+                self.name.append_value(&dep.name);
+                self.op
+                    .append_option(dep.constraint.as_ref().map(|c| c.op.to_string()));
+                self.version
+                    .append_option(dep.constraint.as_ref().map(|c| &c.version));
+
+                self.rows += 1;
+                if self.rows >= BATCH_ROWS {
+                    self.flush()?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        if self.rows == 0 {
+            return Ok(());
+        }
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(self.pkgname.finish()),
+            Arc::new(self.pkgver.finish()),
+            Arc::new(self.arch.finish()),
+            Arc::new(self.kind.finish()),
+            Arc::new(self.name.finish()),
+            Arc::new(self.op.finish()),
+            Arc::new(self.version.finish()),
+        ];
+        self.rows = 0;
+        self.writer
+            .write(&RecordBatch::try_new(self.schema.clone(), columns)?)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+fn arrow_writer<W: Write + Send>(out: W, schema: SchemaRef) -> Result<ArrowWriter<W>, Error> {
+    let props = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build();
+
+    ArrowWriter::try_new(out, schema, Some(props))
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+#[path = "columnar.test.rs"]
+mod test;
@@ -0,0 +1,70 @@
+use std::fs::{self, File};
+use std::io::BufReader;
+
+use arrow_array::cast::AsArray;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+use super::*;
+use crate::internal::test_utils::{assert, S};
+
+fn read_batches(file: File) -> Vec<RecordBatch> {
+    ParquetRecordBatchReaderBuilder::try_new(file)
+        .unwrap()
+        .build()
+        .unwrap()
+        .map(Result::unwrap)
+        .collect()
+}
+
+fn strings(batches: &[RecordBatch], column: &str) -> Vec<String> {
+    batches
+        .iter()
+        .flat_map(|batch| {
+            batch
+                .column_by_name(column)
+                .unwrap()
+                .as_string::<i32>()
+                .iter()
+                .map(|s| s.unwrap_or_default().to_owned())
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+#[test]
+fn write_files() {
+    let path = "../fixtures/apk/rssh-2.3.4-r3.apk";
+    let pkg = Package::load(File::open(path).map(BufReader::new).unwrap()).unwrap();
+
+    let mut writer = FilesWriter::new(tempfile::tempfile().unwrap()).unwrap();
+    writer.add_package(&pkg).unwrap();
+    let batches = read_batches(writer.finish().unwrap());
+
+    let expected_paths = pkg
+        .files_metadata()
+        .map(|f| f.path.to_string_lossy().into_owned())
+        .collect::<Vec<_>>();
+
+    assert!(strings(&batches, "path") == expected_paths);
+    assert!(strings(&batches, "pkgname").iter().all(|s| s == "rssh"));
+}
+
+#[test]
+fn write_depends() {
+    let index =
+        Index::parse(&fs::read_to_string("../fixtures/apkindex/APKINDEX").unwrap()).unwrap();
+
+    let mut writer = DependsWriter::new(tempfile::tempfile().unwrap()).unwrap();
+    writer.add_index(&index).unwrap();
+    let batches = read_batches(writer.finish().unwrap());
+
+    let records = strings(&batches, "pkgname")
+        .into_iter()
+        .zip(strings(&batches, "kind"))
+        .zip(strings(&batches, "name"))
+        .map(|((pkgname, kind), name)| format!("{pkgname} {kind} {name}"))
+        .collect::<Vec<_>>();
+
+    assert!(records.contains(&S!("dropbear conflicts openssh-server")));
+    assert!(records.contains(&S!("busybox provides cmd:sh")));
+}
@@ -0,0 +1,166 @@
+//! Consistency checks between an APKBUILD and a package built from it.
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::apkbuild::Apkbuild;
+use crate::dependency::Dependency;
+use crate::package::{Package, PkgInfo};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// A divergence between an APKBUILD and a package built from it.
+#[derive(Debug, Clone, PartialEq, Eq, Error, Deserialize, Serialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum Divergence {
+    /// The package is neither the main package nor a subpackage of the APKBUILD.
+    #[error("package '{pkgname}' is not built from APKBUILD '{origin}'")]
+    UnknownPackage { pkgname: String, origin: String },
+
+    /// The package version doesn't match `<pkgver>-r<pkgrel>` of the APKBUILD.
+    #[error("version mismatch: expected '{expected}', but got '{actual}'")]
+    VersionMismatch { expected: String, actual: String },
+
+    /// A dependency (or conflict) declared in the APKBUILD is missing in the
+    /// package.
+    #[error("declared dependency is missing: '{dependency}'")]
+    MissingDepends { dependency: String },
+
+    /// The package provides something that isn't declared in the APKBUILD.
+    /// Providers autodiscovered by abuild (e.g. `so:`, `cmd:`, `pc:`) are not
+    /// reported.
+    #[error("provides not declared in APKBUILD: '{provider}'")]
+    UndeclaredProvides { provider: String },
+
+    /// The package arch is not in the APKBUILD's `arch` list. `noarch` is
+    /// always allowed for subpackages.
+    #[error("arch '{arch}' is not declared in APKBUILD")]
+    UndeclaredArch { arch: String },
+
+    /// The package license differs from the APKBUILD's `license`.
+    #[error("license mismatch: expected '{expected}', but got '{actual}'")]
+    LicenseMismatch { expected: String, actual: String },
+
+    /// The package `commit` differs from the expected one (e.g. the last
+    /// commit of the APKBUILD); `actual` is empty if the package has none.
+    #[error("commit mismatch: expected '{expected}', but got '{actual}'")]
+    CommitMismatch { expected: String, actual: String },
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Checks if the given package built from the `apkbuild` is consistent with
+/// it and returns all divergences found (an empty vec if it's consistent).
+///
+/// This is a convenient shortcut for [`check_pkginfo`].
+pub fn check_package(apkbuild: &Apkbuild, pkg: &Package) -> Vec<Divergence> {
+    check_pkginfo(apkbuild, pkg.pkginfo())
+}
+
+/// Checks if the given `.PKGINFO` of a package built from the `apkbuild` is
+/// consistent with it and returns all divergences found.
+///
+/// Dependencies and provides are checked only for the main package and
+/// dependencies of subpackages only for those declared in `depends_<suffix>`
+/// (see [`Apkbuild::subpackage_depends`]), because subpackages' metadata are
+/// typically set in split functions.
+pub fn check_pkginfo(apkbuild: &Apkbuild, pkginfo: &PkgInfo) -> Vec<Divergence> {
+    let is_main = pkginfo.pkgname == apkbuild.pkgname;
+
+    if !is_main && !apkbuild.subpackages.contains(&pkginfo.pkgname) {
+        return vec![Divergence::UnknownPackage {
+            pkgname: pkginfo.pkgname.clone(),
+            origin: apkbuild.pkgname.clone(),
+        }];
+    }
+    let mut divergences = Vec::new();
+
+    let full_pkgver = apkbuild.full_pkgver();
+    if pkginfo.pkgver != full_pkgver {
+        divergences.push(Divergence::VersionMismatch {
+            expected: full_pkgver,
+            actual: pkginfo.pkgver.clone(),
+        });
+    }
+
+    // abuild builds subpackages such as -doc or -openrc as noarch regardless
+    // of the APKBUILD's arch.
+    let arch_declared = if pkginfo.arch == "noarch" {
+        !is_main
+            || apkbuild.arch_raw.is_empty()
+            || apkbuild
+                .arch_raw
+                .split_ascii_whitespace()
+                .any(|s| s == "noarch")
+    } else {
+        apkbuild.arch.contains(&pkginfo.arch)
+    };
+    if !arch_declared {
+        divergences.push(Divergence::UndeclaredArch {
+            arch: pkginfo.arch.clone(),
+        });
+    }
+
+    if is_main && pkginfo.license != apkbuild.license {
+        divergences.push(Divergence::LicenseMismatch {
+            expected: apkbuild.license.clone(),
+            actual: pkginfo.license.clone(),
+        });
+    }
+
+    let declared_depends = if is_main {
+        Some(&apkbuild.depends)
+    } else {
+        pkginfo
+            .pkgname
+            .strip_prefix(&format!("{}-", apkbuild.pkgname))
+            .and_then(|suffix| apkbuild.subpackage_depends.get(suffix))
+    };
+    for dep in declared_depends.into_iter().flatten() {
+        let found = if dep.conflict {
+            contains_dependency(&pkginfo.conflicts, &dep.name)
+        } else {
+            dep.name == pkginfo.pkgname || contains_dependency(&pkginfo.depends, &dep.name)
+        };
+        if !found {
+            divergences.push(Divergence::MissingDepends {
+                dependency: dep.to_string(),
+            });
+        }
+    }
+
+    if is_main {
+        for provider in &pkginfo.provides {
+            if !provider.name.contains(':')
+                && !contains_dependency(&apkbuild.provides, &provider.name)
+            {
+                divergences.push(Divergence::UndeclaredProvides {
+                    provider: provider.to_string(),
+                });
+            }
+        }
+    }
+
+    divergences
+}
+
+/// Checks if the `commit` of the given `.PKGINFO` is the `expected` commit
+/// hash, e.g. the last commit of the APKBUILD obtained by
+/// `apkbuild::last_commit` (requires the `git` feature).
+pub fn check_commit(pkginfo: &PkgInfo, expected: &str) -> Option<Divergence> {
+    let actual = pkginfo.commit.as_deref().unwrap_or_default();
+
+    (actual != expected).then(|| Divergence::CommitMismatch {
+        expected: expected.to_owned(),
+        actual: actual.to_owned(),
+    })
+}
+
+fn contains_dependency(deps: &[Dependency], name: &str) -> bool {
+    deps.iter().any(|dep| dep.name == name)
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+#[path = "consistency.test.rs"]
+mod test;
@@ -0,0 +1,150 @@
+use std::path::Path;
+
+use super::*;
+use crate::apkbuild::ApkbuildReader;
+use crate::internal::test_utils::{assert, dependency, S};
+
+fn sample_apkbuild() -> Apkbuild {
+    ApkbuildReader::new()
+        .read_apkbuild(Path::new("../fixtures/aports/sample/APKBUILD"))
+        .unwrap()
+}
+
+#[test]
+fn check_pkginfo_consistent() {
+    let apkbuild = sample_apkbuild();
+    let mut pkginfo = apkbuild.expected_pkginfo("x86_64");
+    pkginfo.depends.push(dependency("so:libc.musl-x86_64.so.1"));
+    pkginfo.provides.push(dependency("cmd:sample=1.2.3-r2"));
+
+    assert!(check_pkginfo(&apkbuild, &pkginfo).is_empty());
+}
+
+#[test]
+fn check_pkginfo_divergences() {
+    let apkbuild = sample_apkbuild();
+    let pkginfo = PkgInfo {
+        pkgver: S!("1.2.3-r1"),
+        arch: S!("s390x"),
+        license: S!("MIT"),
+        depends: vec![],
+        conflicts: vec![dependency("sample-legacy")],
+        provides: vec![dependency("sample2=1.2.3-r2"), dependency("sample3")],
+        ..apkbuild.expected_pkginfo("x86_64")
+    };
+
+    assert!(
+        check_pkginfo(&apkbuild, &pkginfo)
+            == vec![
+                Divergence::VersionMismatch {
+                    expected: S!("1.2.3-r2"),
+                    actual: S!("1.2.3-r1"),
+                },
+                Divergence::UndeclaredArch { arch: S!("s390x") },
+                Divergence::LicenseMismatch {
+                    expected: S!("ISC and BSD-2-Clause and BSD-3-Clause"),
+                    actual: S!("MIT"),
+                },
+                Divergence::MissingDepends {
+                    dependency: S!("ruby>=3.0"),
+                },
+                Divergence::UndeclaredProvides {
+                    provider: S!("sample3"),
+                },
+            ]
+    );
+}
+
+#[test]
+fn check_pkginfo_noarch() {
+    let apkbuild = sample_apkbuild();
+    let pkginfo = apkbuild.expected_pkginfo("noarch");
+
+    assert!(
+        check_pkginfo(&apkbuild, &pkginfo)
+            == vec![Divergence::UndeclaredArch { arch: S!("noarch") }]
+    );
+
+    let apkbuild = Apkbuild {
+        arch_raw: S!("noarch"),
+        ..apkbuild
+    };
+    assert!(check_pkginfo(&apkbuild, &pkginfo).is_empty());
+
+    let apkbuild = Apkbuild {
+        arch_raw: S!("x86_64\n\tnoarch"),
+        ..apkbuild
+    };
+    assert!(check_pkginfo(&apkbuild, &pkginfo).is_empty());
+}
+
+#[test]
+fn check_pkginfo_noarch_subpackage() {
+    let apkbuild = Apkbuild {
+        arch_raw: S!("all"),
+        ..sample_apkbuild()
+    };
+    let pkginfo = PkgInfo {
+        pkgname: S!("sample-doc"),
+        pkgver: S!("1.2.3-r2"),
+        arch: S!("noarch"),
+        ..Default::default()
+    };
+
+    assert!(check_pkginfo(&apkbuild, &pkginfo).is_empty());
+}
+
+#[test]
+fn check_pkginfo_subpackage() {
+    let apkbuild = sample_apkbuild();
+    let pkginfo = PkgInfo {
+        pkgname: S!("sample-dev"),
+        pkgver: S!("1.2.3-r2"),
+        arch: S!("x86_64"),
+        license: S!("MIT"),
+        depends: vec![dependency("zlib-dev")],
+        ..Default::default()
+    };
+
+    assert!(
+        check_pkginfo(&apkbuild, &pkginfo)
+            == vec![Divergence::MissingDepends {
+                dependency: S!("openssl-dev>3"),
+            }]
+    );
+}
+
+#[test]
+fn check_pkginfo_unknown_package() {
+    let pkginfo = PkgInfo {
+        pkgname: S!("foo"),
+        ..Default::default()
+    };
+
+    assert!(
+        check_pkginfo(&sample_apkbuild(), &pkginfo)
+            == vec![Divergence::UnknownPackage {
+                pkgname: S!("foo"),
+                origin: S!("sample"),
+            }]
+    );
+}
+
+#[test]
+fn check_commit_mismatch() {
+    let commit = "c57128b0e49d551220aff88af0f1487d80cdccf8";
+    let mut pkginfo = PkgInfo {
+        commit: Some(S!(commit)),
+        ..Default::default()
+    };
+    assert!(check_commit(&pkginfo, commit).is_none());
+
+    pkginfo.commit = None;
+    assert!(
+        check_commit(&pkginfo, commit)
+            == Some(Divergence::CommitMismatch {
+                expected: S!(commit),
+                actual: S!(""),
+            })
+    );
+}
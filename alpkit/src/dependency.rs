@@ -1,3 +1,4 @@
+use std::cmp::Ordering;
 use std::fmt::{self, Write};
 use std::str::FromStr;
 
@@ -6,6 +7,7 @@ use serde::de::{self, Deserialize};
 use thiserror::Error;
 
 use crate::internal::{key_value_vec_map::KeyValueLike, macros::bail};
+use crate::version;
 
 ////////////////////////////////////////////////////////////////////////////////
 
@@ -14,7 +16,7 @@ use crate::internal::{key_value_vec_map::KeyValueLike, macros::bail};
 pub struct ConstraintParseError(String);
 
 /// A dependency (or conflict) on a package or provider.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Dependency {
     /// Package or provider name.
     pub name: String,
@@ -142,7 +144,7 @@ impl<'de> Deserialize<'de> for Dependency {
 ////////////////////////////////////////////////////////////////////////////////
 
 /// A version constraint.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Constraint {
     pub op: Op,
     pub version: String,
@@ -155,6 +157,33 @@ impl Constraint {
             version: version.to_string(),
         }
     }
+
+    /// Returns `true` if the given package version satisfies this constraint.
+    /// The versions are compared as by apk (see [`version::compare`]).
+    ///
+    /// Example:
+    /// ```
+    /// use alpkit::dependency::Constraint;
+    ///
+    /// let constraint: Constraint = ">=1.2".parse().unwrap();
+    /// assert!(constraint.matches("1.10-r0"));
+    /// assert!(!constraint.matches("1.2_rc1-r0"));
+    /// ```
+    pub fn matches(&self, version: &str) -> bool {
+        if self.op.is_all() {
+            return true;
+        }
+        let ord = if self.op.contains(Op::Fuzzy) && is_fuzzy_equal(version, &self.version) {
+            Op::Equal
+        } else {
+            match version::compare(version, &self.version) {
+                Ordering::Less => Op::Less,
+                Ordering::Equal => Op::Equal,
+                Ordering::Greater => Op::Greater,
+            }
+        };
+        self.op.intersects(ord)
+    }
 }
 
 impl FromStr for Constraint {
@@ -256,6 +285,14 @@ impl fmt::Display for Op {
 
 ////////////////////////////////////////////////////////////////////////////////
 
+/// Returns `true` if the `version` starts with the `prefix` version, e.g.
+/// `1.2.3-r0` with `1.2`, but not `1.20`.
+fn is_fuzzy_equal(version: &str, prefix: &str) -> bool {
+    version.strip_prefix(prefix).map_or(false, |rest| {
+        !rest.starts_with(|c: char| c.is_ascii_digit())
+    })
+}
+
 #[inline]
 fn is_op(s: char) -> bool {
     matches!(s, '<' | '>' | '=' | '~')
@@ -35,7 +35,7 @@ fn op_from_str_invalid() {
 #[test]
 #[rustfmt::skip]
 fn constraint_from_str_and_display() {
-    for (s       , constraint) in vec![
+    for (s       , constraint) in [
         ("=1.2.3", Constraint::new(Op::Equal, "1.2.3")            ),
         (">=1.2" , Constraint::new(Op::Greater | Op::Equal, "1.2")),
     ] {
@@ -53,6 +53,30 @@ fn constraint_from_str_invalid() {
     }
 }
 
+#[test]
+#[rustfmt::skip]
+fn constraint_matches() {
+    for (constraint, version     , expected) in [
+        ("=1.2.3"  , "1.2.3"     , true ),
+        ("=1.2.3"  , "1.2.3-r0"  , true ),
+        ("=1.2.3"  , "1.2.3-r1"  , false),
+        (">=1.2"   , "1.10"      , true ),
+        (">=1.2"   , "1.2_rc1"   , false),
+        ("<1.2"    , "1.2_rc1"   , true ),
+        (">1.2"    , "1.2"       , false),
+        ("<=1.2"   , "1.2"       , true ),
+        ("~1.2"    , "1.2.3-r0"  , true ),
+        ("~1.2"    , "1.2_p1"    , true ),
+        ("~1.2"    , "1.20"      , false),
+        ("~1.2"    , "1.3"       , false),
+        (">~1.2"   , "1.3"       , true ),
+        ("><1.2"   , "1.3"       , true ),
+    ] {
+        let c = Constraint::from_str(constraint).unwrap();
+        assert!(c.matches(version) == expected, "{constraint} {version}");
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 
 #[test]
@@ -66,7 +90,7 @@ fn dependency_key_value() {
         repo_pin: None,
     };
 
-    for (kv                         , constraint) in vec![
+    for (kv                         , constraint) in [
         (("foo-doc", S!("*"))       , Dependency::new("foo-doc", None)                                           ),
         (("foo-doc", S!("= 1.2.3")) , Dependency::new("foo-doc", Some(Constraint::new(Op::Equal, "1.2.3")))      ),
         (("foo"    , S!("<= 1.2"))  , Dependency::new("foo", Some(Constraint::new(Op::Less | Op::Equal, "1.2"))) ),
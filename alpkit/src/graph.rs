@@ -0,0 +1,176 @@
+//! A dependency graph of APKBUILDs, e.g. of the aports tree.
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io::{self, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::apkbuild::Apkbuild;
+use crate::dependency::Dependency;
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// A graph of dependencies between APKBUILDs. The nodes are the APKBUILDs
+/// (their `pkgname`, i.e. origin) and the dependencies are resolved to the
+/// APKBUILDs that build the package or provide the dependency name.
+/// Dependencies that are not provided by any of the APKBUILDs (e.g.
+/// autodiscovered `so:` and `cmd:` providers) are ignored.
+///
+/// Example:
+/// ```no_run
+/// use alpkit::apkbuild::{find_apkbuilds, ApkbuildReader};
+/// use alpkit::graph::DepGraph;
+///
+/// let reader = ApkbuildReader::new();
+/// let mut graph = DepGraph::new();
+///
+/// for path in find_apkbuilds("aports/main").unwrap() {
+///     graph.add_apkbuild(&reader.read_apkbuild(path).unwrap());
+/// }
+/// graph.write_dot(&mut std::io::stdout()).unwrap();
+/// ```
+#[derive(Debug, Default)]
+pub struct DepGraph {
+    /// Unresolved dependency names of the APKBUILDs by origin.
+    deps: BTreeMap<String, Node>,
+
+    /// Origins of the packages (main packages and subpackages).
+    packages: HashMap<String, String>,
+
+    /// Origins of the provided names.
+    providers: HashMap<String, BTreeSet<String>>,
+}
+
+/// Dependencies of an APKBUILD: names of the APKBUILDs (origins) it depends
+/// on, by the kind of the dependency.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Node {
+    /// Runtime dependencies (`depends` and `depends_<subpkg>`).
+    pub depends: BTreeSet<String>,
+
+    /// Build-time dependencies (`makedepends*`).
+    pub makedepends: BTreeSet<String>,
+
+    /// Dependencies for running tests (`checkdepends`).
+    pub checkdepends: BTreeSet<String>,
+}
+
+impl DepGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the given APKBUILD to the graph. If an APKBUILD with the same
+    /// `pkgname` already exists, it's replaced.
+    pub fn add_apkbuild(&mut self, apkbuild: &Apkbuild) -> &mut Self {
+        let origin = &apkbuild.pkgname;
+        let names = |deps: &mut dyn Iterator<Item = &Dependency>| {
+            deps.filter(|d| !d.conflict)
+                .map(|d| d.name.clone())
+                .collect::<BTreeSet<_>>()
+        };
+
+        let node = Node {
+            depends: names(
+                &mut apkbuild
+                    .depends
+                    .iter()
+                    .chain(apkbuild.subpackage_depends.values().flatten()),
+            ),
+            makedepends: names(
+                &mut apkbuild
+                    .makedepends
+                    .iter()
+                    .chain(&apkbuild.makedepends_build)
+                    .chain(&apkbuild.makedepends_host),
+            ),
+            checkdepends: names(&mut apkbuild.checkdepends.iter()),
+        };
+        self.deps.insert(origin.clone(), node);
+
+        for name in std::iter::once(origin).chain(&apkbuild.subpackages) {
+            self.packages.insert(name.clone(), origin.clone());
+        }
+        for provider in &apkbuild.provides {
+            self.providers
+                .entry(provider.name.clone())
+                .or_default()
+                .insert(origin.clone());
+        }
+        self
+    }
+
+    /// Resolves the dependencies and returns the adjacency lists of the
+    /// graph, sorted by the origin.
+    pub fn adjacency_lists(&self) -> BTreeMap<&str, Node> {
+        self.deps
+            .iter()
+            .map(|(origin, deps)| {
+                let resolve = |names: &BTreeSet<String>| {
+                    names
+                        .iter()
+                        .flat_map(|name| self.resolve(name))
+                        .filter(|dep| *dep != origin)
+                        .cloned()
+                        .collect()
+                };
+                let node = Node {
+                    depends: resolve(&deps.depends),
+                    makedepends: resolve(&deps.makedepends),
+                    checkdepends: resolve(&deps.checkdepends),
+                };
+                (origin.as_str(), node)
+            })
+            .collect()
+    }
+
+    /// Writes the graph in the Graphviz DOT format. Build-time dependencies
+    /// are drawn with dashed lines and check dependencies with dotted lines,
+    /// unless they are also runtime (or build-time) dependencies.
+    pub fn write_dot<W: Write + ?Sized>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(out, "digraph aports {{")?;
+
+        for (origin, node) in self.adjacency_lists() {
+            writeln!(out, "\t{};", dot_quote(origin))?;
+
+            let edges = node
+                .depends
+                .iter()
+                .map(|dep| (dep, ""))
+                .chain(
+                    node.makedepends
+                        .difference(&node.depends)
+                        .map(|dep| (dep, " [style=dashed]")),
+                )
+                .chain(
+                    node.checkdepends
+                        .iter()
+                        .filter(|dep| !node.depends.contains(*dep))
+                        .filter(|dep| !node.makedepends.contains(*dep))
+                        .map(|dep| (dep, " [style=dotted]")),
+                );
+            for (dep, attrs) in edges {
+                writeln!(out, "\t{} -> {}{attrs};", dot_quote(origin), dot_quote(dep))?;
+            }
+        }
+        writeln!(out, "}}")
+    }
+
+    /// Returns the origins of the given package or provider name.
+    fn resolve<'a>(&'a self, name: &str) -> Box<dyn Iterator<Item = &'a String> + 'a> {
+        match (self.packages.get(name), self.providers.get(name)) {
+            (Some(origin), _) => Box::new(std::iter::once(origin)),
+            (None, Some(origins)) => Box::new(origins.iter()),
+            (None, None) => Box::new(std::iter::empty()),
+        }
+    }
+}
+
+fn dot_quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+#[path = "graph.test.rs"]
+mod test;
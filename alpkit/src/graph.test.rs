@@ -0,0 +1,84 @@
+use indoc::indoc;
+
+use super::*;
+use crate::internal::test_utils::{assert, dependency, S};
+
+fn apkbuild(pkgname: &str, depends: &[&str], makedepends: &[&str]) -> Apkbuild {
+    Apkbuild {
+        pkgname: S!(pkgname),
+        depends: depends.iter().map(|s| dependency(s)).collect(),
+        makedepends: makedepends.iter().map(|s| dependency(s)).collect(),
+        ..Default::default()
+    }
+}
+
+fn sample_graph() -> DepGraph {
+    let mut graph = DepGraph::new();
+    graph
+        .add_apkbuild(&Apkbuild {
+            subpackages: vec![S!("openssl-dev"), S!("libssl3")],
+            ..apkbuild("openssl", &[], &["perl"])
+        })
+        .add_apkbuild(&Apkbuild {
+            provides: vec![dependency("ssh-server")],
+            checkdepends: vec![dependency("perl")],
+            ..apkbuild("dropbear", &["so:libc.musl-x86_64.so.1"], &["zlib-dev"])
+        })
+        .add_apkbuild(&apkbuild(
+            "rssh",
+            &["ssh-server", "!foo", "openssl"],
+            &["openssl-dev", "perl"],
+        ))
+        .add_apkbuild(&apkbuild("perl", &[], &[]));
+    graph
+}
+
+#[test]
+fn dep_graph_adjacency_lists() {
+    let graph = sample_graph();
+    let lists = graph.adjacency_lists();
+
+    assert!(
+        lists.keys().copied().collect::<Vec<_>>() == vec!["dropbear", "openssl", "perl", "rssh"]
+    );
+    assert!(
+        lists["rssh"]
+            == Node {
+                depends: BTreeSet::from([S!("dropbear"), S!("openssl")]),
+                makedepends: BTreeSet::from([S!("openssl"), S!("perl")]),
+                checkdepends: BTreeSet::new(),
+            }
+    );
+    // so: and zlib-dev are not provided by any APKBUILD.
+    assert!(
+        lists["dropbear"]
+            == Node {
+                checkdepends: BTreeSet::from([S!("perl")]),
+                ..Node::default()
+            }
+    );
+    assert!(lists["perl"] == Node::default());
+}
+
+#[test]
+fn dep_graph_write_dot() {
+    let mut out = Vec::new();
+    sample_graph().write_dot(&mut out).unwrap();
+
+    assert!(
+        String::from_utf8(out).unwrap()
+            == indoc! {r#"
+                digraph aports {
+                	"dropbear";
+                	"dropbear" -> "perl" [style=dotted];
+                	"openssl";
+                	"openssl" -> "perl" [style=dashed];
+                	"perl";
+                	"rssh";
+                	"rssh" -> "dropbear";
+                	"rssh" -> "openssl";
+                	"rssh" -> "perl" [style=dashed];
+                }
+            "#}
+    );
+}
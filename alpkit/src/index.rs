@@ -0,0 +1,408 @@
+//! Reading of the repository index (`APKINDEX`).
+use std::collections::HashSet;
+use std::io::{self, BufRead, Read};
+#[cfg(feature = "signature")]
+use std::path::Path;
+
+use flate2::bufread::GzDecoder;
+use log::{debug, trace};
+use serde::{Deserialize, Serialize};
+use tar::Archive;
+use thiserror::Error;
+
+use crate::dependency::Dependency;
+use crate::internal::key_value_vec_map;
+use crate::internal::macros::bail;
+use crate::internal::serde_key_value;
+use crate::internal::tee_reader::TeeReader;
+#[cfg(feature = "signature")]
+use crate::keys::KeyRing;
+use crate::package::SignatureInfo;
+#[cfg(feature = "signature")]
+use crate::package::{read_public_key, verify_signatures, SignatureError};
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, Error)]
+pub enum IndexError {
+    #[error("invalid entry #{0}")]
+    Decode(usize, #[source] serde_key_value::Error),
+
+    #[error("I/O error occurred")]
+    Io(#[from] io::Error),
+
+    #[error("no APKINDEX found in the archive")]
+    MissingApkindex,
+
+    #[error("syntax error on line {0}: missing ':' in '{1}'")]
+    Syntax(usize, String),
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// A repository index (`APKINDEX`).
+#[derive(Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct Index {
+    /// The repository description (content of the `DESCRIPTION` file), e.g.
+    /// `v3.19.0-1-gabcdef`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// The packages in the repository.
+    pub entries: Vec<IndexEntry>,
+
+    /// Signatures of the index with the contents of the signature files.
+    #[serde(skip)]
+    signs: Vec<(SignatureInfo, Vec<u8>)>,
+
+    /// The raw (compressed) index segment, i.e. the signed data.
+    #[serde(skip)]
+    #[cfg_attr(not(feature = "signature"), allow(dead_code))]
+    signed_data: Vec<u8>,
+}
+
+impl Index {
+    /// Loads an `Index` from the given buffered reader over an
+    /// `APKINDEX.tar.gz` file (signed or unsigned).
+    ///
+    /// Example:
+    /// ```no_run
+    /// # use std::fs::File;
+    /// # use std::io::BufReader;
+    /// use alpkit::index::Index;
+    ///
+    /// let file = File::open("APKINDEX.tar.gz").map(BufReader::new).unwrap();
+    /// let index = Index::load(file).unwrap();
+    /// ```
+    pub fn load<R: BufRead>(mut reader: R) -> Result<Self, IndexError> {
+        // The signed index consists of two concatenated gzip streams - the
+        // signature and the index segment, the unsigned one only of the latter.
+        let mut segment = Segment::default();
+        Self::read_segment(&mut reader, &mut segment)?;
+
+        let mut signed_data = Vec::new();
+        if !segment.signs.is_empty() && segment.apkindex.is_none() {
+            let mut reader = TeeReader::new(reader, Vec::new());
+            Self::read_segment(&mut reader, &mut segment)?;
+            signed_data = reader.into_writer();
+        }
+
+        match segment.apkindex {
+            Some(apkindex) => Ok(Index {
+                description: segment.description,
+                signs: segment.signs,
+                signed_data,
+                ..Index::parse(&apkindex)?
+            }),
+            None => bail!(IndexError::MissingApkindex),
+        }
+    }
+
+    /// Parses the given (uncompressed) `APKINDEX` file contents.
+    pub fn parse(s: &str) -> Result<Self, IndexError> {
+        let mut entries = Vec::new();
+        let mut pairs = Vec::with_capacity(32);
+
+        for (lno, line) in s.lines().chain(std::iter::once("")).enumerate() {
+            if line.is_empty() {
+                if !pairs.is_empty() {
+                    let entry: IndexEntry = serde_key_value::from_pairs(std::mem::take(&mut pairs))
+                        .map_err(|e| IndexError::Decode(entries.len() + 1, e))?;
+                    trace!("parsed entry {}-{}", entry.pkgname, entry.pkgver);
+                    entries.push(entry);
+                }
+                continue;
+            }
+            let (key, val) = line
+                .split_once(':')
+                .ok_or_else(|| IndexError::Syntax(lno + 1, line.to_owned()))?;
+
+            push_field(&mut pairs, key, val);
+        }
+
+        debug!("parsed {} index entries", entries.len());
+
+        Ok(Index {
+            description: None,
+            entries,
+            ..Index::default()
+        })
+    }
+
+    /// Returns the signatures of the index, if it has been loaded via
+    /// [`Index::load`] from a signed `APKINDEX.tar.gz`.
+    pub fn signatures(&self) -> impl Iterator<Item = &SignatureInfo> {
+        self.signs.iter().map(|(sign, _)| sign)
+    }
+
+    /// Verifies the index signatures using the RSA public keys in the given
+    /// directory (e.g. `/etc/apk/keys`). Returns the first signature that is
+    /// valid, or `None` if none of the signatures could be verified (see
+    /// [`Package::verify_signature`][crate::package::Package::verify_signature]).
+    #[cfg(feature = "signature")]
+    pub fn verify_signature<P: AsRef<Path>>(
+        &self,
+        keys_dir: P,
+    ) -> Result<Option<&SignatureInfo>, SignatureError> {
+        let keys_dir = keys_dir.as_ref();
+
+        verify_signatures(
+            self.signs.iter().map(|(sign, content)| (sign, content)),
+            &self.signed_data,
+            |keyname| read_public_key(&keys_dir.join(keyname)),
+        )
+    }
+
+    /// Verifies the index signatures using the RSA public keys in the given
+    /// key ring. See [`Index::verify_signature`].
+    #[cfg(feature = "signature")]
+    pub fn verify_signature_with(
+        &self,
+        keys: &KeyRing,
+    ) -> Result<Option<&SignatureInfo>, SignatureError> {
+        verify_signatures(
+            self.signs.iter().map(|(sign, content)| (sign, content)),
+            &self.signed_data,
+            |keyname| Ok(keys.get(keyname).cloned()),
+        )
+    }
+
+    /// Returns an iterator over the entries that satisfy the given dependency,
+    /// i.e. packages with the dependency name and a matching version, or
+    /// packages providing it. Unversioned providers satisfy only dependencies
+    /// without a version constraint (as in apk).
+    ///
+    /// Example:
+    /// ```
+    /// use alpkit::index::Index;
+    ///
+    /// # let index = Index::default();
+    /// let dep = "so:libc.musl-x86_64.so.1".parse().unwrap();
+    /// for entry in index.find(&dep) {
+    ///     println!("{}-{}", entry.pkgname, entry.pkgver);
+    /// }
+    /// ```
+    pub fn find<'a>(&'a self, dep: &'a Dependency) -> impl Iterator<Item = &'a IndexEntry> {
+        self.entries
+            .iter()
+            .filter(move |entry| entry.satisfies(dep))
+    }
+
+    /// Returns an iterator over the entries that depend on any package
+    /// satisfying the given dependency (see [`Index::find`]), either directly
+    /// by its name or via anything it provides, or on the dependency name
+    /// itself.
+    pub fn reverse_depends<'a>(
+        &'a self,
+        dep: &'a Dependency,
+    ) -> impl Iterator<Item = &'a IndexEntry> {
+        let mut names: HashSet<&str> = HashSet::new();
+        names.insert(&dep.name);
+
+        for entry in self.find(dep) {
+            names.insert(&entry.pkgname);
+            names.extend(entry.provides.iter().map(|p| p.name.as_str()));
+        }
+
+        self.entries.iter().filter(move |entry| {
+            entry
+                .depends
+                .iter()
+                .any(|d| names.contains(d.name.as_str()))
+        })
+    }
+
+    /// Reads the entries of a single gzip-compressed tar segment of the
+    /// `APKINDEX.tar.gz` into `segment`.
+    fn read_segment<R: BufRead>(reader: R, segment: &mut Segment) -> Result<(), IndexError> {
+        debug!("reading index segment");
+        let mut archive = Archive::new(GzDecoder::new(reader));
+
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            trace!("reading entry {}", entry.path()?.display());
+
+            match entry.path_bytes().as_ref() {
+                b"DESCRIPTION" => {
+                    let mut buf = String::new();
+                    entry.read_to_string(&mut buf)?;
+                    segment.description = Some(buf.trim_end().to_owned());
+                }
+                b"APKINDEX" => {
+                    let mut buf = String::new();
+                    entry.read_to_string(&mut buf)?;
+                    segment.apkindex = Some(buf);
+                }
+                _ => {
+                    if let Some(sign) = SignatureInfo::from_filename(&entry.path()?) {
+                        let mut buf = Vec::new();
+                        entry.read_to_end(&mut buf)?;
+                        segment.signs.push((sign, buf));
+                    }
+                }
+            }
+        }
+        // Read the rest of the segment (e.g. the gzip trailer), if any.
+        io::copy(&mut archive.into_inner(), &mut io::sink())?;
+
+        Ok(())
+    }
+}
+
+/// Contents of the `APKINDEX.tar.gz` segments read so far.
+#[derive(Default)]
+struct Segment {
+    description: Option<String>,
+    apkindex: Option<String>,
+    signs: Vec<(SignatureInfo, Vec<u8>)>,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// A package entry in the repository index.
+#[derive(Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct IndexEntry {
+    /// The SHA-1 checksum of the package's control segment, encoded in base64
+    /// and prefixed with `Q1`.
+    pub checksum: String,
+
+    /// The name and email address of the package's maintainer.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maintainer: Option<String>,
+
+    /// The package name.
+    pub pkgname: String,
+
+    /// A full version of the package (including the release number `-r<n>`).
+    pub pkgver: String,
+
+    /// A brief, one-line description of the package.
+    pub pkgdesc: String,
+
+    /// The homepage of the packaged software.
+    pub url: String,
+
+    /// The architecture of the package (e.g.: `x86_64`).
+    pub arch: String,
+
+    /// License(s) of the source code from which the package was built.
+    pub license: String,
+
+    /// Dependencies of this package (without conflicts).
+    #[serde(default, with = "key_value_vec_map")]
+    pub depends: Vec<Dependency>,
+
+    /// Conflicts of this package (“anti-dependencies” extracted from `D:`).
+    #[serde(default, with = "key_value_vec_map")]
+    pub conflicts: Vec<Dependency>,
+
+    /// A set of dependencies that, if all installed, induce installation of
+    /// this package.
+    #[serde(default, with = "key_value_vec_map")]
+    pub install_if: Vec<Dependency>,
+
+    /// Providers (packages) that this package provides.
+    #[serde(default, with = "key_value_vec_map")]
+    pub provides: Vec<Dependency>,
+
+    /// A priority used to choose a provider of a virtual package.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider_priority: Option<u16>,
+
+    /// Packages whose files this package is allowed to overwrite.
+    #[serde(default, with = "key_value_vec_map")]
+    pub replaces: Vec<Dependency>,
+
+    /// The priority of the `replaces`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replaces_priority: Option<u16>,
+
+    /// The name of the APKBUILD (its main package) from which the package was built.
+    pub origin: String,
+
+    /// The SHA-1 hash of the git commit from which the package was built.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+
+    /// An unix timestamp of the package build date/time.
+    pub builddate: i64,
+
+    /// The size of the package file (`.apk`) in bytes.
+    pub size: u64,
+
+    /// The installed-size of the package in bytes.
+    pub installed_size: u64,
+}
+
+impl IndexEntry {
+    /// Returns `true` if this package satisfies the given dependency (ignoring
+    /// the `conflict` flag).
+    pub fn satisfies(&self, dep: &Dependency) -> bool {
+        if self.pkgname == dep.name {
+            return dep
+                .constraint
+                .as_ref()
+                .map_or(true, |c| c.matches(&self.pkgver));
+        }
+        self.provides
+            .iter()
+            .filter(|p| p.name == dep.name)
+            .any(|p| match (&dep.constraint, &p.constraint) {
+                (None, _) => true,
+                (Some(c), Some(pc)) => c.matches(&pc.version),
+                (Some(_), None) => false,
+            })
+    }
+}
+
+/// Pushes the `APKINDEX` field into `pairs` for deserializing [`IndexEntry`],
+/// i.e. maps the key to the field name and splits the list values.
+pub(crate) fn push_field<'a>(pairs: &mut Vec<(&'a str, &'a str)>, key: &'a str, val: &'a str) {
+    match key {
+        "D" => {
+            for word in val.split_ascii_whitespace() {
+                pairs.push(match word.strip_prefix('!') {
+                    Some(word) => ("conflicts", word),
+                    None => ("depends", word),
+                });
+            }
+        }
+        "i" | "p" | "r" => {
+            for word in val.split_ascii_whitespace() {
+                pairs.push((field_name(key), word));
+            }
+        }
+        _ => pairs.push((field_name(key), val)),
+    }
+}
+
+/// Maps the `APKINDEX` field key to the field name of [`IndexEntry`].
+fn field_name(key: &str) -> &str {
+    match key {
+        "C" => "checksum",
+        "P" => "pkgname",
+        "V" => "pkgver",
+        "A" => "arch",
+        "S" => "size",
+        "I" => "installed_size",
+        "T" => "pkgdesc",
+        "U" => "url",
+        "L" => "license",
+        "o" => "origin",
+        "m" => "maintainer",
+        "t" => "builddate",
+        "c" => "commit",
+        "i" => "install_if",
+        "p" => "provides",
+        "k" => "provider_priority",
+        "r" => "replaces",
+        "q" => "replaces_priority",
+        key => key,
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+#[path = "index.test.rs"]
+mod test;
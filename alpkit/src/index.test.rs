@@ -0,0 +1,114 @@
+use std::fs::{self, File};
+use std::io::BufReader;
+
+use super::*;
+use crate::internal::test_utils::{assert, assert_let, dependency, S};
+
+fn read_index() -> Index {
+    Index::parse(&fs::read_to_string("../fixtures/apkindex/APKINDEX").unwrap()).unwrap()
+}
+
+fn pkgnames<'a, I: Iterator<Item = &'a IndexEntry>>(entries: I) -> Vec<&'a str> {
+    entries.map(|e| e.pkgname.as_str()).collect()
+}
+
+#[test]
+fn index_parse() {
+    let index = read_index();
+
+    assert!(index.entries.len() == 4);
+    assert!(
+        index.entries[3]
+            == IndexEntry {
+                checksum: S!("Q1uP0O4AmJ5dR7jD2fVQqdM+hwQ6g="),
+                maintainer: Some(S!("Natanael Copa <ncopa@alpinelinux.org>")),
+                pkgname: S!("dropbear"),
+                pkgver: S!("2022.83-r4"),
+                pkgdesc: S!("Small SSH 2 client/server designed for small memory environments"),
+                url: S!("https://matt.ucc.asn.au/dropbear/dropbear.html"),
+                arch: S!("x86_64"),
+                license: S!("MIT"),
+                depends: vec![
+                    dependency("so:libc.musl-x86_64.so.1"),
+                    dependency("so:libutmps.so.0.1"),
+                ],
+                conflicts: vec![dependency("openssh-server")],
+                provides: vec![
+                    dependency("cmd:dropbear=2022.83-r4"),
+                    dependency("ssh-server")
+                ],
+                provider_priority: Some(10),
+                origin: S!("dropbear"),
+                commit: Some(S!("b1d1a0ab5d8c2e9f7a4d1e3c9f0a2b6c8d7e4f1a")),
+                builddate: 1700502213,
+                size: 96346,
+                installed_size: 229376,
+                ..Default::default()
+            }
+    );
+    assert!(index.entries[2].install_if == vec![dependency("docs"), dependency("rssh=2.3.4-r3")]);
+}
+
+#[test]
+fn index_parse_invalid() {
+    assert_let!(Err(IndexError::Syntax(2, _)) = Index::parse("P:foo\nfoo\n"));
+    assert_let!(Err(IndexError::Decode(1, _)) = Index::parse("P:foo\nt:yesterday\n"));
+}
+
+#[test]
+fn index_load() {
+    let file = File::open("../fixtures/apkindex/APKINDEX.tar.gz").unwrap();
+
+    assert_let!(Ok(index) = Index::load(BufReader::new(file)));
+    assert!(index.description == Some(S!("v3.19.0-1-gabcdef")));
+    assert!(index.entries == read_index().entries);
+}
+
+#[test]
+fn index_load_signatures() {
+    let file = File::open("../fixtures/apkindex/APKINDEX.tar.gz").unwrap();
+    let index = Index::load(BufReader::new(file)).unwrap();
+
+    assert!(
+        index.signatures().collect::<Vec<_>>()
+            == vec![&SignatureInfo {
+                alg: S!("RSA"),
+                keyname: S!("test@example.org-61234567.rsa.pub"),
+            }]
+    );
+    assert!(index.signed_data.len() == 953);
+    assert!(index.entries.len() == 4);
+
+    assert!(read_index().signatures().next() == None);
+}
+
+#[test]
+#[rustfmt::skip]
+fn index_find() {
+    let index = read_index();
+
+    for (dep                         , expected) in [
+        ("rssh"                      , vec!["rssh"]),
+        ("rssh>2.3.4-r3"             , vec![]),
+        ("rssh~2.3"                  , vec!["rssh"]),
+        ("cmd:sh"                    , vec!["busybox"]),
+        ("cmd:busybox>=1.36"         , vec!["busybox"]),
+        ("ssh-server"                , vec!["dropbear"]),
+        ("ssh-server>=1"             , vec![]),
+        ("so:libc.musl-x86_64.so.1"  , vec![]),
+    ] {
+        assert!(pkgnames(index.find(&dependency(dep))) == expected, "{dep}");
+    }
+}
+
+#[test]
+fn index_reverse_depends() {
+    let index = read_index();
+
+    assert!(
+        pkgnames(index.reverse_depends(&dependency("so:libc.musl-x86_64.so.1")))
+            == vec!["busybox", "rssh", "dropbear"]
+    );
+    assert!(pkgnames(index.reverse_depends(&dependency("busybox"))) == Vec::<&str>::new());
+    assert!(pkgnames(index.reverse_depends(&dependency("openssh"))) == vec!["rssh"]);
+}
@@ -0,0 +1,204 @@
+//! Reading of the database of installed packages (`/lib/apk/db/installed`).
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use log::{debug, trace};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::index::{push_field, IndexEntry};
+use crate::internal::macros::bail;
+use crate::internal::serde_key_value;
+use crate::package::{deserialize_mode, serialize_mode};
+
+/// The path of the installed database relative to the root directory.
+pub const INSTALLED_DB_PATH: &str = "lib/apk/db/installed";
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, Error)]
+pub enum InstalledDbError {
+    #[error("invalid entry #{0}")]
+    Decode(usize, #[source] serde_key_value::Error),
+
+    #[error("invalid line {0}: '{1}'")]
+    InvalidLine(usize, String),
+
+    #[error("I/O error occurred")]
+    Io(#[from] io::Error),
+
+    #[error("syntax error on line {0}: missing ':' in '{1}'")]
+    Syntax(usize, String),
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// The database of installed packages (`/lib/apk/db/installed`).
+#[derive(Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct InstalledDb {
+    /// The installed packages.
+    pub packages: Vec<InstalledPackage>,
+}
+
+impl InstalledDb {
+    /// Loads an `InstalledDb` from the given reader over the `installed` file.
+    ///
+    /// Example:
+    /// ```no_run
+    /// # use std::fs::File;
+    /// use std::path::Path;
+    /// use alpkit::installed::{InstalledDb, INSTALLED_DB_PATH};
+    ///
+    /// let file = File::open(Path::new("/").join(INSTALLED_DB_PATH)).unwrap();
+    /// let db = InstalledDb::load(file).unwrap();
+    /// ```
+    pub fn load<R: Read>(mut reader: R) -> Result<Self, InstalledDbError> {
+        let mut buf = String::new();
+        reader.read_to_string(&mut buf)?;
+
+        Self::parse(&buf)
+    }
+
+    /// Parses the given contents of the `installed` file.
+    pub fn parse(s: &str) -> Result<Self, InstalledDbError> {
+        let mut packages = Vec::new();
+        let mut pairs = Vec::with_capacity(32);
+        let mut files: Vec<InstalledFile> = Vec::new();
+        // The current directory (`F:`) and whether the current file (`R:`) is
+        // in it, i.e. if the file attributes can follow.
+        let mut dir: Option<&str> = None;
+        let mut in_file = false;
+
+        for (lno, line) in s.lines().chain(std::iter::once("")).enumerate() {
+            if line.is_empty() {
+                if !pairs.is_empty() {
+                    let entry: IndexEntry = serde_key_value::from_pairs(std::mem::take(&mut pairs))
+                        .map_err(|e| InstalledDbError::Decode(packages.len() + 1, e))?;
+                    trace!("parsed package {}-{}", entry.pkgname, entry.pkgver);
+                    packages.push(InstalledPackage {
+                        entry,
+                        files: std::mem::take(&mut files),
+                    });
+                }
+                dir = None;
+                in_file = false;
+                continue;
+            }
+            let (key, val) = line
+                .split_once(':')
+                .ok_or_else(|| InstalledDbError::Syntax(lno + 1, line.to_owned()))?;
+
+            let invalid_line = || InstalledDbError::InvalidLine(lno + 1, line.to_owned());
+
+            match key {
+                "F" => {
+                    dir = Some(val);
+                    in_file = false;
+                }
+                // Attributes of the directory.
+                "M" if dir.is_some() => (),
+                "R" => match dir {
+                    Some(dir) => {
+                        files.push(InstalledFile::new(Path::new("/").join(dir).join(val)));
+                        in_file = true;
+                    }
+                    None => bail!(invalid_line()),
+                },
+                "a" if in_file => {
+                    let file = files.last_mut().unwrap();
+                    let mut attrs = val.splitn(4, ':');
+
+                    match (attrs.next(), attrs.next(), attrs.next()) {
+                        (Some(uid), Some(gid), Some(mode)) => {
+                            file.uid = uid.parse().map_err(|_| invalid_line())?;
+                            file.gid = gid.parse().map_err(|_| invalid_line())?;
+                            file.mode = u32::from_str_radix(mode, 8).map_err(|_| invalid_line())?;
+                        }
+                        _ => bail!(invalid_line()),
+                    }
+                }
+                "Z" if in_file => {
+                    files.last_mut().unwrap().checksum = Some(val.to_owned());
+                }
+                "M" | "a" | "Z" => bail!(invalid_line()),
+                _ => push_field(&mut pairs, key, val),
+            }
+        }
+
+        debug!("parsed {} installed packages", packages.len());
+
+        Ok(InstalledDb { packages })
+    }
+
+    /// Returns the installed package that owns the file at the given path
+    /// (absolute, or relative to the root directory), if any. This is an
+    /// equivalent of `apk info --who-owns`, but without resolving symlinks.
+    pub fn owner<P: AsRef<Path>>(&self, path: P) -> Option<&InstalledPackage> {
+        let path = Path::new("/").join(path);
+
+        self.packages
+            .iter()
+            .find(|pkg| pkg.files.iter().any(|f| f.path == path))
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// A package entry in the installed database.
+#[derive(Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct InstalledPackage {
+    /// The package metadata, the same as in the repository index.
+    #[serde(flatten)]
+    pub entry: IndexEntry,
+
+    /// Files owned by the package (directories are not included).
+    #[serde(default)]
+    pub files: Vec<InstalledFile>,
+}
+
+/// A file owned by an installed package.
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct InstalledFile {
+    /// An absolute path of the file.
+    pub path: PathBuf,
+
+    /// The ID of the user who owns the file.
+    #[serde(default)]
+    pub uid: u32,
+
+    /// The ID of the group that owns the file.
+    #[serde(default)]
+    pub gid: u32,
+
+    /// The file mode bits (permissions).
+    #[serde(
+        deserialize_with = "deserialize_mode",
+        serialize_with = "serialize_mode"
+    )]
+    pub mode: u32,
+
+    /// The SHA-1 checksum of the file contents, encoded in base64 and
+    /// prefixed with `Q1`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<String>,
+}
+
+impl InstalledFile {
+    /// Creates a file with the default attributes, i.e. owned by root with
+    /// mode `0644` (as in apk).
+    fn new(path: PathBuf) -> Self {
+        InstalledFile {
+            path,
+            uid: 0,
+            gid: 0,
+            mode: 0o644,
+            checksum: None,
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+#[path = "installed.test.rs"]
+mod test;
@@ -0,0 +1,76 @@
+use std::fs::File;
+
+use super::*;
+use crate::internal::test_utils::{assert, assert_let, dependency, S};
+
+fn read_db() -> InstalledDb {
+    InstalledDb::load(File::open("../fixtures/installed/installed").unwrap()).unwrap()
+}
+
+#[test]
+fn installed_db_parse() {
+    let db = read_db();
+
+    assert!(db.packages.len() == 2);
+
+    let busybox = &db.packages[0];
+    assert!(busybox.entry.pkgname == "busybox");
+    assert!(busybox.entry.pkgver == "1.36.1-r5");
+    assert!(busybox.entry.replaces == vec![dependency("busybox-initscripts")]);
+    assert!(
+        busybox.files
+            == vec![
+                InstalledFile {
+                    path: PathBuf::from("/bin/busybox"),
+                    uid: 0,
+                    gid: 0,
+                    mode: 0o755,
+                    checksum: Some(S!("Q1F8QUH+yTQ7ffWqC9xG2UyCZrD7Y=")),
+                },
+                InstalledFile {
+                    path: PathBuf::from("/bin/sh"),
+                    uid: 0,
+                    gid: 0,
+                    mode: 0o777,
+                    checksum: Some(S!("Q1pcfTfDNEbNKQc2s1tia7da05M8Q=")),
+                },
+                InstalledFile {
+                    path: PathBuf::from("/etc/securetty"),
+                    uid: 0,
+                    gid: 0,
+                    mode: 0o644,
+                    checksum: Some(S!("Q1mB95Hhc8h2rH9yZb4W1ChXQSoEc=")),
+                },
+            ]
+    );
+
+    let rssh = &db.packages[1];
+    assert!(rssh.entry.depends.len() == 3);
+    assert!(rssh.files.len() == 3);
+    assert!(rssh.files[2].path == PathBuf::from("/usr/lib/rssh/rssh_chroot_helper"));
+    assert!(rssh.files[2].mode == 0o4755);
+}
+
+#[test]
+fn installed_db_parse_invalid() {
+    assert_let!(Err(InstalledDbError::Syntax(2, _)) = InstalledDb::parse("P:foo\nfoo\n"));
+    assert_let!(Err(InstalledDbError::InvalidLine(2, _)) = InstalledDb::parse("P:foo\nR:bar\n"));
+    assert_let!(
+        Err(InstalledDbError::InvalidLine(3, _)) = InstalledDb::parse("P:foo\nF:etc\nZ:Q1abc\n")
+    );
+    assert_let!(
+        Err(InstalledDbError::InvalidLine(4, _)) =
+            InstalledDb::parse("P:foo\nF:etc\nR:bar\na:0:0:rwx\n")
+    );
+    assert_let!(Err(InstalledDbError::Decode(1, _)) = InstalledDb::parse("P:foo\nt:yesterday\n"));
+}
+
+#[test]
+fn installed_db_owner() {
+    let db = read_db();
+
+    assert!(db.owner("/bin/sh").map(|p| p.entry.pkgname.as_str()) == Some("busybox"));
+    assert!(db.owner("usr/bin/rssh").map(|p| p.entry.pkgname.as_str()) == Some("rssh"));
+    assert!(db.owner("/usr/bin").is_none());
+    assert!(db.owner("/var/cache/misc").is_none());
+}
@@ -81,6 +81,66 @@ where
     map.end()
 }
 
+/// (De)serialization of a map of vectors of [`KeyValueLike`] elements, where
+/// each vector is represented as a map (as in the parent module).
+pub(crate) mod nested {
+    use std::collections::BTreeMap;
+
+    use super::*;
+
+    struct SerWrapper<'a, T>(&'a Vec<T>);
+
+    impl<'a, T, K, V> Serialize for SerWrapper<'a, T>
+    where
+        T: KeyValueLike<'a, Key = K, Value = V>,
+        K: Serialize,
+        V: Serialize,
+    {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            super::serialize(self.0, serializer)
+        }
+    }
+
+    struct DeWrapper<T>(Vec<T>);
+
+    impl<'de, T, K, V> Deserialize<'de> for DeWrapper<T>
+    where
+        T: Deserialize<'de> + KeyValueLike<'de, Key = K, Value = V>,
+        K: Deserialize<'de>,
+        V: Deserialize<'de>,
+    {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            super::deserialize(deserializer).map(DeWrapper)
+        }
+    }
+
+    pub(crate) fn deserialize<'de, D, T, K, V>(
+        deserializer: D,
+    ) -> Result<BTreeMap<String, Vec<T>>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: Deserialize<'de> + KeyValueLike<'de, Key = K, Value = V>,
+        K: Deserialize<'de>,
+        V: Deserialize<'de>,
+    {
+        let map: BTreeMap<String, DeWrapper<T>> = Deserialize::deserialize(deserializer)?;
+        Ok(map.into_iter().map(|(k, v)| (k, v.0)).collect())
+    }
+
+    pub(crate) fn serialize<'a, S, T, K, V>(
+        map: &'a BTreeMap<String, Vec<T>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        T: KeyValueLike<'a, Key = K, Value = V>,
+        K: Serialize,
+        V: Serialize,
+    {
+        serializer.collect_map(map.iter().map(|(k, v)| (k, SerWrapper(v))))
+    }
+}
+
 #[cfg(test)]
 #[path = "key_value_vec_map.test.rs"]
 mod test;
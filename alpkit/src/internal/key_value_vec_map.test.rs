@@ -5,7 +5,7 @@ use serde::{Deserialize, Serialize};
 
 use super::*;
 use crate::internal::serde_key_value;
-use crate::internal::test_utils::assert;
+use crate::internal::test_utils::{assert, S};
 
 #[derive(Debug, Eq, PartialEq, Serialize)]
 struct Pair {
@@ -130,3 +130,20 @@ fn serialize_map() {
 
     assert!(&serde_json::to_string(&input).unwrap() == r#"{"pairs":{"foo":"42","bar":"55"}}"#);
 }
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+struct NestedPairs {
+    #[serde(with = "nested")]
+    groups: std::collections::BTreeMap<String, Vec<Pair>>,
+}
+
+#[test]
+fn nested_json_roundtrip() {
+    let input = NestedPairs {
+        groups: [(S!("a"), fixture_struct().pairs), (S!("b"), vec![])].into(),
+    };
+    let json = r#"{"groups":{"a":{"foo":"42","bar":"55"},"b":{}}}"#;
+
+    assert!(serde_json::to_string(&input).unwrap() == json);
+    assert!(serde_json::from_str::<NestedPairs>(json).unwrap() == input);
+}
@@ -0,0 +1,35 @@
+/// Returns `true` if the given string is a mailbox in the RFC5322 name-addr
+/// format with a non-empty display name, e.g.
+/// `Kevin Flynn <kevin.flynn@encom.com>`.
+///
+/// This is not a complete implementation of RFC5322, it doesn't allow quoted
+/// display names, comments and quoted local parts.
+pub(crate) fn is_valid_mailbox(s: &str) -> bool {
+    let (name, addr) = match s.strip_suffix('>').and_then(|s| s.split_once(" <")) {
+        Some(pair) => pair,
+        None => return false,
+    };
+    let (local, domain) = match addr.rsplit_once('@') {
+        Some(pair) => pair,
+        None => return false,
+    };
+    let is_atext = |c: char| c.is_alphanumeric() || "!#$%&'*+-/=?^_`{|}~".contains(c);
+
+    !name.trim().is_empty()
+        && !name.contains(['<', '>', '@', '"'])
+        && !local.is_empty()
+        && local
+            .split('.')
+            .all(|s| !s.is_empty() && s.chars().all(is_atext))
+        && domain.contains('.')
+        && domain.split('.').all(|s| {
+            !s.is_empty()
+                && !s.starts_with('-')
+                && !s.ends_with('-')
+                && s.chars().all(|c| c.is_alphanumeric() || c == '-')
+        })
+}
+
+#[cfg(test)]
+#[path = "mailbox.test.rs"]
+mod test;
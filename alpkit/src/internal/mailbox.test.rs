@@ -0,0 +1,37 @@
+use super::*;
+use crate::internal::test_utils::assert;
+
+#[rustfmt::skip]
+#[test]
+fn is_valid_mailbox_valid() {
+    for input in [
+        "Kevin Flynn <kevin.flynn@encom.com>",
+        "Jakub Jirutka <jakub@jirutka.cz>",
+        "Natanael Copa <ncopa+alpine@alpinelinux.org>",
+        "Žluťoučký kůň <kun@example.co.uk>",
+    ] {
+        assert!(is_valid_mailbox(input), "{input}");
+    }
+}
+
+#[rustfmt::skip]
+#[test]
+fn is_valid_mailbox_invalid() {
+    for input in [
+        "",
+        "kevin.flynn@encom.com",
+        "<kevin.flynn@encom.com>",
+        " <kevin.flynn@encom.com>",
+        "Kevin Flynn kevin.flynn@encom.com",
+        "Kevin Flynn <kevin.flynn@encom.com",
+        "Kevin Flynn <kevin.flynn>",
+        "Kevin Flynn <@encom.com>",
+        "Kevin Flynn <kevin..flynn@encom.com>",
+        "Kevin Flynn <kevin flynn@encom.com>",
+        "Kevin Flynn <kevin.flynn@encom>",
+        "Kevin Flynn <kevin.flynn@-encom.com>",
+        "Kevin <Flynn> <kevin.flynn@encom.com>",
+    ] {
+        assert!(!is_valid_mailbox(input), "{input}");
+    }
+}
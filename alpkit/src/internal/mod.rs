@@ -3,9 +3,13 @@
 pub(crate) mod exit_status_error;
 pub(crate) mod key_value_vec_map;
 pub(crate) mod macros;
+pub(crate) mod mailbox;
+#[cfg(feature = "shell-timeout")]
+pub(crate) mod pipe_collector;
 pub(crate) mod serde_key_value;
 pub(crate) mod std_ext;
 pub(crate) mod tar_ext;
+pub(crate) mod tee_reader;
 
 #[cfg(test)]
 pub(crate) mod test_utils;
@@ -0,0 +1,46 @@
+use std::io::{self, Read};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+/// Reads a pipe (e.g. stdout of a child process) into a buffer in a background
+/// thread, so that the data read so far can be retrieved even if the pipe is
+/// never closed (e.g. when the process is killed after a timeout).
+pub(crate) struct PipeCollector {
+    buffer: Arc<Mutex<Vec<u8>>>,
+    handle: JoinHandle<io::Result<()>>,
+}
+
+impl PipeCollector {
+    pub(crate) fn spawn<R: Read + Send + 'static>(mut reader: R) -> Self {
+        let buffer = Arc::new(Mutex::new(Vec::new()));
+        let handle = thread::spawn({
+            let buffer = Arc::clone(&buffer);
+            move || {
+                let mut chunk = [0u8; 4096];
+                loop {
+                    match reader.read(&mut chunk) {
+                        Ok(0) => return Ok(()),
+                        Ok(n) => buffer.lock().unwrap().extend_from_slice(&chunk[..n]),
+                        Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                        Err(e) => return Err(e),
+                    }
+                }
+            }
+        });
+        PipeCollector { buffer, handle }
+    }
+
+    /// Waits until the pipe is closed and returns all the data read from it.
+    pub(crate) fn finish(self) -> io::Result<Vec<u8>> {
+        self.handle
+            .join()
+            .unwrap_or_else(|_| Err(io::Error::new(io::ErrorKind::Other, "reader panicked")))?;
+
+        Ok(std::mem::take(&mut *self.buffer.lock().unwrap()))
+    }
+
+    /// Returns the data read so far without waiting for the pipe to be closed.
+    pub(crate) fn partial(&self) -> Vec<u8> {
+        self.buffer.lock().unwrap().clone()
+    }
+}
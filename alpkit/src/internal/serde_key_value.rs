@@ -13,14 +13,14 @@ pub enum Error {
     MissingField(&'static str),
 
     #[error("invalid field '{1}'")]
-    InvalidField(#[source] Box<dyn StdError>, String),
+    InvalidField(#[source] Box<dyn StdError + Send + Sync>, String),
 
     #[error("decode error: {0}")]
     Other(String),
 
     #[doc(hidden)]
     #[error("decode error: {0}")]
-    Internal(#[source] Box<dyn StdError>),
+    Internal(#[source] Box<dyn StdError + Send + Sync>),
 }
 
 impl de::Error for Error {
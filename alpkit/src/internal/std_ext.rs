@@ -62,6 +62,7 @@ pub(crate) trait Tap: Sized {
     }
 
     /// Conditionally pipes by a value and returns self.
+    #[cfg_attr(not(feature = "shell-timeout"), allow(dead_code))]
     #[inline(always)]
     fn pipe_if<F: FnOnce(Self) -> Self>(self, cond: bool, f: F) -> Self {
         if cond {
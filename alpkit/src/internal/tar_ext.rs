@@ -12,7 +12,7 @@ pub(crate) trait TarEntryExt<'a> {
     fn apk_checksum(&mut self) -> io::Result<Option<&str>>;
 
     /// Returns extended file attributes (xattr) of the entry, if present.
-    fn xattrs(&mut self) -> io::Result<Xattrs>;
+    fn xattrs(&mut self) -> io::Result<Xattrs<'_>>;
 }
 
 impl<'a, R: Read> TarEntryExt<'a> for Entry<'a, R> {
@@ -30,7 +30,7 @@ impl<'a, R: Read> TarEntryExt<'a> for Entry<'a, R> {
         Ok(None)
     }
 
-    fn xattrs(&mut self) -> io::Result<Xattrs> {
+    fn xattrs(&mut self) -> io::Result<Xattrs<'_>> {
         let exts = self.pax_extensions()?;
 
         Ok(exts
@@ -0,0 +1,41 @@
+use std::io::{self, BufRead, Read, Write};
+
+/// A buffered reader that writes all the data read through it into the
+/// `writer`, e.g. a hasher.
+pub(crate) struct TeeReader<R, W> {
+    inner: R,
+    writer: W,
+}
+
+impl<R: BufRead, W: Write> TeeReader<R, W> {
+    pub(crate) fn new(inner: R, writer: W) -> Self {
+        TeeReader { inner, writer }
+    }
+
+    /// Returns the writer with the data read so far.
+    pub(crate) fn into_writer(self) -> W {
+        self.writer
+    }
+}
+
+impl<R: BufRead, W: Write> Read for TeeReader<R, W> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.writer.write_all(&buf[..n])?;
+        Ok(n)
+    }
+}
+
+impl<R: BufRead, W: Write> BufRead for TeeReader<R, W> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        // fill_buf returns the already buffered data here, so it doesn't fail.
+        if let Ok(buf) = self.inner.fill_buf() {
+            let _ = self.writer.write_all(&buf[..amt]);
+        }
+        self.inner.consume(amt)
+    }
+}
@@ -1,6 +1,10 @@
+use std::fs::File;
+use std::io::BufReader;
+
 pub(crate) use assert2::{assert, let_assert as assert_let};
 
 use crate::dependency::Dependency;
+use crate::package::Package;
 
 macro_rules! assert_from_to_json {
     ($strukt:expr, $json:expr $(,)?) => {{
@@ -48,3 +52,15 @@ pub(crate) fn dependency(s: &str) -> Dependency {
     s.parse()
         .unwrap_or_else(|_| panic!("invalid dependency string: `{s}`"))
 }
+
+/// Opens the package `name` from the `fixtures/apk` directory.
+pub(crate) fn open_fixture(name: &str) -> BufReader<File> {
+    let path = format!("../fixtures/apk/{name}");
+    let file = File::open(&path).unwrap_or_else(|_| panic!("Fixture file `{path}` not found"));
+    BufReader::new(file)
+}
+
+/// Loads the package `name` from the `fixtures/apk` directory.
+pub(crate) fn load_fixture(name: &str) -> Package {
+    Package::load(open_fixture(name)).unwrap()
+}
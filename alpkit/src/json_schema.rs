@@ -0,0 +1,151 @@
+//! Validation of JSON documents against the JSON Schemas of the serialized
+//! types.
+//!
+//! The schemas are embedded in the library, so the documents are validated
+//! against the exact version of the schemas matching this version of alpkit.
+//! They are generated by `schema-gen all -o schemas` (requires the
+//! `schema-gen` feature). The schemas alone are available with the `schemas`
+//! feature, the validation requires the `json-schema` feature.
+//!
+//! Example:
+//! ```
+//! # #[cfg(feature = "json-schema")] {
+//! use alpkit::package::Package;
+//! use serde_json::json;
+//!
+//! let report = Package::validate_json(&json!({ "pkgname": "foo" })).unwrap_err();
+//! for violation in report.violations {
+//!     println!("{violation}");
+//! }
+//! # }
+//! ```
+#[cfg(feature = "json-schema")]
+use jsonschema::Validator;
+#[cfg(feature = "json-schema")]
+use once_cell::sync::OnceCell;
+#[cfg(feature = "json-schema")]
+use serde_json::Value;
+
+// Without json-schema, the types are used only in the doc links.
+#[cfg_attr(not(feature = "json-schema"), allow(unused_imports))]
+use crate::apkbuild::Apkbuild;
+#[cfg_attr(not(feature = "json-schema"), allow(unused_imports))]
+use crate::package::{FileInfo, Package, PkgInfo};
+#[cfg(feature = "json-schema")]
+use crate::validate::{ValidationReport, Violation};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// JSON Schema (draft-07) of [`Apkbuild`].
+pub const APKBUILD_SCHEMA: &str = include_str!("../schemas/apkbuild.schema.json");
+
+/// JSON Schema (draft-07) of the dependencies serialized as a map.
+pub const DEPENDENCIES_SCHEMA: &str = include_str!("../schemas/dependencies.schema.json");
+
+/// JSON Schema (draft-07) of [`FileInfo`].
+pub const FILEINFO_SCHEMA: &str = include_str!("../schemas/fileinfo.schema.json");
+
+/// JSON Schema (draft-07) of [`Package`].
+pub const PACKAGE_SCHEMA: &str = include_str!("../schemas/package.schema.json");
+
+/// JSON Schema (draft-07) of [`PkgInfo`].
+pub const PKGINFO_SCHEMA: &str = include_str!("../schemas/pkginfo.schema.json");
+
+#[cfg(feature = "json-schema")]
+impl Apkbuild {
+    /// Validates the given JSON document against the schema of `Apkbuild`.
+    pub fn validate_json(value: &Value) -> Result<(), ValidationReport> {
+        static VALIDATOR: OnceCell<Validator> = OnceCell::new();
+        validate_json(&VALIDATOR, APKBUILD_SCHEMA, value)
+    }
+}
+
+#[cfg(feature = "json-schema")]
+impl FileInfo {
+    /// Validates the given JSON document against the schema of `FileInfo`.
+    pub fn validate_json(value: &Value) -> Result<(), ValidationReport> {
+        static VALIDATOR: OnceCell<Validator> = OnceCell::new();
+        validate_json(&VALIDATOR, FILEINFO_SCHEMA, value)
+    }
+}
+
+#[cfg(feature = "json-schema")]
+impl Package {
+    /// Validates the given JSON document against the schema of `Package`.
+    pub fn validate_json(value: &Value) -> Result<(), ValidationReport> {
+        static VALIDATOR: OnceCell<Validator> = OnceCell::new();
+        validate_json(&VALIDATOR, PACKAGE_SCHEMA, value)
+    }
+}
+
+#[cfg(feature = "json-schema")]
+impl PkgInfo {
+    /// Validates the given JSON document against the schema of `PkgInfo`.
+    pub fn validate_json(value: &Value) -> Result<(), ValidationReport> {
+        static VALIDATOR: OnceCell<Validator> = OnceCell::new();
+        validate_json(&VALIDATOR, PKGINFO_SCHEMA, value)
+    }
+}
+
+/// Validates the JSON document `value` against the given embedded JSON Schema.
+/// Each error is reported as a [`Violation`] with the JSON Schema keyword as
+/// the rule (e.g. `required` or `pattern`).
+///
+/// The validator is compiled from the `schema` on the first use and cached in
+/// the given `cell`.
+#[cfg(feature = "json-schema")]
+fn validate_json(
+    cell: &OnceCell<Validator>,
+    schema: &str,
+    value: &Value,
+) -> Result<(), ValidationReport> {
+    let validator = cell.get_or_init(|| {
+        // The embedded schemas are generated by schemars, so they are valid.
+        let schema = serde_json::from_str(schema).expect("embedded schema should be valid JSON");
+        jsonschema::validator_for(&schema).expect("embedded schema should be valid")
+    });
+
+    let violations: Vec<_> = validator
+        .iter_errors(value)
+        .map(|e| {
+            let schema_path = e.schema_path.to_string();
+            Violation {
+                path: pointer_to_path(&e.instance_path.to_string()),
+                rule: schema_path.rsplit('/').next().unwrap_or("").to_owned(),
+                message: e.to_string(),
+            }
+        })
+        .collect();
+
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(ValidationReport { violations })
+    }
+}
+
+/// Converts JSON Pointer (e.g. `/files/0/mode`) to the path format used in
+/// [`Violation::path`] (e.g. `files[0].mode`).
+#[cfg(feature = "json-schema")]
+fn pointer_to_path(pointer: &str) -> String {
+    let mut path = String::with_capacity(pointer.len());
+
+    for segment in pointer.split('/').skip(1) {
+        let segment = segment.replace("~1", "/").replace("~0", "~");
+        if !segment.is_empty() && segment.chars().all(|c| c.is_ascii_digit()) {
+            path.push_str(&format!("[{segment}]"));
+        } else {
+            if !path.is_empty() {
+                path.push('.');
+            }
+            path.push_str(&segment);
+        }
+    }
+    path
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(all(test, feature = "json-schema"))]
+#[path = "json_schema.test.rs"]
+mod test;
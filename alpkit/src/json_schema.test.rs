@@ -0,0 +1,81 @@
+use std::fs::File;
+use std::io::BufReader;
+
+use serde_json::json;
+
+use super::*;
+use crate::internal::test_utils::{assert, assert_let};
+
+#[test]
+fn validate_json_package_fixture() {
+    let file = File::open("../fixtures/apk/rssh-2.3.4-r3.apk").unwrap();
+    let pkg = Package::load(BufReader::new(file)).unwrap();
+
+    assert_let!(Ok(()) = Package::validate_json(&serde_json::to_value(pkg).unwrap()));
+}
+
+#[test]
+fn validate_json_apkbuild_fixture() {
+    let apkbuild = crate::apkbuild::ApkbuildReader::new()
+        .read_apkbuild("../fixtures/aports/sample/APKBUILD")
+        .unwrap();
+
+    assert_let!(Ok(()) = Apkbuild::validate_json(&serde_json::to_value(apkbuild).unwrap()));
+}
+
+#[test]
+fn validate_json_invalid() {
+    let value = json!({
+        "path": "/usr/bin/foo",
+        "type": "r",
+        "uname": "root",
+        "gname": "root",
+        "mode": "0888",
+        "device": 0,
+        "size": 42,
+    });
+    assert_let!(Err(report) = FileInfo::validate_json(&value));
+
+    let violations: Vec<_> = report
+        .violations
+        .iter()
+        .map(|v| (v.path.as_str(), v.rule.as_str()))
+        .collect();
+
+    assert!(violations == vec![("mode", "pattern")]);
+}
+
+#[test]
+#[rustfmt::skip]
+fn pointer_to_path_cases() {
+    for (input, expected) in [
+        (""                , ""),
+        ("/pkgname"        , "pkgname"),
+        ("/files/0/mode"   , "files[0].mode"),
+        ("/depends/a~1b"   , "depends.a/b"),
+    ] {
+        assert!(pointer_to_path(input) == expected);
+    }
+}
+
+#[cfg(feature = "schema-gen")]
+#[test]
+fn embedded_schemas_are_up_to_date() {
+    use crate::schema::{root_schema, Draft};
+
+    for (name, embedded) in [
+        ("apkbuild", APKBUILD_SCHEMA),
+        ("dependencies", DEPENDENCIES_SCHEMA),
+        ("fileinfo", FILEINFO_SCHEMA),
+        ("package", PACKAGE_SCHEMA),
+        ("pkginfo", PKGINFO_SCHEMA),
+    ] {
+        let generated = serde_json::to_value(root_schema(name, Draft::Draft07).unwrap()).unwrap();
+        let embedded: serde_json::Value = serde_json::from_str(embedded).unwrap();
+
+        assert!(
+            embedded == generated,
+            "schemas/{name}.schema.json is outdated, regenerate it by schema-gen"
+        );
+    }
+}
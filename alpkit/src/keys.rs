@@ -0,0 +1,263 @@
+//! A key ring of the RSA public keys used for verification of the package and
+//! index signatures.
+//!
+//! The keys can be loaded from a directory (e.g. `/etc/apk/keys`), from the
+//! `alpine-keys` package (e.g. to get the official Alpine signing keys without
+//! installing it), or downloaded from a URL (with the `http` feature). The key
+//! ring can be also written into a directory to refresh the trusted keys.
+//!
+//! Example:
+//! ```no_run
+//! # use std::fs::File;
+//! # use std::io::BufReader;
+//! use alpkit::keys::KeyRing;
+//! use alpkit::package::Package;
+//!
+//! let mut keys = KeyRing::load_dir("/etc/apk/keys").unwrap();
+//! let file = File::open("alpine-keys-2.4-r1.apk").map(BufReader::new).unwrap();
+//! keys.add_from_package(file, "x86_64").unwrap();
+//!
+//! let file = File::open("example-1.0-r0.apk").map(BufReader::new).unwrap();
+//! let pkg = Package::load_without_files(file).unwrap();
+//! let sign = pkg.verify_signature_with(&keys).unwrap();
+//! ```
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, BufRead, Read};
+use std::path::{Path, PathBuf};
+#[cfg(feature = "http")]
+use std::time::Duration;
+
+use flate2::bufread::GzDecoder;
+use log::debug;
+use rsa::RsaPublicKey;
+use tar::{Archive, EntryType};
+use thiserror::Error;
+
+use crate::package::{parse_public_key, Package, SignatureError};
+
+/// The directories with the keys in the `alpine-keys` package; the keys for
+/// each architecture are in a subdirectory named after it.
+const PACKAGE_KEYS_DIRS: &[&str] = &["usr/share/apk/keys", "etc/apk/keys"];
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, Error)]
+pub enum KeyRingError {
+    #[error(transparent)]
+    Key(#[from] SignatureError),
+
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    #[error("failed to read directory '{0}'")]
+    ReadDir(PathBuf, #[source] io::Error),
+
+    #[error("failed to write public key '{0}'")]
+    WriteKey(PathBuf, #[source] io::Error),
+
+    #[cfg(feature = "http")]
+    #[error("failed to download '{1}'")]
+    Download(#[source] Box<ureq::Error>, String),
+
+    #[cfg(feature = "http")]
+    #[error("invalid key URL, expected a file name ending with '.pub': '{0}'")]
+    InvalidUrl(String),
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// A set of the RSA public keys by their name (the file name, e.g.
+/// `alpine-devel@lists.alpinelinux.org-6165ee59.rsa.pub`), which is referred
+/// by the signatures.
+#[derive(Debug, Clone, Default)]
+pub struct KeyRing {
+    keys: BTreeMap<String, PublicKey>,
+}
+
+#[derive(Debug, Clone)]
+struct PublicKey {
+    pem: String,
+    key: RsaPublicKey,
+}
+
+impl KeyRing {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a key ring with the keys in the given directory (e.g.
+    /// `/etc/apk/keys`). See [`KeyRing::add_dir`].
+    pub fn load_dir<P: AsRef<Path>>(dir: P) -> Result<Self, KeyRingError> {
+        let mut keys = Self::new();
+        keys.add_dir(dir)?;
+        Ok(keys)
+    }
+
+    /// Adds all the `*.pub` files in the given directory (symlinks are
+    /// followed). Returns the number of the added keys.
+    pub fn add_dir<P: AsRef<Path>>(&mut self, dir: P) -> Result<usize, KeyRingError> {
+        let dir = dir.as_ref();
+        debug!("reading keys from {}", dir.display());
+
+        let read_dir_err = |e| KeyRingError::ReadDir(dir.to_owned(), e);
+        let mut count = 0;
+
+        for entry in fs::read_dir(dir).map_err(read_dir_err)? {
+            let path = entry.map_err(read_dir_err)?.path();
+            let keyname = match path.file_name().and_then(|s| s.to_str()) {
+                Some(name) if name.ends_with(".pub") && path.is_file() => name,
+                _ => continue,
+            };
+            let pem =
+                fs::read_to_string(&path).map_err(|e| SignatureError::ReadKey(path.clone(), e))?;
+            self.add_pem(keyname, &pem)?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Adds the key in the PEM format (either SPKI or PKCS#1) with the given
+    /// name. If a key with the same name already exists, it's replaced.
+    pub fn add_pem<S: ToString>(&mut self, keyname: S, pem: &str) -> Result<(), KeyRingError> {
+        let keyname = keyname.to_string();
+        let key = parse_public_key(pem, Path::new(&keyname))?;
+
+        self.keys.insert(
+            keyname,
+            PublicKey {
+                pem: pem.to_owned(),
+                key,
+            },
+        );
+        Ok(())
+    }
+
+    /// Adds the keys for the given `arch` from the `alpine-keys` package (or
+    /// any package that installs keys into `/usr/share/apk/keys/<arch>/` or
+    /// `/etc/apk/keys/`). The package signature is not verified. Returns the
+    /// number of the added keys.
+    pub fn add_from_package<R: BufRead>(
+        &mut self,
+        mut reader: R,
+        arch: &str,
+    ) -> Result<usize, KeyRingError> {
+        Package::skip_segment(&mut reader)?; // signatures
+        Package::skip_segment(&mut reader)?; // control
+
+        let mut archive = Archive::new(GzDecoder::new(reader));
+        let mut count = 0;
+
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            if !matches!(
+                entry.header().entry_type(),
+                EntryType::Regular | EntryType::Continuous
+            ) {
+                continue;
+            }
+            let path = entry.path()?.into_owned();
+            let keyname = match path.file_name().and_then(|s| s.to_str()) {
+                Some(name) if name.ends_with(".pub") => name,
+                _ => continue,
+            };
+            let dir = path.parent().unwrap_or_else(|| Path::new(""));
+            if !PACKAGE_KEYS_DIRS
+                .iter()
+                .any(|base| dir == Path::new(base) || dir == Path::new(base).join(arch))
+            {
+                continue;
+            }
+            let mut pem = String::new();
+            entry.read_to_string(&mut pem)?;
+
+            self.add_pem(keyname, &pem)?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Downloads the key from the given URL (e.g.
+    /// `https://alpinelinux.org/keys/alpine-devel@lists.alpinelinux.org-6165ee59.rsa.pub`)
+    /// and adds it with the name of the URL's last path segment.
+    #[cfg(feature = "http")]
+    pub fn fetch_key(&mut self, url: &str) -> Result<(), KeyRingError> {
+        let keyname = url
+            .rsplit('/')
+            .next()
+            .filter(|s| s.ends_with(".pub"))
+            .ok_or_else(|| KeyRingError::InvalidUrl(url.to_owned()))?;
+
+        let mut pem = String::new();
+        http_get(url)?.read_to_string(&mut pem)?;
+
+        self.add_pem(keyname, &pem)
+    }
+
+    /// Downloads the `alpine-keys` package (or other package with keys) from
+    /// the given URL and adds the keys for the given `arch` from it. See
+    /// [`KeyRing::add_from_package`].
+    #[cfg(feature = "http")]
+    pub fn fetch_package(&mut self, url: &str, arch: &str) -> Result<usize, KeyRingError> {
+        self.add_from_package(io::BufReader::new(http_get(url)?), arch)
+    }
+
+    /// Returns the key with the given name.
+    pub fn get(&self, keyname: &str) -> Option<&RsaPublicKey> {
+        self.keys.get(keyname).map(|k| &k.key)
+    }
+
+    /// Returns an iterator over names of the keys (sorted).
+    pub fn keynames(&self) -> impl Iterator<Item = &str> {
+        self.keys.keys().map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Writes the keys into the given directory (e.g. `/etc/apk/keys`), each
+    /// into a file named after the key. Existing files with the same content
+    /// are left untouched, other files in the directory are not removed.
+    /// Returns the number of the written (new or updated) keys.
+    pub fn write_dir<P: AsRef<Path>>(&self, dir: P) -> Result<usize, KeyRingError> {
+        let dir = dir.as_ref();
+        let mut count = 0;
+
+        for (keyname, key) in &self.keys {
+            let path = dir.join(keyname);
+            if fs::read_to_string(&path).map_or(false, |pem| pem == key.pem) {
+                continue;
+            }
+            fs::write(&path, &key.pem).map_err(|e| KeyRingError::WriteKey(path, e))?;
+            count += 1;
+        }
+        Ok(count)
+    }
+}
+
+#[cfg(feature = "http")]
+fn http_get(url: &str) -> Result<impl Read + Send, KeyRingError> {
+    debug!("downloading {url}");
+
+    let agent = ureq::AgentBuilder::new()
+        .timeout_connect(Duration::from_secs(30))
+        .user_agent(concat!("alpkit/", env!("CARGO_PKG_VERSION")))
+        .build();
+
+    agent
+        .get(url)
+        .call()
+        .map(ureq::Response::into_reader)
+        .map_err(|e| KeyRingError::Download(Box::new(e), url.to_owned()))
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+#[path = "keys.test.rs"]
+mod test;
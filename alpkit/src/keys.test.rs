@@ -0,0 +1,88 @@
+use std::fs::File;
+use std::io::{BufReader, Write};
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+
+use super::*;
+use crate::internal::test_utils::{assert, assert_let};
+
+const KEYS_DIR: &str = "../fixtures/keys";
+const KEYNAME: &str = "alpkit-test-5f3a9c2e.rsa.pub";
+
+fn load_signed_fixture() -> Package {
+    let file = File::open("../fixtures/apk/rssh-2.3.4-r3-signed.apk").unwrap();
+    Package::load_without_files(BufReader::new(file)).unwrap()
+}
+
+fn gzip(data: &[u8]) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::fast());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+/// Creates an APK package (with empty signature and control segments) with
+/// the given regular files.
+fn make_apk(files: &[(&str, &str)]) -> Vec<u8> {
+    let mut builder = tar::Builder::new(Vec::new());
+    for (path, content) in files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, path, content.as_bytes())
+            .unwrap();
+    }
+    let data = builder.into_inner().unwrap();
+
+    [gzip(b""), gzip(b""), gzip(&data)].concat()
+}
+
+#[test]
+fn load_dir_and_verify() {
+    let keys = KeyRing::load_dir(KEYS_DIR).unwrap();
+
+    assert!(keys.keynames().collect::<Vec<_>>() == [KEYNAME]);
+
+    let pkg = load_signed_fixture();
+
+    let sign = pkg.verify_signature_with(&keys).unwrap();
+    assert!(sign.map(|s| s.keyname.as_str()) == Some(KEYNAME));
+
+    let sign = pkg.verify_signature_with(&KeyRing::new()).unwrap();
+    assert!(sign.is_none());
+}
+
+#[test]
+fn add_from_package() {
+    let pem = fs::read_to_string(format!("{KEYS_DIR}/{KEYNAME}")).unwrap();
+    let apk = make_apk(&[
+        ("usr/share/apk/keys/aarch64/other-aarch64.rsa.pub", &pem),
+        (&format!("usr/share/apk/keys/x86_64/{KEYNAME}"), &pem),
+        ("usr/share/apk/keys/README", "not a key"),
+        ("etc/apk/keys/legacy.rsa.pub", &pem),
+    ]);
+
+    let mut keys = KeyRing::new();
+    assert!(keys.add_from_package(apk.as_slice(), "x86_64").unwrap() == 2);
+    assert!(keys.keynames().collect::<Vec<_>>() == [KEYNAME, "legacy.rsa.pub"]);
+}
+
+#[test]
+fn add_pem_invalid() {
+    assert_let!(
+        Err(KeyRingError::Key(SignatureError::InvalidKey(_))) =
+            KeyRing::new().add_pem("foo.rsa.pub", "foo")
+    );
+}
+
+#[test]
+fn write_dir() {
+    let dir = tempfile::tempdir().unwrap();
+    let keys = KeyRing::load_dir(KEYS_DIR).unwrap();
+
+    assert!(keys.write_dir(dir.path()).unwrap() == 1);
+    assert!(keys.write_dir(dir.path()).unwrap() == 0);
+    assert!(KeyRing::load_dir(dir.path()).unwrap().len() == 1);
+}
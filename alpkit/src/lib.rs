@@ -1,7 +1,38 @@
 //! A library for reading the APK(v2) package format and `APKBUILD`.
+//!
+//! It can be compiled to WebAssembly (`wasm32-unknown-unknown` or WASI)
+//! without the `shell-timeout` feature. Reading of packages, indexes and
+//! dependencies works there, but evaluation of APKBUILDs fails at runtime,
+//! because it requires spawning a shell.
 
+pub mod adbdump;
 pub mod apkbuild;
+#[cfg(feature = "archlinux")]
+pub mod archlinux;
+#[cfg(feature = "parquet")]
+pub mod columnar;
+pub mod consistency;
 pub mod dependency;
+pub mod graph;
+pub mod index;
+pub mod installed;
+#[cfg(feature = "schemas")]
+pub mod json_schema;
+#[cfg(feature = "signature")]
+pub mod keys;
+pub mod license;
+pub mod lint;
+#[cfg(feature = "mirror")]
+pub mod mirror;
 pub mod package;
+pub mod sbom;
+#[cfg(feature = "schema-gen")]
+pub mod schema;
+pub mod secdb;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+#[cfg(feature = "validate")]
+pub mod validate;
+pub mod version;
 
 mod internal;
@@ -0,0 +1,333 @@
+//! Parsing of the `license` field into a SPDX license expression.
+//!
+//! In addition to the [SPDX license expression] syntax, this parser supports
+//! conventions used in Alpine Linux: lowercase operators (`and`, `or`,
+//! `with`) and a list of license identifiers separated by a space, which is
+//! interpreted as a conjunction (`AND`).
+//!
+//! [SPDX license expression]: https://spdx.github.io/spdx-spec/v2.3/SPDX-license-expressions/
+use std::fmt;
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::internal::macros::bail;
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, PartialEq, Eq, Error)]
+pub enum LicenseParseError {
+    #[error("empty license expression")]
+    Empty,
+
+    #[error("unexpected token in license expression: '{0}'")]
+    UnexpectedToken(String),
+
+    #[error("unexpected end of license expression")]
+    UnexpectedEnd,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// A parsed SPDX license expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LicenseExpr {
+    /// A single license (with an optional exception).
+    License(LicenseReq),
+
+    /// A conjunction of expressions (`AND`).
+    And(Vec<LicenseExpr>),
+
+    /// A disjunction of expressions (`OR`).
+    Or(Vec<LicenseExpr>),
+}
+
+impl LicenseExpr {
+    /// Parses the given license expression.
+    pub fn parse(s: &str) -> Result<Self, LicenseParseError> {
+        s.parse()
+    }
+
+    /// Returns an iterator over all licenses in this expression (in order of
+    /// appearance, including duplicates).
+    pub fn licenses(&self) -> impl Iterator<Item = &LicenseReq> {
+        let mut stack = vec![self];
+
+        std::iter::from_fn(move || {
+            while let Some(expr) = stack.pop() {
+                match expr {
+                    LicenseExpr::License(req) => return Some(req),
+                    LicenseExpr::And(exprs) | LicenseExpr::Or(exprs) => {
+                        stack.extend(exprs.iter().rev());
+                    }
+                }
+            }
+            None
+        })
+    }
+
+    /// Returns an iterator over licenses (and exceptions) with identifiers not
+    /// found in the SPDX license list.
+    pub fn unknown(&self) -> impl Iterator<Item = &LicenseReq> {
+        self.licenses().filter(|req| !req.is_known())
+    }
+
+    /// Returns an iterator over licenses (and exceptions) with deprecated SPDX
+    /// identifiers.
+    pub fn deprecated(&self) -> impl Iterator<Item = &LicenseReq> {
+        self.licenses().filter(|req| req.is_deprecated())
+    }
+
+    fn fmt_nested(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LicenseExpr::License(_) => write!(f, "{self}"),
+            _ => write!(f, "({self})"),
+        }
+    }
+}
+
+impl FromStr for LicenseExpr {
+    type Err = LicenseParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tokens = tokenize(s);
+        if tokens.is_empty() {
+            bail!(LicenseParseError::Empty);
+        }
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser.parse_or()?;
+
+        if let Some(token) = parser.peek() {
+            bail!(LicenseParseError::UnexpectedToken(token.to_string()));
+        }
+        Ok(expr)
+    }
+}
+
+impl fmt::Display for LicenseExpr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (exprs, op) = match self {
+            LicenseExpr::License(req) => return write!(f, "{req}"),
+            LicenseExpr::And(exprs) => (exprs, " AND "),
+            LicenseExpr::Or(exprs) => (exprs, " OR "),
+        };
+        for (i, expr) in exprs.iter().enumerate() {
+            if i > 0 {
+                f.write_str(op)?;
+            }
+            expr.fmt_nested(f)?;
+        }
+        Ok(())
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// A license identifier with an optional exception, e.g. `GPL-2.0-or-later`
+/// or `Apache-2.0 WITH LLVM-exception`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LicenseReq {
+    /// SPDX license identifier (or `LicenseRef-*`) without the `+` suffix.
+    pub id: String,
+
+    /// `true` if the identifier is followed by `+` (“or later”).
+    pub or_later: bool,
+
+    /// SPDX license exception identifier specified after `WITH`.
+    pub exception: Option<String>,
+}
+
+impl LicenseReq {
+    pub fn new<S: ToString>(id: S) -> Self {
+        LicenseReq {
+            id: id.to_string(),
+            or_later: false,
+            exception: None,
+        }
+    }
+
+    /// Returns `true` if the license identifier (and exception, if any) is in
+    /// the SPDX license list, or it's a user-defined reference (`LicenseRef-`).
+    pub fn is_known(&self) -> bool {
+        let id_known = is_license_ref(&self.id) || spdx::license_id(&self.id).is_some();
+        let exception_known = self
+            .exception
+            .as_ref()
+            .map_or(true, |id| spdx::exception_id(id).is_some());
+
+        id_known && exception_known
+    }
+
+    /// Returns `true` if the license identifier or exception is deprecated in
+    /// the SPDX license list (e.g. `GPL-2.0`).
+    pub fn is_deprecated(&self) -> bool {
+        spdx::license_id(&self.id).map_or(false, |id| id.is_deprecated())
+            || self
+                .exception
+                .as_ref()
+                .and_then(|id| spdx::exception_id(id))
+                .map_or(false, |id| id.is_deprecated())
+    }
+}
+
+impl fmt::Display for LicenseReq {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.id)?;
+        if self.or_later {
+            f.write_str("+")?;
+        }
+        if let Some(exception) = &self.exception {
+            write!(f, " WITH {exception}")?;
+        }
+        Ok(())
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token<'a> {
+    And,
+    Or,
+    With,
+    Open,
+    Close,
+    Id(&'a str),
+}
+
+impl fmt::Display for Token<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Token::And => "AND",
+            Token::Or => "OR",
+            Token::With => "WITH",
+            Token::Open => "(",
+            Token::Close => ")",
+            Token::Id(s) => s,
+        })
+    }
+}
+
+fn tokenize(s: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+
+    for word in s.split_ascii_whitespace() {
+        let mut rest = word;
+        while !rest.is_empty() {
+            if let Some(r) = rest.strip_prefix('(') {
+                tokens.push(Token::Open);
+                rest = r;
+                continue;
+            }
+            let end = rest.find(['(', ')']).unwrap_or(rest.len());
+            if end == 0 {
+                tokens.push(Token::Close);
+                rest = &rest[1..];
+                continue;
+            }
+            tokens.push(match &rest[..end] {
+                s if s.eq_ignore_ascii_case("and") => Token::And,
+                s if s.eq_ignore_ascii_case("or") => Token::Or,
+                s if s.eq_ignore_ascii_case("with") => Token::With,
+                s => Token::Id(s),
+            });
+            rest = &rest[end..];
+        }
+    }
+    tokens
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token<'a>>,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<Token<'a>> {
+        self.tokens.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Result<Token<'a>, LicenseParseError> {
+        let token = self.peek().ok_or(LicenseParseError::UnexpectedEnd)?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    // or_expr := and_expr ("OR" and_expr)*
+    fn parse_or(&mut self) -> Result<LicenseExpr, LicenseParseError> {
+        let mut exprs = vec![self.parse_and()?];
+        while self.peek() == Some(Token::Or) {
+            self.pos += 1;
+            exprs.push(self.parse_and()?);
+        }
+        Ok(flatten(exprs, LicenseExpr::Or))
+    }
+
+    // and_expr := with_expr (["AND"] with_expr)*
+    fn parse_and(&mut self) -> Result<LicenseExpr, LicenseParseError> {
+        let mut exprs = vec![self.parse_with()?];
+        loop {
+            match self.peek() {
+                Some(Token::And) => self.pos += 1,
+                // Alpine convention: a space-separated list means AND.
+                Some(Token::Id(_) | Token::Open) => {}
+                _ => break,
+            }
+            exprs.push(self.parse_with()?);
+        }
+        Ok(flatten(exprs, LicenseExpr::And))
+    }
+
+    // with_expr := "(" or_expr ")" | id ["+"] ["WITH" id]
+    fn parse_with(&mut self) -> Result<LicenseExpr, LicenseParseError> {
+        match self.next()? {
+            Token::Open => {
+                let expr = self.parse_or()?;
+                match self.next()? {
+                    Token::Close => Ok(expr),
+                    token => Err(LicenseParseError::UnexpectedToken(token.to_string())),
+                }
+            }
+            Token::Id(id) => {
+                let (id, or_later) = id.strip_suffix('+').map_or((id, false), |s| (s, true));
+                let exception = if self.peek() == Some(Token::With) {
+                    self.pos += 1;
+                    match self.next()? {
+                        Token::Id(s) => Some(s.to_owned()),
+                        token => bail!(LicenseParseError::UnexpectedToken(token.to_string())),
+                    }
+                } else {
+                    None
+                };
+                Ok(LicenseExpr::License(LicenseReq {
+                    id: id.to_owned(),
+                    or_later,
+                    exception,
+                }))
+            }
+            token => Err(LicenseParseError::UnexpectedToken(token.to_string())),
+        }
+    }
+}
+
+fn flatten(mut exprs: Vec<LicenseExpr>, op: fn(Vec<LicenseExpr>) -> LicenseExpr) -> LicenseExpr {
+    if exprs.len() == 1 {
+        exprs.pop().unwrap()
+    } else {
+        op(exprs)
+    }
+}
+
+fn is_license_ref(id: &str) -> bool {
+    id.starts_with("LicenseRef-")
+        || id
+            .strip_prefix("DocumentRef-")
+            .and_then(|s| s.split_once(':'))
+            .map_or(false, |(_, s)| s.starts_with("LicenseRef-"))
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+#[path = "license.test.rs"]
+mod test;
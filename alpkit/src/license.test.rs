@@ -0,0 +1,112 @@
+use super::*;
+use crate::internal::test_utils::{assert, assert_let};
+
+////////////////////////////////////////////////////////////////////////////////
+
+fn lic(id: &str) -> LicenseExpr {
+    LicenseExpr::License(LicenseReq::new(id))
+}
+
+#[test]
+fn parse_single() {
+    assert!(LicenseExpr::parse("MIT") == Ok(lic("MIT")));
+}
+
+#[test]
+fn parse_space_separated_as_and() {
+    assert!(
+        LicenseExpr::parse("ISC BSD-2-Clause  BSD-3-Clause")
+            == Ok(LicenseExpr::And(vec![
+                lic("ISC"),
+                lic("BSD-2-Clause"),
+                lic("BSD-3-Clause")
+            ]))
+    );
+}
+
+#[test]
+fn parse_lowercase_operators() {
+    assert!(
+        LicenseExpr::parse("ISC and BSD-2-Clause or MIT")
+            == Ok(LicenseExpr::Or(vec![
+                LicenseExpr::And(vec![lic("ISC"), lic("BSD-2-Clause")]),
+                lic("MIT"),
+            ]))
+    );
+}
+
+#[test]
+fn parse_parentheses_and_with() {
+    let expected = LicenseExpr::And(vec![
+        LicenseExpr::Or(vec![lic("MIT"), lic("Apache-2.0")]),
+        LicenseExpr::License(LicenseReq {
+            id: "GPL-2.0".to_owned(),
+            or_later: true,
+            exception: Some("Classpath-exception-2.0".to_owned()),
+        }),
+    ]);
+    assert!(
+        LicenseExpr::parse("(MIT OR Apache-2.0) AND GPL-2.0+ WITH Classpath-exception-2.0")
+            == Ok(expected)
+    );
+}
+
+#[test]
+#[rustfmt::skip]
+fn parse_invalid() {
+    for (input, expected) in [
+        (""            , LicenseParseError::Empty                        ),
+        ("   "         , LicenseParseError::Empty                        ),
+        ("MIT AND"     , LicenseParseError::UnexpectedEnd                ),
+        ("(MIT"        , LicenseParseError::UnexpectedEnd                ),
+        ("MIT)"        , LicenseParseError::UnexpectedToken(")".into())  ),
+        ("OR MIT"      , LicenseParseError::UnexpectedToken("OR".into()) ),
+        ("MIT WITH ("  , LicenseParseError::UnexpectedToken("(".into())  ),
+    ] {
+        assert!(LicenseExpr::parse(input) == Err(expected), "input: {input:?}");
+    }
+}
+
+#[test]
+#[rustfmt::skip]
+fn display_canonical() {
+    for (input, expected) in [
+        ("MIT"                                  , "MIT"                                     ),
+        ("ISC and BSD-2-Clause"                 , "ISC AND BSD-2-Clause"                    ),
+        ("MIT (Apache-2.0 or GPL-2.0-or-later)" , "MIT AND (Apache-2.0 OR GPL-2.0-or-later)"),
+        ("GPL-2.0+ with Linux-syscall-note"     , "GPL-2.0+ WITH Linux-syscall-note"        ),
+    ] {
+        assert!(LicenseExpr::parse(input).unwrap().to_string() == expected);
+    }
+}
+
+#[test]
+fn licenses_in_order() {
+    let expr = LicenseExpr::parse("(MIT OR Apache-2.0) AND Zlib").unwrap();
+    let ids: Vec<_> = expr.licenses().map(|req| req.id.as_str()).collect();
+
+    assert!(ids == ["MIT", "Apache-2.0", "Zlib"]);
+}
+
+#[test]
+fn unknown_and_deprecated() {
+    let expr =
+        LicenseExpr::parse("MIT GPL-2.0 Foo-1.0 LicenseRef-custom Apache-2.0 WITH Bar-exception")
+            .unwrap();
+
+    let unknown: Vec<_> = expr.unknown().map(|req| req.id.as_str()).collect();
+    assert!(unknown == ["Foo-1.0", "Apache-2.0"]);
+
+    let deprecated: Vec<_> = expr.deprecated().map(|req| req.id.as_str()).collect();
+    assert!(deprecated == ["GPL-2.0"]);
+}
+
+#[test]
+fn license_req_is_known() {
+    assert!(LicenseReq::new("GPL-3.0-or-later").is_known());
+    assert!(LicenseReq::new("DocumentRef-spdx:LicenseRef-foo").is_known());
+    assert!(!LicenseReq::new("custom").is_known());
+
+    assert_let!(LicenseExpr::License(req) = LicenseExpr::parse("GPL-2.0-only").unwrap());
+    assert!(!req.is_deprecated());
+}
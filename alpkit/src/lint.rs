@@ -0,0 +1,387 @@
+//! Linting of APKBUILDs, implementing the rules of `apkbuild-lint` from
+//! [atools](https://gitlab.alpinelinux.org/Leo/atools).
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::apkbuild::{is_variable_name, AbuildOption, Apkbuild, Error, VARIABLES_ORDER};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Top-level variables recognized by abuild that are not in
+/// [`VARIABLES_ORDER`].
+const OTHER_KNOWN_VARIABLES: &[&str] = &[
+    "disturl",
+    "giturl",
+    "langdir",
+    "ldpath",
+    "md5sums",
+    "patch_args",
+    "sha256sums",
+    "sha512sums",
+    "somask",
+];
+
+/// Compiler flags variables that should be extended, not overwritten.
+const XFLAGS_VARIABLES: &[&str] = &["CFLAGS", "CPPFLAGS", "CXXFLAGS", "FFLAGS", "LDFLAGS"];
+
+/// The severity of a lint [`Finding`], from the least to the most severe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// A purely stylistic issue.
+    Style,
+    /// An issue that doesn't affect the build result.
+    Minor,
+    /// An issue that should be fixed.
+    Important,
+    /// An issue that likely breaks the build or the package.
+    Serious,
+}
+
+/// A lint rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Rule {
+    /// `builddir` is set to the default value `$srcdir/$pkgname-$pkgver`.
+    DefaultBuilddirValue,
+    /// `|| return 1` is unnecessary, abuild runs functions with `set -e`.
+    UnnecessaryReturn1,
+    /// `pkgname` is quoted.
+    PkgnameQuoted,
+    /// `pkgver` is quoted.
+    PkgverQuoted,
+    /// An integer value (e.g. `pkgrel`) is quoted.
+    IntegerQuoted,
+    /// A variable is set to an empty string.
+    EmptyVariable,
+    /// A custom top-level variable is not prefixed with an underscore.
+    CustomVariable,
+    /// A line is indented with spaces instead of tabs.
+    IndentTabs,
+    /// A line ends with whitespace.
+    TrailingWhitespace,
+    /// A function is declared with the non-POSIX `function` keyword.
+    FunctionKeyword,
+    /// There's a space between the function name and parentheses.
+    SpaceBeforeFunctionParenthesis,
+    /// The opening brace of a function is on a separate line.
+    NewlineOpeningBrace,
+    /// `cd "$builddir"` is unnecessary, abuild runs functions in `builddir`.
+    SuperfluousCdBuilddir,
+    /// `pkgname` contains uppercase characters.
+    PkgnameHasUppercase,
+    /// `pkgver` contains a release suffix (`-r`).
+    PkgverHasRelease,
+    /// Compiler flags (e.g. `CFLAGS`) are overwritten instead of extended.
+    OverwriteXflags,
+    /// `options` contains an option unknown to abuild.
+    InvalidOption,
+    /// `pkgname` doesn't match the name of the APKBUILD's directory.
+    PkgnameDirnameMismatch,
+    /// The APKBUILD has no `# Maintainer:` comment.
+    MissingMaintainer,
+}
+
+impl Rule {
+    pub fn severity(self) -> Severity {
+        use Rule::*;
+
+        match self {
+            TrailingWhitespace
+            | SpaceBeforeFunctionParenthesis
+            | NewlineOpeningBrace
+            | IndentTabs => Severity::Style,
+            DefaultBuilddirValue
+            | UnnecessaryReturn1
+            | PkgnameQuoted
+            | PkgverQuoted
+            | IntegerQuoted
+            | EmptyVariable
+            | SuperfluousCdBuilddir => Severity::Minor,
+            CustomVariable | MissingMaintainer => Severity::Important,
+            FunctionKeyword
+            | PkgnameHasUppercase
+            | PkgverHasRelease
+            | OverwriteXflags
+            | InvalidOption
+            | PkgnameDirnameMismatch => Severity::Serious,
+        }
+    }
+
+    /// Returns the rule name in kebab-case (e.g. `pkgname-quoted`).
+    pub fn name(self) -> &'static str {
+        use Rule::*;
+
+        match self {
+            DefaultBuilddirValue => "default-builddir-value",
+            UnnecessaryReturn1 => "unnecessary-return-1",
+            PkgnameQuoted => "pkgname-quoted",
+            PkgverQuoted => "pkgver-quoted",
+            IntegerQuoted => "integer-quoted",
+            EmptyVariable => "empty-variable",
+            CustomVariable => "custom-variable",
+            IndentTabs => "indent-tabs",
+            TrailingWhitespace => "trailing-whitespace",
+            FunctionKeyword => "function-keyword",
+            SpaceBeforeFunctionParenthesis => "space-before-function-parenthesis",
+            NewlineOpeningBrace => "newline-opening-brace",
+            SuperfluousCdBuilddir => "superfluous-cd-builddir",
+            PkgnameHasUppercase => "pkgname-has-uppercase",
+            PkgverHasRelease => "pkgver-has-release",
+            OverwriteXflags => "overwrite-xflags",
+            InvalidOption => "invalid-option",
+            PkgnameDirnameMismatch => "pkgname-dirname-mismatch",
+            MissingMaintainer => "missing-maintainer",
+        }
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A problem found in the APKBUILD.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Finding {
+    pub rule: Rule,
+
+    pub severity: Severity,
+
+    /// The line number (1-based), if the problem relates to a specific line.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+
+    /// A human-readable description of the problem.
+    pub message: String,
+}
+
+impl Finding {
+    fn new<S: ToString>(rule: Rule, line: Option<usize>, message: S) -> Self {
+        Self {
+            rule,
+            severity: rule.severity(),
+            line,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "{line}: ")?;
+        }
+        write!(f, "[{}] {}", self.rule, self.message)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Reads the APKBUILD file at the given path and lints it together with the
+/// `apkbuild` parsed from it. The name of the parent directory is checked
+/// against the `pkgname`.
+pub fn lint_file<P: AsRef<Path>>(path: P, apkbuild: &Apkbuild) -> Result<Vec<Finding>, Error> {
+    let path = path.as_ref();
+    let text = fs::read_to_string(path).map_err(|e| Error::ReadFile(e, path.to_owned()))?;
+    let dirname = path
+        .canonicalize()
+        .ok()
+        .and_then(|p| p.parent()?.file_name()?.to_str().map(str::to_owned));
+
+    Ok(lint(apkbuild, &text, dirname.as_deref()))
+}
+
+/// Lints the given APKBUILD contents (`text`) and the `apkbuild` parsed from
+/// it. If `dirname` is given, it's checked against the `pkgname`.
+///
+/// Findings are sorted by the line number; findings not related to a specific
+/// line come first.
+pub fn lint(apkbuild: &Apkbuild, text: &str, dirname: Option<&str>) -> Vec<Finding> {
+    let mut findings = Vec::new();
+
+    if apkbuild.maintainer.is_none() {
+        findings.push(Finding::new(
+            Rule::MissingMaintainer,
+            None,
+            "missing '# Maintainer:' comment",
+        ));
+    }
+    if let Some(dirname) = dirname.filter(|d| *d != apkbuild.pkgname) {
+        findings.push(Finding::new(
+            Rule::PkgnameDirnameMismatch,
+            None,
+            format!(
+                "pkgname '{}' doesn't match directory name '{dirname}'",
+                apkbuild.pkgname
+            ),
+        ));
+    }
+    let pkgname_line = assignment_line(text, "pkgname");
+    if apkbuild.pkgname.chars().any(|c| c.is_ascii_uppercase()) {
+        findings.push(Finding::new(
+            Rule::PkgnameHasUppercase,
+            pkgname_line,
+            "pkgname must not contain uppercase characters",
+        ));
+    }
+    if apkbuild.pkgver.contains("-r") {
+        findings.push(Finding::new(
+            Rule::PkgverHasRelease,
+            assignment_line(text, "pkgver"),
+            "pkgver must not contain a release suffix '-r', use pkgrel instead",
+        ));
+    }
+    for opt in &apkbuild.options {
+        if let AbuildOption::Other(name, _) = opt {
+            findings.push(Finding::new(
+                Rule::InvalidOption,
+                assignment_line(text, "options"),
+                format!("unknown option '{name}'"),
+            ));
+        }
+    }
+
+    for (lno, line) in text.lines().enumerate() {
+        lint_line(line, lno + 1, &mut findings);
+    }
+
+    findings.sort_by_key(|f| f.line);
+    findings
+}
+
+fn lint_line(line: &str, lno: usize, findings: &mut Vec<Finding>) {
+    let mut push = |rule, message: &str| findings.push(Finding::new(rule, Some(lno), message));
+    let trimmed = line.trim();
+
+    if line.ends_with([' ', '\t']) {
+        push(Rule::TrailingWhitespace, "trailing whitespace");
+    }
+    if line.starts_with(' ') && !trimmed.is_empty() {
+        push(Rule::IndentTabs, "indent with tabs");
+    }
+    if trimmed.starts_with("function ") {
+        push(
+            Rule::FunctionKeyword,
+            "do not use the 'function' keyword, it's not POSIX",
+        );
+    }
+    if let Some((name, rest)) = trimmed.trim_start_matches("function ").split_once("()") {
+        if is_variable_name(name.trim_end()) {
+            if name.ends_with(' ') {
+                push(
+                    Rule::SpaceBeforeFunctionParenthesis,
+                    "do not use a space between the function name and parentheses",
+                );
+            }
+            if rest.trim().is_empty() {
+                push(
+                    Rule::NewlineOpeningBrace,
+                    "put the opening brace on the same line as the function name",
+                );
+            }
+        }
+    }
+    if trimmed.contains("|| return 1") {
+        push(
+            Rule::UnnecessaryReturn1,
+            "'|| return 1' is not needed, functions are run with 'set -e'",
+        );
+    }
+    if matches!(
+        trimmed,
+        "cd \"$builddir\"" | "cd $builddir" | "cd \"${builddir}\"" | "cd ${builddir}"
+    ) {
+        push(
+            Rule::SuperfluousCdBuilddir,
+            "'cd \"$builddir\"' is not needed, functions are run in builddir",
+        );
+    }
+
+    let assignment = trimmed.strip_prefix("export ").unwrap_or(trimmed);
+    for var in XFLAGS_VARIABLES {
+        if let Some(value) = assignment
+            .strip_prefix(var)
+            .and_then(|s| s.strip_prefix('='))
+        {
+            if !value.contains(&format!("${var}")) && !value.contains(&format!("${{{var}")) {
+                push(
+                    Rule::OverwriteXflags,
+                    &format!("{var} is overwritten, extend it with \"${var} ...\" instead"),
+                );
+            }
+        }
+    }
+
+    // Top-level variables.
+    let (name, value) = match line.split_once('=') {
+        Some((name, value)) if is_variable_name(name) => (name, value),
+        _ => return,
+    };
+    let value = value.split(" #").next().unwrap_or("").trim_end();
+    let is_quoted = value.starts_with(['"', '\'']);
+
+    match name {
+        "pkgname" if is_quoted => push(Rule::PkgnameQuoted, "pkgname must not be quoted"),
+        "pkgver" if is_quoted => push(Rule::PkgverQuoted, "pkgver must not be quoted"),
+        "pkgrel" | "provider_priority" | "replaces_priority"
+            if is_quoted
+                && value.len() > 2
+                && value[1..value.len() - 1]
+                    .chars()
+                    .all(|c| c.is_ascii_digit()) =>
+        {
+            push(Rule::IntegerQuoted, &format!("{name} must not be quoted"))
+        }
+        "builddir"
+            if matches!(
+                value.trim_matches(['"', '\'']),
+                "$srcdir/$pkgname-$pkgver" | "${srcdir}/${pkgname}-${pkgver}"
+            ) =>
+        {
+            push(
+                Rule::DefaultBuilddirValue,
+                "builddir is set to the default value, remove it",
+            )
+        }
+        _ => (),
+    }
+    if matches!(value, "\"\"" | "''") {
+        push(
+            Rule::EmptyVariable,
+            &format!("{name} is set to an empty string, remove it"),
+        );
+    }
+    if !name.starts_with('_')
+        && !name.starts_with("depends_")
+        && !VARIABLES_ORDER.contains(&name)
+        && !OTHER_KNOWN_VARIABLES.contains(&name)
+        && !XFLAGS_VARIABLES.contains(&name)
+    {
+        push(
+            Rule::CustomVariable,
+            &format!("custom variable '{name}' should be prefixed with an underscore"),
+        );
+    }
+}
+
+/// Returns the line number (1-based) of the first top-level assignment of the
+/// given variable.
+fn assignment_line(text: &str, name: &str) -> Option<usize> {
+    text.lines()
+        .position(|line| {
+            line.strip_prefix(name)
+                .map_or(false, |rest| rest.starts_with('='))
+        })
+        .map(|idx| idx + 1)
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+#[path = "lint.test.rs"]
+mod test;
@@ -0,0 +1,109 @@
+use std::fs;
+
+use indoc::indoc;
+
+use super::*;
+use crate::apkbuild::ApkbuildReader;
+use crate::internal::test_utils::{assert, S};
+
+#[test]
+fn lint_sample() {
+    let path = "../fixtures/aports/sample/APKBUILD";
+    let apkbuild = ApkbuildReader::new().read_apkbuild(path).unwrap();
+    let findings = lint_file(path, &apkbuild).unwrap();
+
+    assert!(
+        findings.iter().all(|f| f.severity < Severity::Important),
+        "{findings:?}"
+    );
+}
+
+#[test]
+fn lint_text() {
+    let text = indoc! {r#"
+        pkgname="Foo"
+        pkgver='1.0-r1'
+        pkgrel="0"
+        pkgdesc="foo" 
+        foo=bar
+        install=""
+        options="!check nope"
+        builddir="$srcdir/$pkgname-$pkgver"
+
+        function build () {
+        	cd "$builddir"
+        	export CFLAGS="-O2"
+        	make || return 1
+        }
+
+        package()
+        {
+            make install
+        }
+    "#};
+    let apkbuild = Apkbuild {
+        pkgname: S!("Foo"),
+        pkgver: S!("1.0-r1"),
+        options: vec!["!check".parse().unwrap(), "nope".parse().unwrap()],
+        ..Default::default()
+    };
+    let findings: Vec<_> = lint(&apkbuild, text, Some("foo"))
+        .into_iter()
+        .map(|f| (f.line, f.rule))
+        .collect();
+
+    assert!(
+        findings
+            == vec![
+                (None, Rule::MissingMaintainer),
+                (None, Rule::PkgnameDirnameMismatch),
+                (Some(1), Rule::PkgnameHasUppercase),
+                (Some(1), Rule::PkgnameQuoted),
+                (Some(2), Rule::PkgverHasRelease),
+                (Some(2), Rule::PkgverQuoted),
+                (Some(3), Rule::IntegerQuoted),
+                (Some(4), Rule::TrailingWhitespace),
+                (Some(5), Rule::CustomVariable),
+                (Some(6), Rule::EmptyVariable),
+                (Some(7), Rule::InvalidOption),
+                (Some(8), Rule::DefaultBuilddirValue),
+                (Some(10), Rule::FunctionKeyword),
+                (Some(10), Rule::SpaceBeforeFunctionParenthesis),
+                (Some(11), Rule::SuperfluousCdBuilddir),
+                (Some(12), Rule::OverwriteXflags),
+                (Some(13), Rule::UnnecessaryReturn1),
+                (Some(16), Rule::NewlineOpeningBrace),
+                (Some(18), Rule::IndentTabs),
+            ]
+    );
+}
+
+#[test]
+fn lint_xflags_extended() {
+    let apkbuild = Apkbuild {
+        pkgname: S!("foo"),
+        maintainer: Some(S!("Kevin Flynn <kevin.flynn@encom.com>")),
+        ..Default::default()
+    };
+    let text = "build() {\n\tCFLAGS=\"$CFLAGS -O2\" LDFLAGS=\"${LDFLAGS} -s\" make\n}\n";
+
+    assert!(lint(&apkbuild, text, Some("foo")).is_empty());
+}
+
+#[test]
+fn finding_display() {
+    let finding = Finding::new(Rule::PkgnameQuoted, Some(2), "pkgname must not be quoted");
+
+    assert!(finding.to_string() == "2: [pkgname-quoted] pkgname must not be quoted");
+    assert!(finding.severity == Severity::Minor);
+}
+
+#[test]
+fn lint_file_missing() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("APKBUILD");
+
+    assert!(lint_file(&path, &Apkbuild::default()).is_err());
+    fs::write(&path, "").unwrap();
+    assert!(lint_file(&path, &Apkbuild::default()).is_ok());
+}
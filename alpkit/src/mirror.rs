@@ -0,0 +1,367 @@
+//! Checking whether a local mirror of a repository is in sync with the
+//! upstream (remote) repository.
+//!
+//! The local mirror directory of a single repository arch (e.g.
+//! `/srv/mirror/alpine/edge/main/x86_64`) is compared against the upstream
+//! `APKINDEX.tar.gz`:
+//!
+//! - the index must be identical (the same SHA-256 checksum),
+//! - each package in the index must be present with the size from the index
+//!   and optionally (see [`MirrorChecker::verify_checksums`]) with the
+//!   checksum of the control segment from the index,
+//! - no other files should be in the directory.
+//!
+//! Example:
+//! ```no_run
+//! use alpkit::mirror::MirrorChecker;
+//!
+//! let apkindex = std::fs::read("/tmp/APKINDEX.tar.gz").unwrap();
+//! let report = MirrorChecker::new()
+//!     .verify_checksums(true)
+//!     .check("/srv/mirror/alpine/edge/main/x86_64", &apkindex)
+//!     .unwrap();
+//!
+//! for filename in &report.missing {
+//!     println!("missing: {filename}");
+//! }
+//! ```
+use std::collections::BTreeSet;
+use std::fs::{self, File};
+#[cfg(feature = "http")]
+use std::io::Read;
+use std::io::{self, BufReader};
+use std::path::{Path, PathBuf};
+#[cfg(feature = "http")]
+use std::time::Duration;
+
+use log::debug;
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use thiserror::Error;
+
+use crate::apkbuild::ChecksumAlg;
+use crate::index::{Index, IndexEntry, IndexError};
+use crate::internal::tee_reader::TeeReader;
+use crate::package::Package;
+
+/// The file name of the repository index.
+pub const APKINDEX_FILENAME: &str = "APKINDEX.tar.gz";
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, Error)]
+pub enum MirrorError {
+    #[error("invalid upstream index")]
+    Index(#[from] IndexError),
+
+    #[error("failed to read directory '{0}'")]
+    ReadDir(PathBuf, #[source] io::Error),
+
+    #[cfg(feature = "http")]
+    #[error("failed to download '{1}'")]
+    Download(#[source] Box<ureq::Error>, String),
+
+    #[cfg(feature = "http")]
+    #[error("I/O error occurred")]
+    Io(#[from] io::Error),
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// A result of [`MirrorChecker::check`]. The file names are relative to the
+/// mirror directory and sorted.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct MirrorReport {
+    /// The number of files expected in the mirror (the packages in the index
+    /// and the index itself).
+    pub expected: usize,
+
+    /// Files in the upstream repository that are missing in the mirror.
+    pub missing: Vec<String>,
+
+    /// Files in the mirror that are not in the upstream repository (e.g.
+    /// removed packages, or leftovers of an interrupted rsync).
+    pub extra: Vec<String>,
+
+    /// Files that are present in the mirror, but differ from the upstream.
+    pub corrupt: Vec<Corruption>,
+}
+
+impl MirrorReport {
+    /// Returns `true` if there are no missing, extra or corrupt files.
+    pub fn is_synced(&self) -> bool {
+        self.missing.is_empty() && self.extra.is_empty() && self.corrupt.is_empty()
+    }
+}
+
+/// A file in the mirror that differs from the upstream.
+#[derive(Debug, Clone, PartialEq, Eq, Error, Deserialize, Serialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum Corruption {
+    /// The file size differs from the size in the index (or of the upstream
+    /// index).
+    #[error("{filename}: size mismatch: expected {expected}, but got {actual}")]
+    SizeMismatch {
+        filename: String,
+        expected: u64,
+        actual: u64,
+    },
+
+    /// The checksum differs from the one in the index; it's the `Q1`-prefixed
+    /// SHA-1 of the control segment for packages and the hex-encoded SHA-256
+    /// of the whole file for the index.
+    #[error("{filename}: checksum mismatch: expected '{expected}', but got '{actual}'")]
+    ChecksumMismatch {
+        filename: String,
+        expected: String,
+        actual: String,
+    },
+
+    /// The file cannot be read or it's not a valid package.
+    #[error("{filename}: {error}")]
+    Unreadable { filename: String, error: String },
+}
+
+impl Corruption {
+    pub fn filename(&self) -> &str {
+        match self {
+            Self::SizeMismatch { filename, .. }
+            | Self::ChecksumMismatch { filename, .. }
+            | Self::Unreadable { filename, .. } => filename,
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// A checker of a local mirror of a repository. See the [module
+/// documentation](self).
+#[derive(Debug, Clone)]
+pub struct MirrorChecker {
+    verify_checksums: bool,
+    #[cfg(feature = "http")]
+    agent: ureq::Agent,
+}
+
+impl Default for MirrorChecker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MirrorChecker {
+    pub fn new() -> Self {
+        Self {
+            verify_checksums: false,
+            #[cfg(feature = "http")]
+            agent: ureq::AgentBuilder::new()
+                .timeout_connect(Duration::from_secs(30))
+                .user_agent(concat!("alpkit/", env!("CARGO_PKG_VERSION")))
+                .build(),
+        }
+    }
+
+    /// Whether to verify checksums of the packages (i.e. read the control
+    /// segment of each package), not only their sizes. Default is `false`.
+    pub fn verify_checksums(&mut self, value: bool) -> &mut Self {
+        self.verify_checksums = value;
+        self
+    }
+
+    /// Sets the HTTP client used for downloading the upstream index.
+    #[cfg(feature = "http")]
+    pub fn agent(&mut self, agent: ureq::Agent) -> &mut Self {
+        self.agent = agent;
+        self
+    }
+
+    /// Compares the mirror directory `dir` against the given upstream
+    /// `APKINDEX.tar.gz` (its raw content).
+    pub fn check<P: AsRef<Path>>(
+        &self,
+        dir: P,
+        apkindex: &[u8],
+    ) -> Result<MirrorReport, MirrorError> {
+        let dir = dir.as_ref();
+        debug!("checking mirror {}", dir.display());
+
+        let index = Index::load(apkindex)?;
+        let mut report = MirrorReport {
+            expected: index.entries.len() + 1,
+            ..MirrorReport::default()
+        };
+        let mut expected = BTreeSet::new();
+
+        expected.insert(APKINDEX_FILENAME.to_owned());
+        self.check_index(dir, apkindex, &mut report);
+
+        for entry in &index.entries {
+            let filename = package_filename(entry);
+            self.check_package(dir, &filename, entry, &mut report);
+            expected.insert(filename);
+        }
+
+        let read_dir_err = |e| MirrorError::ReadDir(dir.to_owned(), e);
+        for dentry in fs::read_dir(dir).map_err(read_dir_err)? {
+            let path = dentry.map_err(read_dir_err)?.path();
+            if path.is_dir() {
+                continue;
+            }
+            let filename = path
+                .file_name()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            if !expected.contains(&filename) {
+                report.extra.push(filename);
+            }
+        }
+
+        report.missing.sort();
+        report.extra.sort();
+        report
+            .corrupt
+            .sort_by(|a, b| a.filename().cmp(b.filename()));
+
+        Ok(report)
+    }
+
+    /// Downloads the upstream `APKINDEX.tar.gz` from the repository arch
+    /// directory at the given URL (e.g.
+    /// `https://dl-cdn.alpinelinux.org/alpine/edge/main/x86_64`) and compares
+    /// the mirror directory `dir` against it. See [`MirrorChecker::check`].
+    #[cfg(feature = "http")]
+    pub fn check_remote<P: AsRef<Path>>(
+        &self,
+        dir: P,
+        repo_url: &str,
+    ) -> Result<MirrorReport, MirrorError> {
+        let url = format!("{}/{APKINDEX_FILENAME}", repo_url.trim_end_matches('/'));
+        debug!("downloading {url}");
+
+        let mut apkindex = Vec::new();
+        self.agent
+            .get(&url)
+            .call()
+            .map_err(|e| MirrorError::Download(Box::new(e), url.clone()))?
+            .into_reader()
+            .read_to_end(&mut apkindex)?;
+
+        self.check(dir, &apkindex)
+    }
+
+    fn check_index(&self, dir: &Path, apkindex: &[u8], report: &mut MirrorReport) {
+        let filename = APKINDEX_FILENAME.to_owned();
+
+        let local = match fs::read(dir.join(&filename)) {
+            Ok(data) => data,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                report.missing.push(filename);
+                return;
+            }
+            Err(e) => {
+                report.corrupt.push(Corruption::Unreadable {
+                    filename,
+                    error: e.to_string(),
+                });
+                return;
+            }
+        };
+        if local.len() != apkindex.len() {
+            report.corrupt.push(Corruption::SizeMismatch {
+                filename,
+                expected: apkindex.len() as u64,
+                actual: local.len() as u64,
+            });
+            return;
+        }
+        // Hashing of a slice cannot fail.
+        let expected = ChecksumAlg::Sha256.digest(apkindex).unwrap();
+        let actual = ChecksumAlg::Sha256.digest(local.as_slice()).unwrap();
+        if actual != expected {
+            report.corrupt.push(Corruption::ChecksumMismatch {
+                filename,
+                expected,
+                actual,
+            });
+        }
+    }
+
+    fn check_package(
+        &self,
+        dir: &Path,
+        filename: &str,
+        entry: &IndexEntry,
+        report: &mut MirrorReport,
+    ) {
+        let path = dir.join(filename);
+        let unreadable = |error: String| Corruption::Unreadable {
+            filename: filename.to_owned(),
+            error,
+        };
+
+        let size = match fs::metadata(&path) {
+            Ok(meta) if meta.is_file() => meta.len(),
+            Ok(_) => {
+                report
+                    .corrupt
+                    .push(unreadable("not a regular file".to_owned()));
+                return;
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                report.missing.push(filename.to_owned());
+                return;
+            }
+            Err(e) => {
+                report.corrupt.push(unreadable(e.to_string()));
+                return;
+            }
+        };
+        if size != entry.size {
+            report.corrupt.push(Corruption::SizeMismatch {
+                filename: filename.to_owned(),
+                expected: entry.size,
+                actual: size,
+            });
+            return;
+        }
+        if !self.verify_checksums {
+            return;
+        }
+        match control_checksum(&path) {
+            Ok(checksum) if checksum == entry.checksum => (),
+            Ok(checksum) => report.corrupt.push(Corruption::ChecksumMismatch {
+                filename: filename.to_owned(),
+                expected: entry.checksum.clone(),
+                actual: checksum,
+            }),
+            Err(e) => report.corrupt.push(unreadable(e.to_string())),
+        }
+    }
+}
+
+/// Returns the file name of the package in the repository, i.e.
+/// `<pkgname>-<pkgver>.apk`.
+pub fn package_filename(entry: &IndexEntry) -> String {
+    format!("{}-{}.apk", entry.pkgname, entry.pkgver)
+}
+
+/// Computes the checksum of the package's control segment as in the index,
+/// i.e. the SHA-1 encoded in base64 and prefixed with `Q1`.
+fn control_checksum(path: &Path) -> io::Result<String> {
+    let mut reader = BufReader::new(File::open(path)?);
+    Package::skip_segment(&mut reader)?; // signatures
+
+    let mut reader = TeeReader::new(reader, Sha1::new());
+    Package::skip_segment(&mut reader)?; // control
+
+    Ok(format!(
+        "Q1{}",
+        base64::encode(reader.into_writer().finalize())
+    ))
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+#[path = "mirror.test.rs"]
+mod test;
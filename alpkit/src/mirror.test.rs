@@ -0,0 +1,156 @@
+use flate2::write::GzEncoder;
+use flate2::Compression;
+
+use super::*;
+use crate::internal::test_utils::{assert, assert_let, S};
+
+const FIXTURE: &str = "../fixtures/apk/rssh-2.3.4-r3.apk";
+const FIXTURE_CHECKSUM: &str = "Q1S5yMA1c7xLdsRp1U8A4JZG7XoQ4=";
+const FIXTURE_SIZE: u64 = 20373;
+
+/// Creates an unsigned `APKINDEX.tar.gz` with the given packages (pkgname,
+/// pkgver, size and checksum).
+fn make_apkindex(entries: &[(&str, &str, u64, &str)]) -> Vec<u8> {
+    let apkindex: String = entries
+        .iter()
+        .map(|(pkgname, pkgver, size, checksum)| {
+            format!(
+                "C:{checksum}\nP:{pkgname}\nV:{pkgver}\nA:x86_64\nS:{size}\nI:4096\nT:{pkgname}\n\
+                 U:https://example.org\nL:MIT\no:{pkgname}\nt:1700000000\n\n"
+            )
+        })
+        .collect();
+
+    let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::fast()));
+    let mut header = tar::Header::new_gnu();
+    header.set_size(apkindex.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    builder
+        .append_data(&mut header, "APKINDEX", apkindex.as_bytes())
+        .unwrap();
+
+    builder.into_inner().unwrap().finish().unwrap()
+}
+
+fn sample_apkindex() -> Vec<u8> {
+    make_apkindex(&[
+        ("rssh", "2.3.4-r3", FIXTURE_SIZE, FIXTURE_CHECKSUM),
+        ("missing", "1.0-r0", 100, "Q1AAAAAAAAAAAAAAAAAAAAAAAAAAA="),
+        ("truncated", "1.0-r0", 10, "Q1AAAAAAAAAAAAAAAAAAAAAAAAAAA="),
+        (
+            "tampered",
+            "1.0-r0",
+            FIXTURE_SIZE,
+            "Q1AAAAAAAAAAAAAAAAAAAAAAAAAAA=",
+        ),
+    ])
+}
+
+fn sample_mirror(apkindex: &[u8]) -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path();
+
+    fs::write(path.join(APKINDEX_FILENAME), apkindex).unwrap();
+    fs::copy(FIXTURE, path.join("rssh-2.3.4-r3.apk")).unwrap();
+    fs::write(path.join("truncated-1.0-r0.apk"), "12345").unwrap();
+    fs::copy(FIXTURE, path.join("tampered-1.0-r0.apk")).unwrap();
+    fs::write(path.join("removed-0.9-r0.apk"), "").unwrap();
+    fs::create_dir(path.join("subdir")).unwrap();
+
+    dir
+}
+
+#[test]
+fn control_checksum_of_package() {
+    assert!(control_checksum(Path::new(FIXTURE)).unwrap() == FIXTURE_CHECKSUM);
+}
+
+#[test]
+fn check_sizes() {
+    let apkindex = sample_apkindex();
+    let mirror = sample_mirror(&apkindex);
+
+    let report = MirrorChecker::new()
+        .check(mirror.path(), &apkindex)
+        .unwrap();
+
+    assert!(
+        report
+            == MirrorReport {
+                expected: 5,
+                missing: vec![S!("missing-1.0-r0.apk")],
+                extra: vec![S!("removed-0.9-r0.apk")],
+                corrupt: vec![Corruption::SizeMismatch {
+                    filename: S!("truncated-1.0-r0.apk"),
+                    expected: 10,
+                    actual: 5,
+                }],
+            }
+    );
+    assert!(!report.is_synced());
+}
+
+#[test]
+fn check_with_checksums() {
+    let apkindex = sample_apkindex();
+    let mirror = sample_mirror(&apkindex);
+
+    let report = MirrorChecker::new()
+        .verify_checksums(true)
+        .check(mirror.path(), &apkindex)
+        .unwrap();
+
+    assert!(
+        report.corrupt
+            == [
+                Corruption::ChecksumMismatch {
+                    filename: S!("tampered-1.0-r0.apk"),
+                    expected: S!("Q1AAAAAAAAAAAAAAAAAAAAAAAAAAA="),
+                    actual: S!(FIXTURE_CHECKSUM),
+                },
+                Corruption::SizeMismatch {
+                    filename: S!("truncated-1.0-r0.apk"),
+                    expected: 10,
+                    actual: 5,
+                },
+            ]
+    );
+}
+
+#[test]
+fn check_outdated_index() {
+    let apkindex = make_apkindex(&[("rssh", "2.3.4-r3", FIXTURE_SIZE, FIXTURE_CHECKSUM)]);
+    let mirror = tempfile::tempdir().unwrap();
+    fs::copy(FIXTURE, mirror.path().join("rssh-2.3.4-r3.apk")).unwrap();
+
+    let report = MirrorChecker::new()
+        .check(mirror.path(), &apkindex)
+        .unwrap();
+    assert!(report.missing == [APKINDEX_FILENAME]);
+
+    let mut outdated = apkindex.clone();
+    outdated[4] ^= 0xff; // the gzip mtime
+    fs::write(mirror.path().join(APKINDEX_FILENAME), outdated).unwrap();
+
+    let report = MirrorChecker::new()
+        .check(mirror.path(), &apkindex)
+        .unwrap();
+    assert_let!([Corruption::ChecksumMismatch { filename, .. }] = report.corrupt.as_slice());
+    assert!(filename == APKINDEX_FILENAME);
+
+    fs::write(mirror.path().join(APKINDEX_FILENAME), &apkindex).unwrap();
+
+    let report = MirrorChecker::new()
+        .verify_checksums(true)
+        .check(mirror.path(), &apkindex)
+        .unwrap();
+    assert!(report.is_synced());
+}
+
+#[test]
+fn check_invalid_index() {
+    let mirror = tempfile::tempdir().unwrap();
+
+    assert_let!(Err(MirrorError::Index(_)) = MirrorChecker::new().check(mirror.path(), b"foo"));
+}
@@ -0,0 +1,138 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use log::warn;
+use rayon::prelude::*;
+use rayon::ThreadPoolBuilder;
+
+use super::{Error, Package};
+
+/// Loads the packages at the given paths in parallel and returns the results
+/// in the order of the `paths`.
+///
+/// The packages are read in the current rayon thread pool, i.e. by at most
+/// as many threads as there are CPUs by default. To limit the concurrency
+/// (e.g. the number of open files), use [`BatchLoader::threads`] or call
+/// this function inside [`rayon::ThreadPool::install`] of a pool with fewer
+/// threads.
+///
+/// Example:
+/// ```no_run
+/// use alpkit::package::load_many;
+///
+/// let paths = ["foo-1.0-r0.apk", "bar-2.0-r0.apk"];
+/// for (path, result) in paths.iter().zip(load_many(&paths)) {
+///     match result {
+///         Ok(pkg) => println!("{path}: {}", pkg.pkginfo().pkgname),
+///         Err(e) => eprintln!("{path}: {e}"),
+///     }
+/// }
+/// ```
+pub fn load_many<P: AsRef<Path> + Sync>(paths: &[P]) -> Vec<Result<Package, Error>> {
+    BatchLoader::new().load(paths)
+}
+
+/// A configurable loader of multiple packages in parallel, e.g. for scanning
+/// a local repository directory.
+///
+/// Example:
+/// ```no_run
+/// use alpkit::package::BatchLoader;
+///
+/// let paths = ["foo-1.0-r0.apk", "bar-2.0-r0.apk"];
+/// let results = BatchLoader::new()
+///     .threads(4)
+///     .without_files(true)
+///     .on_progress(|path| eprintln!("read {}", path.display()))
+///     .load(&paths);
+/// ```
+#[derive(Default)]
+pub struct BatchLoader<'a> {
+    threads: usize,
+    without_files: bool,
+    on_progress: Option<Box<ProgressCallback<'a>>>,
+}
+
+type ProgressCallback<'a> = dyn Fn(&Path) + Sync + 'a;
+
+impl<'a> BatchLoader<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the number of threads used for reading the packages. The default
+    /// is 0, which means the current rayon thread pool (by default as many
+    /// threads as there are CPUs).
+    pub fn threads(&mut self, count: usize) -> &mut Self {
+        self.threads = count;
+        self
+    }
+
+    /// Sets if the packages should be loaded without files, i.e. only the
+    /// signatures and control segments are read (see
+    /// [`Package::load_without_files`]). This is much faster for bigger
+    /// packages.
+    pub fn without_files(&mut self, cond: bool) -> &mut Self {
+        self.without_files = cond;
+        self
+    }
+
+    /// Sets a callback called with the path of each package after it has
+    /// been read (successfully or not). It's called from the worker threads,
+    /// i.e. not in the order of the paths.
+    pub fn on_progress<F>(&mut self, callback: F) -> &mut Self
+    where
+        F: Fn(&Path) + Sync + 'a,
+    {
+        self.on_progress = Some(Box::new(callback));
+        self
+    }
+
+    /// Loads the packages at the given paths and returns the results in the
+    /// order of the `paths`.
+    pub fn load<P: AsRef<Path> + Sync>(&self, paths: &[P]) -> Vec<Result<Package, Error>> {
+        if self.threads == 0 {
+            return self.load_in_current_pool(paths);
+        }
+        match ThreadPoolBuilder::new().num_threads(self.threads).build() {
+            Ok(pool) => pool.install(|| self.load_in_current_pool(paths)),
+            Err(e) => {
+                warn!("failed to create thread pool, using the current one: {e}");
+                self.load_in_current_pool(paths)
+            }
+        }
+    }
+
+    fn load_in_current_pool<P: AsRef<Path> + Sync>(
+        &self,
+        paths: &[P],
+    ) -> Vec<Result<Package, Error>> {
+        paths
+            .par_iter()
+            .map(|path| {
+                let path = path.as_ref();
+                let result = File::open(path)
+                    .map_err(Error::from)
+                    .map(BufReader::new)
+                    .and_then(|reader| {
+                        if self.without_files {
+                            Package::load_without_files(reader)
+                        } else {
+                            Package::load(reader)
+                        }
+                    });
+                if let Some(on_progress) = &self.on_progress {
+                    on_progress(path);
+                }
+                result
+            })
+            .collect()
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+#[path = "batch.test.rs"]
+mod test;
@@ -0,0 +1,51 @@
+use super::*;
+use crate::internal::test_utils::{assert, assert_let};
+
+#[test]
+fn load_many_keeps_order() {
+    let paths = [
+        "../fixtures/apk/rssh-2.3.4-r3-signed.apk",
+        "../fixtures/apk/missing.apk",
+        "../fixtures/apk/rssh-2.3.4-r3.apk",
+    ];
+    let results = load_many(&paths);
+
+    assert!(results.len() == 3);
+    assert_let!(Ok(pkg) = &results[0]);
+    assert!(pkg.signatures().next().unwrap().keyname == "alpkit-test-5f3a9c2e.rsa.pub");
+    assert_let!(Err(Error::Io(_)) = &results[1]);
+    assert_let!(Ok(pkg) = &results[2]);
+    assert!(pkg
+        .signatures()
+        .next()
+        .unwrap()
+        .keyname
+        .starts_with("alpine-devel@"));
+}
+
+#[test]
+fn batch_loader_without_files() {
+    use std::sync::Mutex;
+
+    let paths = [
+        "../fixtures/apk/rssh-2.3.4-r3.apk",
+        "../fixtures/apk/missing.apk",
+    ];
+    let progress = Mutex::new(Vec::new());
+
+    let results = BatchLoader::new()
+        .threads(1)
+        .without_files(true)
+        .on_progress(|path| progress.lock().unwrap().push(path.to_owned()))
+        .load(&paths);
+
+    assert!(results.len() == 2);
+    assert_let!(Ok(pkg) = &results[0]);
+    assert!(pkg.pkginfo().pkgname == "rssh");
+    assert!(pkg.files_metadata().len() == 0);
+    assert_let!(Err(Error::Io(_)) = &results[1]);
+
+    let mut progress = progress.into_inner().unwrap();
+    progress.sort();
+    assert!(progress == paths.iter().rev().map(Path::new).collect::<Vec<_>>());
+}
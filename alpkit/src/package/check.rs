@@ -0,0 +1,128 @@
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{FileType, Package, PkgScript};
+use crate::dependency::Op;
+
+/// A block size used to estimate the installed size of the package.
+const BLOCK_SIZE: u64 = 4096;
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// An internal inconsistency of a package found by [`Package::check`].
+#[derive(Debug, Clone, PartialEq, Eq, Error, Deserialize, Serialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum Finding {
+    /// The declared installed-size is smaller than the total size of the
+    /// files, or bigger than their estimated disk usage.
+    #[error("declared size {declared} doesn't match the size of the files: {min}..{max}")]
+    SizeMismatch { declared: u64, min: u64, max: u64 },
+
+    /// The declared datahash doesn't match the digest of the data segment.
+    #[error("datahash mismatch: declared '{declared}', but the data hash is '{actual}'")]
+    DatahashMismatch { declared: String, actual: String },
+
+    /// The package declares triggers, but it doesn't contain a trigger script.
+    #[error("triggers declared, but the trigger script is missing")]
+    MissingTriggerScript,
+
+    /// The package provides itself (or a command) in a version that doesn't
+    /// match its `pkgver`.
+    #[error("provides '{provider}' doesn't match the package version '{pkgver}'")]
+    ProvidesVersionMismatch { provider: String, pkgver: String },
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+impl Package {
+    /// Checks if the package metadata (`.PKGINFO`) are consistent with the
+    /// package contents and returns all findings (an empty vec if it's
+    /// consistent).
+    ///
+    /// The size and datahash are checked only if the package has been loaded
+    /// with the data hash (i.e. via [`Package::load_with_datahash`]), because
+    /// it requires hashing the whole data segment. Since the declared
+    /// size is the disk usage of the files as reported by `du` on the build
+    /// host, it's checked only to be within the range between the total size
+    /// of the files and their disk usage estimated for 4 KiB blocks.
+    ///
+    /// Example:
+    /// ```no_run
+    /// # use std::fs::File;
+    /// # use std::io::BufReader;
+    /// use alpkit::package::Package;
+    ///
+    /// let file = File::open("example-1.0-r0.apk").map(BufReader::new).unwrap();
+    /// let pkg = Package::load_with_datahash(file).unwrap();
+    ///
+    /// for finding in pkg.check() {
+    ///     println!("{finding}");
+    /// }
+    /// ```
+    pub fn check(&self) -> Vec<Finding> {
+        let pkginfo = &self.pkginfo;
+        let mut findings = Vec::new();
+
+        if let Some(data_hash) = &self.data_hash {
+            let (min, max) = self.files_size();
+            let declared = pkginfo.size as u64;
+
+            if declared < min || declared > max {
+                findings.push(Finding::SizeMismatch { declared, min, max });
+            }
+            if !pkginfo.datahash.eq_ignore_ascii_case(data_hash) {
+                findings.push(Finding::DatahashMismatch {
+                    declared: pkginfo.datahash.clone(),
+                    actual: data_hash.clone(),
+                });
+            }
+        }
+
+        if !pkginfo.triggers.is_empty() && !self.scripts.contains(&PkgScript::Trigger) {
+            findings.push(Finding::MissingTriggerScript);
+        }
+
+        for provider in &pkginfo.provides {
+            if provider.name != pkginfo.pkgname && !provider.name.starts_with("cmd:") {
+                continue;
+            }
+            if let Some(constraint) = &provider.constraint {
+                if constraint.op != Op::Equal || constraint.version != pkginfo.pkgver {
+                    findings.push(Finding::ProvidesVersionMismatch {
+                        provider: provider.to_string(),
+                        pkgver: pkginfo.pkgver.clone(),
+                    });
+                }
+            }
+        }
+
+        findings
+    }
+
+    /// Returns the total size of the files and their estimated disk usage (the
+    /// size rounded up to whole blocks, one block per directory including the
+    /// root directory).
+    fn files_size(&self) -> (u64, u64) {
+        self.files
+            .iter()
+            .fold((0, BLOCK_SIZE), |(size, usage), file| {
+                match file.file_type {
+                    FileType::Regular => {
+                        let len = file.size.unwrap_or(0);
+                        (
+                            size + len,
+                            usage + (len + BLOCK_SIZE - 1) / BLOCK_SIZE * BLOCK_SIZE,
+                        )
+                    }
+                    FileType::Directory => (size, usage + BLOCK_SIZE),
+                    _ => (size, usage),
+                }
+            })
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+#[path = "check.test.rs"]
+mod test;
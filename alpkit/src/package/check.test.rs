@@ -0,0 +1,78 @@
+use std::fs::File;
+use std::io::BufReader;
+
+use super::*;
+use crate::internal::test_utils::{assert, dependency, load_fixture, S};
+
+fn load_with_datahash() -> Package {
+    let file = File::open("../fixtures/apk/rssh-2.3.4-r3.apk").unwrap();
+    Package::load_with_datahash(BufReader::new(file)).unwrap()
+}
+
+#[test]
+fn check_fixture() {
+    assert!(load_with_datahash().check() == vec![]);
+}
+
+#[test]
+fn check_without_datahash() {
+    let mut pkg = load_fixture("rssh-2.3.4-r3.apk");
+    pkg.pkginfo.size = 0;
+    pkg.pkginfo.datahash = S!("abc");
+
+    // Size and datahash are checked only with the data hash.
+    assert!(pkg.check() == vec![]);
+}
+
+#[test]
+fn check_without_files() {
+    let file = File::open("../fixtures/apk/rssh-2.3.4-r3.apk").unwrap();
+    let mut pkg = Package::load_without_files(BufReader::new(file)).unwrap();
+    pkg.pkginfo.size = 0;
+    pkg.pkginfo.datahash = S!("abc");
+
+    // Size and datahash cannot be checked without the data segment.
+    assert!(pkg.check() == vec![]);
+}
+
+#[test]
+fn check_filtered() {
+    let file = File::open("../fixtures/apk/rssh-2.3.4-r3.apk").unwrap();
+    let pkg =
+        Package::load_filtered(BufReader::new(file), |path| path.starts_with("/usr/bin")).unwrap();
+
+    assert!(pkg.check() == vec![]);
+}
+
+#[test]
+fn check_inconsistent() {
+    let mut pkg = load_with_datahash();
+    pkg.pkginfo.size = 1024;
+    pkg.pkginfo.datahash = S!("da39a3ee5e6b4b0d3255bfef95601890afd80709");
+    pkg.pkginfo.triggers = vec![S!("/usr/share/rssh")];
+    pkg.pkginfo.provides = vec![
+        dependency("cmd:rssh=2.3.4-r2"),
+        dependency("rssh=2.3.4-r3"),
+        dependency("so:libfoo.so.1=1.2.3"),
+    ];
+
+    assert!(
+        pkg.check()
+            == vec![
+                Finding::SizeMismatch {
+                    declared: 1024,
+                    min: 54935,
+                    max: 86016,
+                },
+                Finding::DatahashMismatch {
+                    declared: S!("da39a3ee5e6b4b0d3255bfef95601890afd80709"),
+                    actual: S!("db62becd32465838640f39bd35854bd03e9b5e56b1ea8574e9188c3910121477"),
+                },
+                Finding::MissingTriggerScript,
+                Finding::ProvidesVersionMismatch {
+                    provider: S!("cmd:rssh=2.3.4-r2"),
+                    pkgver: S!("2.3.4-r3"),
+                },
+            ]
+    );
+}
@@ -1,5 +1,6 @@
 use std::borrow::Cow;
 use std::error;
+use std::ffi::OsString;
 use std::io::{self, Read};
 use std::path::PathBuf;
 
@@ -13,6 +14,7 @@ use crate::internal::macros::bail;
 /// This struct represents a file (in general sense, so also a directory) in
 /// an APK package archive.
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schema-gen", derive(schemars::JsonSchema))]
 pub struct FileInfo {
     /// An absolute path of the file.
     pub path: PathBuf,
@@ -43,6 +45,10 @@ pub struct FileInfo {
         deserialize_with = "deserialize_mode",
         serialize_with = "serialize_mode"
     )]
+    #[cfg_attr(
+        feature = "schema-gen",
+        schemars(with = "String", regex(pattern = r"^0[0-7]+$"))
+    )]
     pub mode: u32,
 
     /// The device ID (combined major and minor ID), if this file is a block or
@@ -60,6 +66,7 @@ pub struct FileInfo {
         with = "key_value_vec_map",
         skip_serializing_if = "Vec::is_empty"
     )]
+    #[cfg_attr(feature = "schema-gen", schemars(with = "crate::schema::Xattrs"))]
     pub xattrs: Vec<Xattr>,
 }
 
@@ -83,14 +90,28 @@ impl Default for FileInfo {
 impl<'a, R: Read> TryFrom<tar::Entry<'a, R>> for FileInfo {
     type Error = io::Error;
 
-    fn try_from(mut entry: tar::Entry<'a, R>) -> Result<Self, Self::Error> {
+    fn try_from(entry: tar::Entry<'a, R>) -> Result<Self, Self::Error> {
+        let mut path = OsString::new();
+        entry_abs_path(&entry, &mut path)?;
+
+        Self::from_entry(entry, path.into())
+    }
+}
+
+impl FileInfo {
+    /// Creates a `FileInfo` from the tar entry with the given absolute `path`
+    /// (see [`entry_abs_path`]).
+    pub(crate) fn from_entry<R: Read>(
+        mut entry: tar::Entry<'_, R>,
+        path: PathBuf,
+    ) -> io::Result<Self> {
         use crate::internal::tar_ext::*;
 
         let header = entry.header();
         let is_dir = header.entry_type().is_dir();
 
         Ok(FileInfo {
-            path: PathBuf::from("/").join(entry.path()?),
+            path,
             file_type: header.entry_type().try_into()?,
             link_target: entry.link_name()?.map(Cow::into_owned),
             uname: header
@@ -112,6 +133,22 @@ impl<'a, R: Read> TryFrom<tar::Entry<'a, R>> for FileInfo {
     }
 }
 
+/// Writes the absolute path of the tar entry into the `buf`, replacing its
+/// content. This allows to reuse the buffer for all the entries.
+pub(crate) fn entry_abs_path<R: Read>(
+    entry: &tar::Entry<'_, R>,
+    buf: &mut OsString,
+) -> io::Result<()> {
+    let path = entry.path()?;
+    buf.clear();
+    if !path.has_root() {
+        buf.push("/");
+    }
+    buf.push(path.as_os_str());
+
+    Ok(())
+}
+
 fn root() -> String {
     "root".to_owned()
 }
@@ -124,11 +161,16 @@ fn is_zero(num: &u64) -> bool {
     num == &0
 }
 
-fn serialize_mode<S: serde::Serializer>(value: &u32, serializer: S) -> Result<S::Ok, S::Error> {
+pub(crate) fn serialize_mode<S: serde::Serializer>(
+    value: &u32,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
     serializer.serialize_str(&format!("0{value:o}"))
 }
 
-fn deserialize_mode<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+pub(crate) fn deserialize_mode<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<u32, D::Error> {
     let s: &str = Deserialize::deserialize(deserializer)?;
     u32::from_str_radix(s, 8)
         .map_err(|_| de::Error::custom(format!("invalid value: `{s}`, expected octal number")))
@@ -137,6 +179,7 @@ fn deserialize_mode<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result
 ////////////////////////////////////////////////////////////////////////////////
 
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schema-gen", derive(schemars::JsonSchema))]
 pub enum FileType {
     /// Regular file
     #[serde(rename = "r")]
@@ -167,6 +210,23 @@ pub enum FileType {
     Fifo,
 }
 
+impl FileType {
+    /// Returns the one-letter code of the file type as used in the serialized
+    /// form, e.g. `r` for a regular file.
+    #[cfg(any(feature = "parquet", feature = "sqlite"))]
+    pub(crate) fn code(&self) -> &'static str {
+        match self {
+            FileType::Regular => "r",
+            FileType::Link => "H",
+            FileType::Symlink => "l",
+            FileType::Char => "c",
+            FileType::Block => "b",
+            FileType::Directory => "d",
+            FileType::Fifo => "p",
+        }
+    }
+}
+
 impl TryFrom<tar::EntryType> for FileType {
     type Error = io::Error;
 
@@ -192,8 +252,12 @@ impl TryFrom<tar::EntryType> for FileType {
 ////////////////////////////////////////////////////////////////////////////////
 
 #[derive(Debug, Deserialize, PartialEq, Serialize)]
+#[cfg_attr(feature = "schema-gen", derive(schemars::JsonSchema))]
 pub struct Xattr {
+    /// The attribute name, including the namespace (e.g. `security.capability`).
     pub name: String,
+
+    /// The attribute value.
     pub value: Vec<u8>,
 }
 
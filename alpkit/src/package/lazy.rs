@@ -0,0 +1,152 @@
+use std::io::{self, BufRead};
+use std::mem;
+use std::slice::Iter;
+
+use super::{Error, FileInfo, Package, PkgInfo, PkgScript, SignatureInfo};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// A handle to an APK package that reads the package segments only when
+/// they're first needed, i.e. the signatures on the first call of
+/// [`signatures`](LazyPackage::signatures), the control segment on the first
+/// call of [`pkginfo`](LazyPackage::pkginfo) or
+/// [`scripts`](LazyPackage::scripts), and the data segment on the first call
+/// of [`files_metadata`](LazyPackage::files_metadata).
+///
+/// This is useful if you cannot decide upfront between [`Package::load`] and
+/// [`Package::load_without_files`]. Since the segments are read sequentially
+/// from the reader, reading a later segment reads all the preceding ones as
+/// well. If reading fails, all subsequent calls fail too.
+///
+/// Example:
+/// ```no_run
+/// # use std::fs::File;
+/// # use std::io::BufReader;
+/// use alpkit::package::LazyPackage;
+///
+/// let file = File::open("example-1.0-r0.apk").map(BufReader::new).unwrap();
+/// let mut pkg = LazyPackage::new(file);
+///
+/// // Only the signature and control segments are read.
+/// if pkg.pkginfo().unwrap().pkgname.ends_with("-doc") {
+///     // Now the data segment is read.
+///     println!("{} files", pkg.files_metadata().unwrap().len());
+/// }
+/// ```
+pub struct LazyPackage<R> {
+    reader: R,
+    state: State,
+}
+
+/// The segments that have been read so far.
+enum State {
+    Initial,
+    Signatures(Vec<SignatureInfo>, Vec<Vec<u8>>),
+    Control(Package),
+    Complete(Package),
+    Failed,
+}
+
+impl<R: BufRead> LazyPackage<R> {
+    /// Creates a handle to the package read from the given buffered reader
+    /// over an APKv2 file. Nothing is read until an accessor is called.
+    pub fn new(reader: R) -> Self {
+        LazyPackage {
+            reader,
+            state: State::Initial,
+        }
+    }
+
+    pub fn signatures(&mut self) -> Result<Iter<'_, SignatureInfo>, Error> {
+        if matches!(self.state, State::Initial | State::Failed) {
+            self.advance()?;
+        }
+        match &self.state {
+            State::Signatures(signs, _) => Ok(signs.iter()),
+            State::Control(pkg) | State::Complete(pkg) => Ok(pkg.signatures()),
+            State::Initial | State::Failed => unreachable!(),
+        }
+    }
+
+    pub fn pkginfo(&mut self) -> Result<&PkgInfo, Error> {
+        Ok(self.package_without_files()?.pkginfo())
+    }
+
+    pub fn scripts(&mut self) -> Result<Iter<'_, PkgScript>, Error> {
+        Ok(self.package_without_files()?.scripts())
+    }
+
+    /// Returns the content of the given install script, or `None` if the
+    /// package doesn't contain such script.
+    pub fn script_content(&mut self, script: &PkgScript) -> Result<Option<&str>, Error> {
+        Ok(self.package_without_files()?.script_content(script))
+    }
+
+    pub fn files_metadata(&mut self) -> Result<Iter<'_, FileInfo>, Error> {
+        while !matches!(self.state, State::Complete(_)) {
+            self.advance()?;
+        }
+        match &self.state {
+            State::Complete(pkg) => Ok(pkg.files_metadata()),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Reads all the remaining segments and returns the complete `Package`.
+    pub fn into_package(mut self) -> Result<Package, Error> {
+        while !matches!(self.state, State::Complete(_)) {
+            self.advance()?;
+        }
+        match self.state {
+            State::Complete(pkg) => Ok(pkg),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Returns the package with the control segment read, but not
+    /// necessarily the data segment.
+    fn package_without_files(&mut self) -> Result<&Package, Error> {
+        while matches!(
+            self.state,
+            State::Initial | State::Signatures(..) | State::Failed
+        ) {
+            self.advance()?;
+        }
+        match &self.state {
+            State::Control(pkg) | State::Complete(pkg) => Ok(pkg),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Reads the next segment. It must not be called in the `Complete` state.
+    fn advance(&mut self) -> Result<(), Error> {
+        let reader = &mut self.reader;
+
+        let result = match mem::replace(&mut self.state, State::Failed) {
+            State::Initial => Package::read_signatures(reader).map(|signs| {
+                let (signs, sign_contents) = signs.into_iter().unzip();
+                State::Signatures(signs, sign_contents)
+            }),
+            State::Signatures(signs, sign_contents) => {
+                Package::load_control(reader, signs, sign_contents).map(State::Control)
+            }
+            State::Control(mut pkg) => pkg
+                .load_files(reader, |_| true)
+                .map(|_| State::Complete(pkg)),
+            State::Complete(_) => unreachable!(),
+            State::Failed => Err(Error::Io(io::Error::new(
+                io::ErrorKind::Other,
+                "reading of the package has failed before",
+            ))),
+        };
+        self.state = result?;
+
+        Ok(())
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+#[path = "lazy.test.rs"]
+mod test;
@@ -0,0 +1,40 @@
+use std::fs;
+
+use super::*;
+use crate::internal::test_utils::{assert, assert_let, open_fixture};
+
+#[test]
+fn lazy_package_reads_segments_on_demand() {
+    // The data segment is missing, but it's not needed for pkginfo.
+    let mut data = fs::read("../fixtures/apk/rssh-2.3.4-r3.apk").unwrap();
+    let len = data.len();
+    data.truncate(len - 1000);
+
+    let mut pkg = LazyPackage::new(&data[..]);
+
+    assert_let!(Ok(signs) = pkg.signatures());
+    assert!(signs.len() == 1);
+    assert_let!(Ok(pkginfo) = pkg.pkginfo());
+    assert!(pkginfo.pkgname == "rssh");
+    assert!(pkg.scripts().unwrap().len() == 2);
+
+    assert_let!(Err(_) = pkg.files_metadata());
+    // All subsequent reads fail.
+    assert_let!(Err(Error::Io(_)) = pkg.files_metadata());
+    assert_let!(Err(Error::Io(_)) = pkg.pkginfo());
+}
+
+#[test]
+fn lazy_package_into_package() {
+    let mut lazy = LazyPackage::new(open_fixture("rssh-2.3.4-r3.apk"));
+    assert!(lazy.pkginfo().unwrap().pkgname == "rssh");
+
+    assert_let!(Ok(pkg) = lazy.into_package());
+    let full = Package::load(open_fixture("rssh-2.3.4-r3.apk")).unwrap();
+
+    assert!(pkg.files_metadata().eq(full.files_metadata()));
+    assert!(pkg.signatures().eq(full.signatures()));
+    assert!(pkg.data_hash == full.data_hash);
+    #[cfg(feature = "signature")]
+    assert!(pkg.control_segment == full.control_segment);
+}
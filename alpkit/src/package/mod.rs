@@ -1,25 +1,51 @@
+#[cfg(feature = "rayon")]
+mod batch;
+mod check;
 mod fileinfo;
+mod lazy;
 mod pkginfo;
-
-use std::io::{self, BufRead, Read};
-use std::path::Path;
+#[cfg(feature = "http")]
+mod remote;
+#[cfg(feature = "signature")]
+mod signature;
+mod stats;
+
+use std::ffi::OsString;
+use std::fmt;
+use std::io::{self, BufRead, Read, Write};
+use std::path::{Path, PathBuf};
 use std::slice::Iter;
 use std::str::{self, FromStr};
 
 use flate2::bufread::GzDecoder;
+use log::{debug, trace};
 use serde::{de, Deserialize, Serialize};
-use tar::Archive;
+use tar::{Archive, EntryType};
 use thiserror::Error;
 
+use crate::apkbuild::{ChecksumAlg, Hasher};
 use crate::internal::macros::bail;
+use crate::internal::tee_reader::TeeReader;
 
+#[cfg(feature = "rayon")]
+pub use batch::*;
+pub use check::*;
 pub use fileinfo::*;
+pub use lazy::*;
 pub use pkginfo::*;
+#[cfg(feature = "http")]
+pub use remote::*;
+#[cfg(feature = "signature")]
+pub use signature::*;
+pub use stats::*;
 
 ////////////////////////////////////////////////////////////////////////////////
 
 #[derive(Debug, Error)]
 pub enum Error {
+    #[error("file not found in .apk: '{0}'")]
+    FileNotFound(PathBuf),
+
     #[error("invalid .PKGINFO")]
     InvalidPkginfo(#[from] PkgInfoError),
 
@@ -31,21 +57,45 @@ pub enum Error {
 
     #[error("no signatures found in .apk")]
     MissingSignature,
+
+    #[error("not a regular file: '{0}'")]
+    NotRegularFile(PathBuf),
 }
 
 ////////////////////////////////////////////////////////////////////////////////
 
+/// An APK package: its signatures, metadata (`.PKGINFO`), install scripts and
+/// files.
 #[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "schema-gen", derive(schemars::JsonSchema))]
 pub struct Package {
     signs: Vec<SignatureInfo>,
 
+    /// Contents of the signature files (in the same order as `signs`).
+    #[serde(skip)]
+    #[cfg_attr(not(feature = "signature"), allow(dead_code))]
+    sign_contents: Vec<Vec<u8>>,
+
+    /// The raw (compressed) control segment, i.e. the signed data.
+    #[cfg(feature = "signature")]
+    #[serde(skip)]
+    control_segment: Vec<u8>,
+
     #[serde(flatten)]
     pkginfo: PkgInfo,
 
     #[serde(default)]
     scripts: Vec<PkgScript>,
 
+    /// Contents of the `scripts` (in the same order).
+    #[serde(skip)]
+    script_contents: Vec<String>,
+
     files: Vec<FileInfo>,
+
+    /// The SHA-256 digest of the package data segment, if it has been read.
+    #[serde(skip)]
+    data_hash: Option<String>,
 }
 
 // The package file consists of three gzip streams concatenated together, each
@@ -66,9 +116,54 @@ impl Package {
     /// let file = File::open("example-1.0-r0.apk").map(BufReader::new).unwrap();
     /// let pkg = Package::load(file).unwrap();
     /// ```
-    pub fn load<R: BufRead>(mut reader: R) -> Result<Self, Error> {
+    pub fn load<R: BufRead>(reader: R) -> Result<Self, Error> {
+        Self::load_filtered(reader, |_| true)
+    }
+
+    /// Loads a `Package` from the given buffered reader over an APKv2 file, as
+    /// the `load` method, and also computes the SHA-256 digest of the package
+    /// data segment. This is needed for checking the size and datahash by
+    /// [`Package::check`].
+    ///
+    /// Example:
+    /// ```no_run
+    /// # use std::fs::File;
+    /// # use std::io::BufReader;
+    /// use alpkit::package::Package;
+    ///
+    /// let file = File::open("example-1.0-r0.apk").map(BufReader::new).unwrap();
+    /// let pkg = Package::load_with_datahash(file).unwrap();
+    /// assert!(pkg.check().is_empty());
+    /// ```
+    pub fn load_with_datahash<R: BufRead>(mut reader: R) -> Result<Self, Error> {
         let mut pkg = Self::load_without_files(&mut reader)?;
-        pkg.files = Self::read_data(&mut reader)?;
+        pkg.load_files_with_datahash(reader)?;
+
+        Ok(pkg)
+    }
+
+    /// Loads a `Package` from the given buffered reader over an APKv2 file, as
+    /// the `load` method, but reads metadata only of the files whose absolute
+    /// path satisfies the given `filter` - the `files` field will contain only
+    /// these files. The data hash is not computed, so [`Package::check`] can't
+    /// check the size and datahash of such a package.
+    ///
+    /// Example:
+    /// ```no_run
+    /// # use std::fs::File;
+    /// # use std::io::BufReader;
+    /// use alpkit::package::Package;
+    ///
+    /// let file = File::open("example-1.0-r0.apk").map(BufReader::new).unwrap();
+    /// let pkg = Package::load_filtered(file, |path| path.starts_with("/usr/lib")).unwrap();
+    /// ```
+    pub fn load_filtered<R, F>(mut reader: R, filter: F) -> Result<Self, Error>
+    where
+        R: BufRead,
+        F: FnMut(&Path) -> bool,
+    {
+        let mut pkg = Self::load_without_files(&mut reader)?;
+        pkg.load_files(reader, filter)?;
 
         Ok(pkg)
     }
@@ -78,18 +173,107 @@ impl Package {
     /// the `files` field will be empty. This is the preferred method if you
     /// don't need files, because it's much faster for bigger packages.
     pub fn load_without_files<R: BufRead>(mut reader: R) -> Result<Self, Error> {
+        let (signs, sign_contents) = Self::read_signatures(&mut reader)?.into_iter().unzip();
+        Self::load_control(reader, signs, sign_contents)
+    }
+
+    /// Reads the package data segment from the given buffered reader and
+    /// passes metadata of each file to the `callback` as soon as it's read,
+    /// instead of collecting them into a `Package`. This is useful for
+    /// packages with a huge number of files, e.g. to serialize them
+    /// incrementally.
+    ///
+    /// The reader must be positioned at the beginning of the data segment,
+    /// i.e. it must be the reader previously passed (by a mutable reference)
+    /// to [`Package::load_without_files`]. An error returned by the
+    /// `callback` stops the reading and is returned as [`Error::Io`].
+    ///
+    /// Example:
+    /// ```no_run
+    /// # use std::fs::File;
+    /// # use std::io::BufReader;
+    /// use alpkit::package::Package;
+    ///
+    /// let mut file = File::open("example-1.0-r0.apk").map(BufReader::new).unwrap();
+    /// let pkg = Package::load_without_files(&mut file).unwrap();
+    /// Package::stream_files(file, |file| {
+    ///     println!("{}", file.path.display());
+    ///     Ok(())
+    /// })
+    /// .unwrap();
+    /// ```
+    pub fn stream_files<R, F>(mut reader: R, callback: F) -> Result<(), Error>
+    where
+        R: BufRead,
+        F: FnMut(FileInfo) -> io::Result<()>,
+    {
+        Self::read_data(&mut reader, |_| true, callback)?;
+        Ok(())
+    }
+
+    /// Reads only the signatures from the given buffered reader over an APKv2
+    /// file, i.e. stops after the first gzip stream (the signature segment)
+    /// without reading the control and data segments. This is much faster
+    /// than [`Package::load_without_files`] if you only need to know which
+    /// key signed the package.
+    ///
+    /// Example:
+    /// ```no_run
+    /// # use std::fs::File;
+    /// # use std::io::BufReader;
+    /// use alpkit::package::Package;
+    ///
+    /// let file = File::open("example-1.0-r0.apk").map(BufReader::new).unwrap();
+    /// for sign in Package::read_signatures_only(file).unwrap() {
+    ///     println!("{}", sign.keyname);
+    /// }
+    /// ```
+    pub fn read_signatures_only<R: BufRead>(mut reader: R) -> Result<Vec<SignatureInfo>, Error> {
         let signs = Self::read_signatures(&mut reader)?;
-        let (pkginfo, scripts) = Self::read_control(&mut reader)?;
+        Ok(signs.into_iter().map(|(sign, _)| sign).collect())
+    }
 
-        Ok(Self {
-            signs,
-            pkginfo,
-            scripts,
-            files: vec![],
-        })
+    /// Reads a regular file with the given `path` from the package data
+    /// segment of an APKv2 file (from the given buffered reader) and writes
+    /// its content into `writer`. Returns the number of bytes written.
+    ///
+    /// Example:
+    /// ```no_run
+    /// # use std::fs::File;
+    /// # use std::io::{self, BufReader};
+    /// use alpkit::package::Package;
+    ///
+    /// let file = File::open("example-1.0-r0.apk").map(BufReader::new).unwrap();
+    /// Package::extract_file(file, "/etc/example.conf", &mut io::stdout()).unwrap();
+    /// ```
+    pub fn extract_file<R, P, W>(mut reader: R, path: P, writer: &mut W) -> Result<u64, Error>
+    where
+        R: BufRead,
+        P: AsRef<Path>,
+        W: Write + ?Sized,
+    {
+        let path = path.as_ref();
+        // Paths in the tar archive are relative.
+        let rel_path = path.strip_prefix("/").unwrap_or(path);
+
+        Self::skip_segment(&mut reader)?; // signatures
+        Self::skip_segment(&mut reader)?; // control
+
+        let mut archive = Archive::new(GzDecoder::new(reader));
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+
+            if entry.path()? == rel_path {
+                return match entry.header().entry_type() {
+                    EntryType::Regular | EntryType::Continuous => Ok(io::copy(&mut entry, writer)?),
+                    _ => Err(Error::NotRegularFile(path.to_owned())),
+                };
+            }
+        }
+        bail!(Error::FileNotFound(path.to_owned()))
     }
 
-    pub fn signatures(&self) -> Iter<SignatureInfo> {
+    pub fn signatures(&self) -> Iter<'_, SignatureInfo> {
         self.signs.iter()
     }
 
@@ -97,21 +281,94 @@ impl Package {
         &self.pkginfo
     }
 
-    pub fn scripts(&self) -> Iter<PkgScript> {
+    pub fn scripts(&self) -> Iter<'_, PkgScript> {
         self.scripts.iter()
     }
 
-    pub fn files_metadata(&self) -> Iter<FileInfo> {
+    /// Returns the content of the given install script, or `None` if the
+    /// package doesn't contain such script.
+    pub fn script_content(&self, script: &PkgScript) -> Option<&str> {
+        self.scripts
+            .iter()
+            .position(|s| s == script)
+            .and_then(|i| self.script_contents.get(i))
+            .map(String::as_str)
+    }
+
+    pub fn files_metadata(&self) -> Iter<'_, FileInfo> {
         self.files.iter()
     }
 
-    fn read_signatures<R: BufRead>(reader: &mut R) -> Result<Vec<SignatureInfo>, Error> {
+    /// Loads the rest of a `Package` without files from the given buffered
+    /// reader positioned at the control segment, with the given signatures.
+    fn load_control<R: BufRead>(
+        reader: R,
+        signs: Vec<SignatureInfo>,
+        sign_contents: Vec<Vec<u8>>,
+    ) -> Result<Self, Error> {
+        // The control segment is kept only for verifying the signature.
+        #[cfg(feature = "signature")]
+        let mut reader = TeeReader::new(reader, Vec::new());
+        #[cfg(not(feature = "signature"))]
+        let mut reader = reader;
+
+        let (pkginfo, scripts) = Self::read_control(&mut reader)?;
+        let (scripts, script_contents) = scripts.into_iter().unzip();
+
+        Ok(Self {
+            signs,
+            sign_contents,
+            #[cfg(feature = "signature")]
+            control_segment: reader.into_writer(),
+            pkginfo,
+            scripts,
+            script_contents,
+            files: vec![],
+            data_hash: None,
+        })
+    }
+
+    /// Reads metadata of the files that satisfy the `filter` from the given
+    /// buffered reader positioned at the data segment into `files`.
+    fn load_files<R, F>(&mut self, mut reader: R, filter: F) -> Result<(), Error>
+    where
+        R: BufRead,
+        F: FnMut(&Path) -> bool,
+    {
+        Self::read_data(&mut reader, filter, |file| {
+            self.files.push(file);
+            Ok(())
+        })?;
+        Ok(())
+    }
+
+    /// Reads metadata of all the files from the given buffered reader
+    /// positioned at the data segment into `files` and computes `data_hash`.
+    fn load_files_with_datahash<R: BufRead>(&mut self, reader: R) -> Result<(), Error> {
+        let mut reader = TeeReader::new(reader, Hasher::new(ChecksumAlg::Sha256));
+        self.load_files(&mut reader, |_| true)?;
+        // Hash the rest of the data segment (e.g. the gzip trailer).
+        io::copy(&mut reader, &mut io::sink())?;
+        self.data_hash = Some(reader.into_writer().finish_hex());
+
+        Ok(())
+    }
+
+    fn read_signatures<R: BufRead>(reader: &mut R) -> Result<Vec<(SignatureInfo, Vec<u8>)>, Error> {
+        debug!("reading signature segment");
         let mut archive = Archive::new(GzDecoder::new(reader));
 
-        let mut signs: Vec<SignatureInfo> = Vec::with_capacity(1);
+        let mut signs: Vec<(SignatureInfo, Vec<u8>)> = Vec::with_capacity(1);
         for entry in archive.entries()? {
-            if let Some(sign) = SignatureInfo::from_filename(&entry?.path()?) {
-                signs.push(sign);
+            let mut entry = entry?;
+
+            trace!("reading entry {}", entry.path()?.display());
+
+            if let Some(sign) = SignatureInfo::from_filename(&entry.path()?) {
+                let mut buf = Vec::new();
+                entry.read_to_end(&mut buf)?;
+
+                signs.push((sign, buf));
             }
         }
         if signs.is_empty() {
@@ -120,14 +377,18 @@ impl Package {
         Ok(signs)
     }
 
-    fn read_control<R: BufRead>(reader: &mut R) -> Result<(PkgInfo, Vec<PkgScript>), Error> {
+    fn read_control<R: BufRead>(
+        reader: &mut R,
+    ) -> Result<(PkgInfo, Vec<(PkgScript, String)>), Error> {
+        debug!("reading control segment");
         let mut archive = Archive::new(GzDecoder::new(reader));
 
         let mut pkginfo: Option<PkgInfo> = None;
-        let mut scripts: Vec<PkgScript> = vec![];
+        let mut scripts: Vec<(PkgScript, String)> = vec![];
 
         for entry in archive.entries()? {
             let mut entry = entry?;
+            trace!("reading entry {}", entry.path()?.display());
 
             match entry.path_bytes().as_ref() {
                 b".PKGINFO" => {
@@ -139,11 +400,16 @@ impl Package {
                 path => {
                     let name = str::from_utf8(&path[1..]).unwrap_or("");
                     if let Ok(script) = PkgScript::from_str(name) {
-                        scripts.push(script);
+                        let mut buf = Vec::new();
+                        entry.read_to_end(&mut buf)?;
+
+                        scripts.push((script, String::from_utf8_lossy(&buf).into_owned()));
                     }
                 }
             };
         }
+        // Read the rest of the segment (e.g. the gzip trailer), if any.
+        io::copy(&mut archive.into_inner(), &mut io::sink())?;
 
         if let Some(pkginfo) = pkginfo {
             Ok((pkginfo, scripts))
@@ -152,24 +418,51 @@ impl Package {
         }
     }
 
-    fn read_data<R: BufRead>(reader: &mut R) -> io::Result<Vec<FileInfo>> {
+    pub(crate) fn skip_segment<R: BufRead>(reader: &mut R) -> io::Result<u64> {
+        io::copy(&mut GzDecoder::new(reader), &mut io::sink())
+    }
+
+    fn read_data<R, F, S>(reader: &mut R, mut filter: F, mut sink: S) -> io::Result<()>
+    where
+        R: BufRead,
+        F: FnMut(&Path) -> bool,
+        S: FnMut(FileInfo) -> io::Result<()>,
+    {
+        debug!("reading data segment");
         let mut archive = Archive::new(GzDecoder::new(reader));
-        let entries = archive.entries()?;
 
-        entries.map(|entry| FileInfo::try_from(entry?)).collect()
+        // A buffer for the entry path reused for all entries, so the path is
+        // allocated only for the accepted ones.
+        let mut path = OsString::new();
+
+        for entry in archive.entries()? {
+            let entry = entry?;
+            trace!("reading entry {}", entry.path()?.display());
+
+            fileinfo::entry_abs_path(&entry, &mut path)?;
+            if filter(Path::new(&path)) {
+                sink(FileInfo::from_entry(entry, PathBuf::from(&path))?)?;
+            }
+        }
+        Ok(())
     }
 }
 
 ////////////////////////////////////////////////////////////////////////////////
 
+/// A digital signature of the package.
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schema-gen", derive(schemars::JsonSchema))]
 pub struct SignatureInfo {
+    /// The signature algorithm, e.g. `RSA` or `RSA256`.
     pub alg: String,
+
+    /// The name of the public key used to verify the signature.
     pub keyname: String,
 }
 
 impl SignatureInfo {
-    fn from_filename(path: &Path) -> Option<Self> {
+    pub(crate) fn from_filename(path: &Path) -> Option<Self> {
         path.to_string_lossy()
             .strip_prefix(".SIGN.")
             .and_then(|s| s.split_once('.'))
@@ -182,7 +475,11 @@ impl SignatureInfo {
 
 ////////////////////////////////////////////////////////////////////////////////
 
+/// A package install script, executed by apk at the respective phase, or
+/// a trigger script, executed when any of the package's trigger paths has
+/// been modified.
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schema-gen", derive(schemars::JsonSchema))]
 #[serde(rename_all = "kebab-case")]
 pub enum PkgScript {
     PreInstall,
@@ -191,6 +488,30 @@ pub enum PkgScript {
     PostUpgrade,
     PreDeinstall,
     PostDeinstall,
+    Trigger,
+}
+
+impl PkgScript {
+    /// Returns the script name as used in the package, e.g. `post-install`.
+    pub fn name(&self) -> &'static str {
+        use PkgScript::*;
+
+        match self {
+            PreInstall => "pre-install",
+            PostInstall => "post-install",
+            PreUpgrade => "pre-upgrade",
+            PostUpgrade => "post-upgrade",
+            PreDeinstall => "pre-deinstall",
+            PostDeinstall => "post-deinstall",
+            Trigger => "trigger",
+        }
+    }
+}
+
+impl fmt::Display for PkgScript {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
 }
 
 impl FromStr for PkgScript {
@@ -1,9 +1,8 @@
-use std::fs::File;
-use std::io::BufReader;
+use std::fs;
 use std::path::PathBuf;
 
 use super::*;
-use crate::internal::test_utils::{assert, assert_let, dependency, S};
+use crate::internal::test_utils::{assert, assert_let, dependency, open_fixture, S};
 use fileinfo::FileType;
 
 #[test]
@@ -54,7 +53,7 @@ fn package_load() {
     };
     let scripts = vec![&PkgScript::PostInstall, &PkgScript::PostDeinstall];
 
-    let files = vec![
+    let files = [
         dir("/etc", 0o755),
         file(
             "/etc/rssh.conf.default",
@@ -81,7 +80,7 @@ fn package_load() {
     ];
     let files = files.iter().collect::<Vec<_>>();
 
-    let reader = read_fixture("../fixtures/apk/rssh-2.3.4-r3.apk");
+    let reader = open_fixture("rssh-2.3.4-r3.apk");
 
     assert_let!(Ok(pkg) = Package::load(reader));
     assert!(pkg.signatures().collect::<Vec<_>>() == vec![&signature]);
@@ -90,9 +89,114 @@ fn package_load() {
     assert!(pkg.files_metadata().collect::<Vec<_>>() == files);
 }
 
-fn read_fixture(path: &str) -> BufReader<File> {
-    let file = File::open(path).unwrap_or_else(|_| panic!("Fixture file `{}` not found", &path));
-    BufReader::new(file)
+#[test]
+fn package_load_filtered() {
+    let reader = open_fixture("rssh-2.3.4-r3.apk");
+
+    assert_let!(Ok(pkg) = Package::load_filtered(reader, |path| path.starts_with("/usr/bin")));
+    assert!(
+        pkg.files_metadata().map(|f| &f.path).collect::<Vec<_>>()
+            == vec![&PathBuf::from("/usr/bin/"), &PathBuf::from("/usr/bin/rssh")]
+    );
+    assert!(pkg.pkginfo().pkgname == "rssh");
+    // The data hash is not computed from a filtered data segment.
+    assert!(pkg.data_hash.is_none());
+}
+
+#[test]
+fn package_stream_files() {
+    let mut reader = open_fixture("rssh-2.3.4-r3.apk");
+
+    assert_let!(Ok(pkg) = Package::load_without_files(&mut reader));
+    assert!(pkg.pkginfo().pkgname == "rssh");
+
+    let mut files = Vec::new();
+    assert_let!(
+        Ok(()) = Package::stream_files(&mut reader, |file| {
+            files.push(file);
+            Ok(())
+        })
+    );
+    let full = Package::load(open_fixture("rssh-2.3.4-r3.apk")).unwrap();
+    assert!(files.iter().eq(full.files_metadata()));
+
+    // An error from the callback stops the reading.
+    let mut reader = open_fixture("rssh-2.3.4-r3.apk");
+    Package::load_without_files(&mut reader).unwrap();
+    let mut count = 0;
+    let result = Package::stream_files(reader, |_| {
+        count += 1;
+        Err(io::Error::new(io::ErrorKind::Other, "stop"))
+    });
+    assert_let!(Err(Error::Io(_)) = result);
+    assert!(count == 1);
+}
+
+#[test]
+fn package_read_signatures_only() {
+    let reader = open_fixture("rssh-2.3.4-r3.apk");
+
+    assert_let!(Ok(signs) = Package::read_signatures_only(reader));
+    assert!(
+        signs
+            == vec![SignatureInfo {
+                alg: S!("RSA"),
+                keyname: S!("alpine-devel@lists.alpinelinux.org-6165ee59.rsa.pub"),
+            }]
+    );
+
+    // The control and data segments are not read at all.
+    let mut data = fs::read("../fixtures/apk/rssh-2.3.4-r3.apk").unwrap();
+    let len = data.len();
+    data.truncate(len / 2);
+    assert_let!(Ok(_) = Package::read_signatures_only(&data[..]));
+}
+
+#[test]
+fn package_script_content() {
+    let reader = open_fixture("rssh-2.3.4-r3.apk");
+
+    assert_let!(Ok(pkg) = Package::load_without_files(reader));
+    assert_let!(Some(content) = pkg.script_content(&PkgScript::PostInstall));
+    assert!(content.starts_with("#!/bin/sh"));
+    assert!(pkg.script_content(&PkgScript::PreInstall) == None);
+}
+
+#[test]
+fn pkg_script_name() {
+    for script in [PkgScript::PreUpgrade, PkgScript::Trigger] {
+        assert!(script.name().parse::<PkgScript>().unwrap() == script);
+    }
+    assert!(PkgScript::PostDeinstall.to_string() == "post-deinstall");
+}
+
+#[test]
+fn package_extract_file() {
+    let reader = open_fixture("rssh-2.3.4-r3.apk");
+    let mut buf = Vec::new();
+
+    assert_let!(Ok(1791) = Package::extract_file(reader, "/etc/rssh.conf.default", &mut buf));
+    assert!(buf.len() == 1791);
+    assert!(buf.starts_with(b"# "));
+}
+
+#[test]
+fn package_extract_file_not_regular() {
+    let reader = open_fixture("rssh-2.3.4-r3.apk");
+
+    assert_let!(
+        Err(Error::NotRegularFile(_)) = Package::extract_file(reader, "/usr/bin", &mut io::sink())
+    );
+}
+
+#[test]
+fn package_extract_file_not_found() {
+    let reader = open_fixture("rssh-2.3.4-r3.apk");
+
+    assert_let!(
+        Err(Error::FileNotFound(path)) = Package::extract_file(reader, "/etc/foo", &mut io::sink())
+    );
+    assert!(path == PathBuf::from("/etc/foo"));
 }
 
 fn dir(path: &str, mode: u32) -> FileInfo {
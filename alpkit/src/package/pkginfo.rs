@@ -5,6 +5,7 @@ use crate::dependency::Dependency;
 use crate::internal::key_value_vec_map;
 use crate::internal::macros::bail;
 use crate::internal::serde_key_value;
+use crate::license::{LicenseExpr, LicenseParseError};
 
 ////////////////////////////////////////////////////////////////////////////////
 
@@ -21,6 +22,7 @@ pub enum PkgInfoError {
 
 /// This struct represents the `.PKGINFO` file.
 #[derive(Debug, Default, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schema-gen", derive(schemars::JsonSchema))]
 pub struct PkgInfo {
     /// The name and email address of the package's maintainer. It should be in
     /// the RFC5322 mailbox format, e.g. `Kevin Flynn <kevin.flynn@encom.com>`.
@@ -52,6 +54,7 @@ pub struct PkgInfo {
     /// This also means that the `conflict` field in each [Dependency] is always
     /// `false`.
     #[serde(default, alias = "depend", with = "key_value_vec_map")]
+    #[cfg_attr(feature = "schema-gen", schemars(with = "crate::schema::Dependencies"))]
     pub depends: Vec<Dependency>,
 
     /// Conflicts of this package, i.e. it cannot be installed if any of the
@@ -62,6 +65,7 @@ pub struct PkgInfo {
     /// `depend` field. The `conflict` field in each [Dependency] is always
     /// `false`.
     #[serde(default, with = "key_value_vec_map")]
+    #[cfg_attr(feature = "schema-gen", schemars(with = "crate::schema::Dependencies"))]
     pub conflicts: Vec<Dependency>,
 
     /// A set of dependencies that, if all installed, induce installation of
@@ -69,10 +73,12 @@ pub struct PkgInfo {
     /// installed when some packages are already installed or are in the
     /// dependency tree.
     #[serde(default, with = "key_value_vec_map")]
+    #[cfg_attr(feature = "schema-gen", schemars(with = "crate::schema::Dependencies"))]
     pub install_if: Vec<Dependency>,
 
     /// Providers (packages) that this package provides.
     #[serde(default, with = "key_value_vec_map")]
+    #[cfg_attr(feature = "schema-gen", schemars(with = "crate::schema::Dependencies"))]
     pub provides: Vec<Dependency>,
 
     /// A numeric value which is used by apk-tools to break ties when choosing
@@ -84,6 +90,7 @@ pub struct PkgInfo {
     /// Packages whose files this package is allowed to overwrite (i.e. both can
     /// be installed even if they have conflicting files).
     #[serde(default, with = "key_value_vec_map")]
+    #[cfg_attr(feature = "schema-gen", schemars(with = "crate::schema::Dependencies"))]
     pub replaces: Vec<Dependency>,
 
     /// The priority of the `replaces`. If multiple packages replace files of
@@ -146,6 +153,11 @@ impl PkgInfo {
             })
             .and_then(|pairs| serde_key_value::from_pairs(pairs).map_err(PkgInfoError::from))
     }
+
+    /// Parses the `license` field into a SPDX license expression.
+    pub fn license_expr(&self) -> Result<LicenseExpr, LicenseParseError> {
+        self.license.parse()
+    }
 }
 
 fn parse_key_value(s: &str) -> impl Iterator<Item = Result<(&str, &str), PkgInfoError>> {
@@ -0,0 +1,143 @@
+use std::cmp;
+use std::io::{self, BufRead, Read};
+use std::time::Duration;
+
+/// The size of the first chunk requested by [`RemoteReader`].
+const INITIAL_CHUNK_SIZE: usize = 64 * 1024;
+
+/// The maximum size of a chunk requested by [`RemoteReader`].
+const MAX_CHUNK_SIZE: usize = 4 * 1024 * 1024;
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// A buffered reader over a remote file that downloads it lazily in chunks
+/// using HTTP range requests. It's intended to be used with
+/// [`Package::load_without_files`](super::Package::load_without_files) to
+/// fetch only the signature and control segment of a remote package, which
+/// are typically just a few kilobytes at the beginning of the file.
+///
+/// The chunk size starts at 64 KiB and doubles with each request (up to
+/// 4 MiB), so reading the whole file doesn't need too many requests. If the
+/// server doesn't support range requests, the whole file is streamed.
+///
+/// Example:
+/// ```no_run
+/// use alpkit::package::{Package, RemoteReader};
+///
+/// let reader = RemoteReader::new("https://dl-cdn.alpinelinux.org/alpine/edge/main/x86_64/busybox-1.36.1-r5.apk");
+/// let pkg = Package::load_without_files(reader).unwrap();
+/// ```
+pub struct RemoteReader {
+    agent: ureq::Agent,
+    url: String,
+    chunk_size: usize,
+    /// The offset of the next chunk to fetch.
+    offset: u64,
+    buf: Vec<u8>,
+    buf_pos: usize,
+    /// The response body, if the server doesn't support range requests.
+    stream: Option<Box<dyn Read + Send + Sync>>,
+    eof: bool,
+}
+
+impl RemoteReader {
+    pub fn new<S: ToString>(url: S) -> Self {
+        Self {
+            agent: ureq::AgentBuilder::new()
+                .timeout_connect(Duration::from_secs(30))
+                .user_agent(concat!("alpkit/", env!("CARGO_PKG_VERSION")))
+                .build(),
+            url: url.to_string(),
+            chunk_size: INITIAL_CHUNK_SIZE,
+            offset: 0,
+            buf: Vec::new(),
+            buf_pos: 0,
+            stream: None,
+            eof: false,
+        }
+    }
+
+    /// Sets the HTTP client used for downloading.
+    pub fn agent(&mut self, agent: ureq::Agent) -> &mut Self {
+        self.agent = agent;
+        self
+    }
+
+    /// Fetches the next chunk into the buffer.
+    fn fetch_chunk(&mut self) -> io::Result<()> {
+        let end = self.offset + self.chunk_size as u64 - 1;
+
+        let resp = match self
+            .agent
+            .get(&self.url)
+            .set("Range", &format!("bytes={}-{}", self.offset, end))
+            .call()
+        {
+            Ok(resp) => resp,
+            // The offset is beyond the end of the file.
+            Err(ureq::Error::Status(416, _)) => {
+                self.eof = true;
+                return Ok(());
+            }
+            Err(e) => {
+                let msg = format!("failed to download '{}': {}", self.url, e);
+                return Err(io::Error::new(io::ErrorKind::Other, msg));
+            }
+        };
+
+        if resp.status() == 206 {
+            self.buf.clear();
+            resp.into_reader()
+                .take(self.chunk_size as u64)
+                .read_to_end(&mut self.buf)?;
+
+            self.offset += self.buf.len() as u64;
+            self.eof = self.buf.len() < self.chunk_size;
+            self.chunk_size = cmp::min(self.chunk_size * 2, MAX_CHUNK_SIZE);
+        } else {
+            // The server ignored the Range header and sent the whole file.
+            let mut stream = resp.into_reader();
+            io::copy(&mut (&mut stream).take(self.offset), &mut io::sink())?;
+            self.stream = Some(stream);
+        }
+        self.buf_pos = 0;
+
+        Ok(())
+    }
+}
+
+impl Read for RemoteReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.fill_buf()?.read(buf)?;
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+impl BufRead for RemoteReader {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.buf_pos >= self.buf.len() && !self.eof {
+            if self.stream.is_none() {
+                self.fetch_chunk()?;
+            }
+            if let Some(stream) = &mut self.stream {
+                self.buf.resize(INITIAL_CHUNK_SIZE, 0);
+                let n = stream.read(&mut self.buf)?;
+                self.buf.truncate(n);
+                self.buf_pos = 0;
+                self.eof = n == 0;
+            }
+        }
+        Ok(&self.buf[self.buf_pos..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.buf_pos = cmp::min(self.buf_pos + amt, self.buf.len());
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+#[path = "remote.test.rs"]
+mod test;
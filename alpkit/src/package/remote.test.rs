@@ -0,0 +1,97 @@
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+use super::*;
+use crate::internal::test_utils::{assert, assert_let};
+use crate::package::Package;
+
+const FIXTURE: &str = "../fixtures/apk/rssh-2.3.4-r3.apk";
+
+/// Starts a simple HTTP server serving the given data (with support for range
+/// requests if `ranges` is true) and returns its URL and a counter of the
+/// served requests.
+fn serve(data: Vec<u8>, ranges: bool) -> (String, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/foo.apk", listener.local_addr().unwrap());
+    let counter = Arc::new(AtomicUsize::new(0));
+    let requests = Arc::clone(&counter);
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut range = None;
+
+            for line in BufReader::new(&stream).lines() {
+                let line = line.unwrap();
+                if line.is_empty() {
+                    break;
+                }
+                if let Some(value) = line.strip_prefix("Range: bytes=") {
+                    let (start, end) = value.split_once('-').unwrap();
+                    range = Some((
+                        start.parse::<usize>().unwrap(),
+                        end.parse::<usize>().unwrap(),
+                    ));
+                }
+            }
+            requests.fetch_add(1, Ordering::SeqCst);
+
+            let resp = match range.filter(|_| ranges) {
+                Some((start, _)) if start >= data.len() => {
+                    b"HTTP/1.1 416 Range Not Satisfiable\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_vec()
+                }
+                Some((start, end)) => {
+                    let end = end.min(data.len() - 1);
+                    let mut resp = format!(
+                        "HTTP/1.1 206 Partial Content\r\nContent-Length: {}\r\nContent-Range: bytes {start}-{end}/{}\r\nConnection: close\r\n\r\n",
+                        end - start + 1,
+                        data.len()
+                    )
+                    .into_bytes();
+                    resp.extend_from_slice(&data[start..=end]);
+                    resp
+                }
+                None => {
+                    let mut resp =
+                        format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", data.len())
+                            .into_bytes();
+                    resp.extend_from_slice(&data);
+                    resp
+                }
+            };
+            let _ = stream.write_all(&resp);
+        }
+    });
+
+    (url, counter)
+}
+
+#[test]
+fn remote_reader_with_ranges() {
+    let data = fs::read(FIXTURE).unwrap();
+    let (url, requests) = serve(data.clone(), true);
+
+    let mut buf = Vec::new();
+    assert_let!(Ok(_) = RemoteReader::new(&url).read_to_end(&mut buf));
+    assert!(buf == data);
+
+    assert_let!(Ok(pkg) = Package::load_without_files(RemoteReader::new(&url)));
+    assert!(pkg.pkginfo().pkgname == "rssh");
+
+    // The fixture is smaller than the first chunk, so one request is enough
+    // for reading the whole file.
+    assert!(requests.load(Ordering::SeqCst) == 2);
+}
+
+#[test]
+fn remote_reader_without_ranges() {
+    let data = fs::read(FIXTURE).unwrap();
+    let (url, _) = serve(data.clone(), false);
+
+    assert_let!(Ok(pkg) = Package::load_with_datahash(RemoteReader::new(&url)));
+    assert!(pkg.check() == vec![]);
+}
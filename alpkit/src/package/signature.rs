@@ -0,0 +1,147 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use rsa::pkcs1::DecodeRsaPublicKey;
+use rsa::pkcs8::DecodePublicKey;
+use rsa::{Pkcs1v15Sign, RsaPublicKey};
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha512};
+use thiserror::Error;
+
+use super::{Package, SignatureInfo};
+use crate::keys::KeyRing;
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, Error)]
+pub enum SignatureError {
+    #[error("invalid public key: '{0}'")]
+    InvalidKey(PathBuf),
+
+    #[error("failed to read public key: '{0}'")]
+    ReadKey(PathBuf, #[source] io::Error),
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+impl Package {
+    /// Verifies the package signatures using the RSA public keys in the given
+    /// directory (e.g. `/etc/apk/keys`); the key file name must match the
+    /// signature's `keyname`, as in apk-tools. Returns the first signature
+    /// that is valid, or `None` if none of the signatures could be verified
+    /// (i.e. the key was not found, the algorithm is not supported, or the
+    /// signature doesn't match).
+    ///
+    /// The package must be loaded via [`Package::load`] or
+    /// [`Package::load_without_files`], the signatures of a deserialized
+    /// package cannot be verified.
+    ///
+    /// Example:
+    /// ```no_run
+    /// # use std::fs::File;
+    /// # use std::io::BufReader;
+    /// use alpkit::package::Package;
+    ///
+    /// let file = File::open("example-1.0-r0.apk").map(BufReader::new).unwrap();
+    /// let pkg = Package::load_without_files(file).unwrap();
+    ///
+    /// match pkg.verify_signature("/etc/apk/keys").unwrap() {
+    ///     Some(sign) => println!("signed by {}", sign.keyname),
+    ///     None => println!("untrusted signature"),
+    /// }
+    /// ```
+    pub fn verify_signature<P: AsRef<Path>>(
+        &self,
+        keys_dir: P,
+    ) -> Result<Option<&SignatureInfo>, SignatureError> {
+        let keys_dir = keys_dir.as_ref();
+
+        verify_signatures(
+            self.signs.iter().zip(&self.sign_contents),
+            &self.control_segment,
+            |keyname| read_public_key(&keys_dir.join(keyname)),
+        )
+    }
+
+    /// Verifies the package signatures using the RSA public keys in the given
+    /// key ring. See [`Package::verify_signature`].
+    pub fn verify_signature_with(
+        &self,
+        keys: &KeyRing,
+    ) -> Result<Option<&SignatureInfo>, SignatureError> {
+        verify_signatures(
+            self.signs.iter().zip(&self.sign_contents),
+            &self.control_segment,
+            |keyname| Ok(keys.get(keyname).cloned()),
+        )
+    }
+}
+
+/// Verifies the `signed_data` against the given signatures (with their
+/// contents) using the public keys found by `find_key` (by the keyname) and
+/// returns the first signature that is valid.
+pub(crate) fn verify_signatures<'a, I, F>(
+    signs: I,
+    signed_data: &[u8],
+    mut find_key: F,
+) -> Result<Option<&'a SignatureInfo>, SignatureError>
+where
+    I: IntoIterator<Item = (&'a SignatureInfo, &'a Vec<u8>)>,
+    F: FnMut(&str) -> Result<Option<RsaPublicKey>, SignatureError>,
+{
+    for (sign, sign_content) in signs {
+        let (scheme, digest) = match digest(&sign.alg, signed_data) {
+            Some(v) => v,
+            None => continue,
+        };
+        // The keyname is a file name, it must not escape from the keys_dir.
+        if sign.keyname.contains('/') {
+            continue;
+        }
+        let key = match find_key(&sign.keyname)? {
+            Some(key) => key,
+            None => continue,
+        };
+        if key.verify(scheme, &digest, sign_content).is_ok() {
+            return Ok(Some(sign));
+        }
+    }
+    Ok(None)
+}
+
+/// Returns the padding scheme and the digest of `data` for the given signature
+/// algorithm, or `None` if the algorithm is not supported.
+fn digest(alg: &str, data: &[u8]) -> Option<(Pkcs1v15Sign, Vec<u8>)> {
+    match alg {
+        "RSA" => Some((Pkcs1v15Sign::new::<Sha1>(), Sha1::digest(data).to_vec())),
+        "RSA256" => Some((Pkcs1v15Sign::new::<Sha256>(), Sha256::digest(data).to_vec())),
+        "RSA512" => Some((Pkcs1v15Sign::new::<Sha512>(), Sha512::digest(data).to_vec())),
+        _ => None,
+    }
+}
+
+/// Reads an RSA public key in the PEM format (either SPKI or PKCS#1) from the
+/// given file. Returns `None` if the file doesn't exist.
+pub(crate) fn read_public_key(path: &Path) -> Result<Option<RsaPublicKey>, SignatureError> {
+    let pem = match fs::read_to_string(path) {
+        Ok(pem) => pem,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(SignatureError::ReadKey(path.to_owned(), e)),
+    };
+    parse_public_key(&pem, path).map(Some)
+}
+
+/// Parses an RSA public key in the PEM format (either SPKI or PKCS#1); the
+/// `path` is used only in the error.
+pub(crate) fn parse_public_key(pem: &str, path: &Path) -> Result<RsaPublicKey, SignatureError> {
+    RsaPublicKey::from_public_key_pem(pem)
+        .or_else(|_| RsaPublicKey::from_pkcs1_pem(pem))
+        .map_err(|_| SignatureError::InvalidKey(path.to_owned()))
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+#[path = "signature.test.rs"]
+mod test;
@@ -0,0 +1,60 @@
+use std::fs::{self, File};
+use std::io::BufReader;
+
+use super::*;
+use crate::index::Index;
+use crate::internal::test_utils::{assert, load_fixture};
+
+const KEYS_DIR: &str = "../fixtures/keys";
+
+#[test]
+fn verify_signature_valid() {
+    let pkg = load_fixture("rssh-2.3.4-r3-signed.apk");
+    let sign = pkg.verify_signature(KEYS_DIR).unwrap();
+
+    assert!(sign.map(|s| s.alg.as_str()) == Some("RSA256"));
+    assert!(sign.map(|s| s.keyname.as_str()) == Some("alpkit-test-5f3a9c2e.rsa.pub"));
+}
+
+#[test]
+fn verify_signature_with_missing_key() {
+    // Signed by the Alpine key that is not in the fixtures.
+    let pkg = load_fixture("rssh-2.3.4-r3.apk");
+
+    assert!(pkg.verify_signature(KEYS_DIR).unwrap() == None);
+}
+
+#[test]
+fn verify_signature_with_modified_control() {
+    let mut pkg = load_fixture("rssh-2.3.4-r3-signed.apk");
+    let last = pkg.control_segment.len() - 1;
+    pkg.control_segment[last] ^= 0xff;
+
+    assert!(pkg.verify_signature(KEYS_DIR).unwrap() == None);
+}
+
+#[test]
+fn verify_signature_with_invalid_key() {
+    let keys_dir = tempfile::tempdir().unwrap();
+    fs::write(keys_dir.path().join("alpkit-test-5f3a9c2e.rsa.pub"), "foo").unwrap();
+
+    let pkg = load_fixture("rssh-2.3.4-r3-signed.apk");
+
+    assert!(let Err(SignatureError::InvalidKey(_)) = pkg.verify_signature(keys_dir.path()));
+}
+
+#[test]
+fn verify_index_signature() {
+    let file = File::open("../fixtures/apkindex/APKINDEX-signed.tar.gz").unwrap();
+    let index = Index::load(BufReader::new(file)).unwrap();
+    let sign = index.verify_signature(KEYS_DIR).unwrap();
+
+    assert!(sign.map(|s| s.alg.as_str()) == Some("RSA"));
+    assert!(index.entries.len() == 4);
+
+    // The fixture index is signed with a dummy signature.
+    let file = File::open("../fixtures/apkindex/APKINDEX.tar.gz").unwrap();
+    let index = Index::load(BufReader::new(file)).unwrap();
+
+    assert!(index.verify_signature(KEYS_DIR).unwrap() == None);
+}
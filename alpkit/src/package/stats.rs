@@ -0,0 +1,158 @@
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::{FileType, Package};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Aggregate statistics of the package files computed by [`Package::stats`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct PackageStats {
+    /// Number of the files by type.
+    pub file_counts: FileCounts,
+
+    /// The total size of the regular files in bytes.
+    pub total_size: u64,
+
+    /// The installed size declared in `.PKGINFO` (the disk usage of the files
+    /// on the build host) in bytes.
+    pub installed_size: u64,
+
+    /// Number of the regular files with the setuid or setgid bit.
+    pub setuid_files: usize,
+
+    /// The largest regular files, sorted by size in descending order.
+    pub largest_files: Vec<FileSize>,
+
+    /// Number and size of the regular files per directory, sorted by size in
+    /// descending order.
+    pub directories: Vec<DirStats>,
+}
+
+/// Number of the files by [`FileType`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct FileCounts {
+    pub regular: usize,
+    pub directory: usize,
+    pub symlink: usize,
+    pub hardlink: usize,
+    pub device: usize,
+    pub fifo: usize,
+}
+
+/// A path of the regular file and its size.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct FileSize {
+    pub path: PathBuf,
+    pub size: u64,
+}
+
+/// Number and total size of the regular files in the directory (including
+/// subdirectories).
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct DirStats {
+    pub path: PathBuf,
+    pub files: usize,
+    pub size: u64,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+impl Package {
+    /// Computes aggregate statistics of the package files: number of files by
+    /// type, their total size, the `largest` number of the largest files, and
+    /// a breakdown of the files by directories at the given `depth` (e.g. 2
+    /// for `/usr/bin`, `/usr/lib` etc.; files in shallower directories are
+    /// counted in their parent directory).
+    ///
+    /// The files are available only if the package has been loaded via
+    /// [`Package::load`].
+    ///
+    /// Example:
+    /// ```no_run
+    /// # use std::fs::File;
+    /// # use std::io::BufReader;
+    /// use alpkit::package::Package;
+    ///
+    /// let file = File::open("example-1.0-r0.apk").map(BufReader::new).unwrap();
+    /// let stats = Package::load(file).unwrap().stats(10, 2);
+    ///
+    /// println!("{} files, {} bytes", stats.file_counts.regular, stats.total_size);
+    /// ```
+    pub fn stats(&self, largest: usize, depth: usize) -> PackageStats {
+        let mut stats = PackageStats {
+            installed_size: self.pkginfo.size as u64,
+            ..PackageStats::default()
+        };
+        let mut dirs: BTreeMap<PathBuf, (usize, u64)> = BTreeMap::new();
+
+        for file in &self.files {
+            let counts = &mut stats.file_counts;
+            match file.file_type {
+                FileType::Regular => counts.regular += 1,
+                FileType::Directory => counts.directory += 1,
+                FileType::Symlink => counts.symlink += 1,
+                FileType::Link => counts.hardlink += 1,
+                FileType::Char | FileType::Block => counts.device += 1,
+                FileType::Fifo => counts.fifo += 1,
+            }
+            if file.file_type != FileType::Regular {
+                continue;
+            }
+            let size = file.size.unwrap_or(0);
+            stats.total_size += size;
+
+            if file.mode & 0o6000 != 0 {
+                stats.setuid_files += 1;
+            }
+            stats.largest_files.push(FileSize {
+                path: file.path.clone(),
+                size,
+            });
+
+            let dir = dir_at_depth(&file.path, depth);
+            let entry = dirs.entry(dir).or_default();
+            entry.0 += 1;
+            entry.1 += size;
+        }
+
+        // Sort by size (descending), then by path for a stable order.
+        stats
+            .largest_files
+            .sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+        stats.largest_files.truncate(largest);
+
+        stats.directories = dirs
+            .into_iter()
+            .map(|(path, (files, size))| DirStats { path, files, size })
+            .collect();
+        stats
+            .directories
+            .sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+
+        stats
+    }
+}
+
+/// Returns the ancestor directory of the file `path` at the given `depth`, or
+/// its parent directory if it's shallower.
+fn dir_at_depth(path: &Path, depth: usize) -> PathBuf {
+    let parent = path.parent().unwrap_or(path);
+
+    let mut dir = PathBuf::from("/");
+    dir.extend(
+        parent
+            .components()
+            .filter(|c| matches!(c, Component::Normal(_)))
+            .take(depth),
+    );
+    dir
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+#[path = "stats.test.rs"]
+mod test;
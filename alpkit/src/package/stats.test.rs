@@ -0,0 +1,74 @@
+use std::fs::File;
+use std::io::BufReader;
+
+use super::*;
+use crate::internal::test_utils::{assert, load_fixture};
+
+#[test]
+fn stats_fixture() {
+    let stats = load_fixture("rssh-2.3.4-r3.apk").stats(2, 2);
+
+    assert!(
+        stats.file_counts
+            == FileCounts {
+                regular: 3,
+                directory: 5,
+                ..FileCounts::default()
+            }
+    );
+    assert!(stats.total_size == 1791 + 26584 + 26560);
+    assert!(stats.installed_size == 86016);
+    assert!(stats.setuid_files == 1);
+    assert!(
+        stats.largest_files
+            == vec![
+                FileSize {
+                    path: "/usr/bin/rssh".into(),
+                    size: 26584
+                },
+                FileSize {
+                    path: "/usr/lib/rssh/rssh_chroot_helper".into(),
+                    size: 26560
+                },
+            ]
+    );
+    assert!(
+        stats.directories
+            == vec![
+                DirStats {
+                    path: "/usr/bin".into(),
+                    files: 1,
+                    size: 26584
+                },
+                DirStats {
+                    path: "/usr/lib".into(),
+                    files: 1,
+                    size: 26560
+                },
+                DirStats {
+                    path: "/etc".into(),
+                    files: 1,
+                    size: 1791
+                },
+            ]
+    );
+}
+
+#[test]
+fn stats_without_files() {
+    let file = File::open("../fixtures/apk/rssh-2.3.4-r3.apk").unwrap();
+    let pkg = Package::load_without_files(BufReader::new(file)).unwrap();
+    let stats = pkg.stats(10, 2);
+
+    assert!(stats.file_counts == FileCounts::default());
+    assert!(stats.largest_files.is_empty());
+    assert!(stats.directories.is_empty());
+}
+
+#[test]
+fn test_dir_at_depth() {
+    assert!(dir_at_depth(Path::new("/usr/lib/foo/libfoo.so"), 2) == Path::new("/usr/lib"));
+    assert!(dir_at_depth(Path::new("/usr/lib/foo/libfoo.so"), 1) == Path::new("/usr"));
+    assert!(dir_at_depth(Path::new("/etc/foo.conf"), 2) == Path::new("/etc"));
+    assert!(dir_at_depth(Path::new("/foo"), 2) == Path::new("/"));
+}
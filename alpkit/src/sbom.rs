@@ -0,0 +1,412 @@
+//! Generation of a software bill of materials (SBOM) for an APK package in
+//! the [CycloneDX] or [SPDX] format.
+//!
+//! [CycloneDX]: https://cyclonedx.org/docs/1.5/json/
+//! [SPDX]: https://spdx.github.io/spdx-spec/v2.3/
+use serde::Serialize;
+
+use crate::package::{FileType, Package, PkgInfo};
+
+/// The name of the tool recorded in the SBOM.
+const TOOL_NAME: &str = "alpkit";
+
+/// The version of the tool recorded in the SBOM.
+const TOOL_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// The prefix of the SPDX document namespace.
+const SPDX_NAMESPACE_PREFIX: &str = "https://spdx.org/spdxdocs/alpkit";
+
+/// The SPDX value for unknown or unspecified information.
+const NOASSERTION: &str = "NOASSERTION";
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// A CycloneDX (1.5) document describing a single package and its files.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CycloneDx {
+    pub bom_format: &'static str,
+    pub spec_version: &'static str,
+    pub version: u32,
+    pub metadata: CdxMetadata,
+    /// The files of the package.
+    pub components: Vec<CdxComponent>,
+}
+
+impl CycloneDx {
+    /// Creates a CycloneDX document for the given package. The `timestamp`
+    /// (a unix timestamp) is the time of the document creation.
+    ///
+    /// Example:
+    /// ```no_run
+    /// # use std::fs::File;
+    /// # use std::io::BufReader;
+    /// # use std::time::SystemTime;
+    /// use alpkit::package::Package;
+    /// use alpkit::sbom::CycloneDx;
+    ///
+    /// let file = File::open("example-1.0-r0.apk").map(BufReader::new).unwrap();
+    /// let pkg = Package::load(file).unwrap();
+    /// let now = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap();
+    ///
+    /// let bom = CycloneDx::from_package(&pkg, now.as_secs() as i64);
+    /// ```
+    pub fn from_package(pkg: &Package, timestamp: i64) -> Self {
+        let pkginfo = pkg.pkginfo();
+
+        let component = CdxComponent {
+            component_type: "library",
+            bom_ref: Some(purl(pkginfo)),
+            name: pkginfo.pkgname.clone(),
+            version: Some(pkginfo.pkgver.clone()),
+            description: Some(pkginfo.pkgdesc.clone()).filter(|s| !s.is_empty()),
+            licenses: vec![CdxLicense {
+                expression: pkginfo.license.clone(),
+            }],
+            purl: Some(purl(pkginfo)),
+            hashes: vec![],
+            external_references: Some(pkginfo.url.clone())
+                .filter(|s| !s.is_empty())
+                .map(|url| CdxExternalReference {
+                    ref_type: "website",
+                    url,
+                })
+                .into_iter()
+                .collect(),
+        };
+
+        let components = pkg
+            .files_metadata()
+            .filter(|file| file.file_type == FileType::Regular)
+            .map(|file| CdxComponent {
+                component_type: "file",
+                bom_ref: None,
+                name: file.path.to_string_lossy().into_owned(),
+                version: None,
+                description: None,
+                licenses: vec![],
+                purl: None,
+                hashes: file
+                    .digest
+                    .iter()
+                    .map(|digest| CdxHash {
+                        alg: "SHA-1",
+                        content: digest.clone(),
+                    })
+                    .collect(),
+                external_references: vec![],
+            })
+            .collect();
+
+        CycloneDx {
+            bom_format: "CycloneDX",
+            spec_version: "1.5",
+            version: 1,
+            metadata: CdxMetadata {
+                timestamp: format_timestamp(timestamp),
+                tools: vec![CdxTool {
+                    name: TOOL_NAME,
+                    version: TOOL_VERSION,
+                }],
+                component,
+            },
+            components,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct CdxMetadata {
+    /// The time of the document creation in RFC 3339 format.
+    pub timestamp: String,
+    pub tools: Vec<CdxTool>,
+    /// The package.
+    pub component: CdxComponent,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct CdxTool {
+    pub name: &'static str,
+    pub version: &'static str,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CdxComponent {
+    #[serde(rename = "type")]
+    pub component_type: &'static str,
+
+    #[serde(rename = "bom-ref", skip_serializing_if = "Option::is_none")]
+    pub bom_ref: Option<String>,
+
+    pub name: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub licenses: Vec<CdxLicense>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub purl: Option<String>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub hashes: Vec<CdxHash>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub external_references: Vec<CdxExternalReference>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct CdxLicense {
+    pub expression: String,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct CdxHash {
+    pub alg: &'static str,
+    pub content: String,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct CdxExternalReference {
+    #[serde(rename = "type")]
+    pub ref_type: &'static str,
+    pub url: String,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// A SPDX (2.3) document describing a single package and its files.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Spdx {
+    pub spdx_version: &'static str,
+    pub data_license: &'static str,
+    #[serde(rename = "SPDXID")]
+    pub spdx_id: String,
+    pub name: String,
+    pub document_namespace: String,
+    pub creation_info: SpdxCreationInfo,
+    pub packages: Vec<SpdxPackage>,
+    pub files: Vec<SpdxFile>,
+    pub relationships: Vec<SpdxRelationship>,
+}
+
+impl Spdx {
+    /// Creates a SPDX document for the given package. The `timestamp` (a unix
+    /// timestamp) is the time of the document creation.
+    pub fn from_package(pkg: &Package, timestamp: i64) -> Self {
+        let pkginfo = pkg.pkginfo();
+        let doc_id = "SPDXRef-DOCUMENT".to_owned();
+        let pkg_id = format!("SPDXRef-Package-{}", spdx_id_string(&pkginfo.pkgname));
+
+        let files: Vec<_> = pkg
+            .files_metadata()
+            .filter(|file| file.file_type == FileType::Regular)
+            .enumerate()
+            .map(|(i, file)| SpdxFile {
+                spdx_id: format!("SPDXRef-File-{}", i + 1),
+                file_name: format!(".{}", file.path.display()),
+                checksums: file
+                    .digest
+                    .iter()
+                    .map(|digest| SpdxChecksum {
+                        algorithm: "SHA1",
+                        checksum_value: digest.clone(),
+                    })
+                    .collect(),
+                license_concluded: NOASSERTION,
+                copyright_text: NOASSERTION,
+            })
+            .collect();
+
+        let mut relationships = vec![SpdxRelationship {
+            spdx_element_id: doc_id.clone(),
+            relationship_type: "DESCRIBES",
+            related_spdx_element: pkg_id.clone(),
+        }];
+        relationships.extend(files.iter().map(|file| SpdxRelationship {
+            spdx_element_id: pkg_id.clone(),
+            relationship_type: "CONTAINS",
+            related_spdx_element: file.spdx_id.clone(),
+        }));
+
+        let package = SpdxPackage {
+            spdx_id: pkg_id,
+            name: pkginfo.pkgname.clone(),
+            version_info: pkginfo.pkgver.clone(),
+            supplier: pkginfo
+                .maintainer
+                .as_ref()
+                .map(|m| format!("Person: {m}"))
+                .unwrap_or_else(|| NOASSERTION.to_owned()),
+            download_location: NOASSERTION,
+            files_analyzed: false,
+            homepage: Some(pkginfo.url.clone()).filter(|s| !s.is_empty()),
+            license_concluded: NOASSERTION,
+            license_declared: match pkginfo.license_expr() {
+                Ok(_) => pkginfo.license.clone(),
+                Err(_) => NOASSERTION.to_owned(),
+            },
+            copyright_text: NOASSERTION,
+            summary: pkginfo.pkgdesc.clone(),
+            external_refs: vec![SpdxExternalRef {
+                reference_category: "PACKAGE-MANAGER",
+                reference_type: "purl",
+                reference_locator: purl(pkginfo),
+            }],
+        };
+
+        Spdx {
+            spdx_version: "SPDX-2.3",
+            data_license: "CC0-1.0",
+            spdx_id: doc_id,
+            name: format!("{}-{}", pkginfo.pkgname, pkginfo.pkgver),
+            document_namespace: format!(
+                "{SPDX_NAMESPACE_PREFIX}/{}-{}-{}",
+                pkginfo.pkgname, pkginfo.pkgver, pkginfo.datahash
+            ),
+            creation_info: SpdxCreationInfo {
+                created: format_timestamp(timestamp),
+                creators: vec![format!("Tool: {TOOL_NAME}-{TOOL_VERSION}")],
+            },
+            packages: vec![package],
+            files,
+            relationships,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct SpdxCreationInfo {
+    /// The time of the document creation in RFC 3339 format.
+    pub created: String,
+    pub creators: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpdxPackage {
+    #[serde(rename = "SPDXID")]
+    pub spdx_id: String,
+    pub name: String,
+    pub version_info: String,
+    pub supplier: String,
+    pub download_location: &'static str,
+    pub files_analyzed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub homepage: Option<String>,
+    pub license_concluded: &'static str,
+    pub license_declared: String,
+    pub copyright_text: &'static str,
+    pub summary: String,
+    pub external_refs: Vec<SpdxExternalRef>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpdxExternalRef {
+    pub reference_category: &'static str,
+    pub reference_type: &'static str,
+    pub reference_locator: String,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpdxFile {
+    #[serde(rename = "SPDXID")]
+    pub spdx_id: String,
+    pub file_name: String,
+    pub checksums: Vec<SpdxChecksum>,
+    pub license_concluded: &'static str,
+    pub copyright_text: &'static str,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpdxChecksum {
+    pub algorithm: &'static str,
+    pub checksum_value: String,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpdxRelationship {
+    pub spdx_element_id: String,
+    pub relationship_type: &'static str,
+    pub related_spdx_element: String,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Returns the [package URL] of the package, e.g.
+/// `pkg:apk/alpine/rssh@2.3.4-r3?arch=x86_64`.
+///
+/// [package URL]: https://github.com/package-url/purl-spec
+pub fn purl(pkginfo: &PkgInfo) -> String {
+    format!(
+        "pkg:apk/alpine/{}@{}?arch={}",
+        purl_encode(&pkginfo.pkgname),
+        purl_encode(&pkginfo.pkgver),
+        purl_encode(&pkginfo.arch),
+    )
+}
+
+/// Percent-encodes all characters except the unreserved ones.
+fn purl_encode(s: &str) -> String {
+    s.bytes()
+        .fold(String::with_capacity(s.len()), |mut acc, b| {
+            match b {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'.' | b'-' | b'_' | b'~' => {
+                    acc.push(b as char)
+                }
+                _ => acc.push_str(&format!("%{b:02X}")),
+            }
+            acc
+        })
+}
+
+/// Replaces characters not allowed in SPDX identifiers with `-`.
+fn spdx_id_string(s: &str) -> String {
+    s.chars()
+        .map(|c| match c {
+            'A'..='Z' | 'a'..='z' | '0'..='9' | '.' | '-' => c,
+            _ => '-',
+        })
+        .collect()
+}
+
+/// Formats the unix timestamp as RFC 3339 date-time in UTC.
+fn format_timestamp(timestamp: i64) -> String {
+    let days = timestamp.div_euclid(86400);
+    let secs = timestamp.rem_euclid(86400);
+
+    // Converts days since the epoch to the civil date, see
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    )
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+#[path = "sbom.test.rs"]
+mod test;
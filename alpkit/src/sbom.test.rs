@@ -0,0 +1,76 @@
+use super::*;
+use crate::internal::test_utils::{assert, load_fixture, S};
+
+#[test]
+fn cyclonedx_from_package() {
+    let bom = CycloneDx::from_package(&load_fixture("rssh-2.3.4-r3.apk"), 1666619671);
+    let component = &bom.metadata.component;
+
+    assert!(bom.metadata.timestamp == "2022-10-24T13:54:31Z");
+    assert!(component.name == "rssh");
+    assert!(component.version == Some(S!("2.3.4-r3")));
+    assert!(component.purl == Some(S!("pkg:apk/alpine/rssh@2.3.4-r3?arch=x86_64")));
+    assert!(
+        component.licenses
+            == vec![CdxLicense {
+                expression: S!("BSD-2-Clause")
+            }]
+    );
+
+    let names = bom
+        .components
+        .iter()
+        .map(|c| c.name.as_str())
+        .collect::<Vec<_>>();
+    assert!(
+        names
+            == vec![
+                "/etc/rssh.conf.default",
+                "/usr/bin/rssh",
+                "/usr/lib/rssh/rssh_chroot_helper"
+            ]
+    );
+    assert!(bom.components[0].hashes[0].alg == "SHA-1");
+    assert!(bom.components[0].hashes[0].content == "d371f2e400ee8b8c2f186801514b146668373666");
+}
+
+#[test]
+fn spdx_from_package() {
+    let doc = Spdx::from_package(&load_fixture("rssh-2.3.4-r3.apk"), 1666619671);
+    let package = &doc.packages[0];
+
+    assert!(doc.creation_info.created == "2022-10-24T13:54:31Z");
+    assert!(package.spdx_id == "SPDXRef-Package-rssh");
+    assert!(package.license_declared == "BSD-2-Clause");
+    assert!(
+        package.external_refs[0].reference_locator == "pkg:apk/alpine/rssh@2.3.4-r3?arch=x86_64"
+    );
+
+    assert!(doc.files.len() == 3);
+    assert!(doc.files[0].file_name == "./etc/rssh.conf.default");
+    assert!(doc.files[0].checksums[0].algorithm == "SHA1");
+
+    // DESCRIBES the package + CONTAINS each file
+    assert!(doc.relationships.len() == 4);
+    assert!(doc.relationships[0].relationship_type == "DESCRIBES");
+    assert!(doc.relationships[3].related_spdx_element == "SPDXRef-File-3");
+}
+
+#[test]
+fn purl_encodes_special_chars() {
+    let pkginfo = PkgInfo {
+        pkgname: S!("g++"),
+        pkgver: S!("13.2.1_git20231014-r0"),
+        arch: S!("x86_64"),
+        ..Default::default()
+    };
+    assert!(purl(&pkginfo) == "pkg:apk/alpine/g%2B%2B@13.2.1_git20231014-r0?arch=x86_64");
+}
+
+#[test]
+fn format_timestamp_utc() {
+    assert!(format_timestamp(0) == "1970-01-01T00:00:00Z");
+    assert!(format_timestamp(951782400) == "2000-02-29T00:00:00Z");
+    assert!(format_timestamp(1709251199) == "2024-02-29T23:59:59Z");
+    assert!(format_timestamp(-1) == "1969-12-31T23:59:59Z");
+}
@@ -0,0 +1,304 @@
+//! JSON Schema definitions of the (de)serializable types.
+//!
+//! Most of the types derive [`JsonSchema`] directly, this module provides
+//! schemas for the types with a custom (de)serialization (e.g. [`Dependency`]
+//! is represented as a string) and for the collections serialized as maps
+//! (e.g. [`Dependencies`]).
+//!
+//! Example:
+//! ```
+//! use alpkit::schema::{root_schema, Draft};
+//!
+//! let schema = root_schema("package", Draft::Draft2020_12).unwrap();
+//! println!("{}", serde_json::to_string_pretty(&schema).unwrap());
+//! ```
+use std::str::FromStr;
+
+use schemars::gen::{SchemaGenerator, SchemaSettings};
+use schemars::schema::{
+    InstanceType, Metadata, ObjectValidation, RootSchema, Schema, SchemaObject, SingleOrVec,
+    StringValidation,
+};
+use schemars::visit::{self, Visitor};
+use schemars::{JsonSchema, Map};
+use thiserror::Error;
+
+use crate::apkbuild::{AbuildOption, Apkbuild};
+use crate::dependency::{Constraint, Dependency};
+use crate::package::{FileInfo, Package, PkgInfo};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// A regex pattern of the constraint operator.
+const OP_PATTERN: &str = "[<>=~*]{1,2}";
+
+/// Names of the root types accepted by [`root_schema`].
+pub const ROOT_TYPES: &[&str] = &["apkbuild", "dependencies", "fileinfo", "package", "pkginfo"];
+
+#[derive(Debug, PartialEq, Eq, Error)]
+#[error("unknown JSON Schema draft: '{0}', expected draft-07, 2019-09 or 2020-12")]
+pub struct UnknownDraft(String);
+
+/// A version of the JSON Schema specification to generate schemas for.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Draft {
+    /// JSON Schema draft-07, the most widely supported by validators.
+    #[default]
+    Draft07,
+
+    /// JSON Schema 2019-09: definitions are in `$defs`.
+    Draft2019_09,
+
+    /// JSON Schema 2020-12: definitions are in `$defs` and tuples are
+    /// described by `prefixItems`.
+    Draft2020_12,
+}
+
+impl Draft {
+    fn settings(self) -> SchemaSettings {
+        match self {
+            Draft::Draft07 => SchemaSettings::draft07(),
+            Draft::Draft2019_09 => SchemaSettings::draft2019_09()
+                .with(|s| s.definitions_path = "#/$defs/".to_owned())
+                .with_visitor(DraftKeywords(self)),
+            Draft::Draft2020_12 => SchemaSettings::draft2019_09()
+                .with(|s| {
+                    s.definitions_path = "#/$defs/".to_owned();
+                    s.meta_schema = Some("https://json-schema.org/draft/2020-12/schema".to_owned());
+                })
+                .with_visitor(DraftKeywords(self)),
+        }
+    }
+}
+
+impl FromStr for Draft {
+    type Err = UnknownDraft;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "draft-07" | "draft7" | "7" => Ok(Draft::Draft07),
+            "2019-09" | "draft-2019-09" => Ok(Draft::Draft2019_09),
+            "2020-12" | "draft-2020-12" => Ok(Draft::Draft2020_12),
+            _ => Err(UnknownDraft(s.to_owned())),
+        }
+    }
+}
+
+/// Generates a schema of the root type with the given name (see
+/// [`ROOT_TYPES`]) for the given JSON Schema draft, or returns `None` if
+/// there's no such type.
+pub fn root_schema(name: &str, draft: Draft) -> Option<RootSchema> {
+    let gen = draft.settings().into_generator();
+
+    let schema = match name {
+        "apkbuild" => gen.into_root_schema_for::<Apkbuild>(),
+        "dependencies" => gen.into_root_schema_for::<Dependencies>(),
+        "fileinfo" => gen.into_root_schema_for::<FileInfo>(),
+        "package" => gen.into_root_schema_for::<Package>(),
+        "pkginfo" => gen.into_root_schema_for::<PkgInfo>(),
+        _ => return None,
+    };
+    Some(schema)
+}
+
+/// Generates schemas of all the root types (see [`ROOT_TYPES`]) and the types
+/// they reference as [OpenAPI 3.1] component schemas, keyed by the type name
+/// (e.g. `Package`), so they can be embedded into `components.schemas` of an
+/// API specification.
+///
+/// OpenAPI 3.1 is aligned with JSON Schema 2020-12, so optional values are
+/// represented by `null` in `type` (not by `nullable` as in OpenAPI 3.0) and
+/// the schemas reference each other via `#/components/schemas/<name>`.
+///
+/// [OpenAPI 3.1]: https://spec.openapis.org/oas/v3.1.0#schema-object
+pub fn openapi_components() -> Map<String, Schema> {
+    let mut gen = SchemaSettings::draft2019_09()
+        .with(|s| {
+            s.definitions_path = "#/components/schemas/".to_owned();
+            s.meta_schema = None;
+        })
+        .into_generator();
+
+    gen.subschema_for::<Apkbuild>();
+    gen.subschema_for::<Dependencies>();
+    gen.subschema_for::<FileInfo>();
+    gen.subschema_for::<Package>();
+    gen.subschema_for::<PkgInfo>();
+
+    let mut schemas = gen.take_definitions();
+    for schema in schemas.values_mut() {
+        DraftKeywords(Draft::Draft2020_12).visit_schema(schema);
+    }
+    schemas
+}
+
+/// A visitor that adjusts the keywords generated by schemars (which targets
+/// draft-07 and 2019-09) to the given draft.
+#[derive(Debug, Clone)]
+struct DraftKeywords(Draft);
+
+impl Visitor for DraftKeywords {
+    fn visit_root_schema(&mut self, root: &mut RootSchema) {
+        visit::visit_root_schema(self, root);
+
+        if self.0 != Draft::Draft07 && !root.definitions.is_empty() {
+            let defs = std::mem::take(&mut root.definitions);
+            root.schema.extensions.insert(
+                "$defs".to_owned(),
+                serde_json::to_value(defs).expect("schema should be serializable"),
+            );
+        }
+    }
+
+    fn visit_schema_object(&mut self, schema: &mut SchemaObject) {
+        visit::visit_schema_object(self, schema);
+
+        // In 2020-12, tuples are described by `prefixItems` and `items` replaces
+        // `additionalItems`.
+        if self.0 == Draft::Draft2020_12 {
+            if let Some(array) = &mut schema.array {
+                if let Some(SingleOrVec::Vec(items)) = &array.items {
+                    schema.extensions.insert(
+                        "prefixItems".to_owned(),
+                        serde_json::to_value(items).expect("schema should be serializable"),
+                    );
+                    array.items = array.additional_items.take().map(|s| (*s).into());
+                }
+            }
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// A schema of a list of dependencies serialized as a map of the package (or
+/// provider) name to the version constraint, e.g.
+/// `{"foo": "*", "bar": ">= 1.2", "baz": "!"}`.
+pub struct Dependencies;
+
+impl JsonSchema for Dependencies {
+    fn schema_name() -> String {
+        "Dependencies".to_owned()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        let value = string_schema(
+            &format!(r"^(\*|!|!?{OP_PATTERN} ?\S+)$"),
+            "Version constraint: `*` (any version), `!` (conflict), or an \
+             operator followed by a version, optionally prefixed with `!`.",
+        );
+        map_schema(value, "A map of dependency names to version constraints.")
+    }
+}
+
+/// A schema of the security fixes serialized as a map of the version to a list
+/// of vulnerability identifiers, e.g. `{"1.2.3-r0": ["CVE-2022-1234"]}`.
+pub struct Secfixes;
+
+impl JsonSchema for Secfixes {
+    fn schema_name() -> String {
+        "Secfixes".to_owned()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        map_schema(
+            gen.subschema_for::<Vec<String>>(),
+            "A map of package versions to vulnerabilities fixed in that version.",
+        )
+    }
+}
+
+/// A schema of the extended file attributes serialized as a map of the name
+/// to a base64-encoded value.
+pub struct Xattrs;
+
+impl JsonSchema for Xattrs {
+    fn schema_name() -> String {
+        "Xattrs".to_owned()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        let value = string_schema(r"^[A-Za-z0-9+/]*={0,2}$", "Base64-encoded value.");
+        map_schema(value, "A map of extended attribute names to values.")
+    }
+}
+
+impl JsonSchema for Dependency {
+    fn schema_name() -> String {
+        "Dependency".to_owned()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        string_schema(
+            &format!(r"^!?[^\s<>=~*!@]+({OP_PATTERN}[^\s@]+)?(@\S+)?$"),
+            "A dependency on a package or provider, e.g. `foo`, `foo>=1.2`, \
+             `!foo` (conflict) or `foo@edge` (pinned to a repository).",
+        )
+    }
+}
+
+impl JsonSchema for Constraint {
+    fn schema_name() -> String {
+        "Constraint".to_owned()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        string_schema(
+            &format!(r"^{OP_PATTERN}\s*\S+$"),
+            "A version constraint, e.g. `>=1.2` or `~1.2`.",
+        )
+    }
+}
+
+impl JsonSchema for AbuildOption {
+    fn schema_name() -> String {
+        "AbuildOption".to_owned()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        string_schema(
+            r"^!?[a-z0-9_-]+$",
+            "A build-time option for abuild, e.g. `!check` or `net`.",
+        )
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+fn string_schema(pattern: &str, description: &str) -> Schema {
+    SchemaObject {
+        metadata: Some(Box::new(Metadata {
+            description: Some(description.to_owned()),
+            ..Default::default()
+        })),
+        instance_type: Some(InstanceType::String.into()),
+        string: Some(Box::new(StringValidation {
+            pattern: Some(pattern.to_owned()),
+            ..Default::default()
+        })),
+        ..Default::default()
+    }
+    .into()
+}
+
+fn map_schema(value: Schema, description: &str) -> Schema {
+    SchemaObject {
+        metadata: Some(Box::new(Metadata {
+            description: Some(description.to_owned()),
+            ..Default::default()
+        })),
+        instance_type: Some(InstanceType::Object.into()),
+        object: Some(Box::new(ObjectValidation {
+            additional_properties: Some(Box::new(value)),
+            ..Default::default()
+        })),
+        ..Default::default()
+    }
+    .into()
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+#[path = "schema.test.rs"]
+mod test;
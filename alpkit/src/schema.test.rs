@@ -0,0 +1,129 @@
+use serde_json::{json, Value};
+
+use super::*;
+use crate::internal::test_utils::assert;
+
+#[test]
+fn root_schema_all_types() {
+    for name in ROOT_TYPES {
+        assert!(root_schema(name, Draft::default()).is_some(), "{name}");
+    }
+    assert!(root_schema("nope", Draft::default()).is_none());
+}
+
+#[test]
+fn package_schema() {
+    let schema = serde_json::to_value(root_schema("package", Draft::Draft07).unwrap()).unwrap();
+    let defs = &schema["definitions"];
+
+    assert!(ref_of(&schema["properties"]["depends"]) == "#/definitions/Dependencies");
+    assert!(defs["Dependencies"]["type"] == "object");
+    assert!(defs["Dependencies"]["additionalProperties"]["type"] == "string");
+
+    let fileinfo = &defs["FileInfo"]["properties"];
+    assert!(fileinfo["mode"]["type"] == "string");
+    assert!(fileinfo["mode"]["pattern"] == "^0[0-7]+$");
+    assert!(ref_of(&fileinfo["xattrs"]) == "#/definitions/Xattrs");
+    assert!(ref_of(&fileinfo["type"]) == "#/definitions/FileType");
+
+    assert!(
+        defs["PkgScript"]["enum"]
+            == json!([
+                "pre-install",
+                "post-install",
+                "pre-upgrade",
+                "post-upgrade",
+                "pre-deinstall",
+                "post-deinstall",
+                "trigger"
+            ])
+    );
+    assert!(defs["SignatureInfo"]["required"] == json!(["alg", "keyname"]));
+}
+
+#[test]
+fn apkbuild_schema() {
+    let schema = serde_json::to_value(root_schema("apkbuild", Draft::Draft07).unwrap()).unwrap();
+    let props = &schema["properties"];
+
+    assert!(ref_of(&props["secfixes"]) == "#/definitions/Secfixes");
+    assert!(props["options"]["items"]["$ref"] == "#/definitions/AbuildOption");
+    assert!(
+        props["subpackage_depends"]["additionalProperties"]["$ref"] == "#/definitions/Dependencies"
+    );
+    assert!(props["sources"]["items"]["$ref"] == "#/definitions/Source");
+}
+
+#[test]
+fn apkbuild_schema_draft_2020_12() {
+    let schema =
+        serde_json::to_value(root_schema("apkbuild", Draft::Draft2020_12).unwrap()).unwrap();
+
+    assert!(schema["$schema"] == "https://json-schema.org/draft/2020-12/schema");
+    assert!(schema.get("definitions").is_none());
+    assert!(schema["$defs"]["Source"]["type"] == "object");
+    assert!(schema["properties"]["sources"]["items"]["$ref"] == "#/$defs/Source");
+
+    let raw_item = &schema["properties"]["raw"]["items"];
+    assert!(raw_item["prefixItems"] == json!([{ "type": "string" }, { "type": "string" }]));
+    assert!(raw_item.get("items").is_none());
+}
+
+#[test]
+fn apkbuild_schema_draft_2019_09() {
+    let schema =
+        serde_json::to_value(root_schema("apkbuild", Draft::Draft2019_09).unwrap()).unwrap();
+
+    assert!(schema["$schema"] == "https://json-schema.org/draft/2019-09/schema");
+    assert!(schema["$defs"]["Source"]["type"] == "object");
+    assert!(schema["properties"]["raw"]["items"]["items"].is_array());
+}
+
+#[test]
+fn draft_from_str() {
+    assert!("draft-07".parse() == Ok(Draft::Draft07));
+    assert!("2019-09".parse() == Ok(Draft::Draft2019_09));
+    assert!("2020-12".parse() == Ok(Draft::Draft2020_12));
+    assert!("2021-01".parse::<Draft>().is_err());
+}
+
+#[test]
+fn openapi_components_schemas() {
+    let components = openapi_components();
+
+    for name in [
+        "Apkbuild",
+        "Dependencies",
+        "FileInfo",
+        "Package",
+        "PkgInfo",
+        "Source",
+    ] {
+        assert!(components.contains_key(name), "{name}");
+    }
+
+    let package = serde_json::to_value(&components["Package"]).unwrap();
+    assert!(package.get("$schema").is_none());
+    assert!(package["properties"]["depends"]["$ref"] == "#/components/schemas/Dependencies");
+    assert!(package["properties"]["commit"]["type"] == json!(["string", "null"]));
+}
+
+#[test]
+fn dependency_pattern() {
+    let schema =
+        serde_json::to_value(root_schema("dependencies", Draft::Draft07).unwrap()).unwrap();
+    let pattern = match &schema["additionalProperties"]["pattern"] {
+        Value::String(s) => s.clone(),
+        v => panic!("unexpected pattern: {v}"),
+    };
+    assert!(pattern == r"^(\*|!|!?[<>=~*]{1,2} ?\S+)$");
+}
+
+/// Returns the `$ref` of the schema, possibly wrapped in `allOf` (as used by
+/// schemars for fields with a description or default).
+fn ref_of(schema: &Value) -> &Value {
+    match schema.get("allOf") {
+        Some(all_of) => &all_of[0]["$ref"],
+        None => &schema["$ref"],
+    }
+}
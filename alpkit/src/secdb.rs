@@ -0,0 +1,113 @@
+//! Conversion of secfixes into the [Alpine secdb] format used by security
+//! scanners.
+//!
+//! [Alpine secdb]: https://secdb.alpinelinux.org/
+use serde::{Deserialize, Serialize};
+
+use crate::apkbuild::{Apkbuild, Secfix, ARCH_ALL};
+use crate::internal::key_value_vec_map;
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// The default template of URL of an APK package used in secdb.
+pub const APKURL_TEMPLATE: &str =
+    "{{urlprefix}}/{{distroversion}}/{{reponame}}/{{arch}}/{{pkg.name}}-{{pkg.ver}}.apk";
+
+/// The default URL prefix of the Alpine repositories.
+pub const URLPREFIX: &str = "https://dl-cdn.alpinelinux.org/alpine";
+
+/// The secdb document for a single repository (e.g. `v3.17/main`). It can be
+/// serialized to JSON or YAML using any Serde serializer.
+///
+/// Example:
+/// ```no_run
+/// use alpkit::apkbuild::{find_apkbuilds, ApkbuildReader};
+/// use alpkit::secdb::SecDb;
+///
+/// let reader = ApkbuildReader::new();
+/// let mut secdb = SecDb::new("v3.17", "main");
+///
+/// for path in find_apkbuilds("aports/main").unwrap() {
+///     secdb.add_apkbuild(&reader.read_apkbuild(path).unwrap());
+/// }
+/// ```
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct SecDb {
+    /// A template of the package URL.
+    pub apkurl: String,
+
+    /// CPU architectures of the repository.
+    pub archs: Vec<String>,
+
+    /// The repository name, e.g. `main`.
+    pub reponame: String,
+
+    /// The URL prefix of the repositories (mirror).
+    pub urlprefix: String,
+
+    /// The release branch, e.g. `v3.17` or `edge`.
+    pub distroversion: String,
+
+    /// Packages with at least one secfix, sorted by name.
+    pub packages: Vec<SecDbPackage>,
+}
+
+impl SecDb {
+    /// Creates an empty `SecDb` for the given release (e.g. `v3.17`) and
+    /// repository (e.g. `main`) with the default `apkurl`, `archs` and
+    /// `urlprefix`.
+    pub fn new<S: ToString>(distroversion: S, reponame: S) -> Self {
+        SecDb {
+            apkurl: APKURL_TEMPLATE.to_owned(),
+            archs: ARCH_ALL.iter().map(|s| s.to_string()).collect(),
+            reponame: reponame.to_string(),
+            urlprefix: URLPREFIX.to_owned(),
+            distroversion: distroversion.to_string(),
+            packages: vec![],
+        }
+    }
+
+    /// Adds secfixes of the given APKBUILD (under its `pkgname`), if it has
+    /// any. If a package of the same name already exists, it's replaced.
+    pub fn add_apkbuild(&mut self, apkbuild: &Apkbuild) -> &mut Self {
+        if apkbuild.secfixes.is_empty() {
+            return self;
+        }
+        let pkg = SecDbPackage {
+            pkg: SecDbPkg {
+                name: apkbuild.pkgname.clone(),
+                secfixes: apkbuild.secfixes.clone(),
+            },
+        };
+        match self
+            .packages
+            .binary_search_by(|p| p.pkg.name.cmp(&pkg.pkg.name))
+        {
+            Ok(idx) => self.packages[idx] = pkg,
+            Err(idx) => self.packages.insert(idx, pkg),
+        }
+        self
+    }
+}
+
+/// A package entry in the secdb.
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct SecDbPackage {
+    pub pkg: SecDbPkg,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct SecDbPkg {
+    /// The package name (APKBUILD's `pkgname`).
+    pub name: String,
+
+    /// A map of security vulnerabilities fixed in each version of the package.
+    #[serde(with = "key_value_vec_map")]
+    pub secfixes: Vec<Secfix>,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+#[path = "secdb.test.rs"]
+mod test;
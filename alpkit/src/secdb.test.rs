@@ -0,0 +1,67 @@
+use serde_json::json;
+
+use super::*;
+use crate::internal::test_utils::{assert, assert_from_to_json, S};
+
+fn apkbuild(pkgname: &str, secfixes: Vec<Secfix>) -> Apkbuild {
+    Apkbuild {
+        pkgname: S!(pkgname),
+        secfixes,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn secdb_add_apkbuild() {
+    let mut secdb = SecDb::new("v3.17", "main");
+    secdb
+        .add_apkbuild(&apkbuild(
+            "zlib",
+            vec![Secfix::new("1.2.12-r0", vec![S!("CVE-2018-25032")])],
+        ))
+        .add_apkbuild(&apkbuild("busybox", vec![]))
+        .add_apkbuild(&apkbuild(
+            "openssl",
+            vec![Secfix::new("3.0.7-r0", vec![S!("CVE-2022-3602")])],
+        ));
+
+    let names = secdb
+        .packages
+        .iter()
+        .map(|p| p.pkg.name.as_str())
+        .collect::<Vec<_>>();
+    assert!(names == vec!["openssl", "zlib"]);
+}
+
+#[test]
+fn secdb_json() {
+    let mut secdb = SecDb::new("v3.17", "main");
+    secdb.archs = vec![S!("x86_64")];
+    secdb.add_apkbuild(&apkbuild(
+        "openssl",
+        vec![
+            Secfix::new("3.0.7-r0", vec![S!("CVE-2022-3602"), S!("CVE-2022-3786")]),
+            Secfix::new("3.0.5-r0", vec![S!("CVE-2022-2097")]),
+        ],
+    ));
+
+    assert_from_to_json!(
+        secdb,
+        json!({
+            "apkurl": "{{urlprefix}}/{{distroversion}}/{{reponame}}/{{arch}}/{{pkg.name}}-{{pkg.ver}}.apk",
+            "archs": ["x86_64"],
+            "reponame": "main",
+            "urlprefix": "https://dl-cdn.alpinelinux.org/alpine",
+            "distroversion": "v3.17",
+            "packages": [{
+                "pkg": {
+                    "name": "openssl",
+                    "secfixes": {
+                        "3.0.7-r0": ["CVE-2022-3602", "CVE-2022-3786"],
+                        "3.0.5-r0": ["CVE-2022-2097"]
+                    }
+                }
+            }]
+        }),
+    );
+}
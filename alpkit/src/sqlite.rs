@@ -0,0 +1,308 @@
+//! Export of packages and indexes into a normalized SQLite database, e.g. to
+//! run SQL queries over a whole repository snapshot.
+//!
+//! The database contains the following tables:
+//!
+//! - `packages` – one row per package with the basic metadata and the
+//!   `source` it has been read from (e.g. path of the APK or APKINDEX file),
+//! - `depends` – dependencies of the packages; the `kind` is one of
+//!   `depends`, `conflicts`, `install_if` or `replaces`,
+//! - `provides` – the providers (packages) the packages provide,
+//! - `files` – files of the packages (only for APK packages read with files).
+//!
+//! Example:
+//! ```no_run
+//! # use std::fs::File;
+//! # use std::io::BufReader;
+//! use alpkit::package::Package;
+//! use alpkit::sqlite::SqliteExporter;
+//!
+//! let path = "example-1.0-r0.apk";
+//! let pkg = Package::load(File::open(path).map(BufReader::new).unwrap()).unwrap();
+//!
+//! let mut exporter = SqliteExporter::create("repo.db").unwrap();
+//! exporter.add_package(&pkg, path).unwrap();
+//! exporter.finish().unwrap();
+//! ```
+use std::path::Path;
+
+use rusqlite::{params, Savepoint};
+
+use crate::dependency::Dependency;
+use crate::index::{Index, IndexEntry};
+use crate::package::{FileInfo, Package, PkgInfo};
+
+pub use rusqlite::{Connection, Error};
+
+const SCHEMA: &str = r#"
+CREATE TABLE IF NOT EXISTS packages (
+    id INTEGER PRIMARY KEY,
+    source TEXT NOT NULL,
+    pkgname TEXT NOT NULL,
+    pkgver TEXT NOT NULL,
+    arch TEXT NOT NULL,
+    pkgdesc TEXT NOT NULL,
+    url TEXT NOT NULL,
+    license TEXT NOT NULL,
+    maintainer TEXT,
+    origin TEXT NOT NULL,
+    "commit" TEXT,
+    builddate INTEGER NOT NULL,
+    installed_size INTEGER NOT NULL,
+    provider_priority INTEGER,
+    replaces_priority INTEGER
+);
+CREATE INDEX IF NOT EXISTS packages_pkgname ON packages (pkgname);
+
+CREATE TABLE IF NOT EXISTS depends (
+    package_id INTEGER NOT NULL REFERENCES packages (id),
+    kind TEXT NOT NULL,
+    name TEXT NOT NULL,
+    op TEXT,
+    version TEXT
+);
+CREATE INDEX IF NOT EXISTS depends_package_id ON depends (package_id);
+CREATE INDEX IF NOT EXISTS depends_name ON depends (name);
+
+CREATE TABLE IF NOT EXISTS provides (
+    package_id INTEGER NOT NULL REFERENCES packages (id),
+    name TEXT NOT NULL,
+    version TEXT
+);
+CREATE INDEX IF NOT EXISTS provides_package_id ON provides (package_id);
+CREATE INDEX IF NOT EXISTS provides_name ON provides (name);
+
+CREATE TABLE IF NOT EXISTS files (
+    package_id INTEGER NOT NULL REFERENCES packages (id),
+    path TEXT NOT NULL,
+    type TEXT NOT NULL,
+    link_target TEXT,
+    uname TEXT NOT NULL,
+    gname TEXT NOT NULL,
+    size INTEGER,
+    mode INTEGER NOT NULL,
+    digest TEXT
+);
+CREATE INDEX IF NOT EXISTS files_package_id ON files (package_id);
+CREATE INDEX IF NOT EXISTS files_path ON files (path);
+"#;
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// A writer of packages into an SQLite database. All the records are written
+/// in a single transaction, which is committed by
+/// [`finish`](SqliteExporter::finish).
+pub struct SqliteExporter {
+    conn: Connection,
+}
+
+impl SqliteExporter {
+    /// Opens (or creates) the SQLite database at the given path and creates
+    /// the tables if they don't exist yet. If the database already contains
+    /// some packages, the new ones are appended.
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Self::from_connection(Connection::open(path)?)
+    }
+
+    /// Creates an exporter writing into the given database connection. It
+    /// creates the tables if they don't exist yet.
+    pub fn from_connection(conn: Connection) -> Result<Self, Error> {
+        conn.execute_batch(SCHEMA)?;
+        conn.execute_batch("BEGIN")?;
+
+        Ok(SqliteExporter { conn })
+    }
+
+    /// Writes the package with its dependencies, provides and files. The
+    /// `source` is stored in the `packages` table to distinguish packages
+    /// read from different files. Returns the `id` of the package.
+    pub fn add_package(&mut self, pkg: &Package, source: &str) -> Result<i64, Error> {
+        let tx = self.transaction()?;
+        let id = insert_package(&tx, source, &Record::from(pkg.pkginfo()))?;
+
+        let mut stmt = tx.prepare_cached(
+            "INSERT INTO files
+                (package_id, path, type, link_target, uname, gname, size, mode, digest)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        )?;
+        for file in pkg.files_metadata() {
+            let FileInfo {
+                path,
+                file_type,
+                link_target,
+                uname,
+                gname,
+                size,
+                mode,
+                digest,
+                ..
+            } = file;
+
+            stmt.execute(params![
+                id,
+                path.to_string_lossy(),
+                file_type.code(),
+                link_target.as_ref().map(|p| p.to_string_lossy()),
+                uname,
+                gname,
+                size,
+                mode,
+                digest,
+            ])?;
+        }
+        drop(stmt);
+        tx.commit()?;
+
+        Ok(id)
+    }
+
+    /// Writes all the packages in the index with their dependencies and
+    /// provides. The `source` is stored in the `packages` table to
+    /// distinguish packages read from different indexes.
+    pub fn add_index(&mut self, index: &Index, source: &str) -> Result<(), Error> {
+        let tx = self.transaction()?;
+        for entry in &index.entries {
+            insert_package(&tx, source, &Record::from(entry))?;
+        }
+        tx.commit()
+    }
+
+    /// Commits the written records and returns the database connection.
+    pub fn finish(self) -> Result<Connection, Error> {
+        self.conn.execute_batch("COMMIT")?;
+        Ok(self.conn)
+    }
+
+    /// Starts a nested transaction, so that a package is either written
+    /// completely or not at all.
+    fn transaction(&mut self) -> Result<Savepoint<'_>, Error> {
+        self.conn.savepoint()
+    }
+}
+
+/// The package metadata common to [`PkgInfo`] and [`IndexEntry`].
+struct Record<'a> {
+    pkgname: &'a str,
+    pkgver: &'a str,
+    arch: &'a str,
+    pkgdesc: &'a str,
+    url: &'a str,
+    license: &'a str,
+    maintainer: Option<&'a str>,
+    origin: &'a str,
+    commit: Option<&'a str>,
+    builddate: i64,
+    installed_size: u64,
+    provider_priority: Option<u16>,
+    replaces_priority: Option<u16>,
+    depends: [(&'static str, &'a [Dependency]); 4],
+    provides: &'a [Dependency],
+}
+
+impl<'a> From<&'a PkgInfo> for Record<'a> {
+    fn from(pkginfo: &'a PkgInfo) -> Self {
+        Record {
+            pkgname: &pkginfo.pkgname,
+            pkgver: &pkginfo.pkgver,
+            arch: &pkginfo.arch,
+            pkgdesc: &pkginfo.pkgdesc,
+            url: &pkginfo.url,
+            license: &pkginfo.license,
+            maintainer: pkginfo.maintainer.as_deref(),
+            origin: &pkginfo.origin,
+            commit: pkginfo.commit.as_deref(),
+            builddate: pkginfo.builddate,
+            installed_size: pkginfo.size as u64,
+            provider_priority: pkginfo.provider_priority,
+            replaces_priority: pkginfo.replaces_priority,
+            depends: [
+                ("depends", &pkginfo.depends),
+                ("conflicts", &pkginfo.conflicts),
+                ("install_if", &pkginfo.install_if),
+                ("replaces", &pkginfo.replaces),
+            ],
+            provides: &pkginfo.provides,
+        }
+    }
+}
+
+impl<'a> From<&'a IndexEntry> for Record<'a> {
+    fn from(entry: &'a IndexEntry) -> Self {
+        Record {
+            pkgname: &entry.pkgname,
+            pkgver: &entry.pkgver,
+            arch: &entry.arch,
+            pkgdesc: &entry.pkgdesc,
+            url: &entry.url,
+            license: &entry.license,
+            maintainer: entry.maintainer.as_deref(),
+            origin: &entry.origin,
+            commit: entry.commit.as_deref(),
+            builddate: entry.builddate,
+            installed_size: entry.installed_size,
+            provider_priority: entry.provider_priority,
+            replaces_priority: entry.replaces_priority,
+            depends: [
+                ("depends", &entry.depends),
+                ("conflicts", &entry.conflicts),
+                ("install_if", &entry.install_if),
+                ("replaces", &entry.replaces),
+            ],
+            provides: &entry.provides,
+        }
+    }
+}
+
+fn insert_package(conn: &Connection, source: &str, rec: &Record) -> Result<i64, Error> {
+    conn.prepare_cached(
+        r#"INSERT INTO packages
+            (source, pkgname, pkgver, arch, pkgdesc, url, license, maintainer, origin,
+             "commit", builddate, installed_size, provider_priority, replaces_priority)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)"#,
+    )?
+    .execute(params![
+        source,
+        rec.pkgname,
+        rec.pkgver,
+        rec.arch,
+        rec.pkgdesc,
+        rec.url,
+        rec.license,
+        rec.maintainer,
+        rec.origin,
+        rec.commit,
+        rec.builddate,
+        rec.installed_size,
+        rec.provider_priority,
+        rec.replaces_priority,
+    ])?;
+    let id = conn.last_insert_rowid();
+
+    let mut stmt = conn.prepare_cached(
+        "INSERT INTO depends (package_id, kind, name, op, version) VALUES (?1, ?2, ?3, ?4, ?5)",
+    )?;
+    for (kind, deps) in rec.depends {
+        for dep in deps {
+            let (op, version) = match &dep.constraint {
+                Some(c) => (Some(c.op.to_string()), Some(c.version.as_str())),
+                None => (None, None),
+            };
+            stmt.execute(params![id, kind, dep.name, op, version])?;
+        }
+    }
+
+    let mut stmt = conn
+        .prepare_cached("INSERT INTO provides (package_id, name, version) VALUES (?1, ?2, ?3)")?;
+    for dep in rec.provides {
+        let version = dep.constraint.as_ref().map(|c| c.version.as_str());
+        stmt.execute(params![id, dep.name, version])?;
+    }
+
+    Ok(id)
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+#[path = "sqlite.test.rs"]
+mod test;
@@ -0,0 +1,78 @@
+use std::fs::{self, File};
+use std::io::BufReader;
+
+use super::*;
+use crate::internal::test_utils::{assert, S};
+
+fn query_strings(conn: &Connection, sql: &str) -> Vec<String> {
+    let mut stmt = conn.prepare(sql).unwrap();
+    let rows = stmt.query_map([], |row| row.get(0)).unwrap();
+    rows.map(Result::unwrap).collect()
+}
+
+#[test]
+fn export_package() {
+    let path = "../fixtures/apk/rssh-2.3.4-r3.apk";
+    let pkg = Package::load(File::open(path).map(BufReader::new).unwrap()).unwrap();
+
+    let mut exporter =
+        SqliteExporter::from_connection(Connection::open_in_memory().unwrap()).unwrap();
+    let id = exporter.add_package(&pkg, path).unwrap();
+    let conn = exporter.finish().unwrap();
+
+    assert!(
+        query_strings(&conn, "SELECT pkgname || '-' || pkgver FROM packages")
+            == [S!("rssh-2.3.4-r3")]
+    );
+
+    let files_count: usize = conn
+        .query_row(
+            "SELECT count(*) FROM files WHERE package_id = ?1",
+            [id],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert!(files_count == pkg.files_metadata().len());
+
+    assert!(
+        query_strings(
+            &conn,
+            "SELECT name FROM depends WHERE kind = 'depends' ORDER BY rowid"
+        ) == pkg
+            .pkginfo()
+            .depends
+            .iter()
+            .map(|d| d.name.clone())
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn export_index() {
+    let index =
+        Index::parse(&fs::read_to_string("../fixtures/apkindex/APKINDEX").unwrap()).unwrap();
+
+    let mut exporter =
+        SqliteExporter::from_connection(Connection::open_in_memory().unwrap()).unwrap();
+    exporter.add_index(&index, "APKINDEX").unwrap();
+    let conn = exporter.finish().unwrap();
+
+    assert!(
+        query_strings(&conn, "SELECT pkgname FROM packages ORDER BY id")
+            == ["busybox", "rssh", "rssh-doc", "dropbear"]
+    );
+    assert!(
+        query_strings(
+            &conn,
+            "SELECT p.pkgname FROM packages p JOIN depends d ON d.package_id = p.id
+             WHERE d.kind = 'conflicts' AND d.name = 'openssh-server'"
+        ) == ["dropbear"]
+    );
+    assert!(
+        query_strings(
+            &conn,
+            "SELECT name || '=' || version FROM provides
+             WHERE package_id = (SELECT id FROM packages WHERE pkgname = 'busybox')"
+        ) == ["cmd:busybox=1.36.1-r5", "cmd:sh=1.36.1-r5"]
+    );
+}
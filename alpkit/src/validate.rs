@@ -0,0 +1,662 @@
+//! Validation of the parsed metadata.
+//!
+//! The validation checks that values are well-formed (e.g. that `pkgver` is
+//! a valid version, `maintainer` is a valid mailbox), so consumers don't have
+//! to. All violations are collected into a [`ValidationReport`] that can be
+//! serialized, e.g. to emit machine-readable reports in CI.
+//!
+//! Example:
+//! ```no_run
+//! use alpkit::apkbuild::ApkbuildReader;
+//! use alpkit::validate::Validate;
+//!
+//! let apkbuild = ApkbuildReader::new().read_apkbuild("aports/main/sample/APKBUILD").unwrap();
+//! if let Err(report) = apkbuild.validate() {
+//!     println!("{}", serde_json::to_string(&report).unwrap());
+//! }
+//! ```
+use std::fmt;
+use std::path::{Component, Path};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::apkbuild::{Apkbuild, ChecksumAlg};
+use crate::dependency::Dependency;
+use crate::internal::mailbox::is_valid_mailbox;
+use crate::license::LicenseExpr;
+use crate::package::{FileInfo, FileType, Package, PkgInfo, PkgScript};
+use crate::version;
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// The maximum length of `pkgdesc` enforced by abuild.
+const PKGDESC_MAX_LEN: usize = 128;
+
+/// Prefixes of the recognized vulnerability identifiers that are followed by
+/// `-<number>`, e.g. `XSA-123` or `ALPINE-13661`.
+const VULN_ID_NUMBERED: &[&str] = &["ALPINE", "XSA", "ZDI-CAN", "OSVDB", "BDSA"];
+
+/// Prefixes of the recognized vulnerability identifiers that are followed by
+/// `-<year>-<number>`, e.g. `CVE-2022-1234` or `RUSTSEC-2021-0001`.
+const VULN_ID_YEARLY: &[&str] = &["CVE", "GO", "PYSEC", "RUSTSEC", "OSV", "DSA", "TALOS"];
+
+/// A type whose values can be validated.
+pub trait Validate {
+    /// Validates the value with the default [`ValidationContext`] and returns
+    /// a report with all the violations found, if any.
+    fn validate(&self) -> Result<(), ValidationReport> {
+        self.validate_with(&ValidationContext::default())
+    }
+
+    /// Validates the value with the given context and returns a report with
+    /// all the violations found, if any.
+    fn validate_with(&self, ctx: &ValidationContext) -> Result<(), ValidationReport>;
+}
+
+/// Options that adjust the validation rules.
+#[derive(Debug, Clone, Default)]
+pub struct ValidationContext {
+    freeform_vuln_ids: bool,
+    skip_rules: Vec<String>,
+}
+
+impl ValidationContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets if any non-empty identifiers (separated by whitespace) should be
+    /// accepted in `secfixes`, or only the recognized formats (default), i.e.
+    /// CVE, GHSA, XSA, ALPINE and a few other common ones.
+    pub fn freeform_vuln_ids(&mut self, cond: bool) -> &mut Self {
+        self.freeform_vuln_ids = cond;
+        self
+    }
+
+    /// Disables the validation rules with the given names (as reported in
+    /// [`Violation::rule`]), e.g. `url` for aports with intranet-only URLs
+    /// or `license-deprecated`.
+    pub fn skip_rules<S: AsRef<str>>(&mut self, rules: &[S]) -> &mut Self {
+        self.skip_rules
+            .extend(rules.iter().map(|s| s.as_ref().to_owned()));
+        self
+    }
+}
+
+/// A single validation failure.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Violation {
+    /// Path of the invalid field, e.g. `pkgver` or `depends[2]`.
+    pub path: String,
+
+    /// Name of the violated rule, e.g. `version` or `mailbox`.
+    pub rule: String,
+
+    /// A human-readable description of the violation.
+    pub message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// A report of all violations found by [`Validate::validate`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Error, Deserialize, Serialize)]
+pub struct ValidationReport {
+    pub violations: Vec<Violation>,
+}
+
+impl ValidationReport {
+    /// Returns `true` if there are no violations.
+    pub fn is_empty(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    /// Returns an iterator over the violations of the field at the given path.
+    pub fn field<'a>(&'a self, path: &'a str) -> impl Iterator<Item = &'a Violation> + 'a {
+        self.violations.iter().filter(move |v| v.path == path)
+    }
+
+    fn check<P: ToString>(&mut self, path: P, rule: &str, result: Result<(), String>) {
+        if let Err(message) = result {
+            self.violations.push(Violation {
+                path: path.to_string(),
+                rule: rule.to_owned(),
+                message,
+            });
+        }
+    }
+
+    fn check_dependencies(&mut self, field: &str, deps: &[Dependency]) {
+        for (i, dep) in deps.iter().enumerate() {
+            self.check(
+                format_args!("{field}[{i}]"),
+                "dependency",
+                check_dependency(dep),
+            );
+        }
+    }
+
+    /// Moves violations from the `other` report into this one, prefixing their
+    /// paths with the given `prefix`.
+    fn merge<P: fmt::Display>(&mut self, prefix: P, other: Result<(), Self>) {
+        if let Err(other) = other {
+            self.violations
+                .extend(other.violations.into_iter().map(|v| Violation {
+                    path: format!("{prefix}.{}", v.path),
+                    ..v
+                }));
+        }
+    }
+
+    fn check_license(&mut self, path: &str, license: &str) {
+        let expr = match LicenseExpr::parse(license) {
+            Ok(expr) => expr,
+            Err(e) => return self.check(path, "license", Err(e.to_string())),
+        };
+        for req in expr.unknown() {
+            self.check(
+                path,
+                "license",
+                Err(format!("unknown SPDX license identifier: '{req}'")),
+            );
+        }
+        for req in expr.deprecated() {
+            self.check(
+                path,
+                "license-deprecated",
+                Err(format!("deprecated SPDX license identifier: '{req}'")),
+            );
+        }
+    }
+
+    fn into_result(mut self, ctx: &ValidationContext) -> Result<(), Self> {
+        self.violations
+            .retain(|v| !ctx.skip_rules.contains(&v.rule));
+
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, violation) in self.violations.iter().enumerate() {
+            if i > 0 {
+                f.write_str("\n")?;
+            }
+            write!(f, "{violation}")?;
+        }
+        Ok(())
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+impl Validate for Apkbuild {
+    fn validate_with(&self, ctx: &ValidationContext) -> Result<(), ValidationReport> {
+        let mut r = ValidationReport::default();
+
+        if let Some(maintainer) = &self.maintainer {
+            r.check("maintainer", "mailbox", check_mailbox(maintainer));
+        }
+        for (i, contributor) in self.contributors.iter().enumerate() {
+            r.check(
+                format_args!("contributors[{i}]"),
+                "mailbox",
+                check_mailbox(contributor),
+            );
+        }
+        r.check("pkgname", "pkgname", check_pkgname(&self.pkgname));
+        r.check("pkgver", "version", check_version(&self.pkgver, false));
+        r.check("pkgdesc", "pkgdesc", check_pkgdesc(&self.pkgdesc));
+        r.check("url", "url", check_url(&self.url));
+        for (i, arch) in self.arch.iter().enumerate() {
+            r.check(format_args!("arch[{i}]"), "arch", check_arch(arch));
+        }
+        r.check_license("license", &self.license);
+
+        r.check_dependencies("depends", &self.depends);
+        r.check_dependencies("makedepends", &self.makedepends);
+        r.check_dependencies("makedepends_build", &self.makedepends_build);
+        r.check_dependencies("makedepends_host", &self.makedepends_host);
+        r.check_dependencies("checkdepends", &self.checkdepends);
+        r.check_dependencies("install_if", &self.install_if);
+        r.check_dependencies("provides", &self.provides);
+        r.check_dependencies("replaces", &self.replaces);
+        for (suffix, deps) in &self.subpackage_depends {
+            r.check_dependencies(&format!("depends_{suffix}"), deps);
+        }
+        for (i, subpkg) in self.subpackages.iter().enumerate() {
+            let name = subpkg.split(':').next().unwrap_or(subpkg);
+            r.check(
+                format_args!("subpackages[{i}]"),
+                "pkgname",
+                check_pkgname(name),
+            );
+        }
+
+        for (i, source) in self.source.iter().enumerate() {
+            r.check(
+                format_args!("source[{i}].name"),
+                "not-empty",
+                check_not_empty(&source.name),
+            );
+            r.check(
+                format_args!("source[{i}].checksum"),
+                "checksum",
+                check_checksum(&source.checksum, source.checksum_alg),
+            );
+        }
+        for (i, secfix) in self.secfixes.iter().enumerate() {
+            // "0" is used for vulnerabilities that never affected the package.
+            if secfix.version != "0" {
+                r.check(
+                    format_args!("secfixes[{i}].version"),
+                    "version",
+                    check_version(&secfix.version, true),
+                );
+            }
+            for (j, id) in secfix.fixes.iter().enumerate() {
+                r.check(
+                    format_args!("secfixes[{i}].fixes[{j}]"),
+                    "vuln-id",
+                    check_vuln_id(id, ctx.freeform_vuln_ids),
+                );
+            }
+        }
+
+        // Rules spanning multiple fields.
+        let fullver = format!("{}-r{}", self.pkgver, self.pkgrel);
+        for (i, secfix) in self.secfixes.iter().enumerate() {
+            // Invalid versions are already reported by the rules above.
+            if version::is_valid(&fullver)
+                && version::is_valid(&secfix.version)
+                && version::compare(&secfix.version, &fullver).is_gt()
+            {
+                r.check(
+                    format_args!("secfixes[{i}].version"),
+                    "secfix-version",
+                    Err(format!(
+                        "version {} is newer than the package version {fullver}",
+                        secfix.version
+                    )),
+                );
+            }
+        }
+        let pkgnames: Vec<_> = std::iter::once(self.pkgname.as_str())
+            .chain(
+                self.subpackages
+                    .iter()
+                    .map(|s| s.split(':').next().unwrap_or(s)),
+            )
+            .collect();
+        for (i, script) in self.install.iter().enumerate() {
+            r.check(
+                format_args!("install[{i}]"),
+                "install-script",
+                check_install_script(script, &pkgnames),
+            );
+        }
+        for (i, trigger) in self.triggers.iter().enumerate() {
+            r.check(
+                format_args!("triggers[{i}]"),
+                "trigger",
+                check_trigger(trigger, &pkgnames),
+            );
+        }
+        if self.provider_priority.is_some() {
+            r.check(
+                "provider_priority",
+                "provider-priority",
+                check_provider_priority(&self.provides),
+            );
+        }
+        r.into_result(ctx)
+    }
+}
+
+impl Validate for PkgInfo {
+    fn validate_with(&self, ctx: &ValidationContext) -> Result<(), ValidationReport> {
+        let mut r = ValidationReport::default();
+
+        if let Some(maintainer) = &self.maintainer {
+            r.check("maintainer", "mailbox", check_mailbox(maintainer));
+        }
+        r.check("pkgname", "pkgname", check_pkgname(&self.pkgname));
+        r.check("pkgver", "version", check_version(&self.pkgver, true));
+        r.check("pkgdesc", "pkgdesc", check_pkgdesc(&self.pkgdesc));
+        r.check("url", "url", check_url(&self.url));
+        r.check("arch", "arch", check_arch(&self.arch));
+        r.check_license("license", &self.license);
+
+        r.check_dependencies("depends", &self.depends);
+        r.check_dependencies("conflicts", &self.conflicts);
+        r.check_dependencies("install_if", &self.install_if);
+        r.check_dependencies("provides", &self.provides);
+        r.check_dependencies("replaces", &self.replaces);
+
+        r.check("origin", "pkgname", check_pkgname(&self.origin));
+        if let Some(commit) = &self.commit {
+            r.check("commit", "hex", check_hex(commit, 40));
+        }
+        r.check("packager", "one-line", check_one_line(&self.packager));
+        if !self.datahash.is_empty() {
+            r.check("datahash", "hex", check_hex(&self.datahash, 64));
+        }
+        if self.provider_priority.is_some() {
+            r.check(
+                "provider_priority",
+                "provider-priority",
+                check_provider_priority(&self.provides),
+            );
+        }
+        r.into_result(ctx)
+    }
+}
+
+impl Validate for FileInfo {
+    fn validate_with(&self, ctx: &ValidationContext) -> Result<(), ValidationReport> {
+        let mut r = ValidationReport::default();
+        let is_link = matches!(self.file_type, FileType::Link | FileType::Symlink);
+        let is_device = matches!(self.file_type, FileType::Char | FileType::Block);
+
+        r.check("path", "path", check_path(&self.path));
+        match (&self.link_target, is_link) {
+            (Some(target), true) if target.as_os_str().is_empty() => r.check(
+                "link_target",
+                "not-empty",
+                Err("must not be empty".to_owned()),
+            ),
+            (None, true) => r.check(
+                "link_target",
+                "link-target",
+                Err("must be specified for a link".to_owned()),
+            ),
+            (Some(_), false) => r.check(
+                "link_target",
+                "link-target",
+                Err("must not be specified for a non-link file".to_owned()),
+            ),
+            _ => (),
+        }
+        r.check("uname", "not-empty", check_not_empty(&self.uname));
+        r.check("gname", "not-empty", check_not_empty(&self.gname));
+        if self.mode & !0o7777 != 0 {
+            r.check(
+                "mode",
+                "mode",
+                Err(format!(
+                    "must contain only permission bits: 0{:o}",
+                    self.mode
+                )),
+            );
+        }
+        if self.device != 0 && !is_device {
+            r.check(
+                "device",
+                "device",
+                Err("must be 0 for a non-device file".to_owned()),
+            );
+        }
+        if let Some(digest) = &self.digest {
+            // apk-tools stores SHA-1 of the file contents.
+            r.check("digest", "hex", check_hex(digest, 40));
+        }
+        r.into_result(ctx)
+    }
+}
+
+impl Validate for Package {
+    fn validate_with(&self, ctx: &ValidationContext) -> Result<(), ValidationReport> {
+        let mut r = ValidationReport::default();
+
+        r.merge("pkginfo", self.pkginfo().validate_with(ctx));
+        for (i, file) in self.files_metadata().enumerate() {
+            r.merge(format_args!("files[{i}]"), file.validate_with(ctx));
+        }
+        r.into_result(ctx)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+fn check_not_empty(s: &str) -> Result<(), String> {
+    if s.is_empty() {
+        return Err("must not be empty".to_owned());
+    }
+    Ok(())
+}
+
+fn check_one_line(s: &str) -> Result<(), String> {
+    check_not_empty(s)?;
+    if s.contains(|c: char| c.is_control()) {
+        return Err("must be a single line without control characters".to_owned());
+    }
+    Ok(())
+}
+
+fn check_path(path: &Path) -> Result<(), String> {
+    if !path.is_absolute() {
+        return Err(format!("must be an absolute path: '{}'", path.display()));
+    }
+    if path.components().any(|c| c == Component::ParentDir) {
+        return Err(format!("must not contain '..': '{}'", path.display()));
+    }
+    Ok(())
+}
+
+fn check_pkgname(s: &str) -> Result<(), String> {
+    check_not_empty(s)?;
+    if !s.starts_with(|c: char| c.is_ascii_alphanumeric()) {
+        return Err(format!("must start with an alphanumeric character: '{s}'"));
+    }
+    if let Some(c) = s
+        .chars()
+        .find(|c| !c.is_ascii_alphanumeric() && !matches!(c, '.' | '_' | '+' | '-'))
+    {
+        return Err(format!("contains invalid character '{c}': '{s}'"));
+    }
+    Ok(())
+}
+
+fn check_pkgdesc(s: &str) -> Result<(), String> {
+    check_one_line(s)?;
+    if s.chars().count() > PKGDESC_MAX_LEN {
+        return Err(format!(
+            "must not be longer than {PKGDESC_MAX_LEN} characters"
+        ));
+    }
+    Ok(())
+}
+
+fn check_url(s: &str) -> Result<(), String> {
+    let rest = match s
+        .strip_prefix("https://")
+        .or_else(|| s.strip_prefix("http://"))
+    {
+        Some(rest) => rest,
+        None => return Err(format!("must be an http(s) URL: '{s}'")),
+    };
+    if rest.is_empty() || rest.starts_with('/') || rest.contains(|c: char| c.is_whitespace()) {
+        return Err(format!("must be a valid URL: '{s}'"));
+    }
+    Ok(())
+}
+
+fn check_mailbox(s: &str) -> Result<(), String> {
+    if !is_valid_mailbox(s) {
+        return Err(format!(
+            "must be a mailbox in format 'Full Name <email@example.org>': '{s}'"
+        ));
+    }
+    Ok(())
+}
+
+fn check_arch(s: &str) -> Result<(), String> {
+    let name = s.strip_prefix('!').unwrap_or(s);
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+    {
+        return Err(format!("invalid architecture name: '{s}'"));
+    }
+    Ok(())
+}
+
+fn check_dependency(dep: &Dependency) -> Result<(), String> {
+    check_not_empty(&dep.name)?;
+    if dep
+        .name
+        .contains(|c: char| c.is_whitespace() || c.is_control())
+    {
+        return Err(format!("invalid name: '{}'", dep.name));
+    }
+    if let Some(constraint) = &dep.constraint {
+        // Fuzzy and checksum constraints may contain only a version prefix or
+        // a checksum, respectively.
+        if constraint.version.is_empty() || constraint.version.contains(|c: char| c.is_whitespace())
+        {
+            return Err(format!("invalid version constraint: '{dep}'"));
+        }
+    }
+    Ok(())
+}
+
+/// Checks if the `install` entry is `<pkgname>.<script>` with one of the given
+/// package names and a known script type, e.g. `foo.post-install`.
+fn check_install_script(s: &str, pkgnames: &[&str]) -> Result<(), String> {
+    let (name, script) = match s.rsplit_once('.') {
+        Some(pair) => pair,
+        None => return Err(format!("must be in format <pkgname>.<script>: '{s}'")),
+    };
+    if matches!(script.parse::<PkgScript>(), Err(_) | Ok(PkgScript::Trigger)) {
+        return Err(format!("unknown script type '{script}': '{s}'"));
+    }
+    if !pkgnames.contains(&name) {
+        return Err(format!(
+            "'{name}' is not the package name nor a subpackage: '{s}'"
+        ));
+    }
+    Ok(())
+}
+
+/// Checks if the `triggers` entry is `<pkgname>.trigger=<paths>` with one of
+/// the given package names.
+fn check_trigger(s: &str, pkgnames: &[&str]) -> Result<(), String> {
+    let (name, paths) = match s
+        .split_once('=')
+        .and_then(|(script, paths)| Some((script.strip_suffix(".trigger")?, paths)))
+    {
+        Some(pair) => pair,
+        None => {
+            return Err(format!(
+                "must be in format <pkgname>.trigger=<paths>: '{s}'"
+            ))
+        }
+    };
+    if paths.is_empty() {
+        return Err(format!("must specify at least one path: '{s}'"));
+    }
+    if !pkgnames.contains(&name) {
+        return Err(format!(
+            "'{name}' is not the package name nor a subpackage: '{s}'"
+        ));
+    }
+    Ok(())
+}
+
+fn check_provider_priority(provides: &[Dependency]) -> Result<(), String> {
+    if !provides.iter().any(|dep| dep.constraint.is_none()) {
+        return Err("is meaningful only with an unversioned provides".to_owned());
+    }
+    Ok(())
+}
+
+fn check_checksum(s: &str, alg: ChecksumAlg) -> Result<(), String> {
+    let len = match alg {
+        ChecksumAlg::Sha512 => 128,
+        ChecksumAlg::Sha256 => 64,
+        ChecksumAlg::Md5 => 32,
+    };
+    check_hex(s, len)
+}
+
+fn check_hex(s: &str, len: usize) -> Result<(), String> {
+    if s.len() != len || !s.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("must be {len} hexadecimal characters: '{s}'"));
+    }
+    Ok(())
+}
+
+/// Checks if `s` is a known vulnerability identifier (or a sequence of them
+/// separated by whitespace), or any words if `freeform` is `true`.
+fn check_vuln_id(s: &str, freeform: bool) -> Result<(), String> {
+    check_one_line(s)?;
+    if freeform {
+        return Ok(());
+    }
+    if let Some(id) = s.split_whitespace().find(|id| !is_known_vuln_id(id)) {
+        return Err(format!("unknown vulnerability identifier format: '{id}'"));
+    }
+    Ok(())
+}
+
+fn is_known_vuln_id(id: &str) -> bool {
+    fn is_number(s: &str) -> bool {
+        !s.is_empty() && s.chars().all(|c| c.is_ascii_digit())
+    }
+
+    // GHSA-xxxx-xxxx-xxxx
+    if let Some(rest) = id.strip_prefix("GHSA-") {
+        return rest.len() == 14
+            && rest.split('-').all(|part| {
+                part.len() == 4
+                    && part
+                        .chars()
+                        .all(|c| c.is_ascii_digit() || c.is_ascii_lowercase())
+            });
+    }
+    let prefix_rest = |prefixes: &[&str]| {
+        prefixes
+            .iter()
+            .find_map(|prefix| id.strip_prefix(prefix)?.strip_prefix('-'))
+    };
+    if let Some(rest) = prefix_rest(VULN_ID_YEARLY) {
+        return match rest.split_once('-') {
+            Some((year, num)) => year.len() == 4 && is_number(year) && is_number(num),
+            None => false,
+        };
+    }
+    if let Some(num) = prefix_rest(VULN_ID_NUMBERED) {
+        return is_number(num);
+    }
+    false
+}
+
+/// Checks if `s` is a valid apk version, optionally with the release suffix
+/// (`-r<n>`), e.g. `1.2.3_rc1-r0`.
+fn check_version(s: &str, with_release: bool) -> Result<(), String> {
+    // The release is optional for version::is_valid.
+    if s.contains("-r") != with_release || !version::is_valid(s) {
+        return Err(if with_release {
+            format!("must be a version with release (e.g. 1.2.3-r0): '{s}'")
+        } else {
+            format!("must be a version (e.g. 1.2.3 or 1.2.3_rc1): '{s}'")
+        });
+    }
+    Ok(())
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+#[path = "validate.test.rs"]
+mod test;
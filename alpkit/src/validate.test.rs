@@ -0,0 +1,342 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::PathBuf;
+
+use serde_json::json;
+
+use super::*;
+use crate::apkbuild::{ApkbuildReader, Secfix, Source};
+use crate::internal::test_utils::{assert, assert_let, dependency, S};
+use crate::package::Package;
+
+#[test]
+fn validate_apkbuild_sample() {
+    let apkbuild = ApkbuildReader::new()
+        .read_apkbuild("../fixtures/aports/sample/APKBUILD")
+        .unwrap();
+    assert_let!(Err(report) = apkbuild.validate());
+
+    // The sample has provider_priority with only versioned provides.
+    let rules: Vec<_> = report.violations.iter().map(|v| v.rule.as_str()).collect();
+    assert!(rules == vec!["provider-priority"]);
+}
+
+#[test]
+fn validate_pkginfo_fixture() {
+    let file = File::open("../fixtures/apk/rssh-2.3.4-r3.apk").unwrap();
+    let pkg = Package::load(BufReader::new(file)).unwrap();
+
+    assert_let!(Ok(()) = pkg.validate());
+}
+
+#[test]
+fn validate_fileinfo_invalid() {
+    let file = FileInfo {
+        path: PathBuf::from("/usr/../etc/foo"),
+        file_type: FileType::Symlink,
+        mode: 0o100644,
+        device: 42,
+        digest: Some(S!("abc")),
+        ..Default::default()
+    };
+    assert_let!(Err(report) = file.validate());
+
+    let violations: Vec<_> = report
+        .violations
+        .iter()
+        .map(|v| (v.path.as_str(), v.rule.as_str()))
+        .collect();
+
+    assert!(
+        violations
+            == vec![
+                ("path", "path"),
+                ("link_target", "link-target"),
+                ("mode", "mode"),
+                ("device", "device"),
+                ("digest", "hex"),
+            ]
+    );
+}
+
+#[test]
+fn validate_fileinfo_valid() {
+    for file in [
+        FileInfo {
+            path: PathBuf::from("/usr/bin/foo"),
+            mode: 0o4755,
+            digest: Some(S!("da39a3ee5e6b4b0d3255bfef95601890afd80709")),
+            ..Default::default()
+        },
+        FileInfo {
+            path: PathBuf::from("/usr/bin/bar"),
+            file_type: FileType::Symlink,
+            link_target: Some(PathBuf::from("foo")),
+            mode: 0o777,
+            ..Default::default()
+        },
+        FileInfo {
+            path: PathBuf::from("/dev/null"),
+            file_type: FileType::Char,
+            device: 259,
+            ..Default::default()
+        },
+    ] {
+        assert_let!(Ok(()) = file.validate());
+    }
+}
+
+#[test]
+fn validate_apkbuild_invalid() {
+    let apkbuild = Apkbuild {
+        maintainer: Some(S!("Kevin Flynn")),
+        pkgname: S!("-foo"),
+        pkgver: S!("1.0-r1"),
+        pkgdesc: S!("Foo\nbar"),
+        url: S!("example.org"),
+        arch: vec![S!("x86-64")],
+        license: S!("MIT AND GPL-2.0 AND Foo"),
+        depends: vec![dependency("bar"), Dependency::new("baz qux", None)],
+        source: vec![Source::new(
+            "foo.tar.gz",
+            "https://example.org/foo.tar.gz",
+            "abcd",
+        )],
+        secfixes: vec![
+            Secfix::new("0", vec![S!("CVE-2022-1234")]),
+            Secfix::new("1.0", vec![S!("CVE-2022-5678"), S!("bug#123")]),
+        ],
+        ..Default::default()
+    };
+    assert_let!(Err(report) = apkbuild.validate());
+
+    let violations: Vec<_> = report
+        .violations
+        .iter()
+        .map(|v| (v.path.as_str(), v.rule.as_str()))
+        .collect();
+
+    assert!(
+        violations
+            == vec![
+                ("maintainer", "mailbox"),
+                ("pkgname", "pkgname"),
+                ("pkgver", "version"),
+                ("pkgdesc", "pkgdesc"),
+                ("url", "url"),
+                ("arch[0]", "arch"),
+                ("license", "license"),
+                ("license", "license-deprecated"),
+                ("depends[1]", "dependency"),
+                ("source[0].checksum", "checksum"),
+                ("secfixes[1].version", "version"),
+                ("secfixes[1].fixes[1]", "vuln-id"),
+            ]
+    );
+    assert!(report.field("url").count() == 1);
+    assert!(report.field("license").count() == 2);
+}
+
+#[test]
+fn validate_with_freeform_vuln_ids() {
+    let apkbuild = Apkbuild {
+        pkgname: S!("foo"),
+        pkgver: S!("1.0"),
+        pkgdesc: S!("Foo"),
+        url: S!("https://example.org"),
+        license: S!("MIT"),
+        secfixes: vec![Secfix::new("1.0-r0", vec![S!("bug#123")])],
+        ..Default::default()
+    };
+    assert_let!(Err(_) = apkbuild.validate());
+    assert_let!(Ok(()) = apkbuild.validate_with(ValidationContext::new().freeform_vuln_ids(true)));
+}
+
+#[test]
+fn validate_with_skip_rules() {
+    let apkbuild = Apkbuild {
+        pkgname: S!("foo"),
+        pkgver: S!("1.0"),
+        pkgdesc: S!("Foo"),
+        url: S!("intranet/foo"),
+        license: S!("GPL-2.0"),
+        ..Default::default()
+    };
+    assert_let!(Err(report) = apkbuild.validate());
+    assert!(report.violations.len() == 2);
+
+    let mut ctx = ValidationContext::new();
+    ctx.skip_rules(&["url", "license-deprecated"]);
+
+    assert_let!(Ok(()) = apkbuild.validate_with(&ctx));
+}
+
+#[test]
+fn validate_pkginfo_invalid() {
+    let pkginfo = PkgInfo {
+        pkgname: S!("foo"),
+        pkgver: S!("1.0"),
+        pkgdesc: S!("Foo"),
+        url: S!("https://example.org"),
+        arch: S!("x86_64"),
+        license: S!("MIT OR"),
+        origin: S!("foo"),
+        commit: Some(S!("xyz")),
+        packager: S!("Buildozer <alpine-devel@lists.alpinelinux.org>"),
+        ..Default::default()
+    };
+    assert_let!(Err(report) = pkginfo.validate());
+
+    let paths: Vec<_> = report.violations.iter().map(|v| v.path.as_str()).collect();
+    assert!(paths == vec!["pkgver", "license", "commit"]);
+}
+
+#[test]
+fn validation_report_serialize() {
+    let report = ValidationReport {
+        violations: vec![Violation {
+            path: S!("pkgver"),
+            rule: S!("version"),
+            message: S!("must be a version (e.g. 1.2.3 or 1.2.3_rc1): '1.x'"),
+        }],
+    };
+
+    assert!(
+        serde_json::to_value(&report).unwrap()
+            == json!({
+                "violations": [{
+                    "path": "pkgver",
+                    "rule": "version",
+                    "message": "must be a version (e.g. 1.2.3 or 1.2.3_rc1): '1.x'",
+                }]
+            })
+    );
+    assert!(report.to_string() == "pkgver: must be a version (e.g. 1.2.3 or 1.2.3_rc1): '1.x'");
+}
+
+#[test]
+#[rustfmt::skip]
+fn check_version_cases() {
+    for (input, with_release, valid) in [
+        ("1.2.3"          , false, true ),
+        ("1.2.3a"         , false, true ),
+        ("1.2.3_rc1"      , false, true ),
+        ("1.2.3_alpha_p2" , false, true ),
+        ("0_git20230101"  , false, true ),
+        ("1.2.3~abc123"   , false, true ),
+        ("1.2.3-r0"       , true , true ),
+        ("1.2_pre1-r12"   , true , true ),
+        ("1.2.3"          , true , false),
+        ("1.2.3-r0"       , false, false),
+        (""               , false, false),
+        ("v1.2.3"         , false, false),
+        ("1..2"           , false, false),
+        ("1.2.3_foo"      , false, false),
+        ("1.2.3ab"        , false, false),
+        ("1.2.3-rx"       , true , false),
+    ] {
+        assert!(check_version(input, with_release).is_ok() == valid, "{input}");
+    }
+}
+
+#[test]
+#[rustfmt::skip]
+fn check_vuln_id_cases() {
+    for (input, valid) in [
+        ("CVE-2022-1234"           , true ),
+        ("CVE-2021-1000123"        , true ),
+        ("CVE-2020-1234 CVE-2020-5678", true),
+        ("GHSA-q8mf-hqq9-jxv5"     , true ),
+        ("XSA-123"                 , true ),
+        ("ALPINE-13661"            , true ),
+        ("RUSTSEC-2021-0001"       , true ),
+        ("GO-2022-0493"            , true ),
+        ("CVE-22-1234"             , false),
+        ("CVE-2022-"               , false),
+        ("GHSA-q8mf-hqq9"          , false),
+        ("XSA-abc"                 , false),
+        ("CVE2022-1234"            , false),
+        ("bug#123"                 , false),
+        (""                        , false),
+    ] {
+        assert!(check_vuln_id(input, false).is_ok() == valid, "{input}");
+    }
+    assert!(check_vuln_id("bug#123", true).is_ok());
+    assert!(check_vuln_id("bug#123 CVE-2022-1234", true).is_ok());
+    assert!(check_vuln_id("", true).is_err());
+}
+
+#[test]
+fn check_vuln_id_freeform_superset() {
+    let ids = "CVE-2022-1 CVE-2022-2";
+    assert!(check_vuln_id(ids, false).is_ok());
+    assert!(check_vuln_id(ids, true).is_ok());
+}
+
+#[test]
+fn validate_apkbuild_cross_fields() {
+    let apkbuild = Apkbuild {
+        pkgname: S!("foo"),
+        pkgver: S!("1.2"),
+        pkgrel: 1,
+        pkgdesc: S!("Foo"),
+        url: S!("https://example.org"),
+        license: S!("MIT"),
+        provides: vec![dependency("bar=1.2-r1")],
+        provider_priority: Some(10),
+        install: vec![
+            S!("foo.post-install"),
+            S!("foo-doc.pre-deinstall"),
+            S!("baz.post-install"),
+            S!("foo.post-foo"),
+        ],
+        triggers: vec![S!("foo.trigger=/usr/share/foo/*"), S!("baz.trigger=/usr")],
+        subpackages: vec![S!("foo-doc"), S!("foo-bash-completion:bashcomp:noarch")],
+        secfixes: vec![
+            Secfix::new("1.2-r1", vec![S!("CVE-2022-1234")]),
+            Secfix::new("1.2-r2", vec![S!("CVE-2022-5678")]),
+        ],
+        ..Default::default()
+    };
+    assert_let!(Err(report) = apkbuild.validate());
+
+    let violations: Vec<_> = report
+        .violations
+        .iter()
+        .map(|v| (v.path.as_str(), v.rule.as_str()))
+        .collect();
+
+    assert!(
+        violations
+            == vec![
+                ("secfixes[1].version", "secfix-version"),
+                ("install[2]", "install-script"),
+                ("install[3]", "install-script"),
+                ("triggers[1]", "trigger"),
+                ("provider_priority", "provider-priority"),
+            ]
+    );
+}
+
+#[test]
+fn validate_apkbuild_secfix_invalid_version() {
+    let apkbuild = Apkbuild {
+        pkgname: S!("foo"),
+        pkgver: S!("1.2"),
+        pkgrel: 1,
+        pkgdesc: S!("Foo"),
+        url: S!("https://example.org"),
+        license: S!("MIT"),
+        secfixes: vec![Secfix::new("9.x-r0", vec![S!("CVE-2022-1234")])],
+        ..Default::default()
+    };
+    assert_let!(Err(report) = apkbuild.validate());
+
+    let violations: Vec<_> = report
+        .violations
+        .iter()
+        .map(|v| (v.path.as_str(), v.rule.as_str()))
+        .collect();
+
+    assert!(violations == vec![("secfixes[0].version", "version")]);
+}
@@ -0,0 +1,179 @@
+//! Comparison of the package versions as done by apk (`apk version -t`).
+//!
+//! A version consists of numbers separated by dots, an optional letter,
+//! any number of suffixes (`_alpha`, `_beta`, `_pre`, `_rc`, `_cvs`, `_svn`,
+//! `_git`, `_hg`, `_p`) each with an optional number, an optional commit hash
+//! (`~<hex>`) and an optional release (`-r<n>`), e.g. `1.2.3a_rc1-r2`.
+use std::cmp::Ordering;
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Suffixes of pre-release versions, i.e. those that sort before the version
+/// without suffix (in the order of precedence).
+const PRE_SUFFIXES: &[&str] = &["alpha", "beta", "pre", "rc"];
+
+/// Suffixes of post-release versions, i.e. those that sort after the version
+/// without suffix (in the order of precedence).
+const POST_SUFFIXES: &[&str] = &["cvs", "svn", "git", "hg", "p"];
+
+/// Compares two package versions, e.g. `1.2.3-r0` and `1.2.3_rc1-r1`.
+///
+/// If any of the versions is not valid, they are compared as strings.
+///
+/// Example:
+/// ```
+/// use std::cmp::Ordering;
+/// use alpkit::version::compare;
+///
+/// assert_eq!(compare("1.2.3_rc1-r1", "1.2.3-r0"), Ordering::Less);
+/// assert_eq!(compare("1.10", "1.9"), Ordering::Greater);
+/// ```
+pub fn compare(a: &str, b: &str) -> Ordering {
+    match (Version::parse(a), Version::parse(b)) {
+        (Some(va), Some(vb)) => va.cmp(&vb),
+        _ => a.cmp(b),
+    }
+}
+
+/// Returns `true` if the given string is a valid package version (with or
+/// without the release).
+pub fn is_valid(s: &str) -> bool {
+    Version::parse(s).is_some()
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug)]
+struct Version<'a> {
+    numbers: Vec<&'a str>,
+    letter: Option<char>,
+    suffixes: Vec<(i8, u64)>,
+    release: u64,
+}
+
+impl<'a> Version<'a> {
+    fn parse(s: &'a str) -> Option<Self> {
+        let (s, release) = match s.rsplit_once("-r") {
+            Some((ver, rel)) => (ver, parse_number(rel)?),
+            None => (s, 0),
+        };
+        // The commit hash is not significant for the ordering.
+        let s = match s.split_once('~') {
+            Some((ver, hash)) if is_hex(hash) => ver,
+            Some(_) => return None,
+            None => s,
+        };
+
+        let mut parts = s.split('_');
+        let mut base = parts.next()?;
+        let mut letter = None;
+        if let Some(c) = base.chars().last().filter(char::is_ascii_lowercase) {
+            letter = Some(c);
+            base = &base[..base.len() - 1];
+        }
+        let numbers: Vec<_> = base.split('.').collect();
+        if numbers.iter().any(|n| parse_number(n).is_none()) {
+            return None;
+        }
+
+        let suffixes = parts
+            .map(|suffix| {
+                let name = suffix.trim_end_matches(|c: char| c.is_ascii_digit());
+                let num = match &suffix[name.len()..] {
+                    "" => 0,
+                    num => parse_number(num)?,
+                };
+                Some((suffix_rank(name)?, num))
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(Version {
+            numbers,
+            letter,
+            suffixes,
+            release,
+        })
+    }
+}
+
+impl Ord for Version<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        compare_numbers(&self.numbers, &other.numbers)
+            .then_with(|| self.letter.cmp(&other.letter))
+            .then_with(|| compare_suffixes(&self.suffixes, &other.suffixes))
+            .then_with(|| self.release.cmp(&other.release))
+    }
+}
+
+impl PartialEq for Version<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Version<'_> {}
+
+impl PartialOrd for Version<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn compare_numbers(a: &[&str], b: &[&str]) -> Ordering {
+    for (i, (x, y)) in a.iter().zip(b).enumerate() {
+        // Components after the first one with a leading zero are compared as
+        // decimal fractions, e.g. 1.01 < 1.1.
+        let ord = if i > 0 && (x.starts_with('0') || y.starts_with('0')) {
+            x.trim_end_matches('0').cmp(y.trim_end_matches('0'))
+        } else {
+            let (x, y) = (x.trim_start_matches('0'), y.trim_start_matches('0'));
+            x.len().cmp(&y.len()).then_with(|| x.cmp(y))
+        };
+        if ord != Ordering::Equal {
+            return ord;
+        }
+    }
+    a.len().cmp(&b.len())
+}
+
+fn compare_suffixes(a: &[(i8, u64)], b: &[(i8, u64)]) -> Ordering {
+    for i in 0..a.len().max(b.len()) {
+        // A missing suffix is ranked between pre- and post-release suffixes.
+        let x = a.get(i).copied().unwrap_or((0, 0));
+        let y = b.get(i).copied().unwrap_or((0, 0));
+
+        if x != y {
+            return x.cmp(&y);
+        }
+    }
+    Ordering::Equal
+}
+
+fn suffix_rank(name: &str) -> Option<i8> {
+    if let Some(i) = PRE_SUFFIXES.iter().position(|s| *s == name) {
+        Some(i as i8 - PRE_SUFFIXES.len() as i8)
+    } else {
+        POST_SUFFIXES
+            .iter()
+            .position(|s| *s == name)
+            .map(|i| i as i8 + 1)
+    }
+}
+
+fn parse_number(s: &str) -> Option<u64> {
+    if s.is_empty() || !s.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    // Numbers too long for u64 are still valid, just saturated.
+    Some(s.parse().unwrap_or(u64::MAX))
+}
+
+fn is_hex(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+#[path = "version.test.rs"]
+mod test;
@@ -0,0 +1,55 @@
+use std::cmp::Ordering::*;
+
+use super::*;
+use crate::internal::test_utils::assert;
+
+#[test]
+#[rustfmt::skip]
+fn compare_versions() {
+    for (a, b, expected) in [
+        ("1.0"          , "1.0"          , Equal  ),
+        ("1.0"          , "1.0-r0"       , Equal  ),
+        ("1.0-r1"       , "1.0-r0"       , Greater),
+        ("1.10"         , "1.9"          , Greater),
+        ("1.2.1"        , "1.2"          , Greater),
+        ("1.01"         , "1.1"          , Less   ),
+        ("1.2a"         , "1.2"          , Greater),
+        ("1.2b"         , "1.2a"         , Greater),
+        ("1.0_rc1"      , "1.0"          , Less   ),
+        ("1.0_alpha2"   , "1.0_beta1"    , Less   ),
+        ("1.0_rc2"      , "1.0_rc10"     , Less   ),
+        ("1.0_p1"       , "1.0"          , Greater),
+        ("1.0_git2023"  , "1.0_p1"       , Less   ),
+        ("1.0_rc1_p1"   , "1.0_rc1"      , Greater),
+        ("1.0~abc123"   , "1.0"          , Equal  ),
+        ("2.0_rc1-r5"   , "1.9.9-r0"     , Greater),
+        ("20230101"     , "1.0"          , Greater),
+    ] {
+        assert!(compare(a, b) == expected, "{a} vs {b}");
+        assert!(compare(b, a) == expected.reverse(), "{b} vs {a}");
+    }
+}
+
+#[test]
+fn compare_invalid_as_strings() {
+    assert!(compare("foo", "1.0") == Greater);
+}
+
+#[test]
+#[rustfmt::skip]
+fn is_valid_cases() {
+    for (input, expected) in [
+        ("1.2.3"        , true ),
+        ("1.2.3_rc1-r0" , true ),
+        ("0_git20230101", true ),
+        ("1.0~a1b2"     , true ),
+        (""             , false),
+        ("1..2"         , false),
+        ("v1.0"         , false),
+        ("1.0_foo"      , false),
+        ("1.0-rx"       , false),
+        ("1.0~xyz"      , false),
+    ] {
+        assert!(is_valid(input) == expected, "{input}");
+    }
+}
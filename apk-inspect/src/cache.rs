@@ -0,0 +1,154 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use alpkit::apkbuild::{Apkbuild, ApkbuildReader, ChecksumAlg};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+use crate::output::Output;
+
+/// Paths of the files an APKBUILD has been read from, with their SHA-256
+/// digests.
+pub(crate) type Digests = Vec<(PathBuf, String)>;
+
+/// A cache of the APKBUILDs read from an aports tree, stored in a JSON file.
+/// It allows to skip evaluation of the APKBUILDs that haven't changed since
+/// the previous run, including the files they source.
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct ApkbuildCache {
+    /// A fingerprint of the options affecting the evaluation; if it differs,
+    /// the cache is discarded.
+    options: String,
+
+    entries: BTreeMap<PathBuf, CacheEntry>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct CacheEntry {
+    files: Digests,
+    apkbuild: Apkbuild,
+}
+
+/// The result of [`ApkbuildCache::read_apkbuild`].
+pub(crate) enum Evaluated<'a> {
+    /// The APKBUILD hasn't changed, so it's taken from the cache.
+    Cached(&'a Apkbuild),
+    /// The APKBUILD has been evaluated now.
+    Fresh(Box<Apkbuild>, Digests),
+}
+
+impl ApkbuildCache {
+    pub(crate) fn new(options: String) -> Self {
+        ApkbuildCache {
+            options,
+            entries: BTreeMap::new(),
+        }
+    }
+
+    /// Loads the cache from the file at the `path`. If the file doesn't exist,
+    /// it's not a valid cache, or it has been created with different
+    /// `options`, an empty cache is returned.
+    pub(crate) fn load(path: &Path, options: String) -> io::Result<Self> {
+        // It's read into a string first, because some fields of Apkbuild
+        // can be deserialized only by borrowing.
+        let data = match fs::read_to_string(path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::new(options)),
+            Err(e) => return Err(e),
+        };
+        match serde_json::from_str::<Self>(&data) {
+            Ok(cache) if cache.options == options => Ok(cache),
+            Ok(_) => {
+                debug!("discarding cache {}: options have changed", path.display());
+                Ok(Self::new(options))
+            }
+            Err(e) => {
+                warn!("discarding invalid cache {}: {e}", path.display());
+                Ok(Self::new(options))
+            }
+        }
+    }
+
+    /// Writes the cache to the file at the `path`.
+    pub(crate) fn save(&self, path: &Path) -> io::Result<()> {
+        let mut out = Output::open(Some(path))?;
+        serde_json::to_writer(&mut out, self)?;
+        out.write_all(b"\n")?;
+        out.commit()
+    }
+
+    /// Returns the APKBUILD at the `path` from the cache if neither it nor
+    /// any of the files it sources has changed, otherwise reads it using the
+    /// `reader`, which must have [`ApkbuildReader::track_includes`] enabled.
+    pub(crate) fn read_apkbuild(
+        &self,
+        reader: &ApkbuildReader,
+        path: &Path,
+    ) -> Result<Evaluated<'_>, Error> {
+        if let Some(entry) = self.entries.get(path) {
+            if entry
+                .files
+                .iter()
+                .all(|(file, digest)| is_unchanged(file, digest))
+            {
+                return Ok(Evaluated::Cached(&entry.apkbuild));
+            }
+            debug!("{}: changed since cached", path.display());
+        }
+        let mut apkbuild = reader.read_apkbuild(path)?;
+
+        // The includes are omitted, so that the output is the same as
+        // without the cache.
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+        let files = [path.to_owned()]
+            .into_iter()
+            .chain(apkbuild.includes.drain(..).map(|file| dir.join(file)))
+            .map(|file| digest(&file).map(|digest| (file, digest)))
+            .collect::<io::Result<_>>()?;
+
+        Ok(Evaluated::Fresh(Box::new(apkbuild), files))
+    }
+
+    /// Replaces the entries with the given ones; an entry that is `None`
+    /// is retained from this cache.
+    pub(crate) fn replace(&mut self, entries: Vec<(PathBuf, Option<CacheEntry>)>) {
+        let mut old = std::mem::take(&mut self.entries);
+
+        self.entries = entries
+            .into_iter()
+            .filter_map(|(path, entry)| {
+                let entry = entry.or_else(|| old.remove(&path))?;
+                Some((path, entry))
+            })
+            .collect();
+    }
+}
+
+impl CacheEntry {
+    pub(crate) fn new(apkbuild: Box<Apkbuild>, files: Digests) -> Self {
+        CacheEntry {
+            files,
+            apkbuild: *apkbuild,
+        }
+    }
+}
+
+impl Evaluated<'_> {
+    pub(crate) fn apkbuild(&self) -> &Apkbuild {
+        match self {
+            Evaluated::Cached(apkbuild) => apkbuild,
+            Evaluated::Fresh(apkbuild, _) => apkbuild,
+        }
+    }
+}
+
+fn digest(path: &Path) -> io::Result<String> {
+    ChecksumAlg::Sha256.digest(File::open(path)?)
+}
+
+fn is_unchanged(path: &Path, expected: &str) -> bool {
+    matches!(digest(path), Ok(digest) if digest == expected)
+}
@@ -0,0 +1,19 @@
+use std::str::FromStr;
+
+/// A tool whose output shape should be mimicked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Compat {
+    /// apk-tools 3.x (the ADB schema as printed by `apk adbdump`).
+    Apk,
+}
+
+impl FromStr for Compat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "apk" => Ok(Compat::Apk),
+            _ => Err(format!("unknown compat mode: '{s}', expected apk")),
+        }
+    }
+}
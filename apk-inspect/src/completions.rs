@@ -0,0 +1,305 @@
+//! Generation of shell completion scripts from the argp definitions.
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use argp::help::{CommandInfo, HelpInfo, OptionArgInfo};
+
+/// A shell to generate the completion script for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Shell {
+    Bash,
+    Fish,
+    Zsh,
+}
+
+impl FromStr for Shell {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bash" => Ok(Shell::Bash),
+            "fish" => Ok(Shell::Fish),
+            "zsh" => Ok(Shell::Zsh),
+            _ => Err(format!(
+                "unsupported shell: '{s}', expected bash, fish or zsh"
+            )),
+        }
+    }
+}
+
+/// A subcommand with its help info.
+pub(crate) struct Command {
+    pub info: &'static CommandInfo,
+    pub help: &'static HelpInfo,
+}
+
+/// An option parsed from [`OptionArgInfo`].
+struct Opt {
+    short: Option<char>,
+    long: &'static str,
+    arg_name: Option<&'static str>,
+    description: String,
+}
+
+impl Opt {
+    fn parse(info: &'static OptionArgInfo) -> Self {
+        let mut opt = Opt {
+            short: None,
+            long: "",
+            arg_name: None,
+            description: first_sentence(info.description.1),
+        };
+        for word in info.description.0.split([' ', ',']) {
+            if let Some(long) = word.strip_prefix("--") {
+                opt.long = long;
+            } else if let Some(short) = word.strip_prefix('-') {
+                opt.short = short.chars().next();
+            } else if word.starts_with('<') {
+                opt.arg_name = Some(word.trim_matches(['<', '>', ',']));
+            }
+        }
+        opt
+    }
+
+    /// Returns the option names as passed on the command line.
+    fn names(&self) -> Vec<String> {
+        let mut names = Vec::with_capacity(2);
+        if let Some(short) = self.short {
+            names.push(format!("-{short}"));
+        }
+        names.push(format!("--{}", self.long));
+        names
+    }
+}
+
+/// Writes a completion script for the given `shell` to `out`. The `global`
+/// help info is of the top-level command.
+pub(crate) fn generate<W: Write>(
+    shell: Shell,
+    prog_name: &str,
+    global: &'static HelpInfo,
+    commands: &[Command],
+    out: &mut W,
+) -> io::Result<()> {
+    let global_opts = parse_options(global.options, false);
+    let commands: Vec<_> = commands
+        .iter()
+        .map(|cmd| {
+            // Global options are propagated to the subcommands.
+            let mut opts = parse_options(global.options, true);
+            opts.extend(parse_options(cmd.help.options, false));
+            (cmd.info, opts)
+        })
+        .collect();
+
+    match shell {
+        Shell::Bash => write_bash(prog_name, &global_opts, &commands, out),
+        Shell::Fish => write_fish(prog_name, &global_opts, &commands, out),
+        Shell::Zsh => write_zsh(prog_name, &global_opts, &commands, out),
+    }
+}
+
+fn write_bash<W: Write>(
+    prog_name: &str,
+    global_opts: &[Opt],
+    commands: &[(&CommandInfo, Vec<Opt>)],
+    out: &mut W,
+) -> io::Result<()> {
+    let func_name = format!("_{}", prog_name.replace('-', "_"));
+    let names = commands.iter().map(|c| c.0.name).collect::<Vec<_>>();
+    let words = |opts: &[Opt]| opts.iter().flat_map(Opt::names).collect::<Vec<_>>();
+
+    writeln!(out, "{func_name}() {{")?;
+    writeln!(out, "\tlocal cur=\"${{COMP_WORDS[COMP_CWORD]}}\"")?;
+    writeln!(out, "\tlocal cmd= i")?;
+    writeln!(out)?;
+    writeln!(out, "\tfor ((i = 1; i < COMP_CWORD; i++)); do")?;
+    writeln!(out, "\t\tcase \"${{COMP_WORDS[i]}}\" in")?;
+    writeln!(
+        out,
+        "\t\t\t{}) cmd=\"${{COMP_WORDS[i]}}\"; break;;",
+        names.join("|")
+    )?;
+    writeln!(out, "\t\tesac")?;
+    writeln!(out, "\tdone")?;
+    writeln!(out)?;
+    writeln!(out, "\tlocal words")?;
+    writeln!(out, "\tcase \"$cmd\" in")?;
+    writeln!(
+        out,
+        "\t\t'') words=\"{} {}\";;",
+        words(global_opts).join(" "),
+        names.join(" ")
+    )?;
+    for (info, opts) in commands {
+        writeln!(
+            out,
+            "\t\t{}) words=\"{}\";;",
+            info.name,
+            words(opts).join(" ")
+        )?;
+    }
+    writeln!(out, "\tesac")?;
+    writeln!(out)?;
+    writeln!(out, "\tif [[ -z \"$cmd\" || \"$cur\" == -* ]]; then")?;
+    writeln!(out, "\t\tCOMPREPLY=($(compgen -W \"$words\" -- \"$cur\"))")?;
+    writeln!(out, "\tfi")?;
+    writeln!(out, "}}")?;
+    writeln!(out, "complete -o default -F {func_name} {prog_name}")
+}
+
+fn write_fish<W: Write>(
+    prog_name: &str,
+    global_opts: &[Opt],
+    commands: &[(&CommandInfo, Vec<Opt>)],
+    out: &mut W,
+) -> io::Result<()> {
+    let no_cmd = "__fish_use_subcommand";
+
+    writeln!(out, "complete -c {prog_name} -n {no_cmd} -f")?;
+    for opt in global_opts {
+        writeln!(out, "complete -c {prog_name} -n {no_cmd}{}", fish_opt(opt))?;
+    }
+    for (info, _) in commands {
+        writeln!(
+            out,
+            "complete -c {prog_name} -n {no_cmd} -a {} -d {}",
+            info.name,
+            fish_quote(&first_sentence(info.description))
+        )?;
+    }
+    for (info, opts) in commands {
+        let cond = format!("'__fish_seen_subcommand_from {}'", info.name);
+        for opt in opts {
+            writeln!(out, "complete -c {prog_name} -n {cond}{}", fish_opt(opt))?;
+        }
+    }
+    Ok(())
+}
+
+fn fish_opt(opt: &Opt) -> String {
+    let mut s = String::new();
+    if let Some(short) = opt.short {
+        s.push_str(&format!(" -s {short}"));
+    }
+    s.push_str(&format!(" -l {}", opt.long));
+    if opt.arg_name.is_some() {
+        s.push_str(" -r");
+    }
+    if !opt.description.is_empty() {
+        s.push_str(&format!(" -d {}", fish_quote(&opt.description)));
+    }
+    s
+}
+
+fn fish_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+fn write_zsh<W: Write>(
+    prog_name: &str,
+    global_opts: &[Opt],
+    commands: &[(&CommandInfo, Vec<Opt>)],
+    out: &mut W,
+) -> io::Result<()> {
+    let func_name = format!("_{prog_name}");
+
+    writeln!(out, "#compdef {prog_name}")?;
+    writeln!(out)?;
+    writeln!(out, "{func_name}() {{")?;
+    writeln!(out, "\tlocal line state")?;
+    writeln!(out)?;
+    writeln!(out, "\t_arguments -C \\")?;
+    for opt in global_opts {
+        writeln!(out, "\t\t{} \\", zsh_opt(opt))?;
+    }
+    writeln!(out, "\t\t'1: :->cmds' \\")?;
+    writeln!(out, "\t\t'*:: :->args'")?;
+    writeln!(out)?;
+    writeln!(out, "\tcase $state in")?;
+    writeln!(out, "\t\tcmds)")?;
+    writeln!(out, "\t\t\t_values 'command' \\")?;
+    for (i, (info, _)) in commands.iter().enumerate() {
+        let sep = if i + 1 < commands.len() { " \\" } else { "" };
+        writeln!(
+            out,
+            "\t\t\t\t'{}[{}]'{sep}",
+            info.name,
+            zsh_escape(&first_sentence(info.description))
+        )?;
+    }
+    writeln!(out, "\t\t;;")?;
+    writeln!(out, "\t\targs)")?;
+    writeln!(out, "\t\t\tcase $line[1] in")?;
+    for (info, opts) in commands {
+        writeln!(out, "\t\t\t\t{})", info.name)?;
+        writeln!(out, "\t\t\t\t\t_arguments \\")?;
+        for opt in opts {
+            writeln!(out, "\t\t\t\t\t\t{} \\", zsh_opt(opt))?;
+        }
+        writeln!(out, "\t\t\t\t\t\t'*:file:_files'")?;
+        writeln!(out, "\t\t\t\t;;")?;
+    }
+    writeln!(out, "\t\t\tesac")?;
+    writeln!(out, "\t\t;;")?;
+    writeln!(out, "\tesac")?;
+    writeln!(out, "}}")?;
+    writeln!(out)?;
+    writeln!(out, "{func_name} \"$@\"")
+}
+
+fn zsh_opt(opt: &Opt) -> String {
+    let names = opt.names();
+    let desc = zsh_escape(&opt.description);
+    let arg = match opt.arg_name {
+        Some("file") => ": :_files".to_owned(),
+        Some(name) => format!(":{name}: "),
+        None => String::new(),
+    };
+    if names.len() > 1 {
+        format!(
+            "'({0})'{{{1}}}'[{desc}]{arg}'",
+            names.join(" "),
+            names.join(",")
+        )
+    } else {
+        format!("'{}[{desc}]{arg}'", names[0])
+    }
+}
+
+fn zsh_escape(s: &str) -> String {
+    s.replace('\'', "'\\''")
+        .replace('[', "\\[")
+        .replace(']', "\\]")
+}
+
+/// Parses the options, including `--help`. If `only_global` is `true`, only
+/// the global options (without `--help`) are returned.
+fn parse_options(options: &'static [OptionArgInfo], only_global: bool) -> Vec<Opt> {
+    let mut opts: Vec<_> = options
+        .iter()
+        .filter(|info| info.global || !only_global)
+        .map(Opt::parse)
+        .collect();
+    if !only_global {
+        opts.push(Opt {
+            short: Some('h'),
+            long: "help",
+            arg_name: None,
+            description: "Show this help message and exit".to_owned(),
+        });
+    }
+    opts
+}
+
+/// Returns the first sentence of the description without the trailing dot.
+fn first_sentence(s: &str) -> String {
+    // A dot followed by a lowercase letter is an abbreviation (e.g. "e.g.").
+    let end = s
+        .match_indices(". ")
+        .map(|(i, _)| i)
+        .find(|&i| s[i + 2..].starts_with(|c: char| !c.is_lowercase()))
+        .unwrap_or(s.len());
+
+    s[..end].trim().trim_end_matches('.').replace('\n', " ")
+}
@@ -0,0 +1,96 @@
+use std::env;
+use std::fmt::Display;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer};
+
+use crate::error::{Error, ErrorKind};
+use crate::output::Format;
+
+/// The name of the config file in the user's config directory.
+const CONFIG_FILE_NAME: &str = "apk-inspect.toml";
+
+/// Defaults for the command-line options, read from the config file. The
+/// options given on the command line take precedence.
+///
+/// Example of the config file:
+/// ```toml
+/// format = "yaml"
+/// keys_dir = "/etc/apk/keys"
+/// arch_all = ["aarch64", "x86_64"]
+/// shell = "/bin/bash"
+/// timeout = 1000
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct Config {
+    /// The default output format (see `--format`).
+    #[serde(deserialize_with = "deserialize_from_str")]
+    pub(crate) format: Option<Format>,
+
+    /// Directory with the trusted public keys (see `--keys-dir`).
+    pub(crate) keys_dir: Option<PathBuf>,
+
+    /// CPU architectures to which "all" and "noarch" are expanded (see
+    /// `apkbuild --arch-all`).
+    pub(crate) arch_all: Option<Vec<String>>,
+
+    /// The shell to evaluate APKBUILDs with (see `apkbuild --shell`).
+    pub(crate) shell: Option<String>,
+
+    /// The time limit for evaluation of APKBUILD in milliseconds (see
+    /// `apkbuild --timeout`).
+    pub(crate) timeout: Option<u64>,
+}
+
+impl Config {
+    /// Loads the config from the given `path`, or from the default location
+    /// (see [`default_path`]) if `None`. A missing config file is an error
+    /// only if the `path` is given explicitly.
+    pub(crate) fn load(path: Option<&Path>) -> Result<Self, Error> {
+        let (path, required) = match path {
+            Some(path) => (path.to_owned(), true),
+            None => match default_path() {
+                Some(path) => (path, false),
+                None => return Ok(Config::default()),
+            },
+        };
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound && !required => {
+                return Ok(Config::default())
+            }
+            Err(e) => {
+                let msg = format!("cannot read config file '{}': {}", path.display(), e);
+                return Err(Error::new(ErrorKind::Io, msg));
+            }
+        };
+        toml::from_str(&content).map_err(|e| {
+            let msg = format!("invalid config file '{}': {}", path.display(), e);
+            Error::new(ErrorKind::Parse, msg)
+        })
+    }
+}
+
+/// Returns the path of the config file in the user's config directory, i.e.
+/// `$XDG_CONFIG_HOME/apk-inspect.toml` or `~/.config/apk-inspect.toml`.
+fn default_path() -> Option<PathBuf> {
+    env::var_os("XDG_CONFIG_HOME")
+        .filter(|s| !s.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))
+        .map(|dir| dir.join(CONFIG_FILE_NAME))
+}
+
+fn deserialize_from_str<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    let s = String::deserialize(deserializer)?;
+    s.parse().map(Some).map_err(serde::de::Error::custom)
+}
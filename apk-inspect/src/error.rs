@@ -0,0 +1,204 @@
+use std::error::Error as StdError;
+use std::fmt;
+use std::io;
+use std::str::FromStr;
+
+use alpkit::{apkbuild, index, installed, mirror, package};
+use serde::Serialize;
+
+/// A kind of the failure, it determines the exit code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum ErrorKind {
+    /// Any other error.
+    Other = 1,
+    /// Invalid command-line arguments.
+    Usage = 2,
+    /// Failed to read or write a file.
+    Io = 3,
+    /// The input is malformed (e.g. an invalid package).
+    Parse = 4,
+    /// The input has been read, but it didn't pass the validation.
+    Validation = 5,
+    /// The evaluation of APKBUILD exceeded the time limit.
+    Timeout = 6,
+}
+
+impl ErrorKind {
+    pub(crate) fn exit_code(self) -> i32 {
+        self as i32
+    }
+
+    /// Determines the kind of the given error by its type.
+    fn of(error: &(dyn StdError + 'static)) -> Self {
+        use ErrorKind::*;
+
+        if let Some(e) = error.downcast_ref::<io::Error>() {
+            Self::of_io(e)
+        } else if let Some(e) = error.downcast_ref::<package::Error>() {
+            match e {
+                package::Error::Io(e) => Self::of_io(e),
+                package::Error::FileNotFound(_) | package::Error::NotRegularFile(_) => Other,
+                _ => Parse,
+            }
+        } else if let Some(e) = error.downcast_ref::<index::IndexError>() {
+            match e {
+                index::IndexError::Io(e) => Self::of_io(e),
+                _ => Parse,
+            }
+        } else if let Some(e) = error.downcast_ref::<mirror::MirrorError>() {
+            match e {
+                mirror::MirrorError::Index(index::IndexError::Io(e)) => Self::of_io(e),
+                mirror::MirrorError::Index(_) => Parse,
+                _ => Io,
+            }
+        } else if let Some(e) = error.downcast_ref::<installed::InstalledDbError>() {
+            match e {
+                installed::InstalledDbError::Io(e) => Self::of_io(e),
+                _ => Parse,
+            }
+        } else if let Some(e) = error.downcast_ref::<package::SignatureError>() {
+            match e {
+                package::SignatureError::ReadKey(_, e) => Self::of_io(e),
+                package::SignatureError::InvalidKey(_) => Parse,
+            }
+        } else if let Some(e) = error.downcast_ref::<apkbuild::Error>() {
+            match e {
+                apkbuild::Error::Timeout { .. } => Timeout,
+                apkbuild::Error::Io(..)
+                | apkbuild::Error::ReadFile(..)
+                | apkbuild::Error::SpawnShell(..)
+                | apkbuild::Error::WriteFile(..) => Io,
+                _ => Parse,
+            }
+        } else {
+            Other
+        }
+    }
+
+    /// An I/O error caused by a truncated or corrupted input is a parse error.
+    fn of_io(error: &io::Error) -> Self {
+        match error.kind() {
+            io::ErrorKind::InvalidData | io::ErrorKind::UnexpectedEof => ErrorKind::Parse,
+            _ => ErrorKind::Io,
+        }
+    }
+}
+
+/// A failure of the command with the [`ErrorKind`].
+#[derive(Debug)]
+pub(crate) struct Error {
+    pub kind: ErrorKind,
+    source: Box<dyn StdError>,
+}
+
+impl Error {
+    pub(crate) fn new<E: Into<Box<dyn StdError>>>(kind: ErrorKind, error: E) -> Self {
+        Error {
+            kind,
+            source: error.into(),
+        }
+    }
+
+    /// Returns the error message including all the causes.
+    pub(crate) fn message(&self) -> String {
+        let mut msg = self.source.to_string();
+
+        let mut source = self.source.source();
+        while let Some(e) = source {
+            msg.push_str(": ");
+            msg.push_str(&e.to_string());
+
+            source = e.source();
+        }
+        msg
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message())
+    }
+}
+
+macro_rules! impl_from_error {
+    ($($type:ty),*) => {
+        $(
+            impl From<$type> for Error {
+                fn from(error: $type) -> Self {
+                    Error::new(ErrorKind::of(&error), error)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_error!(
+    io::Error,
+    apkbuild::Error,
+    index::IndexError,
+    installed::InstalledDbError,
+    mirror::MirrorError,
+    package::Error,
+    package::SignatureError
+);
+#[cfg(feature = "sqlite")]
+impl_from_error!(alpkit::sqlite::Error);
+#[cfg(feature = "parquet")]
+impl_from_error!(alpkit::columnar::Error);
+
+impl From<String> for Error {
+    fn from(msg: String) -> Self {
+        Error::new(ErrorKind::Other, msg)
+    }
+}
+
+impl From<&str> for Error {
+    fn from(msg: &str) -> Self {
+        Error::new(ErrorKind::Other, msg)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// The format of error messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ErrorFormat {
+    Text,
+    Json,
+}
+
+impl FromStr for ErrorFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(ErrorFormat::Text),
+            "json" => Ok(ErrorFormat::Json),
+            _ => Err(format!("unknown format: '{s}', expected text or json")),
+        }
+    }
+}
+
+/// Prints the error message to stderr in the given format.
+pub(crate) fn report(prog_name: &str, kind: ErrorKind, msg: &str, format: ErrorFormat) {
+    #[derive(Serialize)]
+    struct ErrorRecord<'a> {
+        kind: ErrorKind,
+        exit_code: i32,
+        message: &'a str,
+    }
+
+    match format {
+        ErrorFormat::Text => eprintln!("{prog_name}: {msg}"),
+        ErrorFormat::Json => {
+            let record = ErrorRecord {
+                kind,
+                exit_code: kind.exit_code(),
+                message: msg,
+            };
+            // Serializing of this struct cannot fail.
+            eprintln!("{}", serde_json::to_string(&record).unwrap());
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use log::{LevelFilter, Log, Metadata, Record};
+
+use crate::PROG_NAME;
+
+/// A logger that prints the log records to stderr.
+struct StderrLogger;
+
+static LOGGER: StderrLogger = StderrLogger;
+
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            let level = record.level().as_str().to_ascii_lowercase();
+            eprintln!("{PROG_NAME}: {level}: {}", record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Installs the logger with the level based on the number of `-v` flags:
+/// warnings only by default, debug with one, and trace with two or more.
+pub(crate) fn init(verbosity: u8) {
+    let level = match verbosity {
+        0 => LevelFilter::Warn,
+        1 => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
+    };
+    // This fails only if a logger is already set, which cannot happen here.
+    let _ = log::set_logger(&LOGGER);
+    log::set_max_level(level);
+}
@@ -1,28 +1,135 @@
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeSet, HashMap};
 use std::env;
-use std::error;
-use std::ffi::OsString;
+use std::ffi::{OsStr, OsString};
 use std::fs::File;
-use std::io::{self, BufReader, Write as _};
-use std::path::PathBuf;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
 use std::process::exit;
-use std::time::Duration;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
 
-use alpkit::apkbuild::ApkbuildReader;
-use alpkit::package::Package;
+use alpkit::adbdump::AdbPackage;
+use alpkit::apkbuild::{find_apkbuilds, Apkbuild, ApkbuildReader};
+#[cfg(feature = "parquet")]
+use alpkit::columnar::{DependsWriter, FilesWriter};
+use alpkit::consistency::{self, Divergence};
+use alpkit::dependency::Dependency;
+use alpkit::graph::DepGraph;
+use alpkit::index::Index;
+use alpkit::installed::{InstalledDb, INSTALLED_DB_PATH};
+use alpkit::json_schema;
+use alpkit::mirror::MirrorChecker;
+use alpkit::package::{FileInfo, FileType, Package, PackageStats, PkgScript, SignatureInfo};
+use alpkit::sbom::{CycloneDx, Spdx};
+use alpkit::secdb::SecDb;
+#[cfg(feature = "sqlite")]
+use alpkit::sqlite::SqliteExporter;
+use alpkit::validate::{Validate, ValidationContext, ValidationReport, Violation};
+use cache::{ApkbuildCache, CacheEntry, Evaluated};
 
-use argp::FromArgs;
+use argp::{CommandHelp, EarlyExit, FromArgs, SubCommand};
+use compat::Compat;
+use completions::{Command, Shell};
+use config::Config;
+use error::{Error, ErrorFormat, ErrorKind};
+use glob::{MatchOptions, Pattern};
+use log::debug;
+use output::{Fields, Format, Output, OutputMode, OutputWriter};
+use progress::Progress;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use stream::StreamedPackage;
+use walkdir::WalkDir;
+
+mod cache;
+mod compat;
+mod completions;
+mod config;
+mod error;
+mod logger;
+mod output;
+mod parallel;
+mod progress;
+mod stream;
 
 const PROG_NAME: &str = env!("CARGO_PKG_NAME");
 const PROG_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// The default directory with the trusted public keys.
+const DEFAULT_KEYS_DIR: &str = "/etc/apk/keys";
+
+/// A path that `-` (stdin) in the arguments is replaced with.
+const STDIN_PATH: &str = "/dev/stdin";
+
 /// Extract metadata from Alpine's APK packages and APKBUILDs.
 #[derive(Debug, FromArgs)]
-#[argp(footer = "Please report issues at <https://github.com/jirutka/alpkit>.")]
+#[argp(footer = "Exit status:
+  0  success
+  1  other error
+  2  invalid arguments
+  3  I/O error
+  4  malformed input (e.g. invalid package)
+  5  validation failure
+  6  APKBUILD evaluation timeout
+
+Please report issues at <https://github.com/jirutka/alpkit>.")]
 struct AppOpts {
     /// Format the output to be human-readable.
     #[argp(switch, short = 'p', global)]
     pretty_print: bool,
 
+    /// Output format: json, yaml, toml, or table (only for the list of
+    /// files and stats). Default is json, or the format matching the
+    /// extension of the --output file.
+    #[argp(
+        option,
+        short = 'f',
+        arg_name = "format",
+        global,
+        from_str_fn(str::parse)
+    )]
+    format: Option<Format>,
+
+    /// Output only the given comma-separated fields (e.g. pkgname,pkgver).
+    /// Nested fields can be selected using dots (e.g. files.path).
+    #[argp(option, arg_name = "field,", global, from_str_fn(str::parse))]
+    fields: Option<Fields>,
+
+    /// Sort all maps (by keys) and lists in the output, so that it doesn't
+    /// depend on the order of e.g. dependencies or files in the input.
+    #[argp(switch, global)]
+    sorted: bool,
+
+    /// Write the output to <path> instead of stdout. The file is replaced
+    /// atomically, only if the command succeeds.
+    #[argp(option, short = 'o', arg_name = "path", global)]
+    output: Option<PathBuf>,
+
+    /// Read the defaults of options from the given TOML file instead of
+    /// ~/.config/apk-inspect.toml (or $XDG_CONFIG_HOME/apk-inspect.toml).
+    #[argp(option, arg_name = "path", global)]
+    config: Option<PathBuf>,
+
+    /// Format of error messages printed to stderr: text (default), or json.
+    #[argp(
+        option,
+        arg_name = "format",
+        global,
+        default = "ErrorFormat::Text",
+        from_str_fn(str::parse)
+    )]
+    errors: ErrorFormat,
+
+    /// Report progress (the number of processed files and the current file)
+    /// to stderr when processing multiple packages or APKBUILDs.
+    #[argp(switch, global)]
+    progress: bool,
+
+    /// Print what is being processed to stderr; use -vv for more details.
+    #[argp(switch, short = 'v', global)]
+    verbose: u8,
+
     /// Show program name and version.
     #[argp(switch, short = 'V')]
     version: bool,
@@ -31,6 +138,216 @@ struct AppOpts {
     action: Option<Action>,
 }
 
+/// Export packages and indexes into an SQLite database or a Parquet file,
+/// e.g. to run SQL queries over a whole repository. Requires --output.
+#[derive(Debug, FromArgs)]
+#[argp(subcommand, name = "export")]
+#[cfg_attr(not(any(feature = "sqlite", feature = "parquet")), allow(dead_code))]
+struct ExportOpts {
+    /// Don't read files of the packages.
+    #[argp(switch)]
+    no_files: bool,
+
+    /// Path to a repository index (APKINDEX.tar.gz) to export. Can be
+    /// repeated.
+    #[argp(option, arg_name = "file")]
+    index: Vec<PathBuf>,
+
+    /// Number of packages to read in parallel. Default is the number of CPUs.
+    #[argp(option, short = 'j', arg_name = "n")]
+    jobs: Option<usize>,
+
+    /// Records to export into the Parquet file: files (default), or depends.
+    #[argp(option, arg_name = "name", from_str_fn(str::parse))]
+    table: Option<ExportTable>,
+
+    /// Output format: sqlite, or parquet.
+    #[argp(positional, arg_name = "format", from_str_fn(str::parse))]
+    format: ExportFormat,
+
+    /// Path(s) to APK package(s) or glob pattern(s).
+    #[argp(positional, arg_name = "file")]
+    files: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ExportFormat {
+    Parquet,
+    Sqlite,
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "parquet" => Ok(ExportFormat::Parquet),
+            "sqlite" => Ok(ExportFormat::Sqlite),
+            _ => Err(format!("unknown format: '{s}', expected sqlite or parquet")),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum ExportTable {
+    Depends,
+    #[default]
+    Files,
+}
+
+impl FromStr for ExportTable {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "depends" => Ok(ExportTable::Depends),
+            "files" => Ok(ExportTable::Files),
+            _ => Err(format!("unknown table: '{s}', expected files or depends")),
+        }
+    }
+}
+
+/// Generate software bill of materials (SBOM) for APKv2 package.
+#[derive(Debug, FromArgs)]
+#[argp(subcommand, name = "sbom")]
+struct SbomOpts {
+    /// SBOM format: cyclonedx (default), or spdx.
+    #[argp(
+        option,
+        arg_name = "format",
+        default = "SbomFormat::CycloneDx",
+        from_str_fn(str::parse)
+    )]
+    format: SbomFormat,
+
+    /// Path to an APK package, HTTP(S) URL, or - to read from stdin.
+    #[argp(positional, arg_name = "file")]
+    file: PathBuf,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SbomFormat {
+    CycloneDx,
+    Spdx,
+}
+
+impl FromStr for SbomFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "cyclonedx" => Ok(SbomFormat::CycloneDx),
+            "spdx" => Ok(SbomFormat::Spdx),
+            _ => Err(format!("unknown format: '{s}', expected cyclonedx or spdx")),
+        }
+    }
+}
+
+/// Generate a dependency graph of the APKBUILDs in the aports tree.
+#[derive(Debug, FromArgs)]
+#[argp(subcommand, name = "graph")]
+struct GraphOpts {
+    /// Path to the aports tree (or a repository directory).
+    #[argp(option, arg_name = "dir")]
+    aports: PathBuf,
+
+    /// Read only APKBUILDs in the given repository of the aports tree (e.g.
+    /// main). Can be repeated.
+    #[argp(option, arg_name = "name")]
+    repo: Vec<String>,
+
+    /// Graph format: dot (default), or json (adjacency lists).
+    #[argp(
+        option,
+        arg_name = "format",
+        default = "GraphFormat::Dot",
+        from_str_fn(str::parse)
+    )]
+    format: GraphFormat,
+
+    /// Number of APKBUILDs to evaluate in parallel. Default is the number of
+    /// CPUs.
+    #[argp(option, short = 'j', arg_name = "n")]
+    jobs: Option<usize>,
+}
+
+/// List packages installed in the system (read from /lib/apk/db/installed).
+#[derive(Debug, FromArgs)]
+#[argp(subcommand, name = "installed")]
+struct InstalledOpts {
+    /// Root directory of the system. Default is /.
+    #[argp(option, arg_name = "dir", default = "PathBuf::from(\"/\")")]
+    root: PathBuf,
+
+    /// Include the files owned by the packages.
+    #[argp(switch)]
+    files: bool,
+
+    /// Print only the package that owns the given file (absolute path
+    /// relative to the --root).
+    #[argp(option, arg_name = "path")]
+    owns: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GraphFormat {
+    Dot,
+    Json,
+}
+
+impl FromStr for GraphFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "dot" => Ok(GraphFormat::Dot),
+            "json" => Ok(GraphFormat::Json),
+            _ => Err(format!("unknown format: '{s}', expected dot or json")),
+        }
+    }
+}
+
+/// Generate secdb (security database) from secfixes in the APKBUILDs.
+#[derive(Debug, FromArgs)]
+#[argp(subcommand, name = "secdb")]
+struct SecdbOpts {
+    /// Path to the aports tree.
+    #[argp(option, arg_name = "dir")]
+    aports: PathBuf,
+
+    /// The repository to read (default is main).
+    #[argp(option, arg_name = "name", default = "String::from(\"main\")")]
+    repo: String,
+
+    /// The release branch, e.g. v3.19 (default is edge).
+    #[argp(option, arg_name = "version", default = "String::from(\"edge\")")]
+    release: String,
+
+    /// Number of APKBUILDs to evaluate in parallel. Default is the number of
+    /// CPUs.
+    #[argp(option, short = 'j', arg_name = "n")]
+    jobs: Option<usize>,
+}
+
+/// Check a local mirror of a repository (arch directory) against the upstream
+/// index and report missing, extra and corrupt files.
+#[derive(Debug, FromArgs)]
+#[argp(subcommand, name = "mirror")]
+struct MirrorOpts {
+    /// Verify also checksums of the packages, not only their sizes (reads the
+    /// control segment of each package).
+    #[argp(switch, short = 'c')]
+    checksums: bool,
+
+    /// Path to the mirror directory, e.g. /srv/mirror/alpine/edge/main/x86_64.
+    #[argp(positional, arg_name = "dir")]
+    dir: PathBuf,
+
+    /// Path or HTTP(S) URL of the upstream APKINDEX.tar.gz.
+    #[argp(positional, arg_name = "apkindex")]
+    index: PathBuf,
+}
+
 /// Read APKv2 package.
 #[derive(Debug, FromArgs)]
 #[argp(subcommand, name = "apk")]
@@ -39,9 +356,223 @@ struct ApkOpts {
     #[argp(switch)]
     no_files: bool,
 
-    /// Path to an APK package.
+    /// Read and report only the files with the absolute path matching the
+    /// given glob pattern (e.g. '/usr/lib/*.so*'). Can be repeated.
+    #[argp(option, arg_name = "glob", from_str_fn(parse_glob))]
+    files_filter: Vec<Pattern>,
+
+    /// Validate the package metadata and include the found violations in the
+    /// output. Exit with an error if there are any.
+    #[argp(switch)]
+    validate: bool,
+
+    /// Verify the package signature using the public keys in the --keys-dir
+    /// and include the result in the output. Exit with an error if the
+    /// signature cannot be verified.
+    #[argp(switch)]
+    verify_signature: bool,
+
+    /// Directory with the trusted public keys for --verify-signature.
+    /// Default is /etc/apk/keys.
+    #[argp(option, arg_name = "dir")]
+    keys_dir: Option<PathBuf>,
+
+    /// Shape the output to match another tool: apk (the package format of
+    /// apk-tools 3.x). Cannot be combined with --section, --summary,
+    /// --validate, or --verify-signature.
+    #[argp(option, arg_name = "tool", from_str_fn(str::parse))]
+    compat: Option<Compat>,
+
+    /// Output only the given part of the package: pkginfo, files, scripts, or
+    /// signatures. Cannot be combined with --summary, --validate, or
+    /// --verify-signature.
+    #[argp(option, arg_name = "section", from_str_fn(str::parse))]
+    section: Option<Section>,
+
+    /// Print a concise human-readable summary of the package(s) instead of
+    /// the metadata in the output format. Cannot be combined with --array,
+    /// --validate, or --verify-signature.
+    #[argp(switch, short = 's')]
+    summary: bool,
+
+    /// Print the JSON Schema of the package document and exit.
+    #[argp(switch)]
+    schema: bool,
+
+    /// Read all *.apk files in the given directories (recursively). Packages
+    /// that cannot be read are reported as error records in the output
+    /// instead of aborting.
+    #[argp(switch, short = 'r')]
+    recursive: bool,
+
+    /// Write the packages as an array instead of one JSON document per line
+    /// (NDJSON) or a YAML stream.
+    #[argp(switch)]
+    array: bool,
+
+    /// Write the files as they are read instead of loading the whole package
+    /// into memory first (useful for packages with a huge number of files).
+    /// This doesn't reduce memory usage with --fields or --sorted. Cannot be
+    /// combined with --compat, --no-files, --section, --summary, --validate,
+    /// or --verify-signature.
+    #[argp(switch)]
+    stream: bool,
+
+    /// Path(s) to APK package(s) or glob pattern(s), HTTP(S) URL(s), or - to
+    /// read from stdin.
+    #[argp(positional, arg_name = "file")]
+    files: Vec<PathBuf>,
+}
+
+/// Dump entries of the repository index (APKINDEX.tar.gz).
+#[derive(Debug, FromArgs)]
+#[argp(subcommand, name = "apkindex")]
+struct ApkindexOpts {
+    /// Write the entries one per line (NDJSON) or as a YAML stream instead of
+    /// the whole index as a single document.
+    #[argp(switch, short = 'l')]
+    lines: bool,
+
+    /// Verify the index signature using the public keys in the --keys-dir
+    /// and include the result in the output (only without --lines). Exit
+    /// with an error if the signature cannot be verified.
+    #[argp(switch)]
+    verify_signature: bool,
+
+    /// Directory with the trusted public keys for --verify-signature.
+    /// Default is /etc/apk/keys.
+    #[argp(option, arg_name = "dir")]
+    keys_dir: Option<PathBuf>,
+
+    /// Path(s) to APKINDEX.tar.gz file(s), HTTP(S) URL(s), or - to read from
+    /// stdin.
+    #[argp(positional, arg_name = "file")]
+    files: Vec<PathBuf>,
+}
+
+/// Print content of a file from APKv2 package.
+#[derive(Debug, FromArgs)]
+#[argp(subcommand, name = "cat")]
+struct CatOpts {
+    /// Path to an APK package, or - to read from stdin.
     #[argp(positional, arg_name = "file")]
     file: PathBuf,
+
+    /// Absolute path of the file in the package (e.g. /etc/foo.conf).
+    #[argp(positional, arg_name = "path")]
+    path: PathBuf,
+}
+
+/// Compare APKBUILD with APKv2 package(s) built from it and report the
+/// mismatches (version, dependencies, provides, license, arch).
+#[derive(Debug, FromArgs)]
+#[argp(subcommand, name = "compare")]
+struct CompareOpts {
+    /// Path to an APKBUILD file.
+    #[argp(positional, arg_name = "apkbuild")]
+    apkbuild: PathBuf,
+
+    /// Path(s) to APK package(s) built from the APKBUILD, or glob pattern(s).
+    #[argp(positional, arg_name = "apk")]
+    files: Vec<PathBuf>,
+}
+
+/// List files in APKv2 package.
+#[derive(Debug, FromArgs)]
+#[argp(subcommand, name = "ls")]
+struct LsOpts {
+    /// Show also mode, owner and size of the files.
+    #[argp(switch, short = 'l')]
+    long: bool,
+
+    /// Path to an APK package, or - to read from stdin.
+    #[argp(positional, arg_name = "file")]
+    file: PathBuf,
+}
+
+/// Show aggregate statistics of APKv2 package files.
+#[derive(Debug, FromArgs)]
+#[argp(subcommand, name = "stats")]
+struct StatsOpts {
+    /// Number of the largest files to show. Default is 10.
+    #[argp(option, short = 'n', arg_name = "n", default = "10")]
+    top: usize,
+
+    /// Depth of the directories in the breakdown (e.g. 2 for /usr/bin).
+    /// Default is 2.
+    #[argp(option, short = 'd', arg_name = "n", default = "2")]
+    depth: usize,
+
+    /// Path to an APK package, HTTP(S) URL, or - to read from stdin.
+    #[argp(positional, arg_name = "file")]
+    file: PathBuf,
+}
+
+/// List install scripts of APKv2 package or print their content.
+#[derive(Debug, FromArgs)]
+#[argp(subcommand, name = "scripts")]
+struct ScriptsOpts {
+    /// Print content of the given script (e.g. post-install) instead of
+    /// listing the scripts.
+    #[argp(option, arg_name = "script", from_str_fn(parse_script))]
+    show: Option<PkgScript>,
+
+    /// Path to an APK package, or - to read from stdin.
+    #[argp(positional, arg_name = "file")]
+    file: PathBuf,
+}
+
+/// Find packages in APKINDEX by name or provider (e.g. so:libfoo.so.1).
+#[derive(Debug, FromArgs)]
+#[argp(subcommand, name = "query")]
+struct QueryOpts {
+    /// Path to an APKINDEX.tar.gz file (can be repeated).
+    #[argp(option, short = 'i', arg_name = "file")]
+    index: Vec<PathBuf>,
+
+    /// Find packages that depend on the matching packages (or anything they
+    /// provide) instead.
+    #[argp(switch, short = 'r')]
+    reverse: bool,
+
+    /// A package name or provider, optionally with a version constraint
+    /// (e.g. foo>=1.2).
+    #[argp(positional, arg_name = "dependency", from_str_fn(parse_dependency))]
+    dependency: Dependency,
+}
+
+/// Print shell completion script.
+#[derive(Debug, FromArgs)]
+#[argp(subcommand, name = "completions")]
+struct CompletionsOpts {
+    /// The shell to generate the script for: bash, fish, or zsh.
+    #[argp(positional, arg_name = "shell", from_str_fn(str::parse))]
+    shell: Shell,
+}
+
+/// A part of the package output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    Pkginfo,
+    Files,
+    Scripts,
+    Signatures,
+}
+
+impl FromStr for Section {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pkginfo" => Ok(Section::Pkginfo),
+            "files" => Ok(Section::Files),
+            "scripts" => Ok(Section::Scripts),
+            "signatures" => Ok(Section::Signatures),
+            _ => Err(format!(
+                "unknown section: '{s}', expected pkginfo, files, scripts or signatures"
+            )),
+        }
+    }
 }
 
 /// Read APKBUILD file.
@@ -54,6 +585,17 @@ struct ApkbuildOpts {
     #[argp(option, arg_name = "arch,")]
     arch_all: Option<String>,
 
+    /// Evaluate the APKBUILD for each of the given comma-separated CPU
+    /// architectures (with CARCH and CHOST set accordingly) and output one
+    /// document per arch with the "carch" field.
+    #[argp(option, arg_name = "arch,")]
+    arch: Option<String>,
+
+    /// With --arch, output a single document with the values that are the
+    /// same for all the architectures and the rest in "arch_overrides".
+    #[argp(switch)]
+    merge: bool,
+
     /// Set given variable(s) in the environment for the APKBUILD evaluation.
     #[argp(
         option,
@@ -69,22 +611,53 @@ struct ApkbuildOpts {
     keep_env: bool,
 
     /// Use <shell> to evaluate APKBUILD (default is /bin/sh).
-    #[argp(
-        option,
-        short = 's',
-        arg_name = "shell",
-        default = "OsString::from(\"/bin/sh\")"
-    )]
-    shell: OsString,
+    #[argp(option, short = 's', arg_name = "shell")]
+    shell: Option<OsString>,
 
     /// If shell evaluation of APKBUILD exceeds <msec> milliseconds, kill it.
     /// Default is 250, use 0 to disable.
-    #[argp(option, short = 'T', arg_name = "msec", default = "250")]
-    timeout: u64,
+    #[argp(option, short = 'T', arg_name = "msec")]
+    timeout: Option<u64>,
+
+    /// Validate the APKBUILD and include the found violations in the output.
+    /// Exit with an error if there are any.
+    #[argp(switch)]
+    validate: bool,
+
+    /// Read all APKBUILDs in the given aports tree (or a repository
+    /// directory) in parallel and output them as NDJSON. APKBUILDs that cannot
+    /// be read are reported as error records in the output.
+    #[argp(option, arg_name = "dir")]
+    aports: Option<PathBuf>,
+
+    /// Read only APKBUILDs in the given repository of the aports tree (e.g.
+    /// main). Can be repeated.
+    #[argp(option, arg_name = "name")]
+    repo: Vec<String>,
+
+    /// Number of APKBUILDs to evaluate in parallel with --aports. Default is
+    /// the number of CPUs.
+    #[argp(option, short = 'j', arg_name = "n")]
+    jobs: Option<usize>,
+
+    /// With --aports, evaluate APKBUILDs in long-lived shells (one per job)
+    /// instead of spawning a new shell for each. The shell must support -c.
+    #[argp(switch)]
+    reuse_shell: bool,
+
+    /// With --aports, cache the read APKBUILDs in the given file and evaluate
+    /// only those that have changed (including the files they source) since
+    /// the previous run.
+    #[argp(option, arg_name = "file")]
+    cache: Option<PathBuf>,
+
+    /// Print the JSON Schema of the APKBUILD document and exit.
+    #[argp(switch)]
+    schema: bool,
 
     /// Path to an APKBUILD file.
     #[argp(positional, arg_name = "apkbuild")]
-    file: PathBuf,
+    file: Option<PathBuf>,
 }
 
 #[derive(Debug, FromArgs)]
@@ -92,94 +665,1403 @@ struct ApkbuildOpts {
 enum Action {
     Apk(ApkOpts),
     Apkbuild(ApkbuildOpts),
+    Apkindex(ApkindexOpts),
+    Cat(CatOpts),
+    Compare(CompareOpts),
+    Completions(CompletionsOpts),
+    Export(ExportOpts),
+    Graph(GraphOpts),
+    Installed(InstalledOpts),
+    Ls(LsOpts),
+    Mirror(MirrorOpts),
+    Query(QueryOpts),
+    Sbom(SbomOpts),
+    Scripts(ScriptsOpts),
+    Secdb(SecdbOpts),
+    Stats(StatsOpts),
+}
+
+impl AppOpts {
+    /// Returns the output format specified by `--format`, or the one matching
+    /// the extension of the `--output` file, or JSON.
+    fn format(&self) -> Format {
+        self.format
+            .or_else(|| self.output_format())
+            .unwrap_or(Format::Json)
+    }
+
+    /// Returns the format matching the extension of the `--output` file, if
+    /// any.
+    fn output_format(&self) -> Option<Format> {
+        let ext = self
+            .output
+            .as_deref()
+            .and_then(Path::extension)
+            .and_then(|ext| ext.to_str());
+
+        match ext {
+            Some("json") => Some(Format::Json),
+            Some("yaml" | "yml") => Some(Format::Yaml),
+            Some("toml") => Some(Format::Toml),
+            _ => None,
+        }
+    }
 }
 
 fn main() {
-    let args: AppOpts = argp::parse_args_or_exit(&Default::default());
+    let args = parse_args_or_exit();
 
     if args.version {
         println!("{PROG_NAME} {PROG_VERSION}");
         exit(0);
     }
 
+    logger::init(args.verbose);
+
+    let errors = args.errors;
     if let Err(e) = run(args) {
-        eprintln!("{}", format_error_message(&*e));
-        exit(1);
+        error::report(PROG_NAME, e.kind, &e.message(), errors);
+        exit(e.kind.exit_code());
     }
 }
 
-fn run(args: AppOpts) -> Result<(), Box<dyn std::error::Error>> {
-    let action = args.action.ok_or("no subcommand specified")?;
+/// This is the same as [`argp::parse_args_or_exit`], but it replaces `-`
+/// arguments with [`STDIN_PATH`], because argp doesn't accept `-` as
+/// a positional argument, and reports errors in the format requested by
+/// `--errors` (if it's present).
+fn parse_args_or_exit() -> AppOpts {
+    let args: Vec<_> = env::args_os()
+        .skip(1)
+        .map(|arg| if arg == "-" { STDIN_PATH.into() } else { arg })
+        .collect();
+
+    AppOpts::from_args(&[PROG_NAME], &args).unwrap_or_else(|early_exit| {
+        exit(match early_exit {
+            EarlyExit::Help(help) => {
+                println!("{}", help.generate(&Default::default()));
+                0
+            }
+            EarlyExit::Err(err) => {
+                let kind = ErrorKind::Usage;
+                if args
+                    .windows(2)
+                    .any(|w| w[0] == "--errors" && w[1] == "json")
+                {
+                    error::report(PROG_NAME, kind, &err.to_string(), ErrorFormat::Json);
+                } else {
+                    eprintln!("{err}\nRun {PROG_NAME} --help for more information.");
+                }
+                kind.exit_code()
+            }
+        })
+    })
+}
+
+fn run(mut args: AppOpts) -> Result<(), Error> {
+    let action = args
+        .action
+        .take()
+        .ok_or_else(|| Error::new(ErrorKind::Usage, "no subcommand specified"))?;
+
+    let mut config = Config::load(args.config.as_deref())?;
+    if args.format.is_none() {
+        args.format = args.output_format().or(config.format);
+    }
+    let mut out = Output::open(args.output.as_deref())?;
 
     match action {
+        Action::Apk(opts) if opts.schema => {
+            out.write_all(json_schema::PACKAGE_SCHEMA.as_bytes())?;
+        }
         Action::Apk(opts) => {
-            let reader = File::open(&opts.file).map(BufReader::new).map_err(|e| {
-                format!("cannot open file '{}': {}", &opts.file.to_string_lossy(), e)
-            })?;
-
-            if !opts.file.is_file() {
-                return Err(
-                    format!("'{}' is not a regular file", &opts.file.to_string_lossy()).into(),
-                );
+            if opts.summary && (opts.array || opts.validate || opts.verify_signature) {
+                let msg =
+                    "--summary cannot be combined with --array, --validate, or --verify-signature";
+                return Err(Error::new(ErrorKind::Usage, msg));
             }
-
-            let pkg = if opts.no_files {
-                Package::load_without_files(reader)?
+            if opts.no_files && !opts.files_filter.is_empty() {
+                let msg = "--files-filter cannot be combined with --no-files";
+                return Err(Error::new(ErrorKind::Usage, msg));
+            }
+            if opts.section.is_some() && (opts.summary || opts.validate || opts.verify_signature) {
+                let msg =
+                    "--section cannot be combined with --summary, --validate, or --verify-signature";
+                return Err(Error::new(ErrorKind::Usage, msg));
+            }
+            if opts.compat.is_some()
+                && (opts.section.is_some()
+                    || opts.summary
+                    || opts.validate
+                    || opts.verify_signature)
+            {
+                let msg = "--compat cannot be combined with --section, --summary, --validate, or --verify-signature";
+                return Err(Error::new(ErrorKind::Usage, msg));
+            }
+            if opts.stream
+                && (opts.compat.is_some()
+                    || opts.no_files
+                    || opts.section.is_some()
+                    || opts.summary
+                    || opts.validate
+                    || opts.verify_signature)
+            {
+                let msg = "--stream cannot be combined with --compat, --no-files, --section, --summary, --validate, or --verify-signature";
+                return Err(Error::new(ErrorKind::Usage, msg));
+            }
+            if opts.stream && args.format() == Format::Table {
+                let msg = "--stream cannot be used with table format";
+                return Err(Error::new(ErrorKind::Usage, msg));
+            }
+            if opts.no_files && opts.section == Some(Section::Files) {
+                let msg = "--section files cannot be combined with --no-files";
+                return Err(Error::new(ErrorKind::Usage, msg));
+            }
+            // The data segment is needed only for the files.
+            let no_files = opts.no_files || matches!(opts.section, Some(s) if s != Section::Files);
+            let keys_dir = opts
+                .keys_dir
+                .or(config.keys_dir)
+                .unwrap_or_else(|| PathBuf::from(DEFAULT_KEYS_DIR));
+            let files = if opts.recursive {
+                find_apk_files(&opts.files)?
             } else {
-                Package::load(reader)?
+                expand_globs(opts.files)?
             };
+            let mode = match files.len() {
+                _ if opts.array => OutputMode::Array,
+                _ if opts.recursive => OutputMode::Lines,
+                0 => return Err(Error::new(ErrorKind::Usage, "no file specified")),
+                1 => OutputMode::Single,
+                _ => OutputMode::Lines,
+            };
+            let mut writer = output_writer(&args, &mut out, mode);
+            let mut violations = 0;
+            let mut failures = 0;
+            let mut unverified = 0;
+            let mut progress = Progress::new(files.len(), args.progress);
+
+            for (i, path) in files.iter().enumerate() {
+                progress.advance(path);
+
+                if opts.stream {
+                    // The output may be already partially written, so it
+                    // cannot be reported as an error record.
+                    write_streamed(&mut writer, path, &opts.files_filter).map_err(|e| {
+                        Error::new(e.kind, format!("{}: {}", path.display(), e.message()))
+                    })?;
+                    continue;
+                }
 
-            dump_json(&pkg, args.pretty_print)?;
+                let pkg = match load_package(path, no_files, &opts.files_filter) {
+                    Ok(pkg) => pkg,
+                    Err(e) if opts.recursive && opts.summary => {
+                        let out = writer.get_mut();
+                        if i > 0 {
+                            writeln!(out)?;
+                        }
+                        writeln!(out, "{}: {}", path.display(), e.message())?;
+                        failures += 1;
+                        continue;
+                    }
+                    Err(e) if opts.recursive => {
+                        let error = e.message();
+                        writer.write_full(&ErrorRecord { path, error })?;
+                        failures += 1;
+                        continue;
+                    }
+                    Err(e) => {
+                        let msg = format!("{}: {}", path.display(), e.message());
+                        return Err(Error::new(e.kind, msg));
+                    }
+                };
+
+                if opts.summary {
+                    let out = writer.get_mut();
+                    if i > 0 {
+                        writeln!(out)?;
+                    }
+                    write_summary(&pkg, out)?;
+                } else if args.format() == Format::Table {
+                    let out = writer.get_mut();
+                    if files.len() > 1 {
+                        if i > 0 {
+                            writeln!(out)?;
+                        }
+                        writeln!(out, "{}:", path.display())?;
+                    }
+                    list_files(pkg.files_metadata(), true, out)?;
+                } else if opts.compat == Some(Compat::Apk) {
+                    writer.write(&AdbPackage::from_package(&pkg))?;
+                } else if let Some(section) = opts.section {
+                    match section {
+                        Section::Pkginfo => writer.write(pkg.pkginfo())?,
+                        Section::Files => writer.write(pkg.files_metadata().as_slice())?,
+                        Section::Scripts => writer.write(pkg.scripts().as_slice())?,
+                        Section::Signatures => writer.write(pkg.signatures().as_slice())?,
+                    }
+                } else if opts.verify_signature {
+                    let sign = pkg.verify_signature(&keys_dir)?;
+                    if sign.is_none() {
+                        unverified += 1;
+                    }
+                    let pkg = Verified::new(&pkg, sign);
+                    if opts.validate {
+                        violations += write_validated(&mut writer, &pkg)?;
+                    } else {
+                        writer.write(&pkg)?;
+                    }
+                } else if opts.validate {
+                    violations += write_validated(&mut writer, &pkg)?;
+                } else {
+                    writer.write(&pkg)?;
+                }
+            }
+            writer.finish()?;
+
+            if failures > 0 {
+                let msg = format!("failed to read {failures} package(s)");
+                return Err(Error::new(ErrorKind::Parse, msg));
+            }
+            check_violations(violations)?;
+
+            if unverified > 0 {
+                let msg = format!("failed to verify signature of {unverified} package(s)");
+                return Err(Error::new(ErrorKind::Validation, msg));
+            }
+        }
+        Action::Apkbuild(opts) if opts.schema => {
+            out.write_all(json_schema::APKBUILD_SCHEMA.as_bytes())?;
         }
         Action::Apkbuild(opts) => {
-            let mut reader = ApkbuildReader::new();
-
             if let Some(arches) = opts.arch_all {
-                reader.arch_all(&arches.split(',').collect::<Vec<_>>());
+                config.arch_all = Some(arches.split(',').map(String::from).collect());
+            }
+            let mut reader = apkbuild_reader(&config);
+
+            // The options that affect the result of evaluation, see --cache.
+            let shell = opts
+                .shell
+                .as_deref()
+                .or(config.shell.as_deref().map(OsStr::new));
+            let cache_options = format!(
+                "{PROG_VERSION};{:?};{:?};{};{:?}",
+                config.arch_all, opts.env, opts.keep_env, shell
+            );
+            if let Some(shell) = opts.shell {
+                reader.shell_cmd(shell);
             }
+            let timeout = opts.timeout.or(config.timeout).unwrap_or(250);
             reader
                 .envs(opts.env)
                 .inherit_env(opts.keep_env)
-                .shell_cmd(opts.shell)
-                .time_limit(Duration::from_millis(opts.timeout));
+                .time_limit(Duration::from_millis(timeout));
+
+            if opts.arch.is_some() && opts.aports.is_some() {
+                let msg = "--arch cannot be combined with --aports";
+                return Err(Error::new(ErrorKind::Usage, msg));
+            }
+            if opts.merge && opts.arch.is_none() {
+                let msg = "--merge can be used only with --arch";
+                return Err(Error::new(ErrorKind::Usage, msg));
+            }
+            if opts.reuse_shell && opts.aports.is_none() {
+                let msg = "--reuse-shell can be used only with --aports";
+                return Err(Error::new(ErrorKind::Usage, msg));
+            }
+            if opts.cache.is_some() && opts.aports.is_none() {
+                let msg = "--cache can be used only with --aports";
+                return Err(Error::new(ErrorKind::Usage, msg));
+            }
+
+            let file = match (opts.file, &opts.aports) {
+                (Some(file), None) => file,
+                (None, Some(aports)) => {
+                    let paths = find_aports_apkbuilds(aports, &opts.repo)?;
+                    let jobs = opts.jobs.unwrap_or_else(parallel::default_jobs);
+                    if opts.reuse_shell {
+                        reader.shell_workers(jobs);
+                    }
+                    let mut cache = match &opts.cache {
+                        Some(path) => {
+                            reader.track_includes(true);
+                            Some(ApkbuildCache::load(path, cache_options)?)
+                        }
+                        None => None,
+                    };
+                    let mut cache_entries = Vec::new();
+
+                    let mut writer = output_writer(&args, &mut out, OutputMode::Lines);
+                    let mut violations = 0;
+                    let mut failures = 0;
+                    let mut progress = Progress::new(paths.len(), args.progress);
+
+                    parallel::for_each_ordered(
+                        &paths,
+                        jobs,
+                        // The error is converted into a message, because it's not Send.
+                        |path| {
+                            match &cache {
+                                Some(cache) => cache.read_apkbuild(&reader, path),
+                                None => reader
+                                    .read_apkbuild(path)
+                                    .map(|apkbuild| {
+                                        Evaluated::Fresh(Box::new(apkbuild), Vec::new())
+                                    })
+                                    .map_err(Error::from),
+                            }
+                            .map_err(|e| e.message())
+                        },
+                        |path, result| {
+                            progress.advance(path);
+                            match result {
+                                Ok(evaluated) => {
+                                    if opts.validate {
+                                        violations +=
+                                            write_validated(&mut writer, evaluated.apkbuild())?;
+                                    } else {
+                                        writer.write(evaluated.apkbuild())?;
+                                    }
+                                    if cache.is_some() {
+                                        let entry = match evaluated {
+                                            Evaluated::Cached(_) => None,
+                                            Evaluated::Fresh(apkbuild, files) => {
+                                                Some(CacheEntry::new(apkbuild, files))
+                                            }
+                                        };
+                                        cache_entries.push((path.clone(), entry));
+                                    }
+                                }
+                                Err(error) => {
+                                    writer.write_full(&ErrorRecord { path, error })?;
+                                    failures += 1;
+                                }
+                            }
+                            Ok::<_, io::Error>(())
+                        },
+                    )?;
+                    writer.finish()?;
 
-            let apkbuild = reader.read_apkbuild(&opts.file)?;
+                    if let (Some(cache), Some(path)) = (&mut cache, &opts.cache) {
+                        cache.replace(cache_entries);
+                        cache.save(path)?;
+                    }
+
+                    if failures > 0 {
+                        let msg = format!("failed to read {failures} APKBUILD(s)");
+                        return Err(Error::new(ErrorKind::Parse, msg));
+                    }
+                    check_violations(violations)?;
+
+                    return out.commit().map_err(Error::from);
+                }
+                _ => {
+                    let msg = "either <apkbuild> or --aports must be specified";
+                    return Err(Error::new(ErrorKind::Usage, msg));
+                }
+            };
+            if let Some(arches) = opts.arch {
+                let mut docs = Vec::new();
+                let mut violations = 0;
 
-            dump_json(&apkbuild, args.pretty_print)?;
+                for arch in arches.split(',') {
+                    let apkbuild = reader.target_arch(arch).read_apkbuild(&file)?;
+                    let doc = if opts.validate {
+                        let validated = Validated::new(&apkbuild);
+                        violations += validated.violations.len();
+                        serde_json::to_value(validated)
+                    } else {
+                        serde_json::to_value(apkbuild)
+                    };
+                    docs.push((arch, doc.map_err(io::Error::from)?));
+                }
+
+                if opts.merge {
+                    let mut writer = output_writer(&args, &mut out, OutputMode::Single);
+                    writer.write(&merge_arch_docs(docs))?;
+                    writer.finish()?;
+                } else {
+                    let mut writer = output_writer(&args, &mut out, OutputMode::Lines);
+                    for (carch, doc) in &docs {
+                        writer.write(&ArchDoc { carch, doc })?;
+                    }
+                    writer.finish()?;
+                }
+                check_violations(violations)?;
+
+                return out.commit().map_err(Error::from);
+            }
+            let apkbuild = reader.read_apkbuild(file)?;
+
+            let mut writer = output_writer(&args, &mut out, OutputMode::Single);
+            if opts.validate {
+                let violations = write_validated(&mut writer, &apkbuild)?;
+                writer.finish()?;
+                check_violations(violations)?;
+            } else {
+                writer.write(&apkbuild)?;
+                writer.finish()?;
+            }
+        }
+        Action::Apkindex(opts) => {
+            let keys_dir = opts
+                .keys_dir
+                .or(config.keys_dir)
+                .unwrap_or_else(|| PathBuf::from(DEFAULT_KEYS_DIR));
+            let files = expand_globs(opts.files)?;
+            let mode = match files.len() {
+                0 => return Err(Error::new(ErrorKind::Usage, "no file specified")),
+                _ if opts.lines => OutputMode::Lines,
+                1 => OutputMode::Single,
+                _ => OutputMode::Lines,
+            };
+            let mut writer = output_writer(&args, &mut out, mode);
+            let mut unverified = 0;
+
+            for path in &files {
+                let index = Index::load(open_file(path)?).map_err(|e| {
+                    let e = Error::from(e);
+                    Error::new(e.kind, format!("{}: {}", path.display(), e.message()))
+                })?;
+
+                let sign = if opts.verify_signature {
+                    let sign = index.verify_signature(&keys_dir)?;
+                    if sign.is_none() {
+                        unverified += 1;
+                    }
+                    Some(sign)
+                } else {
+                    None
+                };
+
+                match sign {
+                    _ if opts.lines => {
+                        for entry in &index.entries {
+                            writer.write(entry)?;
+                        }
+                    }
+                    Some(sign) => writer.write(&Verified::new(&index, sign))?,
+                    None => writer.write(&index)?,
+                }
+            }
+            writer.finish()?;
+
+            if unverified > 0 {
+                let msg = format!("failed to verify signature of {unverified} index(es)");
+                return Err(Error::new(ErrorKind::Validation, msg));
+            }
+        }
+        Action::Cat(opts) => {
+            let reader = open_file(&opts.file)?;
+
+            Package::extract_file(reader, &opts.path, &mut out)?;
+        }
+        Action::Compare(opts) => {
+            let files = expand_globs(opts.files)?;
+            let mode = match files.len() {
+                0 => return Err(Error::new(ErrorKind::Usage, "no package specified")),
+                1 => OutputMode::Single,
+                _ => OutputMode::Lines,
+            };
+            let mut reader = apkbuild_reader(&config);
+            // APKBUILDs evaluated for the package arch (CARCH).
+            let mut apkbuilds: HashMap<String, Apkbuild> = HashMap::new();
+
+            let mut writer = output_writer(&args, &mut out, mode);
+            let mut mismatches = 0;
+
+            for path in &files {
+                let pkg = load_package(path, true, &[]).map_err(|e| {
+                    let msg = format!("{}: {}", path.display(), e.message());
+                    Error::new(e.kind, msg)
+                })?;
+                let arch = &pkg.pkginfo().arch;
+
+                let apkbuild = match apkbuilds.entry(arch.clone()) {
+                    Entry::Occupied(entry) => entry.into_mut(),
+                    Entry::Vacant(entry) => {
+                        if arch != "noarch" {
+                            reader.target_arch(arch);
+                        }
+                        entry.insert(reader.read_apkbuild(&opts.apkbuild)?)
+                    }
+                };
+                let divergences = consistency::check_package(apkbuild, &pkg);
+                mismatches += divergences.len();
+
+                writer.write(&Comparison {
+                    path,
+                    pkgname: &pkg.pkginfo().pkgname,
+                    divergences,
+                })?;
+            }
+            writer.finish()?;
+
+            if mismatches > 0 {
+                let msg = format!("found {mismatches} mismatch(es)");
+                return Err(Error::new(ErrorKind::Validation, msg));
+            }
+        }
+        Action::Completions(opts) => {
+            let commands = [
+                command::<ApkOpts>(),
+                command::<ApkbuildOpts>(),
+                command::<ApkindexOpts>(),
+                command::<CatOpts>(),
+                command::<CompareOpts>(),
+                command::<CompletionsOpts>(),
+                command::<ExportOpts>(),
+                command::<GraphOpts>(),
+                command::<InstalledOpts>(),
+                command::<LsOpts>(),
+                command::<MirrorOpts>(),
+                command::<QueryOpts>(),
+                command::<SbomOpts>(),
+                command::<ScriptsOpts>(),
+                command::<SecdbOpts>(),
+                command::<StatsOpts>(),
+            ];
+            completions::generate(opts.shell, PROG_NAME, &AppOpts::HELP, &commands, &mut out)?;
+        }
+        Action::Export(opts) => {
+            let db_path = match out.temp_path() {
+                Some(path) => path.to_owned(),
+                None => return Err(Error::new(ErrorKind::Usage, "export requires --output")),
+            };
+            let files = expand_globs(opts.files.clone())?;
+            if files.is_empty() && opts.index.is_empty() {
+                let msg = "no file or --index specified";
+                return Err(Error::new(ErrorKind::Usage, msg));
+            }
+            match opts.format {
+                ExportFormat::Parquet => {
+                    let table = opts.table.unwrap_or_default();
+                    if table == ExportTable::Files && !opts.index.is_empty() {
+                        let msg = "--index can be exported only with --table depends";
+                        return Err(Error::new(ErrorKind::Usage, msg));
+                    }
+                    export_parquet(&db_path, &opts, table, &files, args.progress)?;
+                }
+                ExportFormat::Sqlite => {
+                    if opts.table.is_some() {
+                        let msg = "--table can be used only with parquet";
+                        return Err(Error::new(ErrorKind::Usage, msg));
+                    }
+                    export_sqlite(&db_path, &opts, &files, args.progress)?;
+                }
+            }
+        }
+        Action::Graph(opts) => {
+            let paths = find_aports_apkbuilds(&opts.aports, &opts.repo)?;
+            let jobs = opts.jobs.unwrap_or_else(parallel::default_jobs);
+            let reader = apkbuild_reader(&config);
+            let mut graph = DepGraph::new();
+            let mut progress = Progress::new(paths.len(), args.progress);
+
+            parallel::for_each_ordered(
+                &paths,
+                jobs,
+                |path| {
+                    reader
+                        .read_apkbuild(path)
+                        .map_err(|e| Error::from(e).message())
+                },
+                |path, result| {
+                    progress.advance(path);
+                    match result {
+                        Ok(apkbuild) => {
+                            graph.add_apkbuild(&apkbuild);
+                        }
+                        // A broken APKBUILD shouldn't block the graph.
+                        Err(msg) => eprintln!("{PROG_NAME}: {}: {msg}", path.display()),
+                    }
+                    Ok::<_, Error>(())
+                },
+            )?;
+
+            match opts.format {
+                GraphFormat::Dot => graph.write_dot(&mut out)?,
+                GraphFormat::Json => {
+                    let mut writer = output_writer(&args, &mut out, OutputMode::Single);
+                    writer.write(&graph.adjacency_lists())?;
+                    writer.finish()?;
+                }
+            }
+        }
+        Action::Installed(opts) => {
+            let db = InstalledDb::load(open_file(&opts.root.join(INSTALLED_DB_PATH))?)?;
+
+            let mut writer = output_writer(&args, &mut out, OutputMode::Single);
+            if let Some(path) = &opts.owns {
+                let pkg = db
+                    .owner(path)
+                    .ok_or_else(|| format!("{} is not owned by any package", path.display()))?;
+                if opts.files {
+                    writer.write(pkg)?;
+                } else {
+                    writer.write(&pkg.entry)?;
+                }
+            } else if opts.files {
+                writer.write(&db.packages)?;
+            } else {
+                let entries: Vec<_> = db.packages.iter().map(|pkg| &pkg.entry).collect();
+                writer.write(&entries)?;
+            }
+            writer.finish()?;
+        }
+        Action::Ls(opts) => {
+            let pkg = Package::load(open_file(&opts.file)?)?;
+
+            let long = opts.long || args.format() == Format::Table;
+            list_files(pkg.files_metadata(), long, &mut out)?;
+        }
+        Action::Mirror(opts) => {
+            let mut apkindex = Vec::new();
+            open_file(&opts.index)?.read_to_end(&mut apkindex)?;
+
+            let report = MirrorChecker::new()
+                .verify_checksums(opts.checksums)
+                .check(&opts.dir, &apkindex)?;
+
+            let mut writer = output_writer(&args, &mut out, OutputMode::Single);
+            writer.write(&report)?;
+            writer.finish()?;
+
+            if !report.is_synced() {
+                let msg = format!(
+                    "mirror is out of sync: {} missing, {} extra, {} corrupt file(s)",
+                    report.missing.len(),
+                    report.extra.len(),
+                    report.corrupt.len()
+                );
+                return Err(Error::new(ErrorKind::Validation, msg));
+            }
+        }
+        Action::Query(opts) => {
+            if opts.index.is_empty() {
+                let msg = "no index specified, use --index";
+                return Err(Error::new(ErrorKind::Usage, msg));
+            }
+            let mut indexes = Vec::with_capacity(opts.index.len());
+            for path in &opts.index {
+                indexes.push(Index::load(open_file(path)?)?);
+            }
+
+            let mut entries = Vec::new();
+            for index in &indexes {
+                if opts.reverse {
+                    entries.extend(index.reverse_depends(&opts.dependency));
+                } else {
+                    entries.extend(index.find(&opts.dependency));
+                }
+            }
+            let mut writer = output_writer(&args, &mut out, OutputMode::Single);
+            writer.write(&entries)?;
+            writer.finish()?;
+        }
+        Action::Scripts(opts) => {
+            let pkg = Package::load_without_files(open_file(&opts.file)?)?;
+
+            if let Some(script) = opts.show {
+                let content = pkg
+                    .script_content(&script)
+                    .ok_or_else(|| format!("no {script} script in the package"))?;
+                out.write_all(content.as_bytes())?;
+            } else {
+                for script in pkg.scripts() {
+                    writeln!(out, "{script}")?;
+                }
+            }
+        }
+        Action::Sbom(opts) => {
+            let pkg = Package::load(open_file(&opts.file)?)?;
+            let now = SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs() as i64);
+
+            let mut writer = output_writer(&args, &mut out, OutputMode::Single);
+            match opts.format {
+                SbomFormat::CycloneDx => writer.write(&CycloneDx::from_package(&pkg, now))?,
+                SbomFormat::Spdx => writer.write(&Spdx::from_package(&pkg, now))?,
+            }
+            writer.finish()?;
+        }
+        Action::Secdb(opts) => {
+            let paths = find_apkbuilds(opts.aports.join(&opts.repo))?;
+            let jobs = opts.jobs.unwrap_or_else(parallel::default_jobs);
+            let reader = apkbuild_reader(&config);
+            let mut secdb = SecDb::new(&opts.release, &opts.repo);
+            let mut progress = Progress::new(paths.len(), args.progress);
+
+            parallel::for_each_ordered(
+                &paths,
+                jobs,
+                |path| {
+                    reader
+                        .read_apkbuild(path)
+                        .map_err(|e| Error::from(e).message())
+                },
+                |path, result| {
+                    progress.advance(path);
+                    match result {
+                        Ok(apkbuild) => {
+                            secdb.add_apkbuild(&apkbuild);
+                        }
+                        // A broken APKBUILD shouldn't block the secdb.
+                        Err(msg) => eprintln!("{PROG_NAME}: {}: {msg}", path.display()),
+                    }
+                    Ok::<_, Error>(())
+                },
+            )?;
+
+            let mut writer = output_writer(&args, &mut out, OutputMode::Single);
+            writer.write(&secdb)?;
+            writer.finish()?;
+        }
+        Action::Stats(opts) => {
+            let pkg = Package::load(open_file(&opts.file)?)?;
+            let stats = pkg.stats(opts.top, opts.depth);
+
+            if args.format() == Format::Table {
+                write_stats(&stats, &mut out)?;
+            } else {
+                let mut writer = output_writer(&args, &mut out, OutputMode::Single);
+                writer.write(&stats)?;
+                writer.finish()?;
+            }
+        }
+    };
+
+    out.commit()?;
+
+    Ok(())
+}
+
+/// An error record written to the output instead of a package that cannot be
+/// read.
+#[derive(Serialize)]
+struct ErrorRecord<'a> {
+    path: &'a Path,
+    error: String,
+}
+
+/// A result of [`consistency::check_package`].
+#[derive(Serialize)]
+struct Comparison<'a> {
+    path: &'a Path,
+    pkgname: &'a str,
+    divergences: Vec<Divergence>,
+}
+
+/// A package or index with the result of its signature verification.
+#[derive(Serialize)]
+struct Verified<'a, T> {
+    #[serde(flatten)]
+    value: &'a T,
+    signature_valid: bool,
+    /// The name of the key that verified the signature.
+    signature_key: Option<&'a str>,
+}
+
+impl<'a, T> Verified<'a, T> {
+    fn new(value: &'a T, sign: Option<&'a SignatureInfo>) -> Self {
+        Verified {
+            value,
+            signature_valid: sign.is_some(),
+            signature_key: sign.map(|s| s.keyname.as_str()),
+        }
+    }
+}
+
+impl<T: Validate> Validate for Verified<'_, T> {
+    fn validate_with(&self, ctx: &ValidationContext) -> Result<(), ValidationReport> {
+        self.value.validate_with(ctx)
+    }
+}
+
+/// Exports the packages at the given paths and the indexes specified in the
+/// `opts` into a new SQLite database at the `db_path`.
+#[cfg(feature = "sqlite")]
+fn export_sqlite(
+    db_path: &Path,
+    opts: &ExportOpts,
+    files: &[PathBuf],
+    progress: bool,
+) -> Result<(), Error> {
+    let mut exporter = SqliteExporter::create(db_path)?;
+
+    for path in &opts.index {
+        let index = Index::load(open_file(path)?).map_err(|e| {
+            let e = Error::from(e);
+            Error::new(e.kind, format!("{}: {}", path.display(), e.message()))
+        })?;
+        exporter.add_index(&index, &path.to_string_lossy())?;
+    }
+
+    for_each_package(files, opts, opts.no_files, progress, |path, pkg| {
+        exporter.add_package(&pkg, &path.to_string_lossy())?;
+        Ok(())
+    })?;
+    exporter.finish()?;
+
+    Ok(())
+}
+
+#[cfg(not(feature = "sqlite"))]
+fn export_sqlite(_: &Path, _: &ExportOpts, _: &[PathBuf], _: bool) -> Result<(), Error> {
+    let msg = format!("{PROG_NAME} was built without support for SQLite");
+    Err(Error::new(ErrorKind::Usage, msg))
+}
+
+/// Exports the `table` records of the packages at the given paths (and of
+/// the indexes specified in the `opts`) into a new Parquet file at the `path`.
+#[cfg(feature = "parquet")]
+fn export_parquet(
+    path: &Path,
+    opts: &ExportOpts,
+    table: ExportTable,
+    files: &[PathBuf],
+    progress: bool,
+) -> Result<(), Error> {
+    let out = io::BufWriter::new(File::create(path)?);
+
+    let mut out = match table {
+        ExportTable::Files => {
+            let mut writer = FilesWriter::new(out)?;
+            for_each_package(files, opts, opts.no_files, progress, |_, pkg| {
+                Ok(writer.add_package(&pkg)?)
+            })?;
+            writer.finish()?
+        }
+        ExportTable::Depends => {
+            let mut writer = DependsWriter::new(out)?;
+            for path in &opts.index {
+                let index = Index::load(open_file(path)?).map_err(|e| {
+                    let e = Error::from(e);
+                    Error::new(e.kind, format!("{}: {}", path.display(), e.message()))
+                })?;
+                writer.add_index(&index)?;
+            }
+            for_each_package(files, opts, true, progress, |_, pkg| {
+                Ok(writer.add_pkginfo(pkg.pkginfo())?)
+            })?;
+            writer.finish()?
+        }
+    };
+    out.flush()?;
+
+    Ok(())
+}
+
+#[cfg(not(feature = "parquet"))]
+fn export_parquet(
+    _: &Path,
+    _: &ExportOpts,
+    _: ExportTable,
+    _: &[PathBuf],
+    _: bool,
+) -> Result<(), Error> {
+    let msg = format!("{PROG_NAME} was built without support for Parquet");
+    Err(Error::new(ErrorKind::Usage, msg))
+}
+
+/// Loads the packages at the given paths in parallel (according to the
+/// `opts.jobs`) and passes them to `f` in the original order.
+#[cfg(any(feature = "sqlite", feature = "parquet"))]
+fn for_each_package<F>(
+    files: &[PathBuf],
+    opts: &ExportOpts,
+    no_files: bool,
+    progress: bool,
+    mut f: F,
+) -> Result<(), Error>
+where
+    F: FnMut(&Path, Package) -> Result<(), Error>,
+{
+    let jobs = opts.jobs.unwrap_or_else(parallel::default_jobs);
+    let mut progress = Progress::new(files.len(), progress);
+
+    parallel::for_each_ordered(
+        files,
+        jobs,
+        // The error is converted into a message, because it's not Send.
+        |path| load_package(path, no_files, &[]).map_err(|e| (e.kind, e.message())),
+        |path, result| {
+            progress.advance(path);
+            let pkg = result
+                .map_err(|(kind, msg)| Error::new(kind, format!("{}: {}", path.display(), msg)))?;
+            f(path, pkg)
+        },
+    )
+}
+
+/// Loads the package, reading only the files matching any of the
+/// `files_filter` patterns (all if empty), or no files if `no_files`.
+fn load_package(path: &Path, no_files: bool, files_filter: &[Pattern]) -> Result<Package, Error> {
+    let reader = open_file(path)?;
+
+    let pkg = if no_files {
+        Package::load_without_files(reader)?
+    } else if !files_filter.is_empty() {
+        Package::load_filtered(reader, |path| matches_files_filter(files_filter, path))?
+    } else {
+        Package::load(reader)?
+    };
+    Ok(pkg)
+}
+
+/// Writes the package at the `path` with the files serialized as they're read
+/// from the package (see [`StreamedPackage`]).
+fn write_streamed<W: Write>(
+    writer: &mut OutputWriter<W>,
+    path: &Path,
+    files_filter: &[Pattern],
+) -> Result<(), Error> {
+    let mut reader = open_file(path)?;
+    let pkg = Package::load_without_files(&mut reader)?;
+
+    writer.write(&StreamedPackage::new(&pkg, reader, |path| {
+        files_filter.is_empty() || matches_files_filter(files_filter, path)
+    }))?;
+    Ok(())
+}
+
+/// Returns `true` if the absolute `path` of a file in a package matches any
+/// of the `files_filter` patterns.
+fn matches_files_filter(files_filter: &[Pattern], path: &Path) -> bool {
+    let options = MatchOptions {
+        require_literal_separator: true,
+        ..MatchOptions::default()
+    };
+    // Directories have a trailing slash.
+    let path = path.to_string_lossy();
+    let path = path.strip_suffix('/').unwrap_or(&path);
+    files_filter.iter().any(|p| p.matches_with(path, options))
+}
+
+/// Opens the file at the given path for buffered reading, stdin if the path
+/// is `-` (see [`STDIN_PATH`]), or a remote file if it's a HTTP(S) URL.
+fn open_file(path: &Path) -> Result<Box<dyn BufRead>, Error> {
+    debug!("opening {}", path.display());
+
+    if path == Path::new(STDIN_PATH) {
+        return Ok(Box::new(io::stdin().lock()));
+    }
+    if let Some(url) = path.to_str().filter(|s| is_url(s)) {
+        #[cfg(feature = "http")]
+        return Ok(Box::new(alpkit::package::RemoteReader::new(url)));
+
+        #[cfg(not(feature = "http"))]
+        return Err(Error::new(
+            ErrorKind::Usage,
+            format!("cannot open '{url}': {PROG_NAME} was built without support for URLs"),
+        ));
+    }
+    let reader = File::open(path).map(BufReader::new).map_err(|e| {
+        let msg = format!("cannot open file '{}': {}", path.to_string_lossy(), e);
+        Error::new(ErrorKind::Io, msg)
+    })?;
+
+    if !path.is_file() {
+        let msg = format!("'{}' is not a regular file", path.to_string_lossy());
+        return Err(Error::new(ErrorKind::Io, msg));
+    }
+    Ok(Box::new(reader))
+}
+
+fn list_files<'a, I, W>(files: I, long: bool, out: &mut W) -> io::Result<()>
+where
+    I: Iterator<Item = &'a FileInfo>,
+    W: Write,
+{
+    if !long {
+        for file in files {
+            writeln!(out, "{}", file.path.display())?;
+        }
+        return Ok(());
+    }
+
+    let rows: Vec<_> = files
+        .map(|file| {
+            let owner = format!("{}:{}", file.uname, file.gname);
+            let size = file.size.unwrap_or(0).to_string();
+            (file, owner, size)
+        })
+        .collect();
+    let owner_width = rows.iter().map(|r| r.1.len()).max().unwrap_or(0);
+    let size_width = rows.iter().map(|r| r.2.len()).max().unwrap_or(0);
+
+    for (file, owner, size) in rows {
+        write!(
+            out,
+            "{} {owner:<owner_width$} {size:>size_width$} {}",
+            format_mode(&file.file_type, file.mode),
+            file.path.display()
+        )?;
+        match &file.link_target {
+            Some(target) => writeln!(out, " -> {}", target.display())?,
+            None => writeln!(out)?,
+        }
+    }
+    Ok(())
+}
+
+/// Writes a human-readable overview of the package.
+fn write_summary<W: Write>(pkg: &Package, out: &mut W) -> io::Result<()> {
+    let pkginfo = pkg.pkginfo();
+    let count = |deps: &[Dependency]| deps.len().to_string();
+
+    let scripts = pkg.scripts().map(PkgScript::name).collect::<Vec<_>>();
+    let signs = pkg
+        .signatures()
+        .map(|s| s.keyname.as_str())
+        .collect::<Vec<_>>();
+
+    let mut rows = vec![
+        ("Maintainer", pkginfo.maintainer.clone().unwrap_or_default()),
+        ("License", pkginfo.license.clone()),
+        ("URL", pkginfo.url.clone()),
+        ("Origin", pkginfo.origin.clone()),
+        ("Installed size", format_size(pkginfo.size as u64)),
+        ("Depends", count(&pkginfo.depends)),
+        ("Provides", count(&pkginfo.provides)),
+    ];
+    for (name, deps) in [
+        ("Conflicts", &pkginfo.conflicts),
+        ("Install if", &pkginfo.install_if),
+        ("Replaces", &pkginfo.replaces),
+    ] {
+        if !deps.is_empty() {
+            rows.push((name, count(deps)));
         }
+    }
+    rows.push(("Scripts", join_or_none(&scripts)));
+    rows.push(("Signed by", join_or_none(&signs)));
+
+    // Files are not available if the package has been loaded without them.
+    let mut files = pkg.files_metadata().peekable();
+    if files.peek().is_some() {
+        let mut count = 0;
+        let mut dirs = BTreeSet::new();
+        for file in files {
+            if file.file_type != FileType::Directory {
+                count += 1;
+            }
+            let mut components = file.path.components().skip(1);
+            if let Some(first) = components.next() {
+                if file.file_type == FileType::Directory || components.next().is_some() {
+                    dirs.insert(Path::new("/").join(first));
+                }
+            }
+        }
+        let dirs = dirs.iter().map(|d| d.to_string_lossy()).collect::<Vec<_>>();
+        rows.push(("Files", format!("{count} in {}", join_or_none(&dirs))));
+    }
+
+    writeln!(
+        out,
+        "{} {} ({})",
+        pkginfo.pkgname, pkginfo.pkgver, pkginfo.arch
+    )?;
+    writeln!(out, "{}", pkginfo.pkgdesc)?;
+    writeln!(out)?;
+
+    let width = rows.iter().map(|r| r.0.len()).max().unwrap_or(0) + 1;
+    for (name, value) in rows {
+        writeln!(out, "{:<width$} {value}", format!("{name}:"))?;
+    }
+    Ok(())
+}
+
+/// Writes the package stats as human-readable tables.
+fn write_stats<W: Write>(stats: &PackageStats, out: &mut W) -> io::Result<()> {
+    let counts = &stats.file_counts;
+
+    writeln!(out, "Total size:     {}", format_size(stats.total_size))?;
+    writeln!(out, "Installed size: {}", format_size(stats.installed_size))?;
+    writeln!(out, "Regular files:  {}", counts.regular)?;
+    writeln!(out, "Directories:    {}", counts.directory)?;
+    writeln!(out, "Symlinks:       {}", counts.symlink)?;
+    writeln!(out, "Hardlinks:      {}", counts.hardlink)?;
+    writeln!(out, "Devices:        {}", counts.device)?;
+    writeln!(out, "FIFOs:          {}", counts.fifo)?;
+    writeln!(out, "Setuid/setgid:  {}", stats.setuid_files)?;
+
+    let size_width = |sizes: &mut dyn Iterator<Item = u64>| {
+        sizes.map(|s| format_size(s).len()).max().unwrap_or(0)
     };
 
+    writeln!(out, "\nLargest files:")?;
+    let width = size_width(&mut stats.largest_files.iter().map(|f| f.size));
+    for file in &stats.largest_files {
+        let size = format_size(file.size);
+        writeln!(out, "  {size:>width$}  {}", file.path.display())?;
+    }
+
+    writeln!(out, "\nDirectories:")?;
+    let width = size_width(&mut stats.directories.iter().map(|d| d.size));
+    let files_width = stats
+        .directories
+        .iter()
+        .map(|d| d.files.to_string().len())
+        .max()
+        .unwrap_or(0);
+    for dir in &stats.directories {
+        let size = format_size(dir.size);
+        writeln!(
+            out,
+            "  {size:>width$}  {:>files_width$} files  {}",
+            dir.files,
+            dir.path.display()
+        )?;
+    }
     Ok(())
 }
 
+/// Formats the size in bytes with a binary unit, e.g. `84.0 KiB`.
+fn format_size(size: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+    if size < 1024 {
+        return format!("{size} B");
+    }
+    let mut value = size as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", UNITS[unit])
+}
+
+fn join_or_none<S: AsRef<str>>(items: &[S]) -> String {
+    if items.is_empty() {
+        "none".to_owned()
+    } else {
+        items
+            .iter()
+            .map(AsRef::as_ref)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Formats the file type and mode as in `ls -l`, e.g. `-rwxr-xr-x`.
+fn format_mode(file_type: &FileType, mode: u32) -> String {
+    let type_char = match file_type {
+        FileType::Regular => '-',
+        FileType::Link => 'h',
+        FileType::Symlink => 'l',
+        FileType::Char => 'c',
+        FileType::Block => 'b',
+        FileType::Directory => 'd',
+        FileType::Fifo => 'p',
+    };
+    let mut s = String::with_capacity(10);
+    s.push(type_char);
+
+    for (i, special, special_char) in [(6, 0o4000, 's'), (3, 0o2000, 's'), (0, 0o1000, 't')] {
+        let bits = mode >> i;
+        s.push(if bits & 0o4 != 0 { 'r' } else { '-' });
+        s.push(if bits & 0o2 != 0 { 'w' } else { '-' });
+        s.push(match (bits & 0o1 != 0, mode & special != 0) {
+            (true, true) => special_char,
+            (false, true) => special_char.to_ascii_uppercase(),
+            (true, false) => 'x',
+            (false, false) => '-',
+        });
+    }
+    s
+}
+
+fn command<T: CommandHelp + SubCommand>() -> Command {
+    Command {
+        info: T::COMMAND,
+        help: &T::HELP,
+    }
+}
+
+fn is_url(s: &str) -> bool {
+    s.starts_with("http://") || s.starts_with("https://")
+}
+
 fn parse_env_var(s: &str) -> Result<(OsString, OsString), String> {
     s.split_once('=')
         .map(|(k, v)| (k.into(), v.into()))
         .ok_or_else(|| format!("expected VAR=VALUE, but got: '{s}'"))
 }
 
-fn dump_json<T: ?Sized + serde::Serialize>(
+fn parse_glob(s: &str) -> Result<Pattern, String> {
+    Pattern::new(s).map_err(|e| format!("invalid glob pattern '{s}': {e}"))
+}
+
+fn parse_dependency(s: &str) -> Result<Dependency, String> {
+    s.parse().map_err(|e| format!("{e}"))
+}
+
+fn parse_script(s: &str) -> Result<PkgScript, String> {
+    s.parse().map_err(|_| format!("unknown script: '{s}'"))
+}
+
+/// Creates an APKBUILD reader with the defaults from the `config`.
+fn apkbuild_reader(config: &Config) -> ApkbuildReader {
+    let mut reader = ApkbuildReader::new();
+
+    if let Some(arches) = &config.arch_all {
+        reader.arch_all(arches);
+    }
+    if let Some(shell) = &config.shell {
+        reader.shell_cmd(shell);
+    }
+    if let Some(timeout) = config.timeout {
+        reader.time_limit(Duration::from_millis(timeout));
+    }
+    reader
+}
+
+/// Creates a writer to the `out` according to the output options.
+fn output_writer<'a>(
+    args: &AppOpts,
+    out: &'a mut Output,
+    mode: OutputMode,
+) -> OutputWriter<&'a mut Output> {
+    let mut writer = OutputWriter::new(out, args.format(), mode, args.pretty_print);
+    writer.fields(args.fields.clone()).sorted(args.sorted);
+    writer
+}
+
+/// A value with the violations found by its validation.
+#[derive(Serialize)]
+struct Validated<'a, T> {
+    #[serde(flatten)]
+    value: &'a T,
+    violations: Vec<Violation>,
+}
+
+impl<'a, T: Validate> Validated<'a, T> {
+    fn new(value: &'a T) -> Self {
+        Validated {
+            value,
+            violations: value.validate().err().unwrap_or_default().violations,
+        }
+    }
+}
+
+/// Validates the `value` and writes it with the `violations` field.
+/// Returns the number of violations.
+fn write_validated<W: Write, T: Serialize + Validate>(
+    writer: &mut OutputWriter<W>,
     value: &T,
-    pretty: bool,
-) -> Result<(), serde_json::Error> {
-    if pretty {
-        serde_json::to_writer(io::stdout(), value)
-    } else {
-        serde_json::to_writer_pretty(io::stdout(), value)?;
-        let _ = io::stdout().write(b"\n");
-        Ok(())
+) -> io::Result<usize> {
+    let validated = Validated::new(value);
+    writer.write(&validated)?;
+
+    Ok(validated.violations.len())
+}
+
+/// An APKBUILD document evaluated for the architecture `carch`.
+#[derive(Serialize)]
+struct ArchDoc<'a> {
+    carch: &'a str,
+    #[serde(flatten)]
+    doc: &'a Value,
+}
+
+/// Merges the APKBUILD documents evaluated for different architectures into
+/// a single document with the fields that are the same for all of them, and
+/// `arch_overrides` with the differing fields per arch.
+fn merge_arch_docs(docs: Vec<(&str, Value)>) -> Value {
+    let mut docs: Vec<_> = docs
+        .into_iter()
+        .map(|(arch, doc)| match doc {
+            Value::Object(map) => (arch, map),
+            _ => (arch, Map::new()),
+        })
+        .collect();
+
+    let mut common = Map::new();
+    if let Some((_, first)) = docs.first() {
+        for (key, value) in first {
+            if docs.iter().all(|(_, doc)| doc.get(key) == Some(value)) {
+                common.insert(key.clone(), value.clone());
+            }
+        }
+    }
+    for (_, doc) in &mut docs {
+        doc.retain(|key, _| !common.contains_key(key));
+    }
+    let overrides = docs
+        .into_iter()
+        .map(|(arch, doc)| (arch.to_owned(), Value::Object(doc)))
+        .collect();
+    common.insert("arch_overrides".to_owned(), Value::Object(overrides));
+
+    Value::Object(common)
+}
+
+fn check_violations(count: usize) -> Result<(), Error> {
+    match count {
+        0 => Ok(()),
+        n => Err(Error::new(
+            ErrorKind::Validation,
+            format!("found {n} violation(s)"),
+        )),
     }
 }
 
-fn format_error_message(error: &dyn error::Error) -> String {
-    let mut msg = String::from(PROG_NAME);
+/// Finds APKBUILDs in the given `repos` of the aports tree, or in the whole
+/// tree if `repos` is empty.
+fn find_aports_apkbuilds(aports: &Path, repos: &[String]) -> io::Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    if repos.is_empty() {
+        paths = find_apkbuilds(aports)?;
+    }
+    for repo in repos {
+        paths.append(&mut find_apkbuilds(aports.join(repo))?);
+    }
+    debug!("found {} APKBUILDs in {}", paths.len(), aports.display());
+
+    Ok(paths)
+}
+
+/// Expands the paths containing glob patterns (e.g. `*.apk`), other paths and
+/// URLs are returned as is.
+fn expand_globs(paths: Vec<PathBuf>) -> Result<Vec<PathBuf>, String> {
+    let mut result = Vec::with_capacity(paths.len());
+
+    for path in paths {
+        let pattern = path.to_string_lossy();
+        if !pattern.contains(['*', '?', '[']) || is_url(&pattern) {
+            result.push(path);
+            continue;
+        }
+        let mut matches = glob::glob(&pattern)
+            .map_err(|e| format!("invalid glob pattern '{pattern}': {e}"))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
 
-    let mut source = Some(error);
-    while let Some(e) = source {
-        msg.push_str(": ");
-        msg.push_str(&e.to_string());
+        if matches.is_empty() {
+            return Err(format!("no files match '{pattern}'"));
+        }
+        result.append(&mut matches);
+    }
+    Ok(result)
+}
+
+/// Finds all `*.apk` files in the given directories (recursively), sorted by
+/// path.
+fn find_apk_files(dirs: &[PathBuf]) -> Result<Vec<PathBuf>, io::Error> {
+    let mut result = Vec::new();
 
-        source = e.source();
+    for dir in dirs {
+        for entry in WalkDir::new(dir).follow_links(true).sort_by_file_name() {
+            let entry = entry?;
+
+            if entry.file_type().is_file()
+                && entry.path().extension().map_or(false, |ext| ext == "apk")
+            {
+                result.push(entry.into_path());
+            }
+        }
     }
-    msg
+    Ok(result)
 }
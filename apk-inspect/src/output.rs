@@ -0,0 +1,421 @@
+use std::cmp::Ordering;
+use std::fs;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use tempfile::NamedTempFile;
+
+/// The output format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Format {
+    Json,
+    Yaml,
+    Toml,
+    /// Aligned columns of text, supported only for the list of files and
+    /// stats (it's not handled by [`OutputWriter`]).
+    Table,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Format::Json),
+            "yaml" => Ok(Format::Yaml),
+            "toml" => Ok(Format::Toml),
+            "table" => Ok(Format::Table),
+            _ => Err(format!(
+                "unknown format: '{s}', expected json, yaml, toml or table"
+            )),
+        }
+    }
+}
+
+/// The output destination: stdout, or a file that is written atomically.
+pub(crate) enum Output {
+    Stdout(io::StdoutLock<'static>),
+    /// A temporary file in the same directory as the target file, which is
+    /// renamed to the target path in [`Output::commit`].
+    File(BufWriter<NamedTempFile>, PathBuf),
+}
+
+impl Output {
+    /// Opens the output to the file at the given `path`, or stdout if `None`.
+    pub(crate) fn open(path: Option<&Path>) -> io::Result<Self> {
+        let path = match path {
+            Some(path) => path,
+            None => return Ok(Output::Stdout(io::stdout().lock())),
+        };
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        let file = NamedTempFile::new_in(dir).map_err(|e| {
+            let msg = format!("cannot create file in '{}': {e}", dir.display());
+            io::Error::new(e.kind(), msg)
+        })?;
+
+        Ok(Output::File(BufWriter::new(file), path.to_owned()))
+    }
+
+    /// Returns the path of the temporary file if the output is a file. It can
+    /// be used to write the output by other means than [`Write`], e.g. as an
+    /// SQLite database.
+    pub(crate) fn temp_path(&self) -> Option<&Path> {
+        match self {
+            Output::Stdout(_) => None,
+            Output::File(out, _) => Some(out.get_ref().path()),
+        }
+    }
+
+    /// Flushes the output and, if it's a file, moves it to the target path.
+    /// If the output is dropped without calling this, the temporary file is
+    /// removed.
+    pub(crate) fn commit(self) -> io::Result<()> {
+        match self {
+            Output::Stdout(mut out) => out.flush(),
+            Output::File(out, path) => {
+                let file = out.into_inner().map_err(|e| e.into_error())?;
+                #[cfg(unix)]
+                {
+                    use std::os::unix::fs::PermissionsExt;
+                    fs::set_permissions(file.path(), fs::Permissions::from_mode(0o644))?;
+                }
+                file.persist(&path).map_err(|e| e.error)?;
+
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Output::Stdout(out) => out.write(buf),
+            Output::File(out, _) => out.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Output::Stdout(out) => out.flush(),
+            Output::File(out, _) => out.flush(),
+        }
+    }
+}
+
+/// A list of the fields to output, each one as a path of the (nested) field
+/// names.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Fields(Vec<Vec<String>>);
+
+impl Fields {
+    /// Returns a copy of the `value` containing only the selected fields. If
+    /// the value (or any nested value on the path) is an array, the fields
+    /// are selected from each of its elements.
+    fn select(&self, value: Value) -> Value {
+        let paths: Vec<_> = self.0.iter().map(Vec::as_slice).collect();
+        select_fields(value, &paths)
+    }
+}
+
+impl FromStr for Fields {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split(',')
+            .map(|field| {
+                let path: Vec<_> = field.trim().split('.').map(String::from).collect();
+                if path.iter().any(String::is_empty) {
+                    return Err(format!("invalid field: '{field}'"));
+                }
+                Ok(path)
+            })
+            .collect::<Result<_, _>>()
+            .map(Fields)
+    }
+}
+
+/// How multiple documents are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum OutputMode {
+    /// A single document.
+    Single,
+    /// One document per line (NDJSON), or a YAML stream of documents.
+    Lines,
+    /// An array of the documents.
+    Array,
+}
+
+/// A writer of a sequence of documents in the given [`Format`].
+pub(crate) struct OutputWriter<W: Write> {
+    out: W,
+    format: Format,
+    mode: OutputMode,
+    pretty: bool,
+    fields: Option<Fields>,
+    sorted: bool,
+    count: usize,
+    /// Documents collected for a YAML array (it cannot be written
+    /// incrementally).
+    yaml_items: Vec<serde_yaml::Value>,
+}
+
+impl<W: Write> OutputWriter<W> {
+    pub(crate) fn new(out: W, format: Format, mode: OutputMode, pretty: bool) -> Self {
+        OutputWriter {
+            out,
+            format,
+            mode,
+            pretty,
+            fields: None,
+            sorted: false,
+            count: 0,
+            yaml_items: Vec::new(),
+        }
+    }
+
+    /// Restricts the output to the given fields (all fields if `None`).
+    pub(crate) fn fields(&mut self, fields: Option<Fields>) -> &mut Self {
+        self.fields = fields;
+        self
+    }
+
+    /// Sorts all maps (by keys) and lists in the output, so that it doesn't
+    /// depend on the order in the input.
+    pub(crate) fn sorted(&mut self, sorted: bool) -> &mut Self {
+        self.sorted = sorted;
+        self
+    }
+
+    pub(crate) fn write<T: ?Sized + Serialize>(&mut self, value: &T) -> io::Result<()> {
+        if self.fields.is_none() && !self.sorted {
+            return self.write_full(value);
+        }
+        let mut value = serde_json::to_value(value)?;
+        if let Some(fields) = &self.fields {
+            value = fields.select(value);
+        }
+        if self.sorted {
+            sort_lists(&mut value);
+            sort_maps(&mut value);
+        }
+        self.write_full(&value)
+    }
+
+    /// Returns a mutable reference to the underlying writer.
+    pub(crate) fn get_mut(&mut self) -> &mut W {
+        &mut self.out
+    }
+
+    /// Writes the closing of the array (in the `Array` mode) and flushes the
+    /// output.
+    pub(crate) fn finish(mut self) -> io::Result<()> {
+        match self.format {
+            Format::Json if self.mode == OutputMode::Array => {
+                if self.count == 0 {
+                    self.out.write_all(b"[")?;
+                } else if self.pretty {
+                    self.out.write_all(b"\n")?;
+                }
+                self.out.write_all(b"]\n")?;
+            }
+            Format::Yaml if self.mode == OutputMode::Array => {
+                serde_yaml::to_writer(&mut self.out, &self.yaml_items).map_err(other_error)?;
+            }
+            _ => (),
+        }
+        self.out.flush()
+    }
+
+    /// Writes the value with all its fields, regardless of the selected
+    /// fields.
+    pub(crate) fn write_full<T: ?Sized + Serialize>(&mut self, value: &T) -> io::Result<()> {
+        match self.format {
+            Format::Json => self.write_json(value)?,
+            Format::Yaml => self.write_yaml(value)?,
+            Format::Toml => self.write_toml(value)?,
+            Format::Table => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "table format is supported only for the list of files and stats",
+                ))
+            }
+        }
+        self.count += 1;
+
+        Ok(())
+    }
+
+    fn write_json<T: ?Sized + Serialize>(&mut self, value: &T) -> io::Result<()> {
+        match self.mode {
+            OutputMode::Array if self.count == 0 => self.out.write_all(b"[")?,
+            OutputMode::Array => self.out.write_all(b",")?,
+            _ => (),
+        };
+        if self.mode == OutputMode::Array && self.pretty {
+            self.out.write_all(b"\n")?;
+        }
+
+        // NDJSON must be always compact.
+        if self.pretty && self.mode != OutputMode::Lines {
+            serde_json::to_writer_pretty(&mut self.out, value)?;
+        } else {
+            serde_json::to_writer(&mut self.out, value)?;
+        }
+        if self.mode != OutputMode::Array {
+            self.out.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+
+    fn write_yaml<T: ?Sized + Serialize>(&mut self, value: &T) -> io::Result<()> {
+        match self.mode {
+            OutputMode::Single => {
+                serde_yaml::to_writer(&mut self.out, value).map_err(other_error)?;
+            }
+            OutputMode::Lines => {
+                self.out.write_all(b"---\n")?;
+                serde_yaml::to_writer(&mut self.out, value).map_err(other_error)?;
+            }
+            OutputMode::Array => {
+                let value = serde_yaml::to_value(value).map_err(other_error)?;
+                self.yaml_items.push(value);
+            }
+        }
+        Ok(())
+    }
+
+    fn write_toml<T: ?Sized + Serialize>(&mut self, value: &T) -> io::Result<()> {
+        if self.mode != OutputMode::Single {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "TOML format supports only a single document",
+            ));
+        }
+        // Converting to toml::Value first ensures that the simple values are
+        // written before the tables, as required by TOML.
+        let value = toml::Value::try_from(value).map_err(other_error)?;
+        let s = if self.pretty {
+            toml::to_string_pretty(&value)
+        } else {
+            toml::to_string(&value)
+        }
+        .map_err(other_error)?;
+
+        self.out.write_all(s.as_bytes())
+    }
+}
+
+fn other_error<E: std::error::Error + Send + Sync + 'static>(e: E) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, e)
+}
+
+/// Sorts the arrays in the `value` recursively. Objects are compared by their
+/// values in the order of the keys, so that e.g. the files are sorted by path
+/// (the first field).
+fn sort_lists(value: &mut Value) {
+    match value {
+        Value::Array(items) => {
+            items.iter_mut().for_each(sort_lists);
+            items.sort_by(compare_values);
+        }
+        Value::Object(map) => map.values_mut().for_each(sort_lists),
+        _ => (),
+    }
+}
+
+/// Sorts the keys of the objects in the `value` recursively.
+fn sort_maps(value: &mut Value) {
+    match value {
+        Value::Array(items) => items.iter_mut().for_each(sort_maps),
+        Value::Object(map) => {
+            let mut entries: Vec<_> = std::mem::take(map).into_iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+            for (key, mut value) in entries {
+                sort_maps(&mut value);
+                map.insert(key, value);
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Compares JSON values of the same type by their content, values of
+/// different types by the order of the types (null, bool, number, string,
+/// array, object).
+fn compare_values(a: &Value, b: &Value) -> Ordering {
+    fn rank(value: &Value) -> u8 {
+        match value {
+            Value::Null => 0,
+            Value::Bool(_) => 1,
+            Value::Number(_) => 2,
+            Value::String(_) => 3,
+            Value::Array(_) => 4,
+            Value::Object(_) => 5,
+        }
+    }
+    match (a, b) {
+        (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+        (Value::Number(a), Value::Number(b)) => match (a.as_i64(), b.as_i64()) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            // as_f64 never fails with the default features of serde_json.
+            _ => a.as_f64().unwrap().total_cmp(&b.as_f64().unwrap()),
+        },
+        (Value::String(a), Value::String(b)) => a.cmp(b),
+        (Value::Array(a), Value::Array(b)) => a
+            .iter()
+            .zip(b)
+            .map(|(a, b)| compare_values(a, b))
+            .find(|ord| ord.is_ne())
+            .unwrap_or_else(|| a.len().cmp(&b.len())),
+        (Value::Object(a), Value::Object(b)) => a
+            .iter()
+            .zip(b)
+            .map(|((ak, av), (bk, bv))| ak.cmp(bk).then_with(|| compare_values(av, bv)))
+            .find(|ord| ord.is_ne())
+            .unwrap_or_else(|| a.len().cmp(&b.len())),
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
+
+fn select_fields(value: Value, paths: &[&[String]]) -> Value {
+    match value {
+        Value::Array(items) => items
+            .into_iter()
+            .map(|item| select_fields(item, paths))
+            .collect(),
+        Value::Object(mut map) => {
+            let mut result = Map::new();
+
+            for path in paths {
+                let key = &path[0];
+                // The key may have been already taken by a preceding path.
+                let value = match map.remove(key) {
+                    Some(value) => value,
+                    None => continue,
+                };
+                let subpaths: Vec<_> = paths
+                    .iter()
+                    .filter(|p| &p[0] == key)
+                    .map(|p| &p[1..])
+                    .collect();
+
+                let value = if subpaths.iter().any(|p| p.is_empty()) {
+                    value
+                } else {
+                    select_fields(value, &subpaths)
+                };
+                result.insert(key.clone(), value);
+            }
+            Value::Object(result)
+        }
+        value => value,
+    }
+}
@@ -0,0 +1,59 @@
+use std::collections::BTreeMap;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+/// Returns the default number of parallel jobs (the number of CPUs).
+pub(crate) fn default_jobs() -> usize {
+    thread::available_parallelism().map_or(1, NonZeroUsize::get)
+}
+
+/// Applies the function `f` to each of the `items` using up to `jobs` threads
+/// and passes the results to the `sink` in the order of the items, as soon as
+/// all the preceding results are available. If the `sink` returns an error,
+/// the processing is stopped and the error is returned.
+pub(crate) fn for_each_ordered<T, R, E, F, S>(
+    items: &[T],
+    jobs: usize,
+    f: F,
+    mut sink: S,
+) -> Result<(), E>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+    S: FnMut(&T, R) -> Result<(), E>,
+{
+    let next = AtomicUsize::new(0);
+
+    thread::scope(|scope| {
+        let (tx, rx) = mpsc::channel();
+
+        for _ in 0..jobs.clamp(1, items.len().max(1)) {
+            let (tx, next, f) = (tx.clone(), &next, &f);
+
+            scope.spawn(move || loop {
+                let idx = next.fetch_add(1, Ordering::Relaxed);
+                // The receiver is dropped if the sink has failed.
+                if idx >= items.len() || tx.send((idx, f(&items[idx]))).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(tx);
+
+        let mut pending = BTreeMap::new();
+        let mut expected = 0;
+
+        for (idx, result) in rx {
+            pending.insert(idx, result);
+
+            while let Some(result) = pending.remove(&expected) {
+                sink(&items[expected], result)?;
+                expected += 1;
+            }
+        }
+        Ok(())
+    })
+}
@@ -0,0 +1,38 @@
+use std::path::Path;
+
+use crate::PROG_NAME;
+
+/// Reports progress of processing multiple files to stderr, one line per
+/// file with the number of files processed so far and the total count.
+pub(crate) struct Progress {
+    total: usize,
+    done: usize,
+    enabled: bool,
+}
+
+impl Progress {
+    /// Creates a progress for `total` files; if not `enabled`, nothing is
+    /// reported.
+    pub(crate) fn new(total: usize, enabled: bool) -> Self {
+        Progress {
+            total,
+            done: 0,
+            enabled,
+        }
+    }
+
+    /// Advances the progress to the file at `path` and reports it.
+    pub(crate) fn advance(&mut self, path: &Path) {
+        self.done += 1;
+
+        if self.enabled {
+            let width = self.total.to_string().len();
+            eprintln!(
+                "{PROG_NAME}: [{:>width$}/{}] {}",
+                self.done,
+                self.total,
+                path.display()
+            );
+        }
+    }
+}
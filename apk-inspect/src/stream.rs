@@ -0,0 +1,69 @@
+use std::cell::RefCell;
+use std::io::{self, BufRead};
+use std::path::Path;
+
+use alpkit::package::{Package, PkgInfo, PkgScript, SignatureInfo};
+use serde::ser::{Error as _, SerializeSeq};
+use serde::{Serialize, Serializer};
+
+use crate::error::Error;
+
+/// A package whose files are read from the data segment while being
+/// serialized, so that they are never all held in memory. It's serialized
+/// the same as [`Package`].
+#[derive(Serialize)]
+#[serde(bound = "F: FnMut(&Path) -> bool")]
+pub(crate) struct StreamedPackage<'a, F> {
+    signs: &'a [SignatureInfo],
+
+    #[serde(flatten)]
+    pkginfo: &'a PkgInfo,
+
+    scripts: &'a [PkgScript],
+
+    files: FileStream<F>,
+}
+
+impl<'a, F: FnMut(&Path) -> bool> StreamedPackage<'a, F> {
+    /// Creates a package with the files read from the `reader`, which must be
+    /// positioned at the data segment (see [`Package::stream_files`]); only
+    /// the files whose path satisfies the `filter` are included.
+    pub(crate) fn new(pkg: &'a Package, reader: Box<dyn BufRead>, filter: F) -> Self {
+        StreamedPackage {
+            signs: pkg.signatures().as_slice(),
+            pkginfo: pkg.pkginfo(),
+            scripts: pkg.scripts().as_slice(),
+            files: FileStream(RefCell::new(Some((reader, filter)))),
+        }
+    }
+}
+
+/// The files to be read when serialized; it can be serialized only once.
+struct FileStream<F>(RefCell<Option<(Box<dyn BufRead>, F)>>);
+
+impl<F: FnMut(&Path) -> bool> Serialize for FileStream<F> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let (reader, mut filter) = self
+            .0
+            .borrow_mut()
+            .take()
+            .ok_or_else(|| S::Error::custom("the files have been already read"))?;
+
+        let mut seq = serializer.serialize_seq(None)?;
+        let mut error = None;
+
+        let result = Package::stream_files(reader, |file| {
+            if !filter(&file.path) {
+                return Ok(());
+            }
+            seq.serialize_element(&file).map_err(|e| {
+                error = Some(e);
+                io::Error::new(io::ErrorKind::Other, "serialization failed")
+            })
+        });
+        if let Err(e) = result {
+            return Err(error.unwrap_or_else(|| S::Error::custom(Error::from(e).message())));
+        }
+        seq.end()
+    }
+}